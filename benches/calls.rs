@@ -0,0 +1,31 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// Function calls, variables, and assignments all start the same way, so they're the most likely
+// to be parsed more than once when trying every alternative
+fn source() -> String {
+    (0..500)
+        .map(|index| {
+            format!(
+                "local v{0} = a.b[c](d, e:f(g))\na.b[{0}], c = v{0}.x, (v{0})\nprint(v{0}.y.z, #v{0})\n",
+                index
+            )
+        })
+        .collect()
+}
+
+fn parse(criterion: &mut Criterion) {
+    let source: &'static str = Box::leak(source().into_boxed_str());
+    let tokens = full_moon::tokenizer::tokens(source).unwrap();
+
+    criterion.bench_function("get ast from parsed calls", move |b| {
+        b.iter(|| full_moon::ast::Ast::from_tokens(black_box(tokens.clone())))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = parse
+}
+
+criterion_main!(benches);
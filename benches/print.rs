@@ -0,0 +1,31 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const DATE_SOURCE: &str = include_str!("./date.lua");
+
+fn print(criterion: &mut Criterion) {
+    let ast = full_moon::parse(DATE_SOURCE).unwrap();
+
+    criterion.bench_function("print date", move |b| {
+        b.iter(|| full_moon::print(black_box(&ast)))
+    });
+}
+
+fn write(criterion: &mut Criterion) {
+    let ast = full_moon::parse(DATE_SOURCE).unwrap();
+    let mut output = Vec::with_capacity(DATE_SOURCE.len());
+
+    criterion.bench_function("write date to a Vec<u8>", move |b| {
+        b.iter(|| {
+            output.clear();
+            full_moon::write(black_box(&ast), &mut output).unwrap();
+        })
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = print, write
+}
+
+criterion_main!(benches);
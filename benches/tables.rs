@@ -0,0 +1,37 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// Table constructors hold every field's expressions inline, so this is where the size of
+// `Expression` and `Value` matters most
+fn source() -> String {
+    (0..500)
+        .map(|index| {
+            format!(
+                "local t{0} = {{ name = \"t{0}\", [{0}] = {{ 1, 2, 3, {{ x = {0}, y = -{0} }} }}, f = function(a) return {{ a, a * 2 }} end }}\n",
+                index
+            )
+        })
+        .collect()
+}
+
+fn parse(criterion: &mut Criterion) {
+    let source: &'static str = Box::leak(source().into_boxed_str());
+    let tokens = full_moon::tokenizer::tokens(source).unwrap();
+
+    criterion.bench_function("get ast from parsed tables", move |b| {
+        b.iter(|| full_moon::ast::Ast::from_tokens(black_box(tokens.clone())))
+    });
+}
+
+fn clone(criterion: &mut Criterion) {
+    let ast = full_moon::parse(Box::leak(source().into_boxed_str())).unwrap();
+
+    criterion.bench_function("clone ast of tables", move |b| b.iter(|| ast.clone()));
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = parse, clone
+}
+
+criterion_main!(benches);
@@ -32,19 +32,59 @@ fn token_getter(
 
 #[derive(PartialEq)]
 enum NodeHint {
+    Contains(String),
     FullRange,
+    Kind(String),
+    Skip,
 }
 
 impl Hint for NodeHint {
+    fn key_value(key: String, value: String) -> Option<Self> {
+        match key.as_str() {
+            "contains" => Some(NodeHint::Contains(value)),
+            "kind" => Some(NodeHint::Kind(value)),
+            _ => None,
+        }
+    }
+
     fn unit(name: String) -> Option<Self> {
-        if name == "full_range" {
-            Some(NodeHint::FullRange)
-        } else {
-            None
+        match name.as_str() {
+            "full_range" => Some(NodeHint::FullRange),
+            "skip" => Some(NodeHint::Skip),
+            _ => None,
         }
     }
 }
 
+// Fields marked with `#[node(skip)]` aren't syntax, such as flags, and are left out of every method
+fn node_fields<'a>(
+    fields: impl IntoIterator<Item = &'a syn::Field>,
+) -> impl Iterator<Item = &'a syn::Field> + Clone {
+    fields
+        .into_iter()
+        .filter(|field| search_hint("node", &field.attrs) != Some(NodeHint::Skip))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+// The fields that the range of a node is searched for in, skipping those inside of a span
+// marked with `#[node(contains = "...")]`, as they can never be at the start or end
+fn range_fields<'a>(fields: impl Iterator<Item = &'a syn::Field> + Clone) -> Vec<&'a syn::Ident> {
+    let fields = node_fields(fields);
+    let contained = fields
+        .clone()
+        .filter_map(|field| match search_hint("node", &field.attrs) {
+            Some(NodeHint::Contains(contained)) => Some(contained),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    fields
+        .map(|field| field.ident.as_ref().unwrap())
+        .filter(|ident| !contained.contains(&ident.to_string()))
+        .collect()
+}
+
 pub struct NodeGenerator;
 
 impl DeriveGenerator for NodeGenerator {
@@ -58,6 +98,9 @@ impl DeriveGenerator for NodeGenerator {
             range => $range:expr,
             similar => $similar:expr,
             tokens => $tokens:expr,
+            first_token => $first_token:expr,
+            last_token => $last_token:expr,
+            kind => $kind:expr,
         }};
 
         quote! {
@@ -72,6 +115,18 @@ impl DeriveGenerator for NodeGenerator {
 
                 ("tokens", #pattern) => {
                     $tokens
+                };
+
+                ("first_token", #pattern) => {
+                    $first_token
+                };
+
+                ("last_token", #pattern) => {
+                    $last_token
+                };
+
+                ("kind", #pattern) => {
+                    $kind
                 }
             }
 
@@ -91,6 +146,18 @@ impl DeriveGenerator for NodeGenerator {
                 fn tokens<'b>(&'b self) -> crate::node::Tokens<'a, 'b> {
                     #macro_name!("tokens", { #tokens })
                 }
+
+                fn first_token<'b>(&'b self) -> Option<&'b crate::tokenizer::TokenReference<'a>> {
+                    #macro_name!("first_token", { #tokens })
+                }
+
+                fn last_token<'b>(&'b self) -> Option<&'b crate::tokenizer::TokenReference<'a>> {
+                    #macro_name!("last_token", { #tokens })
+                }
+
+                fn kind(&self) -> crate::node::NodeKind {
+                    #macro_name!("kind", { #tokens })
+                }
             }
 
             impl #impl_generics crate::private::Sealed for #input_ident #ty_generics #where_clause {}
@@ -103,11 +170,72 @@ impl StructGenerator for NodeGenerator {
         let range = StructRangeGenerator::generate(ident, strukt);
         let similar = StructSimilarGenerator::generate(ident, strukt);
         let tokens = StructTokensGenerator::generate(ident, strukt);
+        let first_token = StructBoundaryTokenGenerator::generate(strukt, Boundary::First);
+        let last_token = StructBoundaryTokenGenerator::generate(strukt, Boundary::Last);
 
         quote! {
             range => { #range },
             similar => { #similar },
             tokens => { #tokens },
+            first_token => { #first_token },
+            last_token => { #last_token },
+            kind => { crate::node::NodeKind::#ident },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Boundary {
+    First,
+    Last,
+}
+
+impl Boundary {
+    fn method(self) -> syn::Ident {
+        match self {
+            Boundary::First => format_ident!("first_token"),
+            Boundary::Last => format_ident!("last_token"),
+        }
+    }
+
+    // Searches the fields the same way as the range generators, so a node's first and last tokens
+    // are where its start and end positions come from
+    fn search_fields(self, fields: &[&syn::Ident], prefix: Option<TokenStream>) -> TokenStream {
+        let method = self.method();
+        let fields: Vec<_> = match self {
+            Boundary::First => fields.iter().collect(),
+            Boundary::Last => fields.iter().rev().collect(),
+        };
+
+        quote! {
+            None#(
+                .or_else(|| {
+                    #prefix#fields.#method()
+                })
+            )*
+        }
+    }
+}
+
+pub struct StructBoundaryTokenGenerator;
+
+impl StructBoundaryTokenGenerator {
+    fn generate(strukt: &syn::DataStruct, boundary: Boundary) -> TokenStream {
+        let fields = range_fields(strukt.fields.iter());
+
+        let full_range = strukt
+            .fields
+            .iter()
+            .find(|field| search_hint("node", &field.attrs) == Some(NodeHint::FullRange));
+
+        if let Some(full_range) = full_range {
+            let ident = full_range.ident.as_ref().unwrap();
+            let method = boundary.method();
+            quote! {
+                self.#ident.#method()
+            }
+        } else {
+            boundary.search_fields(&fields, Some(quote! { self. }))
         }
     }
 }
@@ -116,11 +244,7 @@ pub struct StructRangeGenerator;
 
 impl StructGenerator for StructRangeGenerator {
     fn generate(_: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
-        let fields = strukt
-            .fields
-            .iter()
-            .map(|field| field.ident.as_ref().unwrap())
-            .collect::<Vec<_>>();
+        let fields = range_fields(strukt.fields.iter());
 
         let full_range = strukt
             .fields
@@ -165,9 +289,7 @@ pub struct StructSimilarGenerator;
 
 impl StructGenerator for StructSimilarGenerator {
     fn generate(_: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
-        let fields = strukt
-            .fields
-            .iter()
+        let fields = node_fields(&strukt.fields)
             .map(|field| field.ident.as_ref().unwrap())
             .collect::<Vec<_>>();
 
@@ -185,7 +307,7 @@ impl StructGenerator for StructTokensGenerator {
     fn generate(_: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
         let mut getters = Vec::with_capacity(strukt.fields.len());
 
-        for field in &strukt.fields {
+        for field in node_fields(&strukt.fields) {
             getters.push(token_getter(
                 &field.ty,
                 field.ident.as_ref().unwrap(),
@@ -211,6 +333,9 @@ impl EnumGenerator for NodeGenerator {
         let range = EnumRangeGenerator::generate(ident, enumm);
         let similar = EnumSimilarGenerator::generate(ident, enumm);
         let tokens = EnumTokensGenerator::generate(ident, enumm);
+        let first_token = EnumFirstTokenGenerator::generate(ident, enumm);
+        let last_token = EnumLastTokenGenerator::generate(ident, enumm);
+        let kind = EnumKindGenerator::generate(ident, enumm);
 
         quote! {
             range => {
@@ -220,6 +345,9 @@ impl EnumGenerator for NodeGenerator {
 
             similar => { #similar },
             tokens => { #tokens },
+            first_token => { #first_token },
+            last_token => { #last_token },
+            kind => { #kind },
         }
     }
 }
@@ -237,6 +365,7 @@ impl MatchEnumGenerator for EnumRangeGenerator {
             .iter()
             .map(|field| field.ident.as_ref().unwrap())
             .collect::<Vec<_>>();
+        let searched_fields = range_fields(named.named.iter());
 
         let full_range = named
             .named
@@ -250,11 +379,11 @@ impl MatchEnumGenerator for EnumRangeGenerator {
             }
         } else {
             let (mut start_position, mut end_position) = (
-                Vec::with_capacity(fields.len()),
-                Vec::with_capacity(fields.len()),
+                Vec::with_capacity(searched_fields.len()),
+                Vec::with_capacity(searched_fields.len()),
             );
 
-            for field in &fields {
+            for field in &searched_fields {
                 start_position.push(quote! {
                     .or_else(|| {
                         #field.start_position()
@@ -262,7 +391,7 @@ impl MatchEnumGenerator for EnumRangeGenerator {
                 });
             }
 
-            for field in fields.iter().rev() {
+            for field in searched_fields.iter().rev() {
                 end_position.push(quote! {
                     .or_else(|| {
                         #field.end_position()
@@ -471,3 +600,136 @@ impl MatchEnumGenerator for EnumTokensGenerator {
         }
     }
 }
+
+fn enum_boundary_case_named(
+    input: &syn::Ident,
+    variant: &syn::Ident,
+    named: &syn::FieldsNamed,
+    boundary: Boundary,
+) -> TokenStream {
+    let fields = named
+        .named
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+
+    let full_range = named
+        .named
+        .iter()
+        .find(|field| search_hint("node", &field.attrs) == Some(NodeHint::FullRange));
+
+    let body = if let Some(full_range) = full_range {
+        let ident = full_range.ident.as_ref().unwrap();
+        let method = boundary.method();
+        quote! {
+            #ident.#method()
+        }
+    } else {
+        boundary.search_fields(&range_fields(named.named.iter()), None)
+    };
+
+    quote! {
+        #[allow(unused)]
+        #input::#variant {
+            #(#fields,)*
+        } => {
+            #body
+        }
+    }
+}
+
+fn enum_boundary_case_unnamed(
+    input: &syn::Ident,
+    variant: &syn::Ident,
+    fields: &syn::FieldsUnnamed,
+    boundary: Boundary,
+) -> TokenStream {
+    let names: Vec<_> = fields
+        .unnamed
+        .iter()
+        .enumerate()
+        .map(|(index, _)| format_ident!("__self_{}", index))
+        .collect();
+
+    let body = boundary.search_fields(&names.iter().collect::<Vec<_>>(), None);
+
+    quote! {
+        #input::#variant(
+            #(#names,)*
+        ) => {
+            #body
+        }
+    }
+}
+
+pub struct EnumFirstTokenGenerator;
+
+impl MatchEnumGenerator for EnumFirstTokenGenerator {
+    fn case_named(
+        input: &syn::Ident,
+        variant: &syn::Ident,
+        named: &syn::FieldsNamed,
+    ) -> TokenStream {
+        enum_boundary_case_named(input, variant, named, Boundary::First)
+    }
+
+    fn case_unnamed(
+        input: &syn::Ident,
+        variant: &syn::Ident,
+        fields: &syn::FieldsUnnamed,
+    ) -> TokenStream {
+        enum_boundary_case_unnamed(input, variant, fields, Boundary::First)
+    }
+}
+
+pub struct EnumLastTokenGenerator;
+
+impl MatchEnumGenerator for EnumLastTokenGenerator {
+    fn case_named(
+        input: &syn::Ident,
+        variant: &syn::Ident,
+        named: &syn::FieldsNamed,
+    ) -> TokenStream {
+        enum_boundary_case_named(input, variant, named, Boundary::Last)
+    }
+
+    fn case_unnamed(
+        input: &syn::Ident,
+        variant: &syn::Ident,
+        fields: &syn::FieldsUnnamed,
+    ) -> TokenStream {
+        enum_boundary_case_unnamed(input, variant, fields, Boundary::Last)
+    }
+}
+
+pub struct EnumKindGenerator;
+
+impl EnumGenerator for EnumKindGenerator {
+    // Every variant is its own kind, named after both the enum and the variant, such as `TypeInfoUnion`,
+    // unless it's named with `#[node(kind = "...")]`
+    fn generate(input: &syn::Ident, enumm: &syn::DataEnum) -> TokenStream {
+        let cases = enumm.variants.iter().map(|variant| {
+            let variant_ident = &variant.ident;
+            let kind = match search_hint("node", &variant.attrs) {
+                Some(NodeHint::Kind(kind)) => format_ident!("{}", kind),
+                _ => format_ident!("{}{}", input, variant_ident),
+            };
+
+            let pattern = match &variant.fields {
+                syn::Fields::Named(_) => quote! { #input::#variant_ident { .. } },
+                syn::Fields::Unnamed(_) => quote! { #input::#variant_ident(..) },
+                syn::Fields::Unit => quote! { #input::#variant_ident },
+            };
+
+            quote! {
+                #pattern => crate::node::NodeKind::#kind,
+            }
+        });
+
+        quote! {
+            match self {
+                #(#cases)*
+            }
+        }
+    }
+}
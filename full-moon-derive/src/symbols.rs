@@ -77,6 +77,8 @@ pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         .map(|(symbol, string)| quote!(#string => Symbol::#symbol,))
         .collect();
 
+    let keyword_ident: Vec<_> = keywords.iter().map(|(symbol, _)| symbol).collect();
+
     let operator_array: Vec<_> = operators
         .iter()
         .map(|(symbol, string)| quote!((Symbol::#symbol, #string)))
@@ -95,12 +97,27 @@ pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             )*
         }
 
-        impl<'a> fmt::Display for Symbol {
-            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                match *self {
+        impl Symbol {
+            /// The text of the symbol, such as `while` or `+`
+            pub const fn as_str(self) -> &'static str {
+                match self {
                     #(Symbol::#ident => #string,)*
                 }
-                .fmt(formatter)
+            }
+
+            /// Whether the symbol is a keyword made of letters, such as `while`, rather than an operator, such as `+`
+            pub fn is_keyword(self) -> bool {
+                matches!(self, #(Symbol::#keyword_ident)|*)
+            }
+
+            // Every symbol, for exhaustive tests
+            #[cfg(test)]
+            pub(crate) const ALL: &'static [Symbol] = &[#(Symbol::#ident,)*];
+        }
+
+        impl<'a> fmt::Display for Symbol {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                self.as_str().fmt(formatter)
             }
         }
 
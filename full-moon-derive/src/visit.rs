@@ -144,7 +144,10 @@ impl DeriveGenerator for VisitGenerator {
 
         quote! {
             impl #impl_generics crate::visitors::Visit<#lifetime> for #input_ident #ty_generics #where_clause {
-                fn visit<V: crate::visitors::Visitor<#lifetime>>(&self, visitor: &mut V) {
+                fn visit<'visit, V: crate::visitors::VisitorRef<'visit, #lifetime>>(&'visit self, visitor: &mut V)
+                where
+                    #lifetime: 'visit,
+                {
                     macro_rules! visit {
                         ($visit_what: expr, $visitor: expr) => {
                             $visit_what.visit($visitor);
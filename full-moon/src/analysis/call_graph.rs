@@ -0,0 +1,511 @@
+use super::{scopes, Resolution, ScopeTree, VariableId};
+use crate::{
+    ast::*,
+    debug::escape,
+    node::Node,
+    tokenizer::{Position, TokenReference},
+    visitors::Visitor,
+};
+use std::{collections::HashMap, fmt::Write};
+
+/// Identifies a [`Function`] inside of a [`CallGraph`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionId(usize);
+
+/// How a [`Function`] was defined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FunctionKind {
+    /// The code at the top level of the file, which is not inside of any function
+    Chunk,
+    /// A function declaration without a method, such as `function a.b() end`
+    Declaration,
+    /// A function declaration with a method, such as `function a:b() end`
+    Method,
+    /// A local function, such as `local function f() end`
+    LocalFunction,
+    /// An anonymous function assigned directly to a name, such as `local f = function() end`
+    /// or `a.b = function() end`
+    Assigned,
+}
+
+/// A named function, as well as the chunk, see [`FunctionKind`] for what is included
+#[derive(Clone, Debug)]
+pub struct Function {
+    name: String,
+    kind: FunctionKind,
+    parent: Option<FunctionId>,
+    position: Option<Position>,
+}
+
+impl Function {
+    /// The name of the function as written, such as `a.b:c`. The chunk is named `<chunk>`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the function was defined
+    pub fn kind(&self) -> FunctionKind {
+        self.kind
+    }
+
+    /// The function this one is defined inside of. Only `None` for the chunk
+    pub fn parent(&self) -> Option<FunctionId> {
+        self.parent
+    }
+
+    /// The start position of the name of the function. Only `None` for the chunk
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+}
+
+/// A function call, along with the function it is made from
+#[derive(Clone, Debug)]
+pub struct Call {
+    caller: FunctionId,
+    path: Option<String>,
+    callee: Option<FunctionId>,
+    position: Option<Position>,
+}
+
+impl Call {
+    /// The innermost function the call is made from. Calls inside of anonymous functions
+    /// that are not assigned to a name belong to the function around them
+    pub fn caller(&self) -> FunctionId {
+        self.caller
+    }
+
+    /// The function being called as written, such as `a.b:c`.
+    /// `None` if it is not a plain chain of names, such as `a()()` or `a[b]()`
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// The function being called, if it is defined in the same file
+    pub fn callee(&self) -> Option<FunctionId> {
+        self.callee
+    }
+
+    /// The start position of the call
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+}
+
+/// Which functions call which, as created by [`call_graph`].
+///
+/// Calls are matched to functions by their path, so `a.b()` calls `function a.b() end`.
+/// Locals are matched by the variable they refer to rather than their name, and `self`
+/// inside of a method refers to the table the method was declared on.
+/// If a function is defined more than once, calls go to the last definition before them.
+#[derive(Clone, Debug)]
+pub struct CallGraph {
+    functions: Vec<Function>,
+    calls: Vec<Call>,
+}
+
+impl CallGraph {
+    /// The chunk, which makes every call at the top level of the file
+    pub fn chunk(&self) -> FunctionId {
+        FunctionId(0)
+    }
+
+    /// The function with the given id
+    pub fn function(&self, id: FunctionId) -> &Function {
+        &self.functions[id.0]
+    }
+
+    /// Every function, starting with the chunk, in the order they are defined
+    pub fn functions(&self) -> impl Iterator<Item = (FunctionId, &Function)> {
+        self.functions
+            .iter()
+            .enumerate()
+            .map(|(index, function)| (FunctionId(index), function))
+    }
+
+    /// Every call, in the order they appear
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// Every call made from the given function, in the order they appear
+    pub fn calls_from(&self, caller: FunctionId) -> impl Iterator<Item = &Call> {
+        self.calls.iter().filter(move |call| call.caller == caller)
+    }
+
+    /// Every pair of functions where the first calls the second, without duplicates,
+    /// in the order of the first call between them
+    pub fn edges(&self) -> Vec<(FunctionId, FunctionId)> {
+        let mut edges = Vec::new();
+
+        for call in &self.calls {
+            if let Some(callee) = call.callee {
+                if !edges.contains(&(call.caller, callee)) {
+                    edges.push((call.caller, callee));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Creates a [Graphviz](https://graphviz.org/) digraph of the calls.
+    /// Functions that are called but not defined in the file, such as `print`, are dashed,
+    /// and calls without a [`path`](Call::path) are left out.
+    ///
+    /// ```rust
+    /// # use full_moon::analysis::call_graph;
+    /// let ast = full_moon::parse("local function f() print() end\nf()")?;
+    /// assert_eq!(
+    ///     call_graph(&ast).to_dot(),
+    ///     r#"digraph calls {
+    ///     node [shape=box];
+    ///     f0 [label="<chunk>", shape=plaintext];
+    ///     f1 [label="f"];
+    ///     u0 [label="print", style=dashed];
+    ///     f1 -> u0;
+    ///     f0 -> f1;
+    /// }
+    /// "#
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph calls {\n    node [shape=box];\n");
+
+        for (id, function) in self.functions() {
+            write!(
+                output,
+                "    f{} [label=\"{}\"",
+                id.0,
+                escape(&function.name)
+            )
+            .unwrap();
+            if function.kind == FunctionKind::Chunk {
+                output.push_str(", shape=plaintext");
+            }
+            output.push_str("];\n");
+        }
+
+        let mut undefined = Vec::new();
+        for call in &self.calls {
+            match (&call.path, call.callee) {
+                (Some(path), None) if !undefined.contains(&path) => undefined.push(path),
+                _ => {}
+            }
+        }
+
+        for (index, path) in undefined.iter().enumerate() {
+            writeln!(
+                output,
+                "    u{} [label=\"{}\", style=dashed];",
+                index,
+                escape(path)
+            )
+            .unwrap();
+        }
+
+        let mut edges = Vec::new();
+        for call in &self.calls {
+            let target = match (&call.path, call.callee) {
+                (_, Some(callee)) => format!("f{}", callee.0),
+                (Some(path), None) => format!(
+                    "u{}",
+                    undefined.iter().position(|other| *other == path).unwrap()
+                ),
+                (None, None) => continue,
+            };
+
+            let edge = format!("    f{} -> {};\n", call.caller.0, target);
+            if !edges.contains(&edge) {
+                output.push_str(&edge);
+                edges.push(edge);
+            }
+        }
+
+        output.push_str("}\n");
+        output
+    }
+}
+
+/// Creates a [`CallGraph`] for the given [`Ast`], finding every named function and every call
+/// made from inside of them. This is done syntactically, so only calls to plain chains of names
+/// can be matched to the function they call.
+///
+/// ```rust
+/// # use full_moon::analysis::call_graph;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("function a.b() c() end\nfunction c() end\na.b()")?;
+/// let graph = call_graph(&ast);
+///
+/// let edges = graph
+///     .edges()
+///     .into_iter()
+///     .map(|(caller, callee)| {
+///         format!("{} -> {}", graph.function(caller).name(), graph.function(callee).name())
+///     })
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(edges, vec!["a.b -> c", "<chunk> -> a.b"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn call_graph(ast: &Ast) -> CallGraph {
+    let mut builder = CallGraphBuilder {
+        tree: scopes(ast),
+        functions: vec![Function {
+            name: "<chunk>".to_owned(),
+            kind: FunctionKind::Chunk,
+            parent: None,
+            position: None,
+        }],
+        keys: vec![None],
+        calls: Vec::new(),
+        call_keys: Vec::new(),
+        pending: HashMap::new(),
+        self_keys: HashMap::new(),
+        stack: vec![FunctionId(0)],
+    };
+
+    builder.visit_ast(ast);
+    builder.finish()
+}
+
+// What a function is stored in, so that calls can be matched to it.
+// Paths are compared as written, except that the root is resolved to a variable if it is a local.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    root: Option<VariableId>,
+    path: String,
+}
+
+struct Pending {
+    name: String,
+    kind: FunctionKind,
+    position: Option<Position>,
+    key: Option<Key>,
+}
+
+struct CallGraphBuilder<'a, 'b> {
+    tree: ScopeTree<'a, 'b>,
+    functions: Vec<Function>,
+    keys: Vec<Option<Key>>,
+    calls: Vec<Call>,
+    // The key of every call, along with how many functions had been defined before it
+    call_keys: Vec<(Option<Key>, usize)>,
+    // Functions whose name has been seen, but not their body yet
+    pending: HashMap<*const FunctionBody<'a>, Pending>,
+    // The key of the table a method was declared on, by the variable of its implicit `self`
+    self_keys: HashMap<VariableId, Key>,
+    stack: Vec<FunctionId>,
+}
+
+impl<'a, 'b> CallGraphBuilder<'a, 'b> {
+    fn key(&self, root: &TokenReference<'a>, path: String) -> Option<Key> {
+        let root = match self
+            .tree
+            .resolve(root)
+            .or_else(|| self.tree.declaration(root).map(Resolution::Local))?
+        {
+            Resolution::Global => None,
+            resolution => resolution.variable(),
+        };
+
+        Some(Key { root, path })
+    }
+
+    fn define(&mut self, body: &FunctionBody<'a>, pending: Pending) {
+        self.pending.insert(body as *const _, pending);
+    }
+
+    // Defines an anonymous function assigned to a name, such as `local f = function() end`
+    fn define_assigned(
+        &mut self,
+        root: &TokenReference<'a>,
+        path: String,
+        expression: &Expression<'a>,
+    ) {
+        if let Expression::Value { value, .. } = expression {
+            if let Value::Function(function) = &**value {
+                let pending = Pending {
+                    key: self.key(root, path.clone()),
+                    name: path,
+                    kind: FunctionKind::Assigned,
+                    position: root.start_position(),
+                };
+
+                self.define(function.body(), pending);
+            }
+        }
+    }
+
+    fn finish(self) -> CallGraph {
+        let mut definitions: HashMap<&Key, Vec<FunctionId>> = HashMap::new();
+        for (index, key) in self.keys.iter().enumerate() {
+            if let Some(key) = key {
+                definitions.entry(key).or_default().push(FunctionId(index));
+            }
+        }
+
+        let mut calls = self.calls;
+        for (call, (key, defined)) in calls.iter_mut().zip(&self.call_keys) {
+            let candidates = match key.as_ref().and_then(|key| definitions.get(key)) {
+                Some(candidates) => candidates,
+                None => continue,
+            };
+
+            call.callee = candidates
+                .iter()
+                .rev()
+                .find(|candidate| candidate.0 < *defined)
+                .or_else(|| candidates.first())
+                .copied();
+        }
+
+        CallGraph {
+            functions: self.functions,
+            calls,
+        }
+    }
+}
+
+impl<'a, 'b> Visitor<'a> for CallGraphBuilder<'a, 'b> {
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration<'a>) {
+        let name = declaration.name();
+        let root = match name.names().iter().next() {
+            Some(root) => root,
+            None => return,
+        };
+
+        let mut path = name
+            .names()
+            .iter()
+            .map(|name| name.token().to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        let owner = self.key(root, path.clone());
+        let implicit_self = name
+            .method_name()
+            .and_then(|method_name| self.tree.declaration(method_name));
+
+        if let (Some(owner), Some(implicit_self)) = (&owner, implicit_self) {
+            self.self_keys.insert(implicit_self, owner.clone());
+        }
+
+        let kind = match name.method_name() {
+            Some(method_name) => {
+                path = format!("{}:{}", path, method_name.token());
+                FunctionKind::Method
+            }
+            None => FunctionKind::Declaration,
+        };
+
+        let pending = Pending {
+            key: self.key(root, path.clone()),
+            name: path,
+            kind,
+            position: root.start_position(),
+        };
+
+        self.define(declaration.body(), pending);
+    }
+
+    fn visit_local_function(&mut self, local_function: &LocalFunction<'a>) {
+        let name = local_function.name();
+        let pending = Pending {
+            key: self.key(name, name.token().to_string()),
+            name: name.token().to_string(),
+            kind: FunctionKind::LocalFunction,
+            position: name.start_position(),
+        };
+
+        self.define(local_function.body(), pending);
+    }
+
+    fn visit_local_assignment(&mut self, local_assignment: &LocalAssignment<'a>) {
+        for (name, expression) in local_assignment
+            .names()
+            .iter()
+            .zip(local_assignment.expressions())
+        {
+            self.define_assigned(name, name.token().to_string(), expression);
+        }
+    }
+
+    fn visit_assignment(&mut self, assignment: &Assignment<'a>) {
+        for (var, expression) in assignment.variables().iter().zip(assignment.expressions()) {
+            let root = match var {
+                Var::Name(name) => name,
+                Var::Expression(var_expression) => match var_expression.prefix() {
+                    Prefix::Name(name) => name,
+                    _ => continue,
+                },
+            };
+
+            if let Some(path) = var.to_path_string(false) {
+                self.define_assigned(root, path, expression);
+            }
+        }
+    }
+
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        let current = *self.stack.last().unwrap();
+
+        match self.pending.remove(&(body as *const _)) {
+            Some(pending) => {
+                let id = FunctionId(self.functions.len());
+                self.functions.push(Function {
+                    name: pending.name,
+                    kind: pending.kind,
+                    parent: Some(current),
+                    position: pending.position,
+                });
+                self.keys.push(pending.key);
+                self.stack.push(id);
+            }
+
+            None => self.stack.push(current),
+        }
+    }
+
+    fn visit_function_body_end(&mut self, _: &FunctionBody<'a>) {
+        self.stack.pop();
+    }
+
+    fn visit_function_call(&mut self, call: &FunctionCall<'a>) {
+        let path = call.to_path(false).map(|mut segments| {
+            if call.method_name().is_some() {
+                let method_name = segments.pop().unwrap_or_default();
+                format!("{}:{}", segments.join("."), method_name)
+            } else {
+                segments.join(".")
+            }
+        });
+        let key = match (call.prefix(), &path) {
+            (Prefix::Name(root), Some(path)) => {
+                let key = self.key(root, path.clone());
+                match key.as_ref().and_then(|key| self.self_keys.get(&key.root?)) {
+                    // `self.x` inside of `function a:b()` is `a.x`
+                    Some(owner) => Some(Key {
+                        root: owner.root,
+                        path: format!("{}{}", owner.path, &path[root.token().to_string().len()..]),
+                    }),
+                    None => key,
+                }
+            }
+
+            _ => None,
+        };
+
+        self.calls.push(Call {
+            caller: *self.stack.last().unwrap(),
+            path,
+            callee: None,
+            position: call.start_position(),
+        });
+
+        self.call_keys.push((key, self.functions.len()));
+    }
+}
@@ -0,0 +1,332 @@
+use super::{is_global, scopes, ScopeTree, VariableId};
+use crate::{ast::*, node::Node, query, tokenizer::TokenReference};
+use std::collections::HashMap;
+
+/// A function declared on a class that calls `setmetatable` with the class as the metatable,
+/// such as `function M.new() return setmetatable({}, M) end`
+#[derive(Clone, Debug)]
+pub struct Constructor<'a, 'b> {
+    declaration: &'b FunctionDeclaration<'a>,
+    setmetatable: &'b FunctionCall<'a>,
+}
+
+impl<'a, 'b> Constructor<'a, 'b> {
+    /// The declaration of the constructor, such as `function M.new() ... end`
+    pub fn declaration(&self) -> &'b FunctionDeclaration<'a> {
+        self.declaration
+    }
+
+    /// The first call to `setmetatable` inside of the constructor with the class as the
+    /// metatable, such as `setmetatable({}, M)` or `setmetatable(self, M)`
+    pub fn setmetatable(&self) -> &'b FunctionCall<'a> {
+        self.setmetatable
+    }
+}
+
+/// A local table used as a class, as found by [`find_class_like_tables`]
+#[derive(Clone, Debug)]
+pub struct ClassLikeTable<'a, 'b> {
+    name: &'b TokenReference<'a>,
+    variable: VariableId,
+    definition: &'b LocalAssignment<'a>,
+    index_assignment: Option<&'b Assignment<'a>>,
+    constructors: Vec<Constructor<'a, 'b>>,
+    methods: Vec<&'b FunctionDeclaration<'a>>,
+}
+
+impl<'a, 'b> ClassLikeTable<'a, 'b> {
+    /// The name of the local the class is stored in, such as `M` in `local M = {}`
+    pub fn name(&self) -> &'b TokenReference<'a> {
+        self.name
+    }
+
+    /// The variable the class is stored in, see [`ScopeTree::variable`]
+    pub fn variable(&self) -> VariableId {
+        self.variable
+    }
+
+    /// The local assignment defining the class, such as `local M = {}`
+    pub fn definition(&self) -> &'b LocalAssignment<'a> {
+        self.definition
+    }
+
+    /// The first assignment of the class to its own `__index`, such as `M.__index = M`.
+    /// `self.__index = self` inside of a method of the class is included.
+    pub fn index_assignment(&self) -> Option<&'b Assignment<'a>> {
+        self.index_assignment
+    }
+
+    /// Every constructor of the class, in the order they are declared.
+    /// Methods that call `setmetatable`, such as `function M:new(o)`, are constructors as well.
+    pub fn constructors(&self) -> &[Constructor<'a, 'b>] {
+        &self.constructors
+    }
+
+    /// Every method declared on the class, such as `function M:method() end`,
+    /// in the order they are declared
+    pub fn methods(&self) -> &[&'b FunctionDeclaration<'a>] {
+        &self.methods
+    }
+}
+
+/// Finds every local table that is used as a class with the common idiom:
+///
+/// ```lua
+/// local M = {}
+/// M.__index = M
+///
+/// function M.new()
+///     return setmetatable({}, M)
+/// end
+///
+/// function M:method() end
+/// ```
+///
+/// This is done syntactically, using [`scopes`] to match every part to the same local.
+/// A local assigned a table constructor, or a call to `setmetatable` with one, is a class if it is
+/// assigned to its own `__index`, or if a function declared on it calls `setmetatable` with it as
+/// the metatable. The classes are returned in the order they are defined.
+///
+/// ```rust
+/// # use full_moon::analysis::find_class_like_tables;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse(r#"
+/// local Stack = {}
+/// Stack.__index = Stack
+///
+/// function Stack.new()
+///     return setmetatable({ items = {} }, Stack)
+/// end
+///
+/// function Stack:push(item)
+///     table.insert(self.items, item)
+/// end
+/// "#)?;
+///
+/// let classes = find_class_like_tables(&ast);
+/// assert_eq!(classes.len(), 1);
+/// assert_eq!(classes[0].name().token().to_string(), "Stack");
+/// assert!(classes[0].index_assignment().is_some());
+/// assert_eq!(classes[0].constructors()[0].declaration().name().to_string(), "Stack.new");
+/// assert_eq!(classes[0].methods()[0].name().to_string(), "Stack:push");
+/// # Ok(())
+/// # }
+/// ```
+pub fn find_class_like_tables<'a, 'b>(ast: &'b Ast<'a>) -> Vec<ClassLikeTable<'a, 'b>> {
+    let mut finder = ClassFinder {
+        tree: scopes(ast),
+        classes: Vec::new(),
+        by_variable: HashMap::new(),
+        self_classes: HashMap::new(),
+    };
+
+    for local_assignment in query::<LocalAssignment>(ast) {
+        finder.local_assignment(local_assignment);
+    }
+
+    // Methods come first, so that `self` inside of them is known to be the class
+    let declarations = query::<FunctionDeclaration>(ast)
+        .filter_map(|declaration| Some((finder.declaration(declaration)?, declaration)))
+        .collect::<Vec<_>>();
+
+    for assignment in query::<Assignment>(ast) {
+        finder.assignment(assignment);
+    }
+
+    for call in query::<FunctionCall>(ast) {
+        let class = match finder.setmetatable_arguments(call) {
+            Some((_, metatable)) => match finder.class_of_expression(metatable) {
+                Some(class) => class,
+                None => continue,
+            },
+            None => continue,
+        };
+
+        let position = match call.start_position() {
+            Some(position) => position.bytes(),
+            None => continue,
+        };
+
+        // The innermost function declared on the class that the call is inside of
+        let declaration = declarations
+            .iter()
+            .filter(|(declared_on, declaration)| {
+                *declared_on == class
+                    && declaration.body().range().map_or(false, |(start, end)| {
+                        start.bytes() <= position && position < end.bytes()
+                    })
+            })
+            .map(|(_, declaration)| *declaration)
+            .max_by_key(|declaration| {
+                declaration
+                    .start_position()
+                    .map(|position| position.bytes())
+            });
+
+        let constructors = &mut finder.classes[class].constructors;
+        match declaration {
+            Some(declaration)
+                if !constructors
+                    .iter()
+                    .any(|constructor| std::ptr::eq(constructor.declaration, declaration)) =>
+            {
+                constructors.push(Constructor {
+                    declaration,
+                    setmetatable: call,
+                })
+            }
+            _ => {}
+        }
+    }
+
+    finder
+        .classes
+        .into_iter()
+        .filter(|class| class.index_assignment.is_some() || !class.constructors.is_empty())
+        .collect()
+}
+
+struct ClassFinder<'a, 'b> {
+    tree: ScopeTree<'a, 'b>,
+    classes: Vec<ClassLikeTable<'a, 'b>>,
+    by_variable: HashMap<VariableId, usize>,
+    // The class a method was declared on, by the variable of its implicit `self`
+    self_classes: HashMap<VariableId, usize>,
+}
+
+impl<'a, 'b> ClassFinder<'a, 'b> {
+    // The class the name refers to, either by the local the class is stored in,
+    // or by `self` inside of a method of the class
+    fn class_of(&self, token: &TokenReference<'a>) -> Option<usize> {
+        let variable = self.tree.resolve(token)?.variable()?;
+        self.by_variable
+            .get(&variable)
+            .or_else(|| self.self_classes.get(&variable))
+            .copied()
+    }
+
+    fn class_of_expression(&self, expression: &Expression<'a>) -> Option<usize> {
+        match expression {
+            Expression::Value { value, .. } => match &**value {
+                Value::Var(Var::Name(name)) => self.class_of(name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // The arguments of a call to the global `setmetatable`, such as `setmetatable({}, M)`
+    fn setmetatable_arguments<'c>(
+        &self,
+        call: &'c FunctionCall<'a>,
+    ) -> Option<(&'c Expression<'a>, &'c Expression<'a>)> {
+        let mut suffixes = call.suffixes();
+        let arguments = match (call.prefix(), suffixes.next(), suffixes.next()) {
+            (
+                Prefix::Name(name),
+                Some(Suffix::Call(Call::AnonymousCall(FunctionArgs::Parentheses {
+                    arguments,
+                    ..
+                }))),
+                None,
+            ) if is_global(&self.tree, name, "setmetatable") => arguments,
+            _ => return None,
+        };
+
+        let mut arguments = arguments.iter();
+        Some((arguments.next()?, arguments.next()?))
+    }
+
+    // Whether the expression creates a table, such as `{}` or `setmetatable({}, Base)`
+    fn is_table(&self, expression: &Expression<'a>) -> bool {
+        let value = match expression {
+            Expression::Value { value, .. } => value,
+            _ => return false,
+        };
+
+        match &**value {
+            Value::TableConstructor(_) => true,
+            Value::FunctionCall(call) => match self.setmetatable_arguments(call) {
+                Some((Expression::Value { value, .. }, _)) => {
+                    matches!(&**value, Value::TableConstructor(_))
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn local_assignment(&mut self, local_assignment: &'b LocalAssignment<'a>) {
+        for (name, expression) in local_assignment
+            .names()
+            .iter()
+            .zip(local_assignment.expressions())
+        {
+            let variable = match self.tree.declaration(name) {
+                Some(variable) if self.is_table(expression) => variable,
+                _ => continue,
+            };
+
+            self.by_variable.insert(variable, self.classes.len());
+            self.classes.push(ClassLikeTable {
+                name,
+                variable,
+                definition: local_assignment,
+                index_assignment: None,
+                constructors: Vec::new(),
+                methods: Vec::new(),
+            });
+        }
+    }
+
+    // Records a method of a class, and returns the class the function is declared on,
+    // such as `M` for both `function M.new()` and `function M:method()`
+    fn declaration(&mut self, declaration: &'b FunctionDeclaration<'a>) -> Option<usize> {
+        let name = declaration.name();
+
+        let mut names = name.names().iter();
+        let class = match (names.next(), names.next(), name.method_name()) {
+            (Some(root), None, Some(_)) | (Some(root), Some(_), None) if names.next().is_none() => {
+                self.class_of(root)?
+            }
+            _ => return None,
+        };
+
+        if let Some(method_name) = name.method_name() {
+            self.classes[class].methods.push(declaration);
+
+            if let Some(implicit_self) = self.tree.declaration(method_name) {
+                self.self_classes.insert(implicit_self, class);
+            }
+        }
+
+        Some(class)
+    }
+
+    // Records `M.__index = M`, as well as `self.__index = self` inside of a method of `M`
+    fn assignment(&mut self, assignment: &'b Assignment<'a>) {
+        for (var, expression) in assignment.variables().iter().zip(assignment.expressions()) {
+            let root = match var {
+                Var::Expression(var_expression) => match var_expression.prefix() {
+                    Prefix::Name(root) => root,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            match var.to_path(true).as_deref() {
+                Some([_, "__index"]) => {}
+                _ => continue,
+            }
+
+            let class = match self.class_of(root) {
+                Some(class) if self.class_of_expression(expression) == Some(class) => class,
+                _ => continue,
+            };
+
+            self.classes[class]
+                .index_assignment
+                .get_or_insert(assignment);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod call_graph;
+mod class;
+mod reference;
+mod require;
+mod scope;
+mod stats;
+
+pub use call_graph::*;
+pub use class::*;
+pub use reference::*;
+pub use require::*;
+pub use scope::*;
+pub use stats::*;
@@ -0,0 +1,112 @@
+use super::{scopes, Resolution, VariableKind};
+use crate::{
+    ast::Ast,
+    tokenizer::{TokenReference, TokenType},
+};
+use std::collections::HashMap;
+
+struct Bucket<'a, 'b> {
+    definition: Option<&'b TokenReference<'a>>,
+    tokens: Vec<&'b TokenReference<'a>>,
+}
+
+/// An index of every token that refers to the same variable, as created by [`references`].
+///
+/// Tokens are looked up by their address, so the tokens passed to methods such as
+/// [`references_of`](ReferenceIndex::references_of) must come from the same [`Ast`]
+/// the index was created from.
+pub struct ReferenceIndex<'a, 'b> {
+    buckets: Vec<Bucket<'a, 'b>>,
+    by_token: HashMap<*const TokenReference<'a>, usize>,
+}
+
+impl<'a, 'b> ReferenceIndex<'a, 'b> {
+    /// Every token that refers to the same variable as the given token, in the order they appear.
+    /// This includes the given token itself, as well as the declaration of the variable.
+    /// Every usage of a global with the same name is considered to be the same variable.
+    /// Returns an empty slice if the token does not refer to a variable.
+    pub fn references_of(&self, token: &TokenReference<'a>) -> &[&'b TokenReference<'a>] {
+        match self.by_token.get(&(token as *const _)) {
+            Some(&bucket) => &self.buckets[bucket].tokens,
+            None => &[],
+        }
+    }
+
+    /// The token that declares the variable the given token refers to, such as `x` in `local x = 1`.
+    /// For the implicit `self` of a method, this is the name of the method.
+    /// Returns `None` for globals, or if the token does not refer to a variable.
+    pub fn definition_of(&self, token: &TokenReference<'a>) -> Option<&'b TokenReference<'a>> {
+        self.buckets[*self.by_token.get(&(token as *const _))?].definition
+    }
+}
+
+/// Creates a [`ReferenceIndex`] for the given [`Ast`].
+/// Function parameters, loop variables, and the implicit `self` of methods are all treated as declarations.
+///
+/// ```rust
+/// # use full_moon::{analysis::references, ast::Stmt};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1\nx = x + 1\nprint(x)")?;
+/// let index = references(&ast);
+///
+/// if let Some(Stmt::LocalAssignment(local_assignment)) = ast.nodes().stmts().next() {
+///     let x = local_assignment.names().iter().next().unwrap();
+///     assert_eq!(index.references_of(x).len(), 4);
+///     assert_eq!(index.definition_of(x), Some(x));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn references<'a, 'b>(ast: &'b Ast<'a>) -> ReferenceIndex<'a, 'b> {
+    let tree = scopes(ast);
+
+    let mut buckets = Vec::new();
+    let mut by_token = HashMap::new();
+
+    for (_, variable) in tree.variables() {
+        let mut tokens = Vec::new();
+
+        // The implicit `self` is not written out, so it has no token to be referred to by
+        if variable.kind() != VariableKind::ImplicitSelf {
+            tokens.push(variable.token());
+        }
+
+        tokens.extend(variable.references());
+        tokens.sort_by_key(|token| token.start_position());
+
+        for token in &tokens {
+            by_token.insert(*token as *const _, buckets.len());
+        }
+
+        buckets.push(Bucket {
+            definition: Some(variable.token()),
+            tokens,
+        });
+    }
+
+    let mut globals = HashMap::new();
+    for (token, resolution) in tree.usages() {
+        if resolution != Resolution::Global {
+            continue;
+        }
+
+        let name = match token.token_type() {
+            TokenType::Identifier { identifier } => identifier,
+            _ => continue,
+        };
+
+        let bucket = *globals.entry(name).or_insert_with(|| {
+            buckets.push(Bucket {
+                definition: None,
+                tokens: Vec::new(),
+            });
+
+            buckets.len() - 1
+        });
+
+        buckets[bucket].tokens.push(token);
+        by_token.insert(token as *const _, bucket);
+    }
+
+    ReferenceIndex { buckets, by_token }
+}
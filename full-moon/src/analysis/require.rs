@@ -0,0 +1,187 @@
+use super::{scopes, Resolution, ScopeTree};
+use crate::{
+    ast::*,
+    node::Node,
+    query,
+    tokenizer::{decode_string_literal, Position, TokenReference, TokenType},
+};
+
+/// What is passed to a [`require`](requires) call
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RequireArgument<'b> {
+    /// A string literal, such as `require("module")`, with its escape sequences decoded
+    String(String),
+    /// A chain of names, such as `["script", "Parent", "Module"]` in `require(script.Parent.Module)`.
+    /// String literal bracket keys are included, see [`Var::to_path`]
+    Path(Vec<&'b str>),
+    /// Anything that is not known without running the code, such as `require(name .. ".lua")`
+    Dynamic,
+}
+
+/// The node a call to `require` is part of
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum RequireNode<'a, 'b> {
+    /// A call that is not indexed afterwards, such as `require("x")` or `require("x").y()`
+    FunctionCall(&'b FunctionCall<'a>),
+    /// A call that is indexed afterwards, such as `require("x").y`
+    VarExpression(&'b VarExpression<'a>),
+}
+
+/// A call to `require`, as found by [`requires`]
+#[derive(Clone, Debug)]
+pub struct RequireInfo<'a, 'b> {
+    node: RequireNode<'a, 'b>,
+    argument: RequireArgument<'b>,
+    protected: bool,
+}
+
+impl<'a, 'b> RequireInfo<'a, 'b> {
+    /// The node containing the call to `require`, or to `pcall` for [protected](RequireInfo::is_protected) requires.
+    /// Anything that comes after the call is included, such as `.y` in `require("x").y`
+    pub fn node(&self) -> RequireNode<'a, 'b> {
+        self.node
+    }
+
+    /// The call to `require`, if it is not indexed afterwards. See [`node`](RequireInfo::node)
+    pub fn call(&self) -> Option<&'b FunctionCall<'a>> {
+        match self.node {
+            RequireNode::FunctionCall(call) => Some(call),
+            RequireNode::VarExpression(_) => None,
+        }
+    }
+
+    /// What is being required
+    pub fn argument(&self) -> &RequireArgument<'b> {
+        &self.argument
+    }
+
+    /// The start position of the call
+    pub fn position(&self) -> Option<Position> {
+        match self.node {
+            RequireNode::FunctionCall(call) => call.start_position(),
+            RequireNode::VarExpression(var_expression) => var_expression.start_position(),
+        }
+    }
+
+    /// Whether the require is made through `pcall`, such as `pcall(require, "module")`
+    pub fn is_protected(&self) -> bool {
+        self.protected
+    }
+}
+
+/// Finds every call to the global `require` in the [`Ast`], in the order they appear,
+/// including those nested inside of expressions and functions, as well as `pcall(require, ...)`.
+/// Calls to a local variable named `require` are skipped.
+///
+/// ```rust
+/// # use full_moon::analysis::{requires, RequireArgument};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse(r#"
+/// local json = require("json")
+/// local Roact = require(script.Parent.Roact)
+/// local plugins = { require("plugins/" .. name) }
+/// "#)?;
+///
+/// let arguments = requires(&ast)
+///     .iter()
+///     .map(|require| require.argument().clone())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(arguments, vec![
+///     RequireArgument::String("json".to_owned()),
+///     RequireArgument::Path(vec!["script", "Parent", "Roact"]),
+///     RequireArgument::Dynamic,
+/// ]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn requires<'a, 'b>(ast: &'b Ast<'a>) -> Vec<RequireInfo<'a, 'b>> {
+    let tree = scopes(ast);
+
+    let calls = query::<FunctionCall>(ast).map(|call| {
+        (
+            call.prefix(),
+            call.suffixes().next(),
+            RequireNode::FunctionCall(call),
+        )
+    });
+    let var_expressions = query::<VarExpression>(ast).map(|var_expression| {
+        (
+            var_expression.prefix(),
+            var_expression.suffixes().next(),
+            RequireNode::VarExpression(var_expression),
+        )
+    });
+
+    let mut requires = calls
+        .chain(var_expressions)
+        .filter_map(|(prefix, suffix, node)| {
+            let (name, mut arguments) = match (prefix, suffix) {
+                (Prefix::Name(name), Some(Suffix::Call(Call::AnonymousCall(args)))) => {
+                    (name, args.arguments())
+                }
+                _ => return None,
+            };
+
+            let protected = if is_global(&tree, name, "require") {
+                false
+            } else if is_global(&tree, name, "pcall") {
+                match arguments.next()? {
+                    ArgumentRef::Expression(Expression::Value { value, .. }) => match &**value {
+                        Value::Var(Var::Name(name)) if is_global(&tree, name, "require") => true,
+                        _ => return None,
+                    },
+                    _ => return None,
+                }
+            } else {
+                return None;
+            };
+
+            Some(RequireInfo {
+                node,
+                argument: arguments
+                    .next()
+                    .map_or(RequireArgument::Dynamic, require_argument),
+                protected,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    requires.sort_by_key(|require| require.position().map(|position| position.bytes()));
+    requires
+}
+
+pub(super) fn is_global(tree: &ScopeTree, token: &TokenReference, name: &str) -> bool {
+    matches!(token.token_type(), TokenType::Identifier { identifier } if identifier == name)
+        && tree.resolve(token) == Some(Resolution::Global)
+}
+
+fn require_argument<'a, 'b>(argument: ArgumentRef<'a, 'b>) -> RequireArgument<'b> {
+    let token = match argument {
+        ArgumentRef::String(token) => token,
+        ArgumentRef::Expression(Expression::Value { value, .. }) => match &**value {
+            Value::String(token) => token,
+            Value::Var(var) => {
+                return var
+                    .to_path(true)
+                    .map_or(RequireArgument::Dynamic, RequireArgument::Path)
+            }
+            _ => return RequireArgument::Dynamic,
+        },
+        _ => return RequireArgument::Dynamic,
+    };
+
+    match token.token_type() {
+        TokenType::StringLiteral {
+            literal,
+            multi_line,
+            ..
+        } => match decode_string_literal(literal, *multi_line) {
+            Some(bytes) => RequireArgument::String(String::from_utf8_lossy(&bytes).into_owned()),
+            None => RequireArgument::Dynamic,
+        },
+        _ => RequireArgument::Dynamic,
+    }
+}
@@ -0,0 +1,701 @@
+use crate::{
+    ast::*,
+    tokenizer::{TokenReference, TokenType},
+};
+use std::collections::HashMap;
+
+#[cfg(feature = "roblox")]
+use crate::ast::types::*;
+
+/// Identifies a [`Scope`] inside of a [`ScopeTree`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(usize);
+
+/// Identifies a [`Variable`] inside of a [`ScopeTree`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VariableId(usize);
+
+/// What created a [`Scope`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScopeKind {
+    /// The root scope of the file
+    Chunk,
+    /// The block of a `do`, `while`, or a branch of an `if`
+    Block,
+    /// A function body, including its parameters
+    Function,
+    /// A numeric for loop, including its index variable
+    NumericFor,
+    /// A generic for loop, including its variables
+    GenericFor,
+    /// A repeat loop. The `until` condition is part of this scope, and so can see the
+    /// locals declared inside the loop
+    Repeat,
+}
+
+/// How a [`Variable`] was declared
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VariableKind {
+    /// A name in a local assignment, such as `x` in `local x = 1`
+    Local,
+    /// The name of a local function, such as `x` in `local function x() end`
+    LocalFunction,
+    /// A named parameter of a function, such as `x` in `function(x) end`
+    Parameter,
+    /// A variable of a numeric or generic for loop, such as `i` in `for i = 1, 10 do end`
+    LoopVariable,
+    /// The implicit `self` parameter of a method, such as `function x:y() end`
+    ImplicitSelf,
+}
+
+/// What an identifier refers to, as returned by [`ScopeTree::resolve`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Resolution {
+    /// A local variable declared in the same function
+    Local(VariableId),
+    /// A local variable declared in an enclosing function
+    Upvalue(VariableId),
+    /// A global variable
+    Global,
+}
+
+impl Resolution {
+    /// The variable being referred to, if it is not a global
+    pub fn variable(self) -> Option<VariableId> {
+        match self {
+            Resolution::Local(variable) | Resolution::Upvalue(variable) => Some(variable),
+            Resolution::Global => None,
+        }
+    }
+}
+
+/// A single lexical scope, see [`ScopeKind`] for what creates one
+#[derive(Clone, Debug)]
+pub struct Scope {
+    kind: ScopeKind,
+    parent: Option<ScopeId>,
+    function: ScopeId,
+    children: Vec<ScopeId>,
+    variables: Vec<VariableId>,
+}
+
+impl Scope {
+    /// What created the scope
+    pub fn kind(&self) -> ScopeKind {
+        self.kind
+    }
+
+    /// The scope this one is nested inside of. Only `None` for the root scope
+    pub fn parent(&self) -> Option<ScopeId> {
+        self.parent
+    }
+
+    /// The innermost function (or the root scope) that this scope is a part of.
+    /// For function scopes, this is the scope itself
+    pub fn function(&self) -> ScopeId {
+        self.function
+    }
+
+    /// The scopes nested directly inside of this one, in the order they appear
+    pub fn children(&self) -> &[ScopeId] {
+        &self.children
+    }
+
+    /// The variables declared directly in this scope, in the order they are declared
+    pub fn variables(&self) -> &[VariableId] {
+        &self.variables
+    }
+}
+
+/// A local variable, as well as every usage that refers to it
+#[derive(Clone, Debug)]
+pub struct Variable<'a, 'b> {
+    name: &'b str,
+    token: &'b TokenReference<'a>,
+    kind: VariableKind,
+    scope: ScopeId,
+    references: Vec<&'b TokenReference<'a>>,
+}
+
+impl<'a, 'b> Variable<'a, 'b> {
+    /// The name of the variable
+    pub fn name(&self) -> &'b str {
+        self.name
+    }
+
+    /// The token that declares the variable, such as `x` in `local x = 1`.
+    /// For [`VariableKind::ImplicitSelf`], this is the name of the method.
+    pub fn token(&self) -> &'b TokenReference<'a> {
+        self.token
+    }
+
+    /// How the variable was declared
+    pub fn kind(&self) -> VariableKind {
+        self.kind
+    }
+
+    /// The scope the variable was declared in
+    pub fn scope(&self) -> ScopeId {
+        self.scope
+    }
+
+    /// Every usage of the variable, in the order they appear. Does not include the declaration
+    pub fn references(&self) -> &[&'b TokenReference<'a>] {
+        &self.references
+    }
+}
+
+/// The scopes and variables of an [`Ast`], as created by [`scopes`].
+///
+/// Tokens are looked up by their address, so the tokens passed to methods such as
+/// [`resolve`](ScopeTree::resolve) must come from the same [`Ast`] the tree was created from.
+#[derive(Clone, Debug)]
+pub struct ScopeTree<'a, 'b> {
+    scopes: Vec<Scope>,
+    variables: Vec<Variable<'a, 'b>>,
+    usages: Vec<(&'b TokenReference<'a>, Resolution)>,
+    declarations: HashMap<*const TokenReference<'a>, VariableId>,
+    resolutions: HashMap<*const TokenReference<'a>, Resolution>,
+}
+
+impl<'a, 'b> ScopeTree<'a, 'b> {
+    /// The root scope of the file
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    /// The scope with the given id
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    /// Every scope, in the order they appear
+    pub fn scopes(&self) -> impl Iterator<Item = (ScopeId, &Scope)> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .map(|(index, scope)| (ScopeId(index), scope))
+    }
+
+    /// The variable with the given id
+    pub fn variable(&self, id: VariableId) -> &Variable<'a, 'b> {
+        &self.variables[id.0]
+    }
+
+    /// Every variable, in the order they are declared
+    pub fn variables(&self) -> impl Iterator<Item = (VariableId, &Variable<'a, 'b>)> {
+        self.variables
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| (VariableId(index), variable))
+    }
+
+    /// Every identifier that is used as a variable (not declared), along with what it refers to,
+    /// in the order they appear
+    pub fn usages(&self) -> impl Iterator<Item = (&'b TokenReference<'a>, Resolution)> + '_ {
+        self.usages.iter().copied()
+    }
+
+    /// What the given identifier refers to. Returns `None` if the token is not used as a variable,
+    /// such as declarations or the `y` in `x.y`.
+    pub fn resolve(&self, token: &TokenReference<'a>) -> Option<Resolution> {
+        self.resolutions.get(&(token as *const _)).copied()
+    }
+
+    /// The variable declared by the given token, such as `x` in `local x = 1`
+    pub fn declaration(&self, token: &TokenReference<'a>) -> Option<VariableId> {
+        self.declarations.get(&(token as *const _)).copied()
+    }
+}
+
+/// Creates a [`ScopeTree`] for the given [`Ast`], resolving every identifier used as a variable
+/// to either a local, an upvalue, or a global.
+///
+/// ```rust
+/// # use full_moon::analysis::{scopes, Resolution};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1 print(x)")?;
+/// let tree = scopes(&ast);
+///
+/// let resolved = tree
+///     .usages()
+///     .map(|(token, resolution)| match resolution {
+///         Resolution::Global => format!("global {}", token),
+///         _ => format!("local {}", token),
+///     })
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(resolved, vec!["global print", "local x"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn scopes<'a, 'b>(ast: &'b Ast<'a>) -> ScopeTree<'a, 'b> {
+    let mut builder = ScopeBuilder {
+        tree: ScopeTree {
+            scopes: vec![Scope {
+                kind: ScopeKind::Chunk,
+                parent: None,
+                function: ScopeId(0),
+                children: Vec::new(),
+                variables: Vec::new(),
+            }],
+            variables: Vec::new(),
+            usages: Vec::new(),
+            declarations: HashMap::new(),
+            resolutions: HashMap::new(),
+        },
+        current: ScopeId(0),
+        in_scope: HashMap::new(),
+    };
+
+    builder.block(ast.nodes());
+    builder.tree
+}
+
+struct ScopeBuilder<'a, 'b> {
+    tree: ScopeTree<'a, 'b>,
+    current: ScopeId,
+    in_scope: HashMap<&'b str, Vec<VariableId>>,
+}
+
+fn identifier<'b>(token: &'b TokenReference) -> Option<&'b str> {
+    match token.token_type() {
+        TokenType::Identifier { identifier } => Some(identifier),
+        _ => None,
+    }
+}
+
+impl<'a, 'b> ScopeBuilder<'a, 'b> {
+    fn enter(&mut self, kind: ScopeKind) {
+        let id = ScopeId(self.tree.scopes.len());
+        let function = match kind {
+            ScopeKind::Function => id,
+            _ => self.tree.scope(self.current).function,
+        };
+
+        self.tree.scopes.push(Scope {
+            kind,
+            parent: Some(self.current),
+            function,
+            children: Vec::new(),
+            variables: Vec::new(),
+        });
+
+        self.tree.scopes[self.current.0].children.push(id);
+        self.current = id;
+    }
+
+    fn exit(&mut self) {
+        let scope = &self.tree.scopes[self.current.0];
+
+        for variable in &scope.variables {
+            let name = self.tree.variables[variable.0].name;
+            if let Some(shadowed) = self.in_scope.get_mut(name) {
+                shadowed.pop();
+            }
+        }
+
+        self.current = scope.parent.expect("exited the root scope");
+    }
+
+    fn declare(&mut self, token: &'b TokenReference<'a>, kind: VariableKind) {
+        let name = match kind {
+            VariableKind::ImplicitSelf => "self",
+            _ => match identifier(token) {
+                Some(name) => name,
+                None => return,
+            },
+        };
+
+        let id = VariableId(self.tree.variables.len());
+        self.tree.variables.push(Variable {
+            name,
+            token,
+            kind,
+            scope: self.current,
+            references: Vec::new(),
+        });
+
+        self.tree.scopes[self.current.0].variables.push(id);
+        self.tree.declarations.insert(token, id);
+        self.in_scope.entry(name).or_default().push(id);
+    }
+
+    fn reference(&mut self, token: &'b TokenReference<'a>) {
+        let name = match identifier(token) {
+            Some(name) => name,
+            None => return,
+        };
+
+        let resolution = match self
+            .in_scope
+            .get(name)
+            .and_then(|variables| variables.last())
+        {
+            Some(&id) => {
+                let variable = &mut self.tree.variables[id.0];
+                variable.references.push(token);
+
+                let declared_in = self.tree.scopes[variable.scope.0].function;
+                if declared_in == self.tree.scope(self.current).function {
+                    Resolution::Local(id)
+                } else {
+                    Resolution::Upvalue(id)
+                }
+            }
+
+            None => Resolution::Global,
+        };
+
+        self.tree.usages.push((token, resolution));
+        self.tree.resolutions.insert(token, resolution);
+    }
+
+    fn block(&mut self, block: &'b Block<'a>) {
+        for stmt in block.stmts() {
+            self.stmt(stmt);
+        }
+
+        if let Some(LastStmt::Return(r#return)) = block.last_stmt() {
+            for expression in r#return.returns() {
+                self.expression(expression);
+            }
+        }
+    }
+
+    fn scoped_block(&mut self, kind: ScopeKind, block: &'b Block<'a>) {
+        self.enter(kind);
+        self.block(block);
+        self.exit();
+    }
+
+    fn stmt(&mut self, stmt: &'b Stmt<'a>) {
+        match stmt {
+            Stmt::Assignment(assignment) => {
+                for var in assignment.variables() {
+                    self.var(var);
+                }
+
+                for expression in assignment.expressions() {
+                    self.expression(expression);
+                }
+            }
+
+            Stmt::Do(r#do) => self.scoped_block(ScopeKind::Block, r#do.block()),
+
+            Stmt::FunctionCall(call) => self.function_call(call),
+
+            Stmt::FunctionDeclaration(declaration) => {
+                let name = declaration.name();
+                if let Some(base) = name.names().iter().next() {
+                    self.reference(base);
+                }
+
+                self.function_body(declaration.body(), name.method_name());
+            }
+
+            Stmt::GenericFor(generic_for) => {
+                for expression in generic_for.expressions() {
+                    self.expression(expression);
+                }
+
+                self.enter(ScopeKind::GenericFor);
+
+                #[cfg(feature = "roblox")]
+                for type_specifier in generic_for.type_specifiers().flatten() {
+                    self.type_info(type_specifier.type_info());
+                }
+
+                for name in generic_for.names() {
+                    self.declare(name, VariableKind::LoopVariable);
+                }
+
+                self.block(generic_for.block());
+                self.exit();
+            }
+
+            Stmt::If(r#if) => {
+                self.expression(r#if.condition());
+                self.scoped_block(ScopeKind::Block, r#if.block());
+
+                for else_if in r#if.else_if().into_iter().flatten() {
+                    self.expression(else_if.condition());
+                    self.scoped_block(ScopeKind::Block, else_if.block());
+                }
+
+                if let Some(block) = r#if.else_block() {
+                    self.scoped_block(ScopeKind::Block, block);
+                }
+            }
+
+            Stmt::LocalAssignment(local_assignment) => {
+                for expression in local_assignment.expressions() {
+                    self.expression(expression);
+                }
+
+                #[cfg(feature = "roblox")]
+                for type_specifier in local_assignment.type_specifiers().flatten() {
+                    self.type_info(type_specifier.type_info());
+                }
+
+                for name in local_assignment.names() {
+                    self.declare(name, VariableKind::Local);
+                }
+            }
+
+            Stmt::LocalFunction(local_function) => {
+                self.declare(local_function.name(), VariableKind::LocalFunction);
+                self.function_body(local_function.body(), None);
+            }
+
+            Stmt::NumericFor(numeric_for) => {
+                self.expression(numeric_for.start());
+                self.expression(numeric_for.end());
+                if let Some(step) = numeric_for.step() {
+                    self.expression(step);
+                }
+
+                self.enter(ScopeKind::NumericFor);
+
+                #[cfg(feature = "roblox")]
+                if let Some(type_specifier) = numeric_for.type_specifier() {
+                    self.type_info(type_specifier.type_info());
+                }
+
+                self.declare(numeric_for.index_variable(), VariableKind::LoopVariable);
+                self.block(numeric_for.block());
+                self.exit();
+            }
+
+            Stmt::Repeat(repeat) => {
+                self.enter(ScopeKind::Repeat);
+                self.block(repeat.block());
+                self.expression(repeat.until());
+                self.exit();
+            }
+
+            Stmt::While(r#while) => {
+                self.expression(r#while.condition());
+                self.scoped_block(ScopeKind::Block, r#while.block());
+            }
+
+            #[cfg(feature = "roblox")]
+            Stmt::CompoundAssignment(compound_assignment) => {
+                self.var(compound_assignment.lhs());
+                self.expression(compound_assignment.rhs());
+            }
+
+            #[cfg(feature = "roblox")]
+            Stmt::ExportedTypeDeclaration(exported_type_declaration) => {
+                self.type_info(
+                    exported_type_declaration
+                        .type_declaration()
+                        .type_definition(),
+                );
+            }
+
+            #[cfg(feature = "roblox")]
+            Stmt::TypeDeclaration(type_declaration) => {
+                self.type_info(type_declaration.type_definition());
+            }
+
+            #[cfg(feature = "lua52")]
+            Stmt::Goto(_) | Stmt::Label(_) => {}
+        }
+    }
+
+    fn function_body(
+        &mut self,
+        body: &'b FunctionBody<'a>,
+        implicit_self: Option<&'b TokenReference<'a>>,
+    ) {
+        self.enter(ScopeKind::Function);
+
+        if let Some(method_name) = implicit_self {
+            self.declare(method_name, VariableKind::ImplicitSelf);
+        }
+
+        #[cfg(feature = "roblox")]
+        {
+            for type_specifier in body.type_specifiers().flatten() {
+                self.type_info(type_specifier.type_info());
+            }
+
+            if let Some(return_type) = body.return_type() {
+                self.type_info(return_type.type_info());
+            }
+        }
+
+        for parameter in body.parameters() {
+            if let Parameter::Name(name) = parameter {
+                self.declare(name, VariableKind::Parameter);
+            }
+        }
+
+        self.block(body.block());
+        self.exit();
+    }
+
+    fn expression(&mut self, expression: &'b Expression<'a>) {
+        match expression {
+            Expression::BinaryOperator { lhs, rhs, .. } => {
+                self.expression(lhs);
+                self.expression(rhs);
+            }
+
+            Expression::Parentheses { expression, .. }
+            | Expression::UnaryOperator { expression, .. } => self.expression(expression),
+
+            Expression::Value {
+                value,
+                #[cfg(feature = "roblox")]
+                type_assertion,
+            } => {
+                self.value(value);
+
+                #[cfg(feature = "roblox")]
+                if let Some(type_assertion) = type_assertion {
+                    self.type_info(type_assertion.cast_to());
+                }
+            }
+        }
+    }
+
+    fn value(&mut self, value: &'b Value<'a>) {
+        match value {
+            Value::Function(function) => self.function_body(function.body(), None),
+            Value::FunctionCall(call) => self.function_call(call),
+            Value::TableConstructor(table_constructor) => self.table_constructor(table_constructor),
+            Value::ParenthesesExpression(expression) => self.expression(expression),
+            Value::Var(var) => self.var(var),
+            Value::Number(_) | Value::String(_) | Value::Symbol(_) => {}
+        }
+    }
+
+    fn table_constructor(&mut self, table_constructor: &'b TableConstructor<'a>) {
+        for field in table_constructor.fields() {
+            match field {
+                Field::ExpressionKey { key, value, .. } => {
+                    self.expression(key);
+                    self.expression(value);
+                }
+
+                Field::NameKey { value, .. } | Field::NoKey(value) => self.expression(value),
+            }
+        }
+    }
+
+    fn var(&mut self, var: &'b Var<'a>) {
+        match var {
+            Var::Name(name) => self.reference(name),
+            Var::Expression(var_expression) => {
+                self.prefix(var_expression.prefix());
+                self.suffixes(var_expression.suffixes());
+            }
+        }
+    }
+
+    fn function_call(&mut self, call: &'b FunctionCall<'a>) {
+        self.prefix(call.prefix());
+        self.suffixes(call.suffixes());
+    }
+
+    fn prefix(&mut self, prefix: &'b Prefix<'a>) {
+        match prefix {
+            Prefix::Expression(expression) => self.expression(expression),
+            Prefix::Name(name) => self.reference(name),
+        }
+    }
+
+    fn suffixes(&mut self, suffixes: impl Iterator<Item = &'b Suffix<'a>>) {
+        for suffix in suffixes {
+            match suffix {
+                Suffix::Index(Index::Brackets { expression, .. }) => self.expression(expression),
+                Suffix::Index(Index::Dot { .. }) => {}
+                Suffix::Call(Call::AnonymousCall(args)) => self.function_args(args),
+                Suffix::Call(Call::MethodCall(method_call)) => {
+                    self.function_args(method_call.args())
+                }
+            }
+        }
+    }
+
+    fn function_args(&mut self, args: &'b FunctionArgs<'a>) {
+        match args {
+            FunctionArgs::Parentheses { arguments, .. } => {
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+
+            FunctionArgs::TableConstructor(table_constructor) => {
+                self.table_constructor(table_constructor)
+            }
+
+            FunctionArgs::String(_) => {}
+        }
+    }
+
+    // Types can only refer to variables through `typeof(...)`
+    #[cfg(feature = "roblox")]
+    fn type_info(&mut self, type_info: &'b TypeInfo<'a>) {
+        match type_info {
+            TypeInfo::Array { type_info, .. }
+            | TypeInfo::Optional {
+                base: type_info, ..
+            }
+            | TypeInfo::Variadic { type_info, .. } => self.type_info(type_info),
+
+            TypeInfo::Callback {
+                arguments,
+                return_type,
+                ..
+            } => {
+                for argument in arguments {
+                    self.type_info(argument);
+                }
+
+                self.type_info(return_type);
+            }
+
+            TypeInfo::Generic {
+                generics: types, ..
+            }
+            | TypeInfo::Tuple { types, .. } => {
+                for type_info in types {
+                    self.type_info(type_info);
+                }
+            }
+
+            TypeInfo::Module { type_info, .. } => {
+                if let IndexedTypeInfo::Generic { generics, .. } = &**type_info {
+                    for type_info in generics {
+                        self.type_info(type_info);
+                    }
+                }
+            }
+
+            TypeInfo::Intersection { left, right, .. } | TypeInfo::Union { left, right, .. } => {
+                self.type_info(left);
+                self.type_info(right);
+            }
+
+            TypeInfo::Table { fields, .. } => {
+                for field in fields {
+                    if let TypeFieldKey::IndexSignature { inner, .. } = field.key() {
+                        self.type_info(inner);
+                    }
+
+                    self.type_info(field.value());
+                }
+            }
+
+            TypeInfo::Typeof { inner, .. } => self.expression(inner),
+
+            TypeInfo::Basic(_) => {}
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{
+    ast::*,
+    node::Node,
+    tokenizer::{TokenKind, TokenReference},
+    visitors::Visitor,
+};
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Metrics about a single function, as found by [`stats`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct FunctionStats {
+    name: Option<String>,
+    line: usize,
+    parameters: usize,
+    statements: usize,
+    lines: usize,
+}
+
+impl FunctionStats {
+    /// The name of the function as written, such as `a.b:c` or `f` in `local function f() end`.
+    /// `None` for anonymous functions, even when they're assigned to a name
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The line the function starts on, starting from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The number of parameters, including `...`. The implicit `self` of methods is not counted
+    pub fn parameters(&self) -> usize {
+        self.parameters
+    }
+
+    /// The number of statements in the function, including those in nested blocks such as `if`s,
+    /// but not those in nested functions
+    pub fn statements(&self) -> usize {
+        self.statements
+    }
+
+    /// The number of lines from the start of the function to its `end`, including both
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+}
+
+/// Structural metrics about an [`Ast`], as found by [`stats`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct AstStats {
+    statements: usize,
+    functions: Vec<FunctionStats>,
+    max_depth: usize,
+    longest_function_statements: usize,
+    longest_function_lines: usize,
+    tokens: usize,
+    lines: usize,
+    comment_lines: usize,
+    comment_density: f64,
+}
+
+impl AstStats {
+    /// The number of statements, including last statements such as `return` and `break`
+    pub fn statements(&self) -> usize {
+        self.statements
+    }
+
+    /// Every function, in the order they start in
+    pub fn functions(&self) -> &[FunctionStats] {
+        &self.functions
+    }
+
+    /// The number of functions, including anonymous functions
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// The deepest nesting of blocks. Code at the top level is at a depth of 0, and every block
+    /// of a `do`, `if`, `elseif`, `else`, loop, or function body is one deeper than the block around it
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// The most [statements](FunctionStats::statements) in a function, or 0 if there are none
+    pub fn longest_function_statements(&self) -> usize {
+        self.longest_function_statements
+    }
+
+    /// The most [lines](FunctionStats::lines) in a function, or 0 if there are none
+    pub fn longest_function_lines(&self) -> usize {
+        self.longest_function_lines
+    }
+
+    /// The number of tokens that are not trivia, not including the end of file
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
+    /// The number of lines in the code. A new line at the very end does not start another line
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// The number of lines with at least part of a comment on them
+    pub fn comment_lines(&self) -> usize {
+        self.comment_lines
+    }
+
+    /// The [lines with comments](AstStats::comment_lines) divided by all [lines](AstStats::lines),
+    /// from 0 to 1. 0 if there are no lines
+    pub fn comment_density(&self) -> f64 {
+        self.comment_density
+    }
+}
+
+/// Computes the structural metrics of the [`Ast`] in a single traversal.
+/// Lines are counted from the positions of tokens.
+///
+/// ```rust
+/// # use full_moon::analysis::stats;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("-- Adds one\nlocal function add(x)\n    return x + 1\nend\n")?;
+/// let stats = stats(&ast);
+///
+/// assert_eq!(stats.statements(), 2);
+/// assert_eq!(stats.max_depth(), 1);
+/// assert_eq!(stats.lines(), 4);
+/// assert_eq!(stats.comment_density(), 0.25);
+///
+/// let add = &stats.functions()[0];
+/// assert_eq!((add.name(), add.parameters(), add.lines()), (Some("add"), 1, 3));
+/// # Ok(())
+/// # }
+/// ```
+pub fn stats(ast: &Ast) -> AstStats {
+    let mut collector = StatsCollector::default();
+    collector.visit_ast(ast);
+
+    let lines = match ast.eof().start_position() {
+        Some(position) if position.character() == 1 => position.line() - 1,
+        Some(position) => position.line(),
+        None => 0,
+    };
+
+    let comment_lines = collector.comment_lines.len();
+    let functions = collector.functions;
+
+    AstStats {
+        statements: collector.statements,
+        max_depth: collector.max_depth,
+        longest_function_statements: functions
+            .iter()
+            .map(FunctionStats::statements)
+            .max()
+            .unwrap_or(0),
+        longest_function_lines: functions
+            .iter()
+            .map(FunctionStats::lines)
+            .max()
+            .unwrap_or(0),
+        functions,
+        tokens: collector.tokens,
+        lines,
+        comment_lines,
+        comment_density: if lines == 0 {
+            0.0
+        } else {
+            comment_lines as f64 / lines as f64
+        },
+    }
+}
+
+fn line_of<'a>(node: &impl Node<'a>) -> usize {
+    node.start_position().map_or(0, |position| position.line())
+}
+
+#[derive(Default)]
+struct StatsCollector {
+    statements: usize,
+    functions: Vec<FunctionStats>,
+    // The indexes of the functions the visitor is currently inside of
+    function_stack: Vec<usize>,
+    // The name and start line of a function whose body is about to be visited
+    pending_function: Option<(Option<String>, usize)>,
+    depth: usize,
+    max_depth: usize,
+    tokens: usize,
+    comment_lines: HashSet<usize>,
+}
+
+impl<'a> Visitor<'a> for StatsCollector {
+    fn visit_block(&mut self, _: &Block<'a>) {
+        // The block at the top level is at a depth of 0
+        self.max_depth = self.max_depth.max(self.depth);
+        self.depth += 1;
+    }
+
+    fn visit_block_end(&mut self, _: &Block<'a>) {
+        self.depth -= 1;
+    }
+
+    fn visit_stmt(&mut self, _: &Stmt<'a>) {
+        self.count_statement();
+    }
+
+    fn visit_last_stmt(&mut self, _: &LastStmt<'a>) {
+        self.count_statement();
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration<'a>) {
+        let name = declaration.name();
+        let mut path = name
+            .names()
+            .iter()
+            .map(|name| name.token().to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+
+        if let Some(method_name) = name.method_name() {
+            path = format!("{}:{}", path, method_name.token());
+        }
+
+        self.pending_function = Some((Some(path), line_of(declaration)));
+    }
+
+    fn visit_local_function(&mut self, local_function: &LocalFunction<'a>) {
+        self.pending_function = Some((
+            Some(local_function.name().token().to_string()),
+            line_of(local_function),
+        ));
+    }
+
+    fn visit_anonymous_function(&mut self, function: &AnonymousFunction<'a>) {
+        self.pending_function = Some((None, line_of(function)));
+    }
+
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        let (name, line) = self
+            .pending_function
+            .take()
+            .unwrap_or_else(|| (None, line_of(body)));
+
+        self.function_stack.push(self.functions.len());
+        self.functions.push(FunctionStats {
+            name,
+            line,
+            parameters: body.parameters().len(),
+            statements: 0,
+            lines: 0,
+        });
+    }
+
+    fn visit_function_body_end(&mut self, body: &FunctionBody<'a>) {
+        if let Some(index) = self.function_stack.pop() {
+            let function = &mut self.functions[index];
+            let end_line = body
+                .end_token()
+                .end_position()
+                .map_or(function.line, |position| position.line());
+            function.lines = end_line.saturating_sub(function.line) + 1;
+        }
+    }
+
+    fn visit_token_reference(&mut self, token: &TokenReference<'a>) {
+        if token.token_kind() != TokenKind::Eof {
+            self.tokens += 1;
+        }
+
+        for trivia in token.leading_trivia().chain(token.trailing_trivia()) {
+            if matches!(
+                trivia.token_kind(),
+                TokenKind::SingleLineComment | TokenKind::MultiLineComment
+            ) {
+                self.comment_lines
+                    .extend(trivia.start_position().line()..=trivia.end_position().line());
+            }
+        }
+    }
+}
+
+impl StatsCollector {
+    fn count_statement(&mut self) {
+        self.statements += 1;
+
+        if let Some(&index) = self.function_stack.last() {
+            self.functions[index].statements += 1;
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// Accessors for the variants of `Stmt` and `Value`, so that callers don't need to know which
+// variants are boxed to keep the enums small.
+use super::*;
+
+macro_rules! variant_accessors {
+    ($enum:ident { $($(#[$meta:meta])* $variant:ident($node:ty) => $as:ident, $as_mut:ident;)* }) => {
+        impl<'a> $enum<'a> {
+            $(
+                $(#[$meta])*
+                #[doc = concat!("Returns the [`", stringify!($node), "`] if this is a `", stringify!($enum), "::", stringify!($variant), "`")]
+                pub fn $as(&self) -> Option<&$node> {
+                    match self {
+                        $enum::$variant(node) => Some(node),
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    }
+                }
+
+                $(#[$meta])*
+                #[doc = concat!("Returns the [`", stringify!($node), "`] mutably if this is a `", stringify!($enum), "::", stringify!($variant), "`")]
+                pub fn $as_mut(&mut self) -> Option<&mut $node> {
+                    match self {
+                        $enum::$variant(node) => Some(node),
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    }
+                }
+            )*
+        }
+    };
+}
+
+variant_accessors!(Stmt {
+    Assignment(Assignment<'a>) => as_assignment, as_assignment_mut;
+    Do(Do<'a>) => as_do, as_do_mut;
+    FunctionCall(FunctionCall<'a>) => as_function_call, as_function_call_mut;
+    FunctionDeclaration(FunctionDeclaration<'a>) => as_function_declaration, as_function_declaration_mut;
+    GenericFor(GenericFor<'a>) => as_generic_for, as_generic_for_mut;
+    If(If<'a>) => as_if, as_if_mut;
+    LocalAssignment(LocalAssignment<'a>) => as_local_assignment, as_local_assignment_mut;
+    LocalFunction(LocalFunction<'a>) => as_local_function, as_local_function_mut;
+    NumericFor(NumericFor<'a>) => as_numeric_for, as_numeric_for_mut;
+    Repeat(Repeat<'a>) => as_repeat, as_repeat_mut;
+    While(While<'a>) => as_while, as_while_mut;
+    #[cfg(feature = "roblox")]
+    CompoundAssignment(CompoundAssignment<'a>) => as_compound_assignment, as_compound_assignment_mut;
+    #[cfg(feature = "roblox")]
+    ExportedTypeDeclaration(ExportedTypeDeclaration<'a>) => as_exported_type_declaration, as_exported_type_declaration_mut;
+    #[cfg(feature = "roblox")]
+    TypeDeclaration(TypeDeclaration<'a>) => as_type_declaration, as_type_declaration_mut;
+    #[cfg(feature = "lua52")]
+    Goto(Goto<'a>) => as_goto, as_goto_mut;
+    #[cfg(feature = "lua52")]
+    Label(Label<'a>) => as_label, as_label_mut;
+});
+
+variant_accessors!(Value {
+    Function(AnonymousFunction<'a>) => as_function, as_function_mut;
+    FunctionCall(FunctionCall<'a>) => as_function_call, as_function_call_mut;
+    TableConstructor(TableConstructor<'a>) => as_table_constructor, as_table_constructor_mut;
+    Var(Var<'a>) => as_var, as_var_mut;
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_boxed_variants() {
+        let mut ast = parse("do end\nlocal x = { 1 }").unwrap();
+        let stmts: Vec<_> = ast.nodes().stmts().collect();
+
+        assert!(stmts[0].as_do().is_some());
+        assert!(stmts[0].as_local_assignment().is_none());
+
+        let value = match stmts[1]
+            .as_local_assignment()
+            .unwrap()
+            .expressions()
+            .iter()
+            .next()
+        {
+            Some(Expression::Value { value, .. }) => value,
+            other => panic!("expected value, got {:?}", other),
+        };
+        assert_eq!(value.as_table_constructor().unwrap().fields().len(), 1);
+
+        let nodes = ast.nodes_mut();
+        let r#do = nodes
+            .stmts
+            .iter_mut()
+            .next()
+            .unwrap()
+            .0
+            .as_do_mut()
+            .unwrap();
+        r#do.block = parse("x() ").unwrap().nodes().clone();
+        assert_eq!(nodes.to_string(), "do x() end\nlocal x = { 1 }");
+    }
+}
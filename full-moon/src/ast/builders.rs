@@ -0,0 +1,409 @@
+//! Functions for building parts of the AST from scratch, with tokens spaced the way
+//! most Lua code is written, so that printing the result gives readable code.
+//!
+//! ```rust
+//! # use full_moon::{ast::{builders::{expr, stmt}, BinOp}, tokenizer::{Symbol, TokenReference}};
+//! let statement = stmt::local(
+//!     ["result"],
+//!     vec![expr::call(
+//!         expr::name("compute"),
+//!         vec![
+//!             expr::binop(
+//!                 expr::name("x"),
+//!                 BinOp::Plus(TokenReference::from_symbol(Symbol::Plus)),
+//!                 expr::number(1),
+//!             ),
+//!             expr::string("mode"),
+//!         ],
+//!     )],
+//! );
+//!
+//! assert_eq!(statement.to_string(), "local result = compute(x + 1, \"mode\")");
+//! ```
+
+use super::{
+    punctuated::{Pair, Punctuated},
+    span::ContainedSpan,
+};
+use crate::tokenizer::{Symbol, Token, TokenReference, TokenType};
+
+pub(crate) fn identifier<'a>(name: &str) -> Option<TokenReference<'a>> {
+    let mut token = TokenReference::new(
+        Vec::new(),
+        Token::new(TokenType::Identifier {
+            identifier: "_".into(),
+        }),
+        Vec::new(),
+    );
+
+    token.set_identifier(name).ok().map(|_| token)
+}
+
+fn comma_separated<'a, T>(values: impl IntoIterator<Item = T>) -> Punctuated<'a, T> {
+    let mut punctuated = Punctuated::new();
+    for value in values {
+        if let Some(Pair::End(last)) = punctuated.pop() {
+            punctuated.push(Pair::Punctuated(
+                last,
+                TokenReference::spaced_symbol("", Symbol::Comma, " "),
+            ));
+        }
+
+        punctuated.push(Pair::End(value));
+    }
+
+    punctuated
+}
+
+/// Functions for building [`Expression`]s
+pub mod expr {
+    use super::{comma_separated, identifier, ContainedSpan};
+    use crate::{
+        ast::{
+            BinOp, Call, Expression, FunctionArgs, FunctionCall, Index, Prefix, Suffix, UnOp,
+            Value, Var, VarExpression,
+        },
+        tokenizer::{StringLiteralQuoteType, Symbol, Token, TokenReference, TokenType},
+    };
+
+    fn value(value: Value<'_>) -> Expression<'_> {
+        Expression::Value {
+            value: Box::new(value),
+            #[cfg(feature = "roblox")]
+            type_assertion: None,
+        }
+    }
+
+    fn parentheses(expression: Expression<'_>) -> Expression<'_> {
+        Expression::Parentheses {
+            contained: Box::new(ContainedSpan::parentheses()),
+            expression: Box::new(expression),
+        }
+    }
+
+    // Splits an expression into the prefix and suffixes that can be added on to,
+    // putting it in parentheses if it can't be indexed or called as is
+    fn prefix_suffixes(base: Expression<'_>) -> (Prefix<'_>, Vec<Suffix<'_>>) {
+        let base = match base {
+            #[cfg(feature = "roblox")]
+            Expression::Value {
+                type_assertion: Some(_),
+                ..
+            } => base,
+
+            Expression::Value { value: inner, .. } => match *inner {
+                Value::Var(Var::Name(name)) => return (Prefix::Name(name), Vec::new()),
+                Value::Var(Var::Expression(var_expression)) => {
+                    return (var_expression.prefix, var_expression.suffixes)
+                }
+                Value::FunctionCall(call) => return (call.prefix, call.suffixes),
+                other => value(other),
+            },
+
+            Expression::Parentheses { .. } => return (Prefix::Expression(base), Vec::new()),
+            other => other,
+        };
+
+        (Prefix::Expression(parentheses(base)), Vec::new())
+    }
+
+    /// A number, such as `5` or `0.5`. Negative numbers are negated with `-`, such as `-5`.
+    ///
+    /// # Panics
+    /// Panics if the number is infinite or NaN, since those can't be written as a number in Lua.
+    pub fn number<'a>(number: impl Into<f64>) -> Expression<'a> {
+        let number = number.into();
+        assert!(
+            number.is_finite(),
+            "{} can't be written as a number",
+            number
+        );
+
+        let expression = value(Value::Number(TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::Number {
+                text: number.abs().to_string().into(),
+            }),
+            Vec::new(),
+        )));
+
+        if number.is_sign_negative() {
+            Expression::UnaryOperator {
+                unop: UnOp::Minus(TokenReference::from_symbol(Symbol::Minus)),
+                expression: Box::new(expression),
+            }
+        } else {
+            expression
+        }
+    }
+
+    /// A double quoted string, such as `"hi"`, escaping the contents as needed
+    pub fn string<'a>(contents: &str) -> Expression<'a> {
+        let mut string = TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::StringLiteral {
+                literal: "".into(),
+                multi_line: None,
+                quote_type: StringLiteralQuoteType::Double,
+            }),
+            Vec::new(),
+        );
+
+        string
+            .set_string_contents(contents)
+            .expect("token was not a string");
+
+        value(Value::String(string))
+    }
+
+    /// A variable, such as `foo`
+    ///
+    /// # Panics
+    /// Panics if the name is not a valid identifier, such as a keyword.
+    pub fn name<'a>(name: &str) -> Expression<'a> {
+        let name =
+            identifier(name).unwrap_or_else(|| panic!("{:?} is not a valid identifier", name));
+
+        value(Value::Var(Var::Name(name)))
+    }
+
+    /// Indexes the base with a field, such as `base.field`. If the field isn't a valid identifier,
+    /// such as a keyword, a string key is used instead, such as `base["end"]`.
+    /// The base is put in parentheses if it can't be indexed as is, such as `("text").len`.
+    pub fn index<'a>(base: Expression<'a>, field: &str) -> Expression<'a> {
+        let index = match identifier(field) {
+            Some(name) => Index::Dot {
+                dot: TokenReference::from_symbol(Symbol::Dot),
+                name,
+            },
+
+            None => Index::Brackets {
+                brackets: ContainedSpan::brackets(),
+                expression: string(field),
+            },
+        };
+
+        let (prefix, mut suffixes) = prefix_suffixes(base);
+        suffixes.push(Suffix::Index(index));
+        value(Value::Var(Var::Expression(
+            VarExpression::new(prefix).with_suffixes(suffixes),
+        )))
+    }
+
+    /// Calls the base with the arguments, such as `base(a, b)`.
+    /// The base is put in parentheses if it can't be called as is, such as `(function() end)()`.
+    pub fn call<'a>(
+        base: Expression<'a>,
+        arguments: impl IntoIterator<Item = Expression<'a>>,
+    ) -> Expression<'a> {
+        let (prefix, mut suffixes) = prefix_suffixes(base);
+        suffixes.push(Suffix::Call(Call::AnonymousCall(
+            FunctionArgs::Parentheses {
+                parentheses: ContainedSpan::parentheses(),
+                arguments: comma_separated(arguments),
+            },
+        )));
+
+        value(Value::FunctionCall(
+            FunctionCall::new(prefix).with_suffixes(suffixes),
+        ))
+    }
+
+    // Whether the operand has to be put in parentheses to keep the same meaning next to the operator
+    fn needs_parentheses(operand: &Expression<'_>, binop: &BinOp<'_>, is_rhs: bool) -> bool {
+        let precedence = match operand {
+            Expression::BinaryOperator { binop, .. } => binop.precedence(),
+            Expression::UnaryOperator { unop, .. } if !is_rhs => unop.precedence(),
+            _ => return false,
+        };
+
+        precedence < binop.precedence()
+            || (precedence == binop.precedence() && binop.is_right_associative() != is_rhs)
+    }
+
+    /// A binary operation, such as `lhs + rhs`. Either side is put in parentheses if the
+    /// precedence of the operator requires it, such as `(a + b) * c`.
+    /// Operators without any trivia, such as ones created with [`TokenReference::from_symbol`],
+    /// are surrounded with spaces.
+    pub fn binop<'a>(lhs: Expression<'a>, binop: BinOp<'a>, rhs: Expression<'a>) -> Expression<'a> {
+        let token = binop.token();
+        let binop = if token.leading_trivia().next().is_none()
+            && token.trailing_trivia().next().is_none()
+        {
+            let token = TokenReference::new(
+                vec![Token::new(TokenType::spaces(1))],
+                token.token().clone(),
+                vec![Token::new(TokenType::spaces(1))],
+            );
+
+            binop.with_token(token)
+        } else {
+            binop
+        };
+
+        let lhs = if needs_parentheses(&lhs, &binop, false) {
+            parentheses(lhs)
+        } else {
+            lhs
+        };
+
+        let rhs = if needs_parentheses(&rhs, &binop, true) {
+            parentheses(rhs)
+        } else {
+            rhs
+        };
+
+        Expression::BinaryOperator {
+            lhs: Box::new(lhs),
+            binop,
+            rhs: Box::new(rhs),
+        }
+    }
+}
+
+/// Functions for building [`Stmt`](crate::ast::Stmt)s
+pub mod stmt {
+    use super::{comma_separated, identifier};
+    use crate::{
+        ast::{Expression, LocalAssignment, Stmt},
+        tokenizer::{Symbol, TokenReference},
+    };
+
+    /// A local assignment, such as `local a, b = 1, 2`. Without any expressions,
+    /// this only declares the names, such as `local a, b`.
+    ///
+    /// # Panics
+    /// Panics if any of the names are not valid identifiers, such as keywords.
+    pub fn local<'a, N: AsRef<str>>(
+        names: impl IntoIterator<Item = N>,
+        expressions: impl IntoIterator<Item = Expression<'a>>,
+    ) -> Stmt<'a> {
+        let names = comma_separated(names.into_iter().map(|name| {
+            let name = name.as_ref();
+            identifier(name).unwrap_or_else(|| panic!("{:?} is not a valid identifier", name))
+        }));
+
+        let expressions = comma_separated(expressions);
+        let equal_token = if expressions.is_empty() {
+            None
+        } else {
+            Some(TokenReference::spaced_symbol(" ", Symbol::Equal, " "))
+        };
+
+        Stmt::LocalAssignment(
+            LocalAssignment::new(names)
+                .with_equal_token(equal_token)
+                .with_expressions(expressions),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expr, stmt};
+    use crate::{
+        ast::BinOp,
+        tokenizer::{Symbol, TokenReference},
+    };
+
+    fn plus() -> BinOp<'static> {
+        BinOp::Plus(TokenReference::from_symbol(Symbol::Plus))
+    }
+
+    fn star() -> BinOp<'static> {
+        BinOp::Star(TokenReference::from_symbol(Symbol::Star))
+    }
+
+    fn caret() -> BinOp<'static> {
+        BinOp::Caret(TokenReference::from_symbol(Symbol::Caret))
+    }
+
+    #[test]
+    fn test_binop_parentheses() {
+        let sum = expr::binop(expr::name("a"), plus(), expr::name("b"));
+        let product = expr::binop(expr::name("a"), star(), expr::name("b"));
+
+        assert_eq!(
+            expr::binop(sum.clone(), star(), expr::name("c")).to_string(),
+            "(a + b) * c"
+        );
+        assert_eq!(
+            expr::binop(expr::name("c"), plus(), product).to_string(),
+            "c + a * b"
+        );
+        assert_eq!(
+            expr::binop(sum.clone(), plus(), expr::name("c")).to_string(),
+            "a + b + c"
+        );
+        assert_eq!(
+            expr::binop(expr::name("c"), plus(), sum).to_string(),
+            "c + (a + b)"
+        );
+
+        let power = expr::binop(expr::name("a"), caret(), expr::name("b"));
+        assert_eq!(
+            expr::binop(expr::name("c"), caret(), power.clone()).to_string(),
+            "c ^ a ^ b"
+        );
+        assert_eq!(
+            expr::binop(power, caret(), expr::name("c")).to_string(),
+            "(a ^ b) ^ c"
+        );
+
+        assert_eq!(
+            expr::binop(expr::number(-2), caret(), expr::number(-0.5)).to_string(),
+            "(-2) ^ -0.5"
+        );
+
+        let spaced = BinOp::Plus(TokenReference::symbol("+  ").unwrap());
+        assert_eq!(
+            expr::binop(expr::number(1), spaced, expr::number(2)).to_string(),
+            "1+  2"
+        );
+    }
+
+    #[test]
+    fn test_index_and_call() {
+        let format = expr::index(expr::name("string"), "format");
+        assert_eq!(format.to_string(), "string.format");
+        assert_eq!(
+            expr::call(format, vec![expr::string("%d\n"), expr::number(1.5)]).to_string(),
+            "string.format(\"%d\\n\", 1.5)"
+        );
+
+        let call = expr::call(expr::name("get"), Vec::new());
+        assert_eq!(
+            expr::index(expr::index(call, "end"), "x").to_string(),
+            "get()[\"end\"].x"
+        );
+
+        assert_eq!(
+            expr::index(expr::string("text"), "len").to_string(),
+            "(\"text\").len"
+        );
+
+        let sum = expr::binop(expr::number(1), plus(), expr::number(2));
+        assert_eq!(
+            expr::call(expr::index(sum, "x"), vec![expr::name("y")]).to_string(),
+            "(1 + 2).x(y)"
+        );
+    }
+
+    #[test]
+    fn test_local() {
+        assert_eq!(
+            stmt::local(["a", "b"], Vec::new()).to_string(),
+            "local a, b"
+        );
+
+        let statement = stmt::local(["a"], vec![expr::number(1), expr::number(2)]);
+        assert_eq!(statement.to_string(), "local a = 1, 2");
+        crate::parse(&statement.to_string()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "\"end\" is not a valid identifier")]
+    fn test_invalid_name() {
+        expr::name("end");
+    }
+}
@@ -0,0 +1,656 @@
+use super::{
+    content::{ContentDeserializer, ContentSerializer},
+    owned::Owned,
+    Ast,
+};
+use crate::tokenizer::{
+    tokens_with_options, Token, TokenKind, TokenReference, TokenType, TokenizerOptions,
+};
+use serde::{
+    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    ser::{
+        self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{borrow::Cow, fmt};
+
+/// A slimmed down form of an [`Ast`] for serialization, created with [`Ast::to_compact`].
+/// Every token is serialized as only its [`TokenKind`](crate::tokenizer::TokenKind) and text,
+/// such as `["Symbol", "local"]`, leaving out trivia and positions, which makes the output many times smaller.
+///
+/// When deserialized, every token other than the end of file gets a single space as trailing trivia,
+/// and positions are recalculated, so the [`Ast`] can still be printed as valid code.
+///
+/// ```rust
+/// # use full_moon::ast::CompactAst;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x=1--comment\nprint(x)")?;
+/// let json = serde_json::to_string(&ast.to_compact())?;
+///
+/// let compact: CompactAst = serde_json::from_str(&json)?;
+/// assert_eq!(full_moon::print(&compact.into_ast()), "local x = 1 print ( x ) ");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompactAst<'a, 'b> {
+    ast: Cow<'b, Ast<'a>>,
+}
+
+impl<'a, 'b> CompactAst<'a, 'b> {
+    /// The [`Ast`] being wrapped
+    pub fn ast(&self) -> &Ast<'a> {
+        &self.ast
+    }
+
+    /// Returns the [`Ast`] being wrapped, cloning it if it was borrowed
+    pub fn into_ast(self) -> Ast<'a> {
+        self.ast.into_owned()
+    }
+}
+
+impl<'a> From<Ast<'a>> for CompactAst<'a, 'static> {
+    fn from(ast: Ast<'a>) -> Self {
+        Self {
+            ast: Cow::Owned(ast),
+        }
+    }
+}
+
+impl<'a> Ast<'a> {
+    /// Wraps the Ast in a [`CompactAst`], which serializes it without trivia and positions
+    pub fn to_compact(&self) -> CompactAst<'a, '_> {
+        CompactAst {
+            ast: Cow::Borrowed(self),
+        }
+    }
+}
+
+impl Serialize for CompactAst<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.ast.serialize(CompactSerializer(serializer))
+    }
+}
+
+impl<'de, 'a, 'b> Deserialize<'de> for CompactAst<'a, 'b> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ast = Ast::deserialize(CompactDeserializer(deserializer))?;
+
+        Ok(CompactAst {
+            ast: Cow::Owned(ast.update_positions()),
+        })
+    }
+}
+
+// Creates the TokenReference of a token serialized as its kind and text
+pub(crate) fn token_reference<'a, E: de::Error>(
+    kind: TokenKind,
+    text: &str,
+) -> Result<TokenReference<'a>, E> {
+    // The text is tokenized again to find the details of the token, such as the quotes of a string.
+    // It was tokenized once already, so it's as lenient as the tokenizer can be.
+    let options = TokenizerOptions::new()
+        .with_allow_unicode_identifiers(true)
+        .with_invalid_escapes_as_warnings(true);
+    let token_type = match tokens_with_options(text, &options)
+        .map(|(tokens, _)| tokens)
+        .as_deref()
+    {
+        Ok([eof]) if kind == TokenKind::Eof => eof.token_type().owned(),
+        Ok([token, _]) if token.token_kind() == kind => token.token_type().owned(),
+        _ => {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Str(text),
+                &format!("the text of a single {:?} token", kind).as_str(),
+            ))
+        }
+    };
+
+    let trailing_trivia = if kind == TokenKind::Eof {
+        Vec::new()
+    } else {
+        vec![Token::new(TokenType::spaces(1))]
+    };
+
+    Ok(TokenReference::new(
+        Vec::new(),
+        Token::new(token_type),
+        trailing_trivia,
+    ))
+}
+
+// Serializes values the same as the serializer it wraps, other than TokenReferences, which are
+// serialized as only their kind and text
+struct CompactSerializer<S>(S);
+
+// A value that's serialized with a CompactSerializer
+struct CompactValue<'a, T: ?Sized>(&'a T);
+
+impl<T: Serialize + ?Sized> Serialize for CompactValue<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(CompactSerializer(serializer))
+    }
+}
+
+impl<S: Serializer> Serializer for CompactSerializer<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = CompactSerializer<S::SerializeSeq>;
+    type SerializeTuple = CompactSerializer<S::SerializeTuple>;
+    type SerializeTupleStruct = CompactSerializer<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = CompactSerializer<S::SerializeTupleVariant>;
+    type SerializeMap = CompactSerializer<S::SerializeMap>;
+    type SerializeStruct = CompactStruct<S>;
+    type SerializeStructVariant = CompactSerializer<S::SerializeStructVariant>;
+
+    fn serialize_bool(self, value: bool) -> Result<S::Ok, S::Error> {
+        self.0.serialize_bool(value)
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i8(value)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i16(value)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i32(value)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i64(value)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u8(value)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u16(value)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u32(value)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u64(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_f32(value)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_f64(value)
+    }
+
+    fn serialize_char(self, value: char) -> Result<S::Ok, S::Error> {
+        self.0.serialize_char(value)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<S::Ok, S::Error> {
+        self.0.serialize_str(value)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<S::Ok, S::Error> {
+        self.0.serialize_bytes(value)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.0.serialize_some(&CompactValue(value))
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize_newtype_struct(name, &CompactValue(value))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.0
+            .serialize_newtype_variant(name, variant_index, variant, &CompactValue(value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        self.0.serialize_seq(len).map(CompactSerializer)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        self.0.serialize_tuple(len).map(CompactSerializer)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        self.0
+            .serialize_tuple_struct(name, len)
+            .map(CompactSerializer)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        self.0
+            .serialize_tuple_variant(name, variant_index, variant, len)
+            .map(CompactSerializer)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        self.0.serialize_map(len).map(CompactSerializer)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        if name == "TokenReference" {
+            return Ok(CompactStruct::TokenReference(self.0, None));
+        }
+
+        self.0
+            .serialize_struct(name, len)
+            .map(CompactStruct::Struct)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        self.0
+            .serialize_struct_variant(name, variant_index, variant, len)
+            .map(CompactSerializer)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! impl_compact_compound {
+    ($($trait:ident::$method:ident),*) => {
+        $(
+            impl<S: $trait> $trait for CompactSerializer<S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+                    self.0.$method(&CompactValue(value))
+                }
+
+                fn end(self) -> Result<S::Ok, S::Error> {
+                    self.0.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_compact_compound!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+impl<S: SerializeMap> SerializeMap for CompactSerializer<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), S::Error> {
+        self.0.serialize_key(&CompactValue(key))
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), S::Error> {
+        self.0.serialize_value(&CompactValue(value))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+impl<S: SerializeStructVariant> SerializeStructVariant for CompactSerializer<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        self.0.serialize_field(key, &CompactValue(value))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        self.0.skip_field(key)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+// The fields of a TokenReference are only read to find the kind and text of its token,
+// which are serialized once all of them have been
+enum CompactStruct<S: Serializer> {
+    Struct(S::SerializeStruct),
+    TokenReference(S, Option<(TokenKind, String)>),
+}
+
+impl<S: Serializer> SerializeStruct for CompactStruct<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        match self {
+            CompactStruct::Struct(state) => state.serialize_field(key, &CompactValue(value)),
+            CompactStruct::TokenReference(serializer, token) => {
+                if key == "token" {
+                    let human_readable = serializer.is_human_readable();
+                    let content = value
+                        .serialize(ContentSerializer::<de::value::Error>::new(human_readable))
+                        .map_err(ser::Error::custom)?;
+                    let value = Token::deserialize(ContentDeserializer::<de::value::Error>::new(
+                        content,
+                        human_readable,
+                    ))
+                    .map_err(ser::Error::custom)?;
+
+                    *token = Some((value.token_kind(), value.to_string()));
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        match self {
+            CompactStruct::Struct(state) => state.skip_field(key),
+            CompactStruct::TokenReference(..) => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            CompactStruct::Struct(state) => state.end(),
+            CompactStruct::TokenReference(serializer, Some(token)) => token.serialize(serializer),
+            CompactStruct::TokenReference(_, None) => {
+                Err(ser::Error::custom("TokenReference has no token"))
+            }
+        }
+    }
+}
+
+// Deserializes values the same as the deserializer it wraps, other than TokenReferences, which
+// are deserialized from only their kind and text
+struct CompactDeserializer<D>(D);
+
+// A visitor, seed, or access of values that are deserialized with a CompactDeserializer
+struct Compact<T>(T);
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $type:ty),*)),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $type,)* visitor: V) -> Result<V::Value, D::Error> {
+                self.0.$method($($arg,)* Compact(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for CompactDeserializer<D> {
+    type Error = D::Error;
+
+    forward_deserialize!(
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any()
+    );
+
+    // TokenReferences tell their compact form apart by being given it as a newtype
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        if name == "TokenReference" {
+            return visitor.visit_newtype_struct(self.0);
+        }
+
+        self.0.deserialize_struct(name, fields, Compact(visitor))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($type:ty)),*) => {
+        $(
+            fn $method<E: de::Error>(self, value: $type) -> Result<V::Value, E> {
+                self.0.$method(value)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Compact<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(formatter)
+    }
+
+    forward_visit!(
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>)
+    );
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.0.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.0.visit_some(CompactDeserializer(deserializer))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.0.visit_unit()
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.0
+            .visit_newtype_struct(CompactDeserializer(deserializer))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.0.visit_seq(Compact(seq))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.0.visit_map(Compact(map))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.0.visit_enum(Compact(data))
+    }
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for Compact<T> {
+    type Value = T::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T::Value, D::Error> {
+        self.0.deserialize(CompactDeserializer(deserializer))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Compact<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.0.next_element_seed(Compact(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Compact<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.0.next_key_seed(Compact(seed))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.0.next_value_seed(Compact(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Compact<A> {
+    type Error = A::Error;
+    type Variant = Compact<A::Variant>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), A::Error> {
+        self.0
+            .variant_seed(seed)
+            .map(|(value, variant)| (value, Compact(variant)))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Compact<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.0.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.newtype_variant_seed(Compact(seed))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.0.tuple_variant(len, Compact(visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.0.struct_variant(fields, Compact(visitor))
+    }
+}
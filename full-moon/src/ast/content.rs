@@ -0,0 +1,654 @@
+// A buffer for any value of a self describing format, for when a value has to be read before
+// it's known how to deserialize it, and a serializer into that buffer.
+use serde::{
+    de::{
+        self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+        Visitor,
+    },
+    forward_to_deserialize_any, ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{fmt, marker::PhantomData};
+
+// Any value of a self describing format, kept to be deserialized later
+pub(crate) enum Content {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+}
+
+impl<'de> Deserialize<'de> for Content {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl<'de> Visitor<'de> for ContentVisitor {
+    type Value = Content;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Content, E> {
+        Ok(Content::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Content, E> {
+        Ok(Content::I64(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Content, E> {
+        Ok(Content::U64(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Content, E> {
+        Ok(Content::F64(value))
+    }
+
+    fn visit_char<E>(self, value: char) -> Result<Content, E> {
+        Ok(Content::Char(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Content, E> {
+        Ok(Content::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Content, E> {
+        Ok(Content::String(value))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Content, E> {
+        Ok(Content::Bytes(value.to_owned()))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Content, E> {
+        Ok(Content::Bytes(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Content, D::Error> {
+        Ok(Content::Some(Box::new(Content::deserialize(deserializer)?)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Content, D::Error> {
+        Content::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Content, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(Content::Seq(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Content, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(Content::Map(entries))
+    }
+}
+
+// Deserializes a kept value, as human readable or not the same as the format it was read from
+pub(crate) struct ContentDeserializer<E> {
+    content: Content,
+    human_readable: bool,
+    error: PhantomData<E>,
+}
+
+impl<E> ContentDeserializer<E> {
+    pub(crate) fn new(content: Content, human_readable: bool) -> Self {
+        Self {
+            content,
+            human_readable,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for ContentDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        let human_readable = self.human_readable;
+        match self.content {
+            Content::Bool(value) => visitor.visit_bool(value),
+            Content::I64(value) => visitor.visit_i64(value),
+            Content::U64(value) => visitor.visit_u64(value),
+            Content::F64(value) => visitor.visit_f64(value),
+            Content::Char(value) => visitor.visit_char(value),
+            Content::String(value) => visitor.visit_string(value),
+            Content::Bytes(value) => visitor.visit_byte_buf(value),
+            Content::None => visitor.visit_none(),
+            Content::Some(value) => visitor.visit_some(Self::new(*value, human_readable)),
+            Content::Unit => visitor.visit_unit(),
+            Content::Seq(values) => visitor.visit_seq(ContentAccess::new(values, human_readable)),
+            Content::Map(entries) => visitor.visit_map(ContentAccess::new(entries, human_readable)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        match self.content {
+            Content::None | Content::Unit => visitor.visit_none(),
+            Content::Some(value) => visitor.visit_some(Self::new(*value, self.human_readable)),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Enums are either the name of a unit variant, or a map of the variant to its value
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        let (variant, value) = match self.content {
+            Content::Map(entries) if entries.len() == 1 => entries.into_iter().next().unwrap(),
+            variant @ (Content::String(_) | Content::U64(_)) => (variant, Content::Unit),
+            _ => return Err(de::Error::custom("expected an enum")),
+        };
+
+        visitor.visit_enum(ContentEnum {
+            variant: Self::new(variant, self.human_readable),
+            value: Self::new(value, self.human_readable),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, E: de::Error> IntoDeserializer<'de, E> for ContentDeserializer<E> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+// The elements of a kept sequence, or the entries of a kept map
+struct ContentAccess<T, E> {
+    items: std::vec::IntoIter<T>,
+    value: Option<Content>,
+    human_readable: bool,
+    error: PhantomData<E>,
+}
+
+impl<T, E> ContentAccess<T, E> {
+    fn new(items: Vec<T>, human_readable: bool) -> Self {
+        Self {
+            items: items.into_iter(),
+            value: None,
+            human_readable,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<'de, E: de::Error> SeqAccess<'de> for ContentAccess<Content, E> {
+    type Error = E;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, E> {
+        match self.items.next() {
+            Some(value) => seed
+                .deserialize(ContentDeserializer::new(value, self.human_readable))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+impl<'de, E: de::Error> MapAccess<'de> for ContentAccess<(Content, Content), E> {
+    type Error = E;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, E> {
+        match self.items.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ContentDeserializer::new(key, self.human_readable))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, E> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is missing"))?;
+        seed.deserialize(ContentDeserializer::new(value, self.human_readable))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct ContentEnum<E> {
+    variant: ContentDeserializer<E>,
+    value: ContentDeserializer<E>,
+}
+
+impl<'de, E: de::Error> EnumAccess<'de> for ContentEnum<E> {
+    type Error = E;
+    type Variant = ContentDeserializer<E>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, ContentDeserializer<E>), E> {
+        Ok((seed.deserialize(self.variant)?, self.value))
+    }
+}
+
+impl<'de, E: de::Error> VariantAccess<'de> for ContentDeserializer<E> {
+    type Error = E;
+
+    fn unit_variant(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, E> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, E> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, E> {
+        self.deserialize_any(visitor)
+    }
+}
+
+// Serializes a value into a Content, naming enum variants and struct fields so that it can be
+// deserialized with a ContentDeserializer
+pub(crate) struct ContentSerializer<E> {
+    human_readable: bool,
+    error: PhantomData<E>,
+}
+
+impl<E> ContentSerializer<E> {
+    pub(crate) fn new(human_readable: bool) -> Self {
+        Self {
+            human_readable,
+            error: PhantomData,
+        }
+    }
+}
+
+impl<E: ser::Error> Serializer for ContentSerializer<E> {
+    type Ok = Content;
+    type Error = E;
+    type SerializeSeq = SerializeContentSeq<E>;
+    type SerializeTuple = SerializeContentSeq<E>;
+    type SerializeTupleStruct = SerializeContentSeq<E>;
+    type SerializeTupleVariant = SerializeContentSeq<E>;
+    type SerializeMap = SerializeContentMap<E>;
+    type SerializeStruct = SerializeContentMap<E>;
+    type SerializeStructVariant = SerializeContentMap<E>;
+
+    fn serialize_bool(self, value: bool) -> Result<Content, E> {
+        Ok(Content::Bool(value))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Content, E> {
+        Ok(Content::I64(value.into()))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Content, E> {
+        Ok(Content::I64(value.into()))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Content, E> {
+        Ok(Content::I64(value.into()))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Content, E> {
+        Ok(Content::I64(value))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Content, E> {
+        Ok(Content::U64(value.into()))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Content, E> {
+        Ok(Content::U64(value.into()))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Content, E> {
+        Ok(Content::U64(value.into()))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Content, E> {
+        Ok(Content::U64(value))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Content, E> {
+        Ok(Content::F64(value.into()))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Content, E> {
+        Ok(Content::F64(value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Content, E> {
+        Ok(Content::Char(value))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Content, E> {
+        Ok(Content::String(value.to_owned()))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Content, E> {
+        Ok(Content::Bytes(value.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Content, E> {
+        Ok(Content::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content, E> {
+        Ok(Content::String(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Content, E> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content, E> {
+        Ok(Content::Map(vec![(
+            Content::String(variant.to_owned()),
+            value.serialize(self)?,
+        )]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeContentSeq<E>, E> {
+        Ok(SerializeContentSeq::new(self, None, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeContentSeq<E>, E> {
+        Ok(SerializeContentSeq::new(self, None, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeContentSeq<E>, E> {
+        Ok(SerializeContentSeq::new(self, None, len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeContentSeq<E>, E> {
+        Ok(SerializeContentSeq::new(self, Some(variant), len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeContentMap<E>, E> {
+        Ok(SerializeContentMap::new(self, None, len.unwrap_or(0)))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeContentMap<E>, E> {
+        Ok(SerializeContentMap::new(self, None, len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeContentMap<E>, E> {
+        Ok(SerializeContentMap::new(self, Some(variant), len))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+// Enum variants are kept as a map of the variant to its value, the same as they're deserialized
+fn in_variant(variant: Option<&'static str>, content: Content) -> Content {
+    match variant {
+        Some(variant) => Content::Map(vec![(Content::String(variant.to_owned()), content)]),
+        None => content,
+    }
+}
+
+pub(crate) struct SerializeContentSeq<E> {
+    serializer: ContentSerializer<E>,
+    variant: Option<&'static str>,
+    values: Vec<Content>,
+}
+
+impl<E> SerializeContentSeq<E> {
+    fn new(serializer: ContentSerializer<E>, variant: Option<&'static str>, len: usize) -> Self {
+        Self {
+            serializer,
+            variant,
+            values: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E>
+    where
+        E: ser::Error,
+    {
+        let serializer = ContentSerializer::new(self.serializer.human_readable);
+        self.values.push(value.serialize(serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        in_variant(self.variant, Content::Seq(self.values))
+    }
+}
+
+macro_rules! impl_serialize_content_seq {
+    ($($trait:ident::$method:ident),*) => {
+        $(
+            impl<E: ser::Error> ser::$trait for SerializeContentSeq<E> {
+                type Ok = Content;
+                type Error = E;
+
+                fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+                    self.push(value)
+                }
+
+                fn end(self) -> Result<Content, E> {
+                    Ok(self.finish())
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_content_seq!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+pub(crate) struct SerializeContentMap<E> {
+    serializer: ContentSerializer<E>,
+    variant: Option<&'static str>,
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl<E> SerializeContentMap<E> {
+    fn new(serializer: ContentSerializer<E>, variant: Option<&'static str>, len: usize) -> Self {
+        Self {
+            serializer,
+            variant,
+            entries: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+
+    fn content<T: Serialize + ?Sized>(&self, value: &T) -> Result<Content, E>
+    where
+        E: ser::Error,
+    {
+        value.serialize(ContentSerializer::new(self.serializer.human_readable))
+    }
+
+    fn push_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), E>
+    where
+        E: ser::Error,
+    {
+        let value = self.content(value)?;
+        self.entries.push((Content::String(key.to_owned()), value));
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        in_variant(self.variant, Content::Map(self.entries))
+    }
+}
+
+impl<E: ser::Error> ser::SerializeMap for SerializeContentMap<E> {
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), E> {
+        self.key = Some(self.content(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), E> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("value serialized before its key"))?;
+        let value = self.content(value)?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(self.finish())
+    }
+}
+
+impl<E: ser::Error> ser::SerializeStruct for SerializeContentMap<E> {
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), E> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(self.finish())
+    }
+}
+
+impl<E: ser::Error> ser::SerializeStructVariant for SerializeContentMap<E> {
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), E> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(self.finish())
+    }
+}
@@ -0,0 +1,249 @@
+#[cfg(feature = "roblox")]
+use super::types::{TypeField, TypeFieldKey, TypeInfo};
+use super::{identifier_str, Expression, Field, TableConstructor, UnOp, Value};
+use crate::tokenizer::{decode_string_literal, TokenReference, TokenType};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+
+/// The key of a table field, normalized so that keys Lua considers the same compare equal,
+/// such as `x` and `["x"]`, or `[1]` and `[1.0]`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum TableKey {
+    /// A string key, such as `x = 1` or `["x"] = 1`, as the bytes of the string
+    String(Vec<u8>),
+    /// A number key, such as `[1] = "a"`, or the implicit index of a field without a key, such as
+    /// `"a"` in `{ "a" }`. Compared using Lua's numeric equality, so `1` and `1.0` are the same key.
+    Number(f64),
+    /// A key that isn't a constant, such as `[x]` or `[f()]`. These are never duplicates.
+    Expression,
+}
+
+impl fmt::Display for TableKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableKey::String(bytes) => write!(formatter, "{:?}", String::from_utf8_lossy(bytes)),
+            TableKey::Number(number) => write!(formatter, "{}", number),
+            TableKey::Expression => write!(formatter, "expression"),
+        }
+    }
+}
+
+/// A key that is used by more than one field of the same table, as returned by
+/// [`TableConstructor::duplicate_keys`]. `F` is the type of the fields, [`Field`] for table
+/// constructors or [`TypeField`](crate::ast::types::TypeField) for table types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateKey<'b, F> {
+    key: TableKey,
+    first: &'b F,
+    duplicate: &'b F,
+}
+
+impl<'b, F> DuplicateKey<'b, F> {
+    /// The key both fields use
+    pub fn key(&self) -> &TableKey {
+        &self.key
+    }
+
+    /// The first field using the key
+    pub fn first(&self) -> &'b F {
+        self.first
+    }
+
+    /// A later field using the same key, which replaces the value of the first one
+    pub fn duplicate(&self) -> &'b F {
+        self.duplicate
+    }
+}
+
+impl<'a> TableConstructor<'a> {
+    /// Returns every field that uses the same key as an earlier field of the table, paired with
+    /// that first field. Fields without a key use their implicit index, so `{ "a", [1] = "b" }`
+    /// has a duplicate key. Keys that aren't constants, such as `[x]`, are never reported.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Expression, Stmt, TableKey, Value};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local t = { x = 1, y = 2, [\"x\"] = 3 }")?;
+    /// if let Some(Stmt::LocalAssignment(assignment)) = ast.nodes().stmts().next() {
+    ///     if let Some(Expression::Value { value, .. }) = assignment.expressions().iter().next() {
+    ///         if let Value::TableConstructor(table) = &**value {
+    ///             let duplicates = table.duplicate_keys();
+    ///             assert_eq!(duplicates.len(), 1);
+    ///             assert_eq!(duplicates[0].key(), &TableKey::String(b"x".to_vec()));
+    ///             assert_eq!(duplicates[0].duplicate().to_string(), "[\"x\"] = 3 ");
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn duplicate_keys(&self) -> Vec<DuplicateKey<'_, Field<'a>>> {
+        let mut index = 0.0;
+        duplicates(self.fields.iter().map(|field| {
+            let key = match field {
+                Field::ExpressionKey { key, .. } => expression_key(key),
+                Field::NameKey { key, .. } => name_key(key),
+                Field::NoKey(_) => {
+                    index += 1.0;
+                    TableKey::Number(index)
+                }
+            };
+
+            (key, field)
+        }))
+    }
+}
+
+#[cfg(feature = "roblox")]
+impl<'a> TypeInfo<'a> {
+    /// Returns every field of a table type that uses the same name as an earlier field, paired
+    /// with that first field, such as the second `x` in `{ x: number, x: string }`.
+    /// Index signatures such as `[string]: number` are never reported, and types other than
+    /// [`TypeInfo::Table`] have no duplicates.
+    pub fn duplicate_keys(&self) -> Vec<DuplicateKey<'_, TypeField<'a>>> {
+        match self {
+            TypeInfo::Table { fields, .. } => duplicates(fields.iter().map(|field| {
+                let key = match field.key() {
+                    TypeFieldKey::Name(name) => name_key(name),
+                    _ => TableKey::Expression,
+                };
+
+                (key, field)
+            })),
+
+            _ => Vec::new(),
+        }
+    }
+}
+
+// A key that can be hashed, with numbers as their bits so that equal numbers have equal bits
+#[derive(PartialEq, Eq, Hash)]
+enum HashedKey {
+    String(Vec<u8>),
+    Number(u64),
+}
+
+impl HashedKey {
+    fn new(key: &TableKey) -> Option<Self> {
+        match key {
+            TableKey::String(bytes) => Some(HashedKey::String(bytes.clone())),
+            // -0.0 is the same key as 0.0, but has different bits
+            TableKey::Number(number) if *number == 0.0 => Some(HashedKey::Number(0f64.to_bits())),
+            TableKey::Number(number) => Some(HashedKey::Number(number.to_bits())),
+            TableKey::Expression => None,
+        }
+    }
+}
+
+fn duplicates<'b, F>(keys: impl Iterator<Item = (TableKey, &'b F)>) -> Vec<DuplicateKey<'b, F>> {
+    let mut seen: HashMap<HashedKey, &'b F> = HashMap::new();
+    let mut duplicates = Vec::new();
+
+    for (key, field) in keys {
+        let hashed = match HashedKey::new(&key) {
+            Some(hashed) => hashed,
+            None => continue,
+        };
+
+        match seen.entry(hashed) {
+            Entry::Occupied(first) => duplicates.push(DuplicateKey {
+                key,
+                first: *first.get(),
+                duplicate: field,
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(field);
+            }
+        }
+    }
+
+    duplicates
+}
+
+fn name_key(name: &TokenReference) -> TableKey {
+    match identifier_str(name) {
+        Some(identifier) => TableKey::String(identifier.as_bytes().to_vec()),
+        None => TableKey::Expression,
+    }
+}
+
+fn expression_key(expression: &Expression) -> TableKey {
+    match expression {
+        Expression::Parentheses { expression, .. } => expression_key(expression),
+
+        Expression::UnaryOperator {
+            unop: UnOp::Minus(_),
+            expression,
+        } => match expression_key(expression) {
+            TableKey::Number(number) => TableKey::Number(-number),
+            _ => TableKey::Expression,
+        },
+
+        Expression::Value {
+            value,
+            #[cfg(feature = "roblox")]
+                type_assertion: None,
+        } => match &**value {
+            Value::Number(number) => number
+                .as_number()
+                .and_then(parse_number)
+                .map_or(TableKey::Expression, TableKey::Number),
+
+            Value::String(string) => match string.token_type() {
+                TokenType::StringLiteral {
+                    literal,
+                    multi_line,
+                    ..
+                } => decode_string_literal(literal, *multi_line)
+                    .map_or(TableKey::Expression, TableKey::String),
+                _ => TableKey::Expression,
+            },
+
+            Value::ParenthesesExpression(expression) => expression_key(expression),
+            _ => TableKey::Expression,
+        },
+
+        _ => TableKey::Expression,
+    }
+}
+
+// The value of a number literal, or `None` if it's too big to know exactly
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.replace('_', "");
+
+    let (digits, radix) = match text.get(..2) {
+        Some("0x") | Some("0X") => (&text[2..], 16),
+        Some("0b") | Some("0B") => (&text[2..], 2),
+        _ => return text.parse().ok(),
+    };
+
+    u64::from_str_radix(digits, radix)
+        .ok()
+        .map(|number| number as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_number;
+
+    #[test]
+    fn test_parse_number() {
+        for (text, number) in [
+            ("1", Some(1.0)),
+            ("1.", Some(1.0)),
+            (".5", Some(0.5)),
+            ("1e2", Some(100.0)),
+            ("1E-2", Some(0.01)),
+            ("0x10", Some(16.0)),
+            ("0XfF", Some(255.0)),
+            ("0b101", Some(5.0)),
+            ("1_000", Some(1000.0)),
+            ("0xFFFFFFFFFFFFFFFFFF", None),
+        ] {
+            assert_eq!(parse_number(text), number, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{ast::Ast, tokenizer::Position};
+
+/// The byte offsets of the start of every line in some code, created with [`Ast::line_index`]
+/// or [`LineIndex::new`]. Converting between byte offsets and lines takes a binary search,
+/// rather than going over the code again.
+///
+/// Lines are split after every `\n`, so the `\r` of a CRLF line ending is part of the line it ends,
+/// the same as in [`Position`].
+///
+/// ```rust
+/// # use full_moon::ast::LineIndex;
+/// let index = LineIndex::new("local x = 1\r\nlocal y = 2\n");
+///
+/// assert_eq!(index.line_starts(), &[0, 13, 25]);
+/// assert_eq!(index.line_column(16), Some((2, 3)));
+/// assert_eq!(index.offset(2, 3), Some(16));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    /// Creates the index of the lines in the given code
+    pub fn new(code: &str) -> Self {
+        Self {
+            line_starts: std::iter::once(0)
+                .chain(code.match_indices('\n').map(|(index, _)| index + 1))
+                .collect(),
+            len: code.len(),
+        }
+    }
+
+    /// The byte offset of the start of every line, in order. The first line always starts at 0.
+    pub fn line_starts(&self) -> &[usize] {
+        &self.line_starts
+    }
+
+    /// The number of lines, which is one more than the number of `\n`s
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The byte offset of the start of the given line, starting from 1 like [`Position::line`]
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line.checked_sub(1)?).copied()
+    }
+
+    /// The line, starting from 1, and the byte column, starting from 0, of the given byte offset,
+    /// matching [`Position::line`] and [`Position::byte_column`].
+    /// Returns `None` if the offset is past the end of the code.
+    pub fn line_column(&self, bytes: usize) -> Option<(usize, usize)> {
+        if bytes > self.len {
+            return None;
+        }
+
+        let line = self.line_starts.partition_point(|&start| start <= bytes);
+        Some((line, bytes - self.line_starts[line - 1]))
+    }
+
+    /// The byte offset of the given line, starting from 1, and byte column, starting from 0.
+    /// Returns `None` if the line does not exist or the column is past its end.
+    pub fn offset(&self, line: usize, byte_column: usize) -> Option<usize> {
+        let start = self.line_start(line)?;
+        let end = self.line_start(line + 1).unwrap_or(self.len);
+
+        if start + byte_column <= end {
+            Some(start + byte_column)
+        } else {
+            None
+        }
+    }
+
+    /// The byte offset of the position, calculated from its line and byte column rather than
+    /// [`Position::bytes`]. Returns `None` if the position does not fit in the code.
+    pub fn position_offset(&self, position: Position) -> Option<usize> {
+        self.offset(position.line(), position.byte_column())
+    }
+}
+
+impl Ast<'_> {
+    /// Creates a [`LineIndex`] of the code of the Ast, to convert between byte offsets and lines.
+    /// The positions of tokens must be up to date, so use [`update_positions`](Ast::update_positions)
+    /// first if the Ast has been changed.
+    pub fn line_index(&self) -> LineIndex {
+        LineIndex::new(&crate::print(self))
+    }
+}
@@ -1,7 +1,7 @@
 //! Contains the nodes necessary to parse [Lua 5.2](http://www.lua.org/manual/5.2/).
 //! Only usable when the "lua52" feature flag is enabled.
 
-use crate::tokenizer::TokenReference;
+use crate::tokenizer::{Symbol, TokenReference};
 use derive_more::Display;
 use full_moon_derive::{Node, Owned, Visit};
 
@@ -13,7 +13,6 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", "goto_token", "label_name")]
 pub struct Goto<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) goto_token: TokenReference<'a>,
     pub(crate) label_name: TokenReference<'a>,
 }
@@ -22,7 +21,7 @@ impl<'a> Goto<'a> {
     /// Creates a new Goto with the given label name
     pub fn new(label_name: TokenReference<'a>) -> Self {
         Self {
-            goto_token: TokenReference::symbol("goto").unwrap(),
+            goto_token: TokenReference::spaced_symbol("", Symbol::Goto, ""),
             label_name,
         }
     }
@@ -53,7 +52,6 @@ impl<'a> Goto<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "left_colons", "name", "right_colons")]
 pub struct Label<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) left_colons: TokenReference<'a>,
     pub(crate) name: TokenReference<'a>,
     pub(crate) right_colons: TokenReference<'a>,
@@ -63,9 +61,9 @@ impl<'a> Label<'a> {
     /// Creates a new Label with the given name
     pub fn new(name: TokenReference<'a>) -> Self {
         Self {
-            left_colons: TokenReference::symbol("::").unwrap(),
+            left_colons: TokenReference::spaced_symbol("", Symbol::TwoColons, ""),
             name,
-            right_colons: TokenReference::symbol("::").unwrap(),
+            right_colons: TokenReference::spaced_symbol("", Symbol::TwoColons, ""),
         }
     }
 
@@ -0,0 +1,229 @@
+use super::{
+    punctuated::{Pair, Punctuated},
+    span::ContainedSpan,
+    suffixes_path, Call, Expression, FunctionArgs, FunctionCall, Index, MethodCall, Prefix, Suffix,
+    Value, Var, VarExpression,
+};
+use crate::{
+    tokenizer::{Symbol, Token, TokenReference, TokenType},
+    util::{map_first_token, map_last_token},
+};
+
+impl<'a> FunctionCall<'a> {
+    /// Rewrites the last call in the chain from a method call to a call of the indexed function
+    /// with the receiver as the first argument, such as `obj:m(a)` to `obj.m(obj, a)`.
+    ///
+    /// Returns `None` if the last call isn't a method call, or if the receiver isn't a name or a chain
+    /// of names and dot indexes, such as `f():m()`, since the receiver would be evaluated twice.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("self.items:insert(item)")?;
+    /// if let Some(Stmt::FunctionCall(call)) = ast.nodes().stmts().next() {
+    ///     let call = call.to_dot_call().unwrap();
+    ///     assert_eq!(call.to_string(), "self.items.insert(self.items, item)");
+    ///     assert_eq!(call.to_method_call().unwrap().to_string(), "self.items:insert(item)");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_dot_call(&self) -> Option<Self> {
+        let (last, receiver_suffixes) = self.suffixes.split_last()?;
+        let method_call = match last {
+            Suffix::Call(Call::MethodCall(method_call)) => method_call,
+            _ => return None,
+        };
+
+        suffixes_path(&self.prefix, receiver_suffixes.iter(), false)?;
+        let receiver = receiver_expression(&self.prefix, receiver_suffixes);
+
+        let mut suffixes = receiver_suffixes.to_vec();
+        // The space in `obj:m "x"` would end up before the parentheses that are added
+        let mut name = method_call.name.clone();
+        if !matches!(method_call.args, FunctionArgs::Parentheses { .. })
+            && name
+                .trailing_trivia
+                .iter()
+                .all(|trivia| matches!(trivia.token_type, TokenType::Whitespace { .. }))
+        {
+            name.trailing_trivia.clear();
+        }
+
+        suffixes.push(Suffix::Index(Index::Dot {
+            dot: with_symbol(&method_call.colon_token, Symbol::Dot),
+            name,
+        }));
+        suffixes.push(Suffix::Call(Call::AnonymousCall(with_receiver(
+            method_call,
+            receiver,
+        ))));
+
+        Some(Self {
+            prefix: self.prefix.clone(),
+            suffixes,
+        })
+    }
+
+    /// Rewrites the last call in the chain from a call of an indexed function with the receiver
+    /// as the first argument to a method call, such as `obj.m(obj, a)` to `obj:m(a)`.
+    ///
+    /// Returns `None` if the first argument isn't the same name or chain of names and dot indexes
+    /// as the receiver, such as in `obj.m(other)` or `f().m(f())`.
+    pub fn to_method_call(&self) -> Option<Self> {
+        let (last, rest) = self.suffixes.split_last()?;
+        let (index, receiver_suffixes) = rest.split_last()?;
+
+        let (dot, name) = match index {
+            Suffix::Index(Index::Dot { dot, name }) => (dot, name),
+            _ => return None,
+        };
+
+        let (parentheses, arguments) = match last {
+            Suffix::Call(Call::AnonymousCall(FunctionArgs::Parentheses {
+                parentheses,
+                arguments,
+            })) => (parentheses, arguments),
+            _ => return None,
+        };
+
+        let receiver = suffixes_path(&self.prefix, receiver_suffixes.iter(), false)?;
+        let first_argument = match arguments.iter().next()? {
+            Expression::Value {
+                value,
+                #[cfg(feature = "roblox")]
+                    type_assertion: None,
+            } => match &**value {
+                Value::Var(var) => var.to_path(false)?,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        if first_argument != receiver {
+            return None;
+        }
+
+        let mut suffixes = receiver_suffixes.to_vec();
+        suffixes.push(Suffix::Call(Call::MethodCall(
+            MethodCall::new(
+                name.clone(),
+                FunctionArgs::Parentheses {
+                    parentheses: parentheses.clone(),
+                    arguments: arguments.clone().into_pairs().skip(1).collect(),
+                },
+            )
+            .with_colon_token(with_symbol(dot, Symbol::Colon)),
+        )));
+
+        Some(Self {
+            prefix: self.prefix.clone(),
+            suffixes,
+        })
+    }
+}
+
+// A copy of the receiver to pass as an argument, without the trivia around it
+fn receiver_expression<'a>(prefix: &Prefix<'a>, suffixes: &[Suffix<'a>]) -> Expression<'a> {
+    let var = match (prefix, suffixes) {
+        (Prefix::Name(name), []) => Var::Name(name.clone()),
+        _ => Var::Expression(VarExpression::new(prefix.clone()).with_suffixes(suffixes.to_vec())),
+    };
+
+    let receiver = Expression::Value {
+        value: Box::new(Value::Var(var)),
+        #[cfg(feature = "roblox")]
+        type_assertion: None,
+    };
+
+    let receiver = map_first_token(receiver, |token| TokenReference {
+        leading_trivia: Vec::new(),
+        ..token
+    });
+
+    map_last_token(receiver, |token| TokenReference {
+        trailing_trivia: Vec::new(),
+        ..token
+    })
+}
+
+// The arguments of the method call in parentheses with the receiver before them. Calls without
+// parentheses, such as `obj:m "x"`, get them, and the trivia after the argument goes after them.
+fn with_receiver<'a>(method_call: &MethodCall<'a>, receiver: Expression<'a>) -> FunctionArgs<'a> {
+    let (parentheses, argument) = match method_call.args.clone() {
+        FunctionArgs::Parentheses {
+            parentheses,
+            arguments,
+        } => {
+            let mut with_receiver = Punctuated::new();
+            if arguments.is_empty() {
+                with_receiver.push(Pair::End(receiver));
+            } else {
+                with_receiver.push(Pair::Punctuated(receiver, comma()));
+                with_receiver.extend(arguments.into_pairs());
+            }
+
+            return FunctionArgs::Parentheses {
+                parentheses,
+                arguments: with_receiver,
+            };
+        }
+
+        FunctionArgs::String(mut string) => {
+            let trailing_trivia = std::mem::take(&mut string.trailing_trivia);
+            (close_parentheses(trailing_trivia), Value::String(string))
+        }
+
+        FunctionArgs::TableConstructor(table_constructor) => {
+            let mut trailing_trivia = Vec::new();
+            let table_constructor = map_last_token(table_constructor, |mut token| {
+                trailing_trivia = std::mem::take(&mut token.trailing_trivia);
+                token
+            });
+
+            (
+                close_parentheses(trailing_trivia),
+                Value::TableConstructor(Box::new(table_constructor)),
+            )
+        }
+    };
+
+    let mut arguments = Punctuated::new();
+    arguments.push(Pair::Punctuated(receiver, comma()));
+    arguments.push(Pair::End(Expression::Value {
+        value: Box::new(argument),
+        #[cfg(feature = "roblox")]
+        type_assertion: None,
+    }));
+
+    FunctionArgs::Parentheses {
+        parentheses,
+        arguments,
+    }
+}
+
+fn close_parentheses(trailing_trivia: Vec<Token<'_>>) -> ContainedSpan<'_> {
+    ContainedSpan::new(
+        TokenReference::from_symbol(Symbol::LeftParen),
+        TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::Symbol {
+                symbol: Symbol::RightParen,
+            }),
+            trailing_trivia,
+        ),
+    )
+}
+
+fn comma<'a>() -> TokenReference<'a> {
+    TokenReference::spaced_symbol("", Symbol::Comma, " ")
+}
+
+// The token with its symbol replaced, keeping its trivia
+fn with_symbol<'a>(token: &TokenReference<'a>, symbol: Symbol) -> TokenReference<'a> {
+    TokenReference::new(
+        token.leading_trivia.clone(),
+        Token::new(TokenType::Symbol { symbol }),
+        token.trailing_trivia.clone(),
+    )
+}
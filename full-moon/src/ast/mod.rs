@@ -1,22 +1,39 @@
+mod accessors;
+pub mod builders;
+#[cfg(feature = "serde")]
+pub(crate) mod compact;
+#[cfg(feature = "serde")]
+mod content;
+mod duplicate_keys;
+mod method_calls;
 pub mod owned;
+mod parentheses;
 #[macro_use]
 mod parser_util;
+mod line_index;
 mod parsers;
+mod position_encoder;
 pub mod punctuated;
+#[cfg(feature = "serde")]
+mod serde_repr;
 pub mod span;
+mod splice;
 mod update_positions;
 mod visitors;
 
 use crate::{
-    tokenizer::{Symbol, Token, TokenReference, TokenType},
+    tokenizer::{
+        Origin, StringLiteralQuoteType, Symbol, Token, TokenKind, TokenReference, TokenType,
+    },
     util::*,
+    visitors::{Visit, VisitMut},
 };
 use derive_more::Display;
 use full_moon_derive::{Node, Owned, Visit};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, fmt};
+use std::{borrow::Cow, cmp::Ordering, fmt};
 
 use parser_util::{
     InternalAstError, OneOrMore, Parser, ParserState, ZeroOrMore, ZeroOrMoreDelimited,
@@ -25,6 +42,12 @@ use parser_util::{
 use punctuated::{Pair, Punctuated};
 use span::ContainedSpan;
 
+#[cfg(feature = "serde")]
+pub use compact::CompactAst;
+pub use duplicate_keys::{DuplicateKey, TableKey};
+pub use line_index::LineIndex;
+pub use position_encoder::PositionEncoder;
+
 #[cfg(feature = "roblox")]
 pub mod types;
 #[cfg(feature = "roblox")]
@@ -40,16 +63,13 @@ use lua52::*;
 
 /// A block of statements, such as in if/do/etc block
 #[derive(Clone, Debug, Default, Display, PartialEq, Owned, Node, Visit)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(
     fmt = "{}{}",
     "display_optional_punctuated_vec(stmts)",
     "display_option(&last_stmt.as_ref().map(display_optional_punctuated))"
 )]
 pub struct Block<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     stmts: Vec<(Stmt<'a>, Option<TokenReference<'a>>)>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     last_stmt: Option<(LastStmt<'a>, Option<TokenReference<'a>>)>,
 }
 
@@ -99,6 +119,241 @@ impl<'a> Block<'a> {
     ) -> Self {
         Self { last_stmt, ..self }
     }
+
+    /// Sets the last statement of the block, such as `return foo`, with an optional semicolon,
+    /// replacing the existing one
+    pub fn set_last_stmt(
+        &mut self,
+        last_stmt: LastStmt<'a>,
+        semicolon: Option<TokenReference<'a>>,
+    ) {
+        self.last_stmt = Some((last_stmt, semicolon));
+    }
+
+    /// Removes the last statement of the block, returning it along with its semicolon if there was one
+    pub fn take_last_stmt(&mut self) -> Option<(LastStmt<'a>, Option<TokenReference<'a>>)> {
+        self.last_stmt.take()
+    }
+
+    /// Sets or removes the semicolon after the statement at the given index.
+    /// An added semicolon is put before the trailing trivia of the statement, such as `foo(); -- comment`,
+    /// and the trivia of a removed semicolon is kept after the statement.
+    ///
+    /// # Panics
+    /// Panics if there is no statement at the index.
+    pub fn set_semicolon(&mut self, index: usize, semicolon: Option<TokenReference<'a>>) {
+        let (stmt, previous) = self.stmts.swap_remove(index);
+        let pair = replace_semicolon(stmt, previous, semicolon);
+
+        self.stmts.push(pair);
+        let last = self.stmts.len() - 1;
+        self.stmts.swap(index, last);
+    }
+
+    /// Sets or removes the semicolon after the last statement, if there is one.
+    /// See [`Block::set_semicolon`].
+    pub fn set_last_stmt_semicolon(&mut self, semicolon: Option<TokenReference<'a>>) {
+        if let Some((last_stmt, previous)) = self.last_stmt.take() {
+            self.last_stmt = Some(replace_semicolon(last_stmt, previous, semicolon));
+        }
+    }
+
+    /// Moves the statements into the order given by the permutation, so that the statement at
+    /// `permutation[i]` is put at index `i`. The last statement stays where it is.
+    ///
+    /// Every statement keeps its semicolon, the comments and blank lines before it, and the comments
+    /// after it on the same line. The line breaks or spaces between statements stay where they are,
+    /// so the block still ends the same way, and comments before the first statement that are separated
+    /// from it by a blank line, such as a header at the top of a file, stay at the start of the block.
+    /// The statement that is moved to the start loses the blank lines before it.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("-- Header\n\nlocal b = 2\n\n-- First\nlocal a = 1; -- one\n")?;
+    /// let mut block = ast.nodes().clone();
+    /// block.reorder_stmts(&[1, 0]);
+    ///
+    /// assert_eq!(block.to_string(), "-- Header\n\n-- First\nlocal a = 1; -- one\nlocal b = 2\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the permutation does not contain every index of the statements exactly once.
+    pub fn reorder_stmts(&mut self, permutation: &[usize]) {
+        let mut seen = vec![false; self.stmts.len()];
+        assert_eq!(
+            permutation.len(),
+            self.stmts.len(),
+            "the permutation must have an index for each of the statements"
+        );
+
+        for &index in permutation {
+            assert!(
+                index < seen.len() && !seen[index],
+                "{} is out of bounds or used more than once in the permutation",
+                index
+            );
+
+            seen[index] = true;
+        }
+
+        if permutation.iter().enumerate().all(|(i, &index)| i == index) {
+            return;
+        }
+
+        // What separates each statement from the next one belongs to where the statement is
+        let mut separators = Vec::with_capacity(self.stmts.len());
+        let mut stmts = Vec::with_capacity(self.stmts.len());
+        for (stmt, mut semicolon) in self.stmts.drain(..) {
+            let stmt = match &mut semicolon {
+                Some(semicolon) => {
+                    separators.push(take_separator(semicolon));
+                    stmt
+                }
+
+                None => map_last_token(stmt, |mut token| {
+                    separators.push(take_separator(&mut token));
+                    token
+                }),
+            };
+
+            stmts.push(Some((stmt, semicolon)));
+        }
+
+        // As does everything up to the last blank line before the first statement
+        let (first, semicolon) = stmts[0].take().unwrap();
+        let mut header = Vec::new();
+        let first = map_first_token(first, |mut token| {
+            let header_end = (0..token.leading_trivia.len())
+                .rev()
+                .find(|&index| is_blank_line(&token.leading_trivia, index))
+                .map_or(0, |index| index + 1);
+
+            header = token.leading_trivia.drain(..header_end).collect();
+            token
+        });
+        stmts[0] = Some((first, semicolon));
+
+        for (index, separator) in permutation.iter().zip(separators) {
+            let (stmt, mut semicolon) = stmts[*index].take().unwrap();
+            let stmt = match &mut semicolon {
+                Some(semicolon) => {
+                    put_separator(semicolon, separator);
+                    stmt
+                }
+
+                None => map_last_token(stmt, |mut token| {
+                    put_separator(&mut token, separator);
+                    token
+                }),
+            };
+
+            self.stmts.push((stmt, semicolon));
+        }
+
+        let (first, semicolon) = self.stmts.remove(0);
+        let first = map_first_token(first, |mut token| {
+            let blank_lines = token
+                .leading_trivia
+                .iter()
+                .take_while(|trivia| {
+                    matches!(&trivia.token_type, TokenType::Whitespace { characters } if characters.ends_with('\n'))
+                })
+                .count();
+
+            token.leading_trivia.splice(..blank_lines, header);
+            token
+        });
+        self.stmts.insert(0, (first, semicolon));
+    }
+
+    /// Sorts the statements with the comparison function, keeping their trivia the same as
+    /// [`Block::reorder_stmts`]. The sort is stable, so statements that are equal keep their order.
+    /// Use [`Block::reorder_stmts`] to only sort some of the statements.
+    pub fn sort_stmts_by(&mut self, mut compare: impl FnMut(&Stmt<'a>, &Stmt<'a>) -> Ordering) {
+        let mut permutation = (0..self.stmts.len()).collect::<Vec<_>>();
+        permutation.sort_by(|&a, &b| compare(&self.stmts[a].0, &self.stmts[b].0));
+        self.reorder_stmts(&permutation);
+    }
+}
+
+// Removes the whitespace at the end of the trailing trivia, such as a new line
+fn take_separator<'a>(token: &mut TokenReference<'a>) -> Vec<Token<'a>> {
+    let start = token
+        .trailing_trivia
+        .iter()
+        .rposition(|trivia| trivia.token_kind() != TokenKind::Whitespace)
+        .map_or(0, |index| index + 1);
+
+    token.trailing_trivia.drain(start..).collect()
+}
+
+fn put_separator<'a>(token: &mut TokenReference<'a>, separator: Vec<Token<'a>>) {
+    let ends_with_comment = matches!(
+        token.trailing_trivia.last().map(Token::token_kind),
+        Some(TokenKind::SingleLineComment)
+    );
+
+    // A comment that goes to the end of the line needs a new line after it
+    if ends_with_comment
+        && !separator
+            .iter()
+            .any(|trivia| trivia.to_string().contains('\n'))
+    {
+        token
+            .trailing_trivia
+            .push(Token::new(TokenType::Whitespace {
+                characters: Cow::Borrowed("\n"),
+            }));
+    } else {
+        token.trailing_trivia.extend(separator);
+    }
+}
+
+// Whether the trivia at the index is an empty line, rather than the end of a line with a comment
+fn is_blank_line(trivia: &[Token], index: usize) -> bool {
+    let ends_line = |trivia: &Token| matches!(&trivia.token_type, TokenType::Whitespace { characters } if characters.ends_with('\n'));
+
+    ends_line(&trivia[index]) && (index == 0 || ends_line(&trivia[index - 1]))
+}
+
+// Replaces the semicolon after a statement, keeping the trivia around both
+fn replace_semicolon<'a, N: Visit<'a> + VisitMut<'a>>(
+    stmt: N,
+    previous: Option<TokenReference<'a>>,
+    semicolon: Option<TokenReference<'a>>,
+) -> (N, Option<TokenReference<'a>>) {
+    match (previous, semicolon) {
+        (Some(previous), Some(mut semicolon)) => {
+            semicolon
+                .leading_trivia
+                .splice(0..0, previous.leading_trivia);
+            semicolon.trailing_trivia.extend(previous.trailing_trivia);
+            (stmt, Some(semicolon))
+        }
+
+        (Some(previous), None) => {
+            let stmt = map_last_token(stmt, |mut token| {
+                token.trailing_trivia.extend(previous.leading_trivia);
+                token.trailing_trivia.extend(previous.trailing_trivia);
+                token
+            });
+
+            (stmt, None)
+        }
+
+        (None, Some(mut semicolon)) => {
+            let stmt = map_last_token(stmt, |mut token| {
+                semicolon.trailing_trivia.append(&mut token.trailing_trivia);
+                token
+            });
+
+            (stmt, Some(semicolon))
+        }
+
+        (None, None) => (stmt, None),
+    }
 }
 
 /// The last statement of a [`Block`]
@@ -106,7 +361,6 @@ impl<'a> Block<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum LastStmt<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     /// A `break` statement
     Break(TokenReference<'a>),
     /// A continue statement
@@ -122,19 +376,53 @@ pub enum LastStmt<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", token, returns)]
 pub struct Return<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     token: TokenReference<'a>,
     returns: Punctuated<'a, Expression<'a>>,
 }
 
 impl<'a> Return<'a> {
-    /// Creates a new empty Return
-    /// Default return token is followed by a single space
-    pub fn new() -> Self {
-        Self {
-            token: TokenReference::symbol("return ").unwrap(),
+    /// Creates a new Return of the given values, separated by `, `, such as `return a, b`.
+    /// The return token is followed by a single space if there are any values.
+    pub fn new(returns: Vec<Expression<'a>>) -> Self {
+        let mut r#return = Self {
+            token: TokenReference::spaced_symbol("", Symbol::Return, ""),
             returns: Punctuated::new(),
+        };
+
+        for expression in returns {
+            r#return.push_expression(expression);
+        }
+
+        r#return
+    }
+
+    /// Adds a value to the end of the values being returned, separated from the previous value by `, `.
+    /// If there were no values, a space is added after the `return` token unless it already has trailing trivia.
+    pub fn push_expression(&mut self, expression: Expression<'a>) {
+        match self.returns.pop() {
+            Some(pair) => {
+                let last = pair.into_value();
+                self.returns.push(Pair::Punctuated(
+                    last,
+                    TokenReference::spaced_symbol("", Symbol::Comma, " "),
+                ));
+            }
+
+            None => {
+                if self
+                    .token
+                    .trailing_trivia()
+                    .all(|trivia| trivia.to_string().is_empty())
+                {
+                    self.token.retain_trailing_trivia(|_| false);
+                    self.token
+                        .push_trailing_trivia(Token::new(TokenType::spaces(1)))
+                        .expect("spaces were not trivia");
+                }
+            }
         }
+
+        self.returns.push(Pair::End(expression));
     }
 
     /// The `return` token
@@ -160,7 +448,7 @@ impl<'a> Return<'a> {
 
 impl Default for Return<'_> {
     fn default() -> Self {
-        Self::new()
+        Self::new(Vec::new())
     }
 }
 
@@ -180,7 +468,6 @@ pub enum Field<'a> {
     )]
     ExpressionKey {
         /// The `[...]` part of `[expression] = value`
-        #[cfg_attr(feature = "serde", serde(borrow))]
         brackets: ContainedSpan<'a>,
         /// The `expression` part of `[expression] = value`
         key: Expression<'a>,
@@ -193,7 +480,6 @@ pub enum Field<'a> {
     /// A key in the format of `name = value`
     #[display(fmt = "{}{}{}", "key", "equal", "value")]
     NameKey {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The `name` part of `name = value`
         key: TokenReference<'a>,
         /// The `=` part of `name = value`
@@ -203,17 +489,70 @@ pub enum Field<'a> {
     },
 
     /// A field with no key, just a value (such as `"a"` in `{ "a" }`)
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[display(fmt = "{}", "_0")]
     NoKey(Expression<'a>),
 }
 
+impl<'a> Field<'a> {
+    /// Creates a field in the format of `name = value`. If the name isn't a valid identifier,
+    /// such as a keyword, a string key is used instead, such as `["end"] = value`.
+    pub fn name_value(name: &str, value: Expression<'a>) -> Self {
+        let mut key = TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::Identifier {
+                identifier: "_".into(),
+            }),
+            Vec::new(),
+        );
+
+        if key.set_identifier(name).is_err() {
+            let mut string = TokenReference::new(
+                Vec::new(),
+                Token::new(TokenType::StringLiteral {
+                    literal: "".into(),
+                    multi_line: None,
+                    quote_type: StringLiteralQuoteType::Double,
+                }),
+                Vec::new(),
+            );
+
+            string
+                .set_string_contents(name)
+                .expect("token was not a string");
+
+            return Self::expression_key(
+                Expression::Value {
+                    value: Box::new(Value::String(string)),
+                    #[cfg(feature = "roblox")]
+                    type_assertion: None,
+                },
+                value,
+            );
+        }
+
+        Field::NameKey {
+            key,
+            equal: TokenReference::spaced_symbol(" ", Symbol::Equal, " "),
+            value,
+        }
+    }
+
+    /// Creates a field in the format of `[key] = value`
+    pub fn expression_key(key: Expression<'a>, value: Expression<'a>) -> Self {
+        Field::ExpressionKey {
+            brackets: ContainedSpan::brackets(),
+            key,
+            equal: TokenReference::spaced_symbol(" ", Symbol::Equal, " "),
+            value,
+        }
+    }
+}
+
 /// A table being constructed, such as `{ 1, 2, 3 }` or `{ a = 1 }`
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "braces.tokens().0", "fields", "braces.tokens().1")]
 pub struct TableConstructor<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[node(full_range)]
     #[visit(contains = "fields")]
     braces: ContainedSpan<'a>,
@@ -225,10 +564,7 @@ impl<'a> TableConstructor<'a> {
     /// Brace tokens are followed by spaces, such that { `fields` }
     pub fn new() -> Self {
         Self {
-            braces: ContainedSpan::new(
-                TokenReference::symbol("{ ").unwrap(),
-                TokenReference::symbol(" }").unwrap(),
-            ),
+            braces: ContainedSpan::braces(),
             fields: Punctuated::new(),
         }
     }
@@ -252,6 +588,69 @@ impl<'a> TableConstructor<'a> {
     pub fn with_fields(self, fields: Punctuated<'a, Field<'a>>) -> Self {
         Self { fields, ..self }
     }
+
+    /// Creates a TableConstructor from pairs of names and values, such as `{ a = 1, b = 2 }`.
+    /// Names that aren't valid identifiers are used as string keys instead, such as `["end"] = 3`.
+    /// Without any pairs, this creates `{}`.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Expression, LastStmt, TableConstructor};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let ast = full_moon::parse("return 1, 2")?;
+    /// # let values = match ast.nodes().last_stmt() {
+    /// #     Some(LastStmt::Return(r#return)) => r#return.returns().iter().cloned().collect::<Vec<_>>(),
+    /// #     _ => unreachable!(),
+    /// # };
+    /// // `values` is the expressions `1` and `2`
+    /// let table = TableConstructor::from_pairs(vec![("a", values[0].clone()), ("end", values[1].clone())]);
+    /// assert_eq!(table.to_string(), r#"{ a = 1, ["end"] = 2 }"#);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_pairs<K: AsRef<str>>(pairs: impl IntoIterator<Item = (K, Expression<'a>)>) -> Self {
+        let mut table = Self::new();
+        for (name, value) in pairs {
+            table.push_field(Field::name_value(name.as_ref(), value));
+        }
+
+        if table.fields.is_empty() {
+            table.braces = ContainedSpan::new(
+                TokenReference::from_symbol(Symbol::LeftBrace),
+                TokenReference::from_symbol(Symbol::RightBrace),
+            );
+        }
+
+        table
+    }
+
+    /// Returns a mutable reference to the fields used to create the table
+    pub fn fields_mut(&mut self) -> &mut Punctuated<'a, Field<'a>> {
+        &mut self.fields
+    }
+
+    /// Adds a field to the end of the table, separated from the previous field by `, `.
+    /// If the previous field had a trailing separator, such as in `{ 1, 2, }`, so does the new one.
+    pub fn push_field(&mut self, field: Field<'a>) {
+        let trailing_separator = match self.fields.pop() {
+            Some(Pair::End(last)) => {
+                self.fields.push(Pair::Punctuated(
+                    last,
+                    TokenReference::spaced_symbol("", Symbol::Comma, " "),
+                ));
+                None
+            }
+
+            Some(pair @ Pair::Punctuated(..)) => {
+                let separator = pair.punctuation().cloned();
+                self.fields.push(pair);
+                separator
+            }
+
+            None => None,
+        };
+
+        self.fields.push(Pair::new(field, trailing_separator));
+    }
 }
 
 impl Default for TableConstructor<'_> {
@@ -262,8 +661,6 @@ impl Default for TableConstructor<'_> {
 
 /// An expression, mostly useful for getting values
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[cfg_attr(feature = "serde", serde(untagged))]
 #[non_exhaustive]
 pub enum Expression<'a> {
     /// A binary operation, such as `1 + 3`
@@ -272,7 +669,6 @@ pub enum Expression<'a> {
         /// The left hand side of the binary operation, the `1` part of `1 + 3`
         lhs: Box<Expression<'a>>,
         /// The binary operation used, the `+` part of `1 + 3`
-        #[cfg_attr(feature = "serde", serde(borrow))]
         binop: BinOp<'a>,
         /// The right hand side of the binary operation, the `3` part of `1 + 3`
         rhs: Box<Expression<'a>>,
@@ -287,9 +683,8 @@ pub enum Expression<'a> {
     )]
     Parentheses {
         /// The parentheses of the `ParenExpression`
-        #[cfg_attr(feature = "serde", serde(borrow))]
         #[node(full_range)]
-        contained: ContainedSpan<'a>,
+        contained: Box<ContainedSpan<'a>>,
         /// The expression inside the parentheses
         expression: Box<Expression<'a>>,
     },
@@ -298,7 +693,6 @@ pub enum Expression<'a> {
     #[display(fmt = "{}{}", "unop", "expression")]
     UnaryOperator {
         /// The unary operation, the `#` part of `#list`
-        #[cfg_attr(feature = "serde", serde(borrow))]
         unop: UnOp<'a>,
         /// The expression the operation is being done on, the `list` part of `#list`
         expression: Box<Expression<'a>>,
@@ -312,14 +706,11 @@ pub enum Expression<'a> {
     )]
     Value {
         /// The value itself
-        #[cfg_attr(feature = "serde", serde(borrow))]
         value: Box<Value<'a>>,
         /// What the value is being asserted as using `::`.
         /// Only available when the "roblox" feature flag is enabled.
         #[cfg(feature = "roblox")]
-        #[cfg_attr(feature = "serde", serde(borrow))]
-        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
-        type_assertion: Option<TypeAssertion<'a>>,
+        type_assertion: Option<Box<TypeAssertion<'a>>>,
     },
 }
 
@@ -328,16 +719,15 @@ pub enum Expression<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum Value<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     /// An anonymous function, such as `function() end)`
-    #[display(fmt = "{}{}", "_0.0", "_0.1")]
-    Function((TokenReference<'a>, FunctionBody<'a>)),
+    #[display(fmt = "{}", "_0")]
+    Function(Box<AnonymousFunction<'a>>),
     /// A call of a function, such as `call()`
     #[display(fmt = "{}", "_0")]
     FunctionCall(FunctionCall<'a>),
     /// A table constructor, such as `{ 1, 2, 3 }`
     #[display(fmt = "{}", "_0")]
-    TableConstructor(TableConstructor<'a>),
+    TableConstructor(Box<TableConstructor<'a>>),
     /// A number token, such as `3.3`
     #[display(fmt = "{}", "_0")]
     Number(TokenReference<'a>),
@@ -360,54 +750,53 @@ pub enum Value<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum Stmt<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     /// An assignment, such as `x = 1`
     #[display(fmt = "{}", _0)]
     Assignment(Assignment<'a>),
     /// A do block, `do end`
     #[display(fmt = "{}", _0)]
-    Do(Do<'a>),
+    Do(Box<Do<'a>>),
     /// A function call on its own, such as `call()`
     #[display(fmt = "{}", _0)]
     FunctionCall(FunctionCall<'a>),
     /// A function declaration, such as `function x() end`
     #[display(fmt = "{}", _0)]
-    FunctionDeclaration(FunctionDeclaration<'a>),
+    FunctionDeclaration(Box<FunctionDeclaration<'a>>),
     /// A generic for loop, such as `for index, value in pairs(list) do end`
     #[display(fmt = "{}", _0)]
-    GenericFor(GenericFor<'a>),
+    GenericFor(Box<GenericFor<'a>>),
     /// An if statement
     #[display(fmt = "{}", _0)]
-    If(If<'a>),
+    If(Box<If<'a>>),
     /// A local assignment, such as `local x = 1`
     #[display(fmt = "{}", _0)]
     LocalAssignment(LocalAssignment<'a>),
     /// A local function declaration, such as `local function x() end`
     #[display(fmt = "{}", _0)]
-    LocalFunction(LocalFunction<'a>),
+    LocalFunction(Box<LocalFunction<'a>>),
     /// A numeric for loop, such as `for index = 1, 10 do end`
     #[display(fmt = "{}", _0)]
-    NumericFor(NumericFor<'a>),
+    NumericFor(Box<NumericFor<'a>>),
     /// A repeat loop
     #[display(fmt = "{}", _0)]
-    Repeat(Repeat<'a>),
+    Repeat(Box<Repeat<'a>>),
     /// A while loop
     #[display(fmt = "{}", _0)]
-    While(While<'a>),
+    While(Box<While<'a>>),
 
     /// A compound assignment, such as `+=`
     /// Only available when the "roblox" feature flag is enabled
     #[cfg(feature = "roblox")]
     #[display(fmt = "{}", _0)]
-    CompoundAssignment(CompoundAssignment<'a>),
+    CompoundAssignment(Box<CompoundAssignment<'a>>),
     /// An exported type declaration, such as `export type Meters = number`
     /// Only available when the "roblox" feature flag is enabled.
     #[cfg(feature = "roblox")]
-    ExportedTypeDeclaration(ExportedTypeDeclaration<'a>),
+    ExportedTypeDeclaration(Box<ExportedTypeDeclaration<'a>>),
     /// A type declaration, such as `type Meters = number`
     /// Only available when the "roblox" feature flag is enabled.
     #[cfg(feature = "roblox")]
-    TypeDeclaration(TypeDeclaration<'a>),
+    TypeDeclaration(Box<TypeDeclaration<'a>>),
 
     /// A goto statement, such as `goto label`
     /// Only available when the "lua52" feature flag is enabled.
@@ -416,7 +805,7 @@ pub enum Stmt<'a> {
     /// A label, such as `::label::`
     /// Only available when the "lua52" feature flag is enabled.
     #[cfg(feature = "lua52")]
-    Label(Label<'a>),
+    Label(Box<Label<'a>>),
 }
 
 /// A node used before another in cases such as function calling
@@ -425,7 +814,6 @@ pub enum Stmt<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum Prefix<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[display(fmt = "{}", _0)]
     /// A complicated expression, such as `("foo")`
     Expression(Expression<'a>),
@@ -434,6 +822,17 @@ pub enum Prefix<'a> {
     Name(TokenReference<'a>),
 }
 
+impl<'a> Prefix<'a> {
+    /// The name of the prefix as a single path segment, such as `["foo"]`.
+    /// Returns `None` for parenthesized expressions.
+    pub fn to_path(&self) -> Option<Vec<&str>> {
+        match self {
+            Prefix::Name(name) => Some(vec![identifier_str(name)?]),
+            Prefix::Expression(_) => None,
+        }
+    }
+}
+
 /// The indexing of something, such as `x.y` or `x["y"]`
 /// Values of variants are the keys, such as `"y"`
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node)]
@@ -448,8 +847,8 @@ pub enum Index<'a> {
         "brackets.tokens().1"
     )]
     Brackets {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The `[...]` part of `["y"]`
+        #[node(full_range)]
         brackets: ContainedSpan<'a>,
         /// The `"y"` part of `["y"]`
         expression: Expression<'a>,
@@ -458,7 +857,6 @@ pub enum Index<'a> {
     /// Indexing in the form of `x.y`
     #[display(fmt = "{}{}", "dot", "name")]
     Dot {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The `.` part of `.y`
         dot: TokenReference<'a>,
         /// The `y` part of `.y`
@@ -483,11 +881,9 @@ pub enum FunctionArgs<'a> {
         #[node(full_range)]
         parentheses: ContainedSpan<'a>,
         /// The `1, 2, 3` part of `1, 2, 3`
-        #[cfg_attr(feature = "serde", serde(borrow))]
         arguments: Punctuated<'a, Expression<'a>>,
     },
     /// Used when a function is called in the form of `call "foobar"`
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[display(fmt = "{}", "_0")]
     String(TokenReference<'a>),
     /// Used when a function is called in the form of `call { 1, 2, 3 }`
@@ -495,11 +891,98 @@ pub enum FunctionArgs<'a> {
     TableConstructor(TableConstructor<'a>),
 }
 
+impl<'a> FunctionArgs<'a> {
+    /// An iterator over the arguments passed to the function.
+    /// The string and table constructor forms yield a single argument.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{ArgumentRef, Stmt};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("call { 1, 2, 3 }")?;
+    /// if let Some(Stmt::FunctionCall(call)) = ast.nodes().stmts().next() {
+    ///     let mut arguments = call.call_arguments().unwrap();
+    ///     assert_eq!(arguments.len(), 1);
+    ///     assert!(matches!(arguments.next(), Some(ArgumentRef::TableConstructor(_))));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn arguments(&self) -> Arguments<'a, '_> {
+        Arguments {
+            inner: match self {
+                FunctionArgs::Parentheses { arguments, .. } => {
+                    ArgumentsInner::Parentheses(arguments.iter())
+                }
+                FunctionArgs::String(string) => {
+                    ArgumentsInner::Single(Some(ArgumentRef::String(string)))
+                }
+                FunctionArgs::TableConstructor(table_constructor) => {
+                    ArgumentsInner::Single(Some(ArgumentRef::TableConstructor(table_constructor)))
+                }
+            },
+        }
+    }
+}
+
+/// A single argument passed to a function, as returned by [`FunctionArgs::arguments`]
+#[derive(Clone, Copy, Debug, Display, PartialEq)]
+#[non_exhaustive]
+pub enum ArgumentRef<'a, 'b> {
+    /// An argument inside parentheses, such as `1` in `call(1)`
+    #[display(fmt = "{}", "_0")]
+    Expression(&'b Expression<'a>),
+    /// The string in `call "foobar"`
+    #[display(fmt = "{}", "_0")]
+    String(&'b TokenReference<'a>),
+    /// The table in `call { 1, 2, 3 }`
+    #[display(fmt = "{}", "_0")]
+    TableConstructor(&'b TableConstructor<'a>),
+}
+
+/// An iterator over the arguments of a function call, as returned by [`FunctionArgs::arguments`]
+pub struct Arguments<'a, 'b> {
+    inner: ArgumentsInner<'a, 'b>,
+}
+
+enum ArgumentsInner<'a, 'b> {
+    Parentheses(punctuated::Iter<'a, 'b, Expression<'a>>),
+    Single(Option<ArgumentRef<'a, 'b>>),
+}
+
+impl<'a, 'b> Arguments<'a, 'b> {
+    /// Whether there are no arguments left, such as in `call()`
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, 'b> Iterator for Arguments<'a, 'b> {
+    type Item = ArgumentRef<'a, 'b>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            ArgumentsInner::Parentheses(iter) => iter.next().map(ArgumentRef::Expression),
+            ArgumentsInner::Single(argument) => argument.take(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            ArgumentsInner::Parentheses(iter) => iter.size_hint(),
+            ArgumentsInner::Single(argument) => {
+                let len = argument.is_some() as usize;
+                (len, Some(len))
+            }
+        }
+    }
+}
+
+impl<'a, 'b> ExactSizeIterator for Arguments<'a, 'b> {}
+
 /// A numeric for loop, such as `for index = 1, 10 do end`
 #[derive(Clone, Debug, PartialEq, Owned, Node)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct NumericFor<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     for_token: TokenReference<'a>,
     index_variable: TokenReference<'a>,
     equal_token: TokenReference<'a>,
@@ -512,7 +995,6 @@ pub struct NumericFor<'a> {
     block: Block<'a>,
     end_token: TokenReference<'a>,
     #[cfg(feature = "roblox")]
-    #[cfg_attr(feature = "serde", serde(borrow))]
     type_specifier: Option<TypeSpecifier<'a>>,
 }
 
@@ -524,17 +1006,17 @@ impl<'a> NumericFor<'a> {
         end: Expression<'a>,
     ) -> Self {
         Self {
-            for_token: TokenReference::symbol("for ").unwrap(),
+            for_token: TokenReference::spaced_symbol("", Symbol::For, " "),
             index_variable,
-            equal_token: TokenReference::symbol(" = ").unwrap(),
+            equal_token: TokenReference::spaced_symbol(" ", Symbol::Equal, " "),
             start,
-            start_end_comma: TokenReference::symbol(", ").unwrap(),
+            start_end_comma: TokenReference::spaced_symbol("", Symbol::Comma, " "),
             end,
             end_step_comma: None,
             step: None,
-            do_token: TokenReference::symbol(" do\n").unwrap(),
+            do_token: TokenReference::spaced_symbol(" ", Symbol::Do, "\n"),
             block: Block::new(),
-            end_token: TokenReference::symbol("\nend").unwrap(),
+            end_token: TokenReference::spaced_symbol("\n", Symbol::End, ""),
             #[cfg(feature = "roblox")]
             type_specifier: None,
         }
@@ -731,7 +1213,6 @@ impl fmt::Display for NumericFor<'_> {
 #[derive(Clone, Debug, PartialEq, Owned, Node)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct GenericFor<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     for_token: TokenReference<'a>,
     names: Punctuated<'a, TokenReference<'a>>,
     in_token: TokenReference<'a>,
@@ -740,7 +1221,6 @@ pub struct GenericFor<'a> {
     block: Block<'a>,
     end_token: TokenReference<'a>,
     #[cfg(feature = "roblox")]
-    #[cfg_attr(feature = "serde", serde(borrow))]
     type_specifiers: Vec<Option<TypeSpecifier<'a>>>,
 }
 
@@ -751,13 +1231,13 @@ impl<'a> GenericFor<'a> {
         expr_list: Punctuated<'a, Expression<'a>>,
     ) -> Self {
         Self {
-            for_token: TokenReference::symbol("for ").unwrap(),
+            for_token: TokenReference::spaced_symbol("", Symbol::For, " "),
             names,
-            in_token: TokenReference::symbol(" in ").unwrap(),
+            in_token: TokenReference::spaced_symbol(" ", Symbol::In, " "),
             expr_list,
-            do_token: TokenReference::symbol(" do\n").unwrap(),
+            do_token: TokenReference::spaced_symbol(" ", Symbol::Do, "\n"),
             block: Block::new(),
-            end_token: TokenReference::symbol("\nend").unwrap(),
+            end_token: TokenReference::spaced_symbol("\n", Symbol::End, ""),
             #[cfg(feature = "roblox")]
             type_specifiers: Vec::new(),
         }
@@ -862,7 +1342,7 @@ impl fmt::Display for GenericFor<'_> {
             formatter,
             "{}{}{}{}{}{}{}",
             self.for_token,
-            join_type_specifiers(&self.names, self.type_specifiers()),
+            join_type_specifiers(&self.names, &self.type_specifiers),
             self.in_token,
             self.expr_list,
             self.do_token,
@@ -902,7 +1382,6 @@ impl fmt::Display for GenericFor<'_> {
     "end_token"
 )]
 pub struct If<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     if_token: TokenReference<'a>,
     condition: Expression<'a>,
     then_token: TokenReference<'a>,
@@ -918,14 +1397,14 @@ impl<'a> If<'a> {
     /// Creates a new If from the given condition
     pub fn new(condition: Expression<'a>) -> Self {
         Self {
-            if_token: TokenReference::symbol("if ").unwrap(),
+            if_token: TokenReference::spaced_symbol("", Symbol::If, " "),
             condition,
-            then_token: TokenReference::symbol(" then").unwrap(),
+            then_token: TokenReference::spaced_symbol(" ", Symbol::Then, ""),
             block: Block::new(),
             else_if: None,
             else_token: None,
             r#else: None,
-            end_token: TokenReference::symbol("\nend").unwrap(),
+            end_token: TokenReference::spaced_symbol("\n", Symbol::End, ""),
         }
     }
 
@@ -1010,6 +1489,49 @@ impl<'a> If<'a> {
     pub fn with_end_token(self, end_token: TokenReference<'a>) -> Self {
         Self { end_token, ..self }
     }
+
+    /// Adds an `elseif` branch after the existing ones, before the `else` branch if there is one.
+    /// The `elseif` token is indented the same as the `if` token, so the block before it should
+    /// end with a new line, as parsed code does.
+    pub fn push_else_if(&mut self, mut else_if: ElseIf<'a>) {
+        else_if
+            .else_if_token
+            .set_leading_whitespace(self.indentation())
+            .expect("indentation was not whitespace");
+
+        self.else_if.get_or_insert_with(Vec::new).push(else_if);
+    }
+
+    /// Sets the block of the `else` branch, creating the `else` token, indented the same as the
+    /// `if` token, if there isn't one already. The `end` token keeps its trivia.
+    pub fn set_else(&mut self, block: Block<'a>) {
+        if self.else_token.is_none() {
+            let mut else_token = TokenReference::spaced_symbol("", Symbol::Else, "\n");
+            else_token
+                .set_leading_whitespace(self.indentation())
+                .expect("indentation was not whitespace");
+            self.else_token = Some(else_token);
+        }
+
+        self.r#else = Some(block);
+    }
+
+    /// Removes the `else` branch, returning its token and block if there was one
+    pub fn remove_else(&mut self) -> Option<(TokenReference<'a>, Block<'a>)> {
+        let else_token = self.else_token.take()?;
+        let block = self.r#else.take().unwrap_or_default();
+        Some((else_token, block))
+    }
+
+    // The whitespace on the same line before the `if` token
+    fn indentation(&self) -> &str {
+        match self.if_token.leading_trivia().last().map(Token::token_type) {
+            Some(TokenType::Whitespace { characters }) => {
+                characters.rsplit('\n').next().unwrap_or_default()
+            }
+            _ => "",
+        }
+    }
 }
 
 /// An elseif block in a bigger [`If`] statement
@@ -1017,7 +1539,6 @@ impl<'a> If<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}{}", "else_if_token", "condition", "then_token", "block")]
 pub struct ElseIf<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     else_if_token: TokenReference<'a>,
     condition: Expression<'a>,
     then_token: TokenReference<'a>,
@@ -1028,9 +1549,9 @@ impl<'a> ElseIf<'a> {
     /// Creates a new ElseIf from the given condition
     pub fn new(condition: Expression<'a>) -> Self {
         Self {
-            else_if_token: TokenReference::symbol("elseif ").unwrap(),
+            else_if_token: TokenReference::spaced_symbol("", Symbol::ElseIf, " "),
             condition,
-            then_token: TokenReference::symbol(" then\n").unwrap(),
+            then_token: TokenReference::spaced_symbol(" ", Symbol::Then, "\n"),
             block: Block::new(),
         }
     }
@@ -1091,7 +1612,6 @@ impl<'a> ElseIf<'a> {
     "end_token"
 )]
 pub struct While<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     while_token: TokenReference<'a>,
     condition: Expression<'a>,
     do_token: TokenReference<'a>,
@@ -1103,11 +1623,11 @@ impl<'a> While<'a> {
     /// Creates a new While from the given condition
     pub fn new(condition: Expression<'a>) -> Self {
         Self {
-            while_token: TokenReference::symbol("while ").unwrap(),
+            while_token: TokenReference::spaced_symbol("", Symbol::While, " "),
             condition,
-            do_token: TokenReference::symbol(" do\n").unwrap(),
+            do_token: TokenReference::spaced_symbol(" ", Symbol::Do, "\n"),
             block: Block::new(),
-            end_token: TokenReference::symbol("end\n").unwrap(),
+            end_token: TokenReference::spaced_symbol("", Symbol::End, "\n"),
         }
     }
 
@@ -1170,7 +1690,6 @@ impl<'a> While<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}{}", "repeat_token", "block", "until_token", "until")]
 pub struct Repeat<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     repeat_token: TokenReference<'a>,
     block: Block<'a>,
     until_token: TokenReference<'a>,
@@ -1181,9 +1700,9 @@ impl<'a> Repeat<'a> {
     /// Creates a new Repeat from the given expression to repeat until
     pub fn new(until: Expression<'a>) -> Self {
         Self {
-            repeat_token: TokenReference::symbol("repeat\n").unwrap(),
+            repeat_token: TokenReference::spaced_symbol("", Symbol::Repeat, "\n"),
             block: Block::new(),
-            until_token: TokenReference::symbol("\nuntil ").unwrap(),
+            until_token: TokenReference::spaced_symbol("\n", Symbol::Until, " "),
             until,
         }
     }
@@ -1240,7 +1759,6 @@ impl<'a> Repeat<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "colon_token", "name", "args")]
 pub struct MethodCall<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     colon_token: TokenReference<'a>,
     name: TokenReference<'a>,
     args: FunctionArgs<'a>,
@@ -1250,7 +1768,7 @@ impl<'a> MethodCall<'a> {
     /// Returns a new MethodCall from the given name and args
     pub fn new(name: TokenReference<'a>, args: FunctionArgs<'a>) -> Self {
         Self {
-            colon_token: TokenReference::symbol(":").unwrap(),
+            colon_token: TokenReference::spaced_symbol("", Symbol::Colon, ""),
             name,
             args,
         }
@@ -1295,7 +1813,6 @@ impl<'a> MethodCall<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum Call<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[display(fmt = "{}", "_0")]
     /// A function being called directly, such as `x(1)`
     AnonymousCall(FunctionArgs<'a>),
@@ -1306,23 +1823,21 @@ pub enum Call<'a> {
 
 /// A function body, everything except `function x` in `function x(a, b, c) call() end`
 #[derive(Clone, Debug, PartialEq, Owned, Node)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct FunctionBody<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     parameters_parentheses: ContainedSpan<'a>,
     parameters: Punctuated<'a, Parameter<'a>>,
 
     #[cfg(feature = "roblox")]
-    #[cfg_attr(feature = "serde", serde(borrow))]
     type_specifiers: Vec<Option<TypeSpecifier<'a>>>,
 
     #[cfg(feature = "roblox")]
-    #[cfg_attr(feature = "serde", serde(borrow))]
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     return_type: Option<TypeSpecifier<'a>>,
 
     block: Block<'a>,
     end_token: TokenReference<'a>,
+
+    #[node(skip)]
+    implicit_self: bool,
 }
 
 impl<'a> FunctionBody<'a> {
@@ -1330,8 +1845,8 @@ impl<'a> FunctionBody<'a> {
     pub fn new() -> Self {
         Self {
             parameters_parentheses: ContainedSpan::new(
-                TokenReference::symbol("(").unwrap(),
-                TokenReference::symbol(")").unwrap(),
+                TokenReference::spaced_symbol("", Symbol::LeftParen, ""),
+                TokenReference::spaced_symbol("", Symbol::RightParen, ""),
             ),
             parameters: Punctuated::new(),
 
@@ -1342,7 +1857,9 @@ impl<'a> FunctionBody<'a> {
             return_type: None,
 
             block: Block::new(),
-            end_token: TokenReference::symbol("\nend").unwrap(),
+            end_token: TokenReference::spaced_symbol("\n", Symbol::End, ""),
+
+            implicit_self: false,
         }
     }
 
@@ -1366,6 +1883,31 @@ impl<'a> FunctionBody<'a> {
         &self.end_token
     }
 
+    /// Whether the function has an implicit `self` parameter, which is only the case for the body
+    /// of a method declaration such as `function x:y() end`. This is kept up to date by
+    /// [`FunctionDeclaration`], and is always false for [`LocalFunction`]s and [`AnonymousFunction`]s.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("function x:y() end\nfunction x.z(self) end")?;
+    /// let implicit_self = ast
+    ///     .nodes()
+    ///     .stmts()
+    ///     .map(|stmt| match stmt {
+    ///         Stmt::FunctionDeclaration(declaration) => declaration.body().has_implicit_self(),
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(implicit_self, vec![true, false]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn has_implicit_self(&self) -> bool {
+        self.implicit_self
+    }
+
     /// The type specifiers of the variables, in the order that they were assigned.
     /// `(foo: number, bar, baz: boolean)` returns an iterator containing:
     /// `Some(TypeSpecifier(number)), None, Some(TypeSpecifier(boolean))`
@@ -1375,6 +1917,53 @@ impl<'a> FunctionBody<'a> {
         self.type_specifiers.iter().map(Option::as_ref)
     }
 
+    /// The parameters of the function along with their type specifiers, if any.
+    /// The implicit `self` parameter of a method such as `function x:y() end` is not included.
+    /// Only available when the "roblox" feature flag is enabled.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Parameter, Stmt};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local function foo(a: number, b, ...: string): boolean end")?;
+    /// if let Some(Stmt::LocalFunction(local_function)) = ast.nodes().stmts().next() {
+    ///     let body = local_function.body();
+    ///     let parameters = body
+    ///         .typed_parameters()
+    ///         .map(|typed_parameter| {
+    ///             let name = match typed_parameter.parameter() {
+    ///                 Parameter::Name(name) => name.token().to_string(),
+    ///                 _ => "...".to_owned(),
+    ///             };
+    ///
+    ///             match typed_parameter.type_specifier() {
+    ///                 Some(type_specifier) => format!("{}: {}", name, type_specifier.type_info()),
+    ///                 None => name,
+    ///             }
+    ///         })
+    ///         .collect::<Vec<_>>();
+    ///
+    ///     let mut signature = format!("{}({})", local_function.name(), parameters.join(", "));
+    ///     if let Some(return_type) = body.return_type() {
+    ///         signature += &format!(" -> {}", return_type.type_info().to_string().trim());
+    ///     }
+    ///
+    ///     assert_eq!(signature, "foo(a: number, b, ...: string) -> boolean");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "roblox")]
+    pub fn typed_parameters(&self) -> impl Iterator<Item = TypedParameter<'a, '_>> {
+        self.parameters
+            .iter()
+            .enumerate()
+            .map(move |(index, parameter)| TypedParameter {
+                parameter,
+                type_specifier: self.type_specifiers.get(index).and_then(Option::as_ref),
+                index,
+            })
+    }
+
     /// The return type of the function, if one exists.
     /// Only available when the "roblox" feature flag is enabled.
     #[cfg(feature = "roblox")]
@@ -1437,7 +2026,7 @@ impl fmt::Display for FunctionBody<'_> {
             formatter,
             "{}{}{}{}{}{}",
             self.parameters_parentheses.tokens().0,
-            join_type_specifiers(&self.parameters, self.type_specifiers()),
+            join_type_specifiers(&self.parameters, &self.type_specifiers),
             self.parameters_parentheses.tokens().1,
             display_option(self.return_type.as_ref()),
             self.block,
@@ -1464,20 +2053,46 @@ impl fmt::Display for FunctionBody<'_> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum Parameter<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     /// The `...` vararg syntax, such as `function x(...)`
     Ellipse(TokenReference<'a>),
     /// A name parameter, such as `function x(a, b, c)`
     Name(TokenReference<'a>),
 }
 
+/// A parameter of a function along with its type specifier, as returned by [`FunctionBody::typed_parameters`].
+/// Only available when the "roblox" feature flag is enabled.
+#[cfg(feature = "roblox")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TypedParameter<'a, 'b> {
+    parameter: &'b Parameter<'a>,
+    type_specifier: Option<&'b TypeSpecifier<'a>>,
+    index: usize,
+}
+
+#[cfg(feature = "roblox")]
+impl<'a, 'b> TypedParameter<'a, 'b> {
+    /// The parameter, such as `a` in `function x(a: number)`
+    pub fn parameter(&self) -> &'b Parameter<'a> {
+        self.parameter
+    }
+
+    /// The type specifier of the parameter, such as `: number` in `function x(a: number)`
+    pub fn type_specifier(&self) -> Option<&'b TypeSpecifier<'a>> {
+        self.type_specifier
+    }
+
+    /// The position of the parameter in the parameter list, starting from 0
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
 /// A suffix in certain cases, such as `:y()` in `x:y()`
 /// Can be stacked on top of each other, such as in `x()()()`
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum Suffix<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[display(fmt = "{}", "_0")]
     /// A call, including method calls and direct calls
     Call(Call<'a>),
@@ -1491,7 +2106,6 @@ pub enum Suffix<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", "prefix", "join_vec(suffixes)")]
 pub struct VarExpression<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     prefix: Prefix<'a>,
     suffixes: Vec<Suffix<'a>>,
 }
@@ -1524,6 +2138,12 @@ impl<'a> VarExpression<'a> {
     pub fn with_suffixes(self, suffixes: Vec<Suffix<'a>>) -> Self {
         Self { suffixes, ..self }
     }
+
+    /// The segments of the expression if it is a plain chain of names and dot indexes.
+    /// See [`Var::to_path`] for more details.
+    pub fn to_path(&self, include_string_keys: bool) -> Option<Vec<&str>> {
+        suffixes_path(&self.prefix, self.suffixes.iter(), include_string_keys)
+    }
 }
 
 /// Used in [`Assignment`s](Assignment) and [`Value`s](Value)
@@ -1532,20 +2152,53 @@ impl<'a> VarExpression<'a> {
 #[non_exhaustive]
 pub enum Var<'a> {
     /// An expression, such as `x.y.z` or `x()`
-    #[cfg_attr(feature = "serde", serde(borrow))]
     #[display(fmt = "{}", "_0")]
+    #[node(kind = "VarComplex")]
     Expression(VarExpression<'a>),
     /// A literal identifier, such as `x`
     #[display(fmt = "{}", "_0")]
     Name(TokenReference<'a>),
 }
 
+impl<'a> Var<'a> {
+    /// The segments of the variable if it is a plain chain of names and dot indexes,
+    /// such as `["game", "Workspace", "Part"]` for `game.Workspace.Part`.
+    /// Returns `None` if a call, a parenthesized expression, or a bracket index appears.
+    /// If `include_string_keys` is true, bracket indexes with string literal keys
+    /// (such as `a["b"]`) are included as segments rather than returning `None`.
+    /// Keys that aren't the same as their value as written, such as `a["\120"]` or a long string
+    /// spanning lines, still return `None`.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("a.b[\"c\"] = 1")?;
+    /// if let Some(Stmt::Assignment(assignment)) = ast.nodes().stmts().next() {
+    ///     let var = assignment.variables().iter().next().unwrap();
+    ///     assert_eq!(var.to_path(false), None);
+    ///     assert_eq!(var.to_path(true), Some(vec!["a", "b", "c"]));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_path(&self, include_string_keys: bool) -> Option<Vec<&str>> {
+        match self {
+            Var::Name(name) => Some(vec![identifier_str(name)?]),
+            Var::Expression(var_expression) => var_expression.to_path(include_string_keys),
+        }
+    }
+
+    /// The segments returned by [`to_path`](Var::to_path) joined with `.`, such as `game.Workspace.Part`
+    pub fn to_path_string(&self, include_string_keys: bool) -> Option<String> {
+        Some(self.to_path(include_string_keys)?.join("."))
+    }
+}
+
 /// An assignment, such as `x = y`. Not used for [`LocalAssignment`s](LocalAssignment)
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "var_list", "equal_token", "expr_list")]
 pub struct Assignment<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     var_list: Punctuated<'a, Var<'a>>,
     equal_token: TokenReference<'a>,
     expr_list: Punctuated<'a, Expression<'a>>,
@@ -1559,7 +2212,7 @@ impl<'a> Assignment<'a> {
     ) -> Self {
         Self {
             var_list,
-            equal_token: TokenReference::symbol(" = ").unwrap(),
+            equal_token: TokenReference::spaced_symbol(" ", Symbol::Equal, " "),
             expr_list,
         }
     }
@@ -1581,6 +2234,28 @@ impl<'a> Assignment<'a> {
         &self.var_list
     }
 
+    /// Pairs each variable with the expression at the same position, or `None` if there are
+    /// fewer expressions than variables. Expressions past the last variable are not included,
+    /// use [`extra_values`](Assignment::extra_values) for those.
+    ///
+    /// Note that if the last expression is a function call or `...`, it can expand to
+    /// multiple values, filling in the variables that would otherwise be paired with `None`.
+    /// Use [`is_last_value_multi`](Assignment::is_last_value_multi) to check for this.
+    pub fn var_value_pairs(&self) -> impl Iterator<Item = (&Var<'a>, Option<&Expression<'a>>)> {
+        let mut values = self.expr_list.iter();
+        self.var_list.iter().map(move |var| (var, values.next()))
+    }
+
+    /// The expressions past the last variable, such as `3` in `a, b = 1, 2, 3`
+    pub fn extra_values(&self) -> impl Iterator<Item = &Expression<'a>> {
+        self.expr_list.iter().skip(self.var_list.len())
+    }
+
+    /// Whether the last expression is a function call or `...`, which can expand to multiple values
+    pub fn is_last_value_multi(&self) -> bool {
+        matches!(self.expr_list.iter().last(), Some(expression) if is_multi_value(expression))
+    }
+
     /// Returns a new Assignment with the given variables
     pub fn with_variables(self, var_list: Punctuated<'a, Var<'a>>) -> Self {
         Self { var_list, ..self }
@@ -1603,9 +2278,24 @@ impl<'a> Assignment<'a> {
 /// A declaration of a local function, such as `local function x() end`
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[display(fmt = "{}{}{}{}", "local_token", "function_token", "name", "body")]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(fmt = "{}{}{}{}", "local_token", "function_token", "name", "body")
+)]
+#[cfg_attr(
+    feature = "roblox",
+    display(
+        fmt = "{}{}{}{}{}",
+        "join_vec(attributes)",
+        "local_token",
+        "function_token",
+        "name",
+        "body"
+    )
+)]
 pub struct LocalFunction<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
+    #[cfg(feature = "roblox")]
+    attributes: Vec<Attribute<'a>>,
     local_token: TokenReference<'a>,
     function_token: TokenReference<'a>,
     name: TokenReference<'a>,
@@ -1616,8 +2306,10 @@ impl<'a> LocalFunction<'a> {
     /// Returns a new LocalFunction from the given name
     pub fn new(name: TokenReference<'a>) -> Self {
         LocalFunction {
-            local_token: TokenReference::symbol("local ").unwrap(),
-            function_token: TokenReference::symbol("function ").unwrap(),
+            #[cfg(feature = "roblox")]
+            attributes: Vec::new(),
+            local_token: TokenReference::spaced_symbol("", Symbol::Local, " "),
+            function_token: TokenReference::spaced_symbol("", Symbol::Function, " "),
             name,
             body: FunctionBody::new(),
         }
@@ -1643,6 +2335,13 @@ impl<'a> LocalFunction<'a> {
         &self.name
     }
 
+    /// The attributes on the function, such as `@native`.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn attributes(&self) -> impl Iterator<Item = &Attribute<'a>> {
+        self.attributes.iter()
+    }
+
     /// Returns a new LocalFunction with the given `local` token
     pub fn with_local_token(self, local_token: TokenReference<'a>) -> Self {
         Self {
@@ -1664,20 +2363,110 @@ impl<'a> LocalFunction<'a> {
         Self { name, ..self }
     }
 
-    /// Returns a new LocalFunction with the given function body
+    /// Returns a new LocalFunction with the given function body, which won't have an implicit `self`
     pub fn with_body(self, body: FunctionBody<'a>) -> Self {
-        Self { body, ..self }
+        Self {
+            body: FunctionBody {
+                implicit_self: false,
+                ..body
+            },
+            ..self
+        }
     }
-}
 
-/// An assignment to a local variable, such as `local x = 1`
-#[derive(Clone, Debug, PartialEq, Owned, Node)]
+    /// Returns a new LocalFunction with the given attributes.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn with_attributes(self, attributes: Vec<Attribute<'a>>) -> Self {
+        Self { attributes, ..self }
+    }
+}
+
+/// An anonymous function, such as `function() end`
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(fmt = "{}{}", "function_token", "body")
+)]
+#[cfg_attr(
+    feature = "roblox",
+    display(fmt = "{}{}{}", "join_vec(attributes)", "function_token", "body")
+)]
+pub struct AnonymousFunction<'a> {
+    #[cfg(feature = "roblox")]
+    attributes: Vec<Attribute<'a>>,
+    function_token: TokenReference<'a>,
+    body: FunctionBody<'a>,
+}
+
+impl<'a> AnonymousFunction<'a> {
+    /// Returns a new AnonymousFunction with an empty body
+    pub fn new() -> Self {
+        AnonymousFunction {
+            #[cfg(feature = "roblox")]
+            attributes: Vec::new(),
+            function_token: TokenReference::spaced_symbol("", Symbol::Function, ""),
+            body: FunctionBody::new(),
+        }
+    }
+
+    /// The `function` token
+    pub fn function_token(&self) -> &TokenReference<'a> {
+        &self.function_token
+    }
+
+    /// The function body, everything except `function` in `function(a, b, c) call() end`
+    pub fn body(&self) -> &FunctionBody<'a> {
+        &self.body
+    }
+
+    /// The attributes on the function, such as `@native`.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn attributes(&self) -> impl Iterator<Item = &Attribute<'a>> {
+        self.attributes.iter()
+    }
+
+    /// Returns a new AnonymousFunction with the given `function` token
+    pub fn with_function_token(self, function_token: TokenReference<'a>) -> Self {
+        Self {
+            function_token,
+            ..self
+        }
+    }
+
+    /// Returns a new AnonymousFunction with the given function body, which won't have an implicit `self`
+    pub fn with_body(self, body: FunctionBody<'a>) -> Self {
+        Self {
+            body: FunctionBody {
+                implicit_self: false,
+                ..body
+            },
+            ..self
+        }
+    }
+
+    /// Returns a new AnonymousFunction with the given attributes.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn with_attributes(self, attributes: Vec<Attribute<'a>>) -> Self {
+        Self { attributes, ..self }
+    }
+}
+
+impl Default for AnonymousFunction<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An assignment to a local variable, such as `local x = 1`
+#[derive(Clone, Debug, PartialEq, Owned, Node)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct LocalAssignment<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     local_token: TokenReference<'a>,
     #[cfg(feature = "roblox")]
-    #[cfg_attr(feature = "serde", serde(borrow))]
     type_specifiers: Vec<Option<TypeSpecifier<'a>>>,
     name_list: Punctuated<'a, TokenReference<'a>>,
     equal_token: Option<TokenReference<'a>>,
@@ -1688,7 +2477,7 @@ impl<'a> LocalAssignment<'a> {
     /// Returns a new LocalAssignment from the given name list
     pub fn new(name_list: Punctuated<'a, TokenReference<'a>>) -> Self {
         Self {
-            local_token: TokenReference::symbol("local ").unwrap(),
+            local_token: TokenReference::spaced_symbol("", Symbol::Local, " "),
             #[cfg(feature = "roblox")]
             type_specifiers: Vec::new(),
             name_list,
@@ -1728,6 +2517,78 @@ impl<'a> LocalAssignment<'a> {
         self.type_specifiers.iter().map(Option::as_ref)
     }
 
+    /// Pairs each name with the expression at the same position, or `None` if there are
+    /// fewer expressions than names. Expressions past the last name are not included,
+    /// use [`extra_values`](LocalAssignment::extra_values) for those.
+    ///
+    /// Note that if the last expression is a function call or `...`, it can expand to
+    /// multiple values, filling in the names that would otherwise be paired with `None`.
+    /// Use [`is_last_value_multi`](LocalAssignment::is_last_value_multi) to check for this.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local a, b, c = 1, 2")?;
+    /// if let Some(Stmt::LocalAssignment(local_assignment)) = ast.nodes().stmts().next() {
+    ///     let pairs = local_assignment
+    ///         .name_value_pairs()
+    ///         .map(|(name, value)| (name.token().to_string(), value.map(ToString::to_string)))
+    ///         .collect::<Vec<_>>();
+    ///
+    ///     assert_eq!(pairs[0], ("a".to_owned(), Some("1".to_owned())));
+    ///     assert_eq!(pairs[1], ("b".to_owned(), Some("2".to_owned())));
+    ///     assert_eq!(pairs[2], ("c".to_owned(), None));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "roblox"))]
+    pub fn name_value_pairs(
+        &self,
+    ) -> impl Iterator<Item = (&TokenReference<'a>, Option<&Expression<'a>>)> {
+        let mut values = self.expr_list.iter();
+        self.name_list.iter().map(move |name| (name, values.next()))
+    }
+
+    /// Pairs each name with the expression at the same position, or `None` if there are
+    /// fewer expressions than names, along with the type specifier of the name.
+    /// Expressions past the last name are not included,
+    /// use [`extra_values`](LocalAssignment::extra_values) for those.
+    ///
+    /// Note that if the last expression is a function call or `...`, it can expand to
+    /// multiple values, filling in the names that would otherwise be paired with `None`.
+    /// Use [`is_last_value_multi`](LocalAssignment::is_last_value_multi) to check for this.
+    #[cfg(feature = "roblox")]
+    pub fn name_value_pairs(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &TokenReference<'a>,
+            Option<&Expression<'a>>,
+            Option<&TypeSpecifier<'a>>,
+        ),
+    > {
+        let mut values = self.expr_list.iter();
+        let mut type_specifiers = self.type_specifiers.iter();
+        self.name_list.iter().map(move |name| {
+            (
+                name,
+                values.next(),
+                type_specifiers.next().and_then(Option::as_ref),
+            )
+        })
+    }
+
+    /// The expressions past the last name, such as `3` in `local a, b = 1, 2, 3`
+    pub fn extra_values(&self) -> impl Iterator<Item = &Expression<'a>> {
+        self.expr_list.iter().skip(self.name_list.len())
+    }
+
+    /// Whether the last expression is a function call or `...`, which can expand to multiple values
+    pub fn is_last_value_multi(&self) -> bool {
+        matches!(self.expr_list.iter().last(), Some(expression) if is_multi_value(expression))
+    }
+
     /// Returns a new LocalAssignment with the given `local` token
     pub fn with_local_token(self, local_token: TokenReference<'a>) -> Self {
         Self {
@@ -1771,7 +2632,7 @@ impl fmt::Display for LocalAssignment<'_> {
             formatter,
             "{}{}{}{}",
             self.local_token,
-            join_type_specifiers(&self.name_list, self.type_specifiers()),
+            join_type_specifiers(&self.name_list, &self.type_specifiers),
             display_option(&self.equal_token),
             self.expr_list
         )
@@ -1796,7 +2657,6 @@ impl fmt::Display for LocalAssignment<'_> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "do_token", "block", "end_token")]
 pub struct Do<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     do_token: TokenReference<'a>,
     block: Block<'a>,
     end_token: TokenReference<'a>,
@@ -1806,9 +2666,9 @@ impl<'a> Do<'a> {
     /// Creates an empty Do
     pub fn new() -> Self {
         Self {
-            do_token: TokenReference::symbol("do\n").unwrap(),
+            do_token: TokenReference::spaced_symbol("", Symbol::Do, "\n"),
             block: Block::new(),
-            end_token: TokenReference::symbol("\nend").unwrap(),
+            end_token: TokenReference::spaced_symbol("\n", Symbol::End, ""),
         }
     }
 
@@ -1854,7 +2714,6 @@ impl Default for Do<'_> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", "prefix", "join_vec(suffixes)")]
 pub struct FunctionCall<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     prefix: Prefix<'a>,
     suffixes: Vec<Suffix<'a>>,
 }
@@ -1869,8 +2728,8 @@ impl<'a> FunctionCall<'a> {
                 FunctionArgs::Parentheses {
                     arguments: Punctuated::new(),
                     parentheses: ContainedSpan::new(
-                        TokenReference::symbol("(").unwrap(),
-                        TokenReference::symbol(")").unwrap(),
+                        TokenReference::spaced_symbol("", Symbol::LeftParen, ""),
+                        TokenReference::spaced_symbol("", Symbol::RightParen, ""),
                     ),
                 },
             ))],
@@ -1896,6 +2755,65 @@ impl<'a> FunctionCall<'a> {
     pub fn with_suffixes(self, suffixes: Vec<Suffix<'a>>) -> Self {
         Self { suffixes, ..self }
     }
+
+    /// The arguments passed to the last call in the chain, such as `c` in `a(b)(c)`.
+    /// Returns `None` if the last suffix is not a call.
+    pub fn call_arguments(&self) -> Option<Arguments<'a, '_>> {
+        match self.suffixes.last()? {
+            Suffix::Call(Call::AnonymousCall(args)) => Some(args.arguments()),
+            Suffix::Call(Call::MethodCall(method_call)) => Some(method_call.args().arguments()),
+            _ => None,
+        }
+    }
+
+    /// Whether the last call in the chain is a method call, such as `a:b()`, which passes `a` as `self`.
+    /// See [`to_dot_call`](FunctionCall::to_dot_call) to rewrite it as `a.b(a)`.
+    pub fn is_method_call(&self) -> bool {
+        self.method_name().is_some()
+    }
+
+    /// The name of the method if the last call in the chain is a method call, such as `b` in `a:b()`
+    pub fn method_name(&self) -> Option<&TokenReference<'a>> {
+        match self.suffixes.last()? {
+            Suffix::Call(Call::MethodCall(method_call)) => Some(method_call.name()),
+            _ => None,
+        }
+    }
+
+    /// The segments of the function being called if it is a plain chain of names and dot indexes,
+    /// such as `["game", "GetService"]` for both `game.GetService()` and `game:GetService()`.
+    /// Returns `None` if the function being called is itself the result of a call, a parenthesized
+    /// expression, or a bracket index. See [`Var::to_path`] for more details.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("string.format('%d', 1)")?;
+    /// if let Some(Stmt::FunctionCall(call)) = ast.nodes().stmts().next() {
+    ///     assert_eq!(call.to_path_string(false).as_deref(), Some("string.format"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_path(&self, include_string_keys: bool) -> Option<Vec<&str>> {
+        let (last, rest) = self.suffixes.split_last()?;
+        let mut path = suffixes_path(&self.prefix, rest.iter(), include_string_keys)?;
+
+        match last {
+            Suffix::Call(Call::AnonymousCall(_)) => {}
+            Suffix::Call(Call::MethodCall(method_call)) => {
+                path.push(identifier_str(method_call.name())?)
+            }
+            _ => return None,
+        }
+
+        Some(path)
+    }
+
+    /// The segments returned by [`to_path`](FunctionCall::to_path) joined with `.`
+    pub fn to_path_string(&self, include_string_keys: bool) -> Option<String> {
+        Some(self.to_path(include_string_keys)?.join("."))
+    }
 }
 
 /// A function name when being declared as [`FunctionDeclaration`]
@@ -1908,7 +2826,6 @@ impl<'a> FunctionCall<'a> {
     "display_option(self.method_name())"
 )]
 pub struct FunctionName<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     names: Punctuated<'a, TokenReference<'a>>,
     colon_name: Option<(TokenReference<'a>, TokenReference<'a>)>,
 }
@@ -1957,9 +2874,23 @@ impl<'a> FunctionName<'a> {
 /// as well as complicated declarations such as `function x.y.z:a() end`
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[display(fmt = "{}{}{}", "function_token", "name", "body")]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(fmt = "{}{}{}", "function_token", "name", "body")
+)]
+#[cfg_attr(
+    feature = "roblox",
+    display(
+        fmt = "{}{}{}{}",
+        "join_vec(attributes)",
+        "function_token",
+        "name",
+        "body"
+    )
+)]
 pub struct FunctionDeclaration<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
+    #[cfg(feature = "roblox")]
+    attributes: Vec<Attribute<'a>>,
     function_token: TokenReference<'a>,
     name: FunctionName<'a>,
     body: FunctionBody<'a>,
@@ -1969,9 +2900,14 @@ impl<'a> FunctionDeclaration<'a> {
     /// Creates a new FunctionDeclaration from the given name
     pub fn new(name: FunctionName<'a>) -> Self {
         Self {
-            function_token: TokenReference::symbol("function ").unwrap(),
+            #[cfg(feature = "roblox")]
+            attributes: Vec::new(),
+            function_token: TokenReference::spaced_symbol("", Symbol::Function, " "),
+            body: FunctionBody {
+                implicit_self: name.method_name().is_some(),
+                ..FunctionBody::new()
+            },
             name,
-            body: FunctionBody::new(),
         }
     }
 
@@ -1990,6 +2926,19 @@ impl<'a> FunctionDeclaration<'a> {
         &self.name
     }
 
+    /// Whether the function is declared as a method, such as `function x:y() end`,
+    /// which gives its body an implicit `self` parameter
+    pub fn is_method(&self) -> bool {
+        self.name.method_name().is_some()
+    }
+
+    /// The attributes on the function, such as `@native`.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn attributes(&self) -> impl Iterator<Item = &Attribute<'a>> {
+        self.attributes.iter()
+    }
+
     /// Returns a new FunctionDeclaration with the given `function` token
     pub fn with_function_token(self, function_token: TokenReference<'a>) -> Self {
         Self {
@@ -1998,14 +2947,36 @@ impl<'a> FunctionDeclaration<'a> {
         }
     }
 
-    /// Returns a new FunctionDeclaration with the given function name
+    /// Returns a new FunctionDeclaration with the given function name.
+    /// The body has an implicit `self` if the name is a method name.
     pub fn with_name(self, name: FunctionName<'a>) -> Self {
-        Self { name, ..self }
+        Self {
+            body: FunctionBody {
+                implicit_self: name.method_name().is_some(),
+                ..self.body
+            },
+            name,
+            ..self
+        }
     }
 
-    /// Returns a new FunctionDeclaration with the given function body
+    /// Returns a new FunctionDeclaration with the given function body.
+    /// The body has an implicit `self` if the function is declared as a method.
     pub fn with_body(self, body: FunctionBody<'a>) -> Self {
-        Self { body, ..self }
+        Self {
+            body: FunctionBody {
+                implicit_self: self.is_method(),
+                ..body
+            },
+            ..self
+        }
+    }
+
+    /// Returns a new FunctionDeclaration with the given attributes.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn with_attributes(self, attributes: Vec<Attribute<'a>>) -> Self {
+        Self { attributes, ..self }
     }
 }
 
@@ -2086,7 +3057,6 @@ pub enum AstError<'a> {
     /// An unexpected token, the most likely scenario when getting an AstError
     UnexpectedToken {
         /// The token that caused the error
-        #[cfg_attr(feature = "serde", serde(borrow))]
         token: Token<'a>,
         /// Any additional information that could be provided for debugging
         additional: Option<Cow<'a, str>>,
@@ -2115,9 +3085,98 @@ impl<'a> fmt::Display for AstError<'a> {
     }
 }
 
+impl<'a> AstError<'a> {
+    /// A short name for the type of error that stays the same between versions, such as `unexpected-token`.
+    /// See [`Error::code`](crate::Error::code).
+    pub fn code(&self) -> &'static str {
+        match self {
+            AstError::Empty => "empty",
+            AstError::NoEof => "no-eof",
+            AstError::UnexpectedToken { .. } => "unexpected-token",
+        }
+    }
+}
+
 impl<'a> std::error::Error for AstError<'a> {}
 
+/// The version of the format that [`Ast`]s are serialized with, which is serialized along with them.
+/// It changes whenever the shape of a node does, including when a variant is added to an enum,
+/// since formats that aren't human readable, such as bincode, identify variants by their position.
+/// Human readable formats, such as JSON, identify variants by their names, which don't change.
+pub const AST_VERSION: u32 = 3;
+
+/// An [`Ast`] that was deserialized along with the [`AST_VERSION`] it was serialized with.
+/// Deserializing an Ast that was serialized with a different version gives an error from the format,
+/// while this gives an [`Error::IncompatibleAstVersion`](crate::Error::IncompatibleAstVersion),
+/// such as to know when a cache of Asts has to be rebuilt.
+/// Only available when the "serde" feature flag is enabled.
+///
+/// ```rust
+/// # use full_moon::{ast::{owned::Owned, VersionedAst}, Error};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let json = serde_json::to_string(&full_moon::parse("local x = 1")?.owned())?;
+/// let ast = serde_json::from_str::<VersionedAst>(&json)?.into_ast()?;
+/// assert_eq!(full_moon::print(&ast), "local x = 1");
+///
+/// // An Ast serialized before versions were added
+/// let json = r#"{"nodes":{"stmts":[]},"eof":{"leading_trivia":[],"token":{"start_position":{"bytes":0,"line":1,"character":1,"byte_column":0},"end_position":{"bytes":0,"line":1,"character":1,"byte_column":0},"token_type":{"type":"Eof"}},"trailing_trivia":[]}}"#;
+/// assert!(matches!(
+///     serde_json::from_str::<VersionedAst>(json)?.into_ast(),
+///     Err(Error::IncompatibleAstVersion { found: 0, .. })
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug)]
+pub struct VersionedAst<'a> {
+    pub(crate) version: u32,
+    pub(crate) ast: Option<Ast<'a>>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> VersionedAst<'a> {
+    /// The version the Ast was serialized with, or 0 if it was serialized before versions were added
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The Ast, if it was serialized with the current [`AST_VERSION`]
+    ///
+    /// # Errors
+    /// Returns an [`Error::IncompatibleAstVersion`](crate::Error::IncompatibleAstVersion) if it was serialized
+    /// with a different version, in which case the nodes aren't deserialized
+    pub fn into_ast(self) -> Result<Ast<'a>, crate::Error<'static>> {
+        self.ast.ok_or(crate::Error::IncompatibleAstVersion {
+            found: self.version,
+            expected: AST_VERSION,
+        })
+    }
+}
+
 /// An abstract syntax tree, contains all the nodes used in the code
+///
+/// When the "serde" feature flag is enabled, the Ast can be serialized with any format.
+/// Human readable formats, such as JSON, use a representation that is easier to read, while
+/// other formats, such as bincode or MessagePack, use a plain representation that they can read back.
+/// Deserializing never borrows from the input, so to cache an Ast between runs, serialize its
+/// [`owned`](owned::Owned::owned) version with a binary format, which keeps all positions and trivia.
+/// If only the structure of the code is needed, use [`CompactAst`] instead.
+///
+/// The [`AST_VERSION`] is serialized before the nodes, and deserializing an Ast that was serialized
+/// with a different version is an error. Deserialize a [`VersionedAst`] to tell that error apart from others.
+///
+/// ```rust
+/// # use full_moon::ast::{owned::Owned, Ast};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1 -- comment")?.owned();
+/// let bytes = bincode::serialize(&ast)?;
+///
+/// let cached: Ast<'static> = bincode::deserialize(&bytes)?;
+/// assert_eq!(full_moon::print(&cached), "local x = 1 -- comment");
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Clone, Debug, Owned)]
 pub struct Ast<'a> {
     pub(crate) nodes: Block<'a>,
@@ -2125,19 +3184,40 @@ pub struct Ast<'a> {
 }
 
 impl<'a> Ast<'a> {
-    /// Create an Ast from the passed tokens. You probably want [`parse`](crate::parse)
+    /// Create an Ast from the passed tokens. You probably want [`parse`](crate::parse),
+    /// unless you already have the tokens from [`tokens`](crate::tokenizer::tokens) and don't want
+    /// to tokenize the code twice. Parsing the output of `tokens` gives the same Ast as `parse`.
+    ///
+    /// ```rust
+    /// # use full_moon::{ast::Ast, tokenizer::tokens};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let tokens = tokens("local x = 1")?;
+    /// // ...use the tokens for something else, such as highlighting...
+    /// let ast = Ast::from_tokens(tokens)?;
+    /// assert_eq!(ast.nodes().stmts().count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
     ///
     /// # Errors
     ///
     /// If the tokens passed are impossible to get through normal tokenization,
-    /// an error of Empty (if the vector is empty) or NoEof (if there is no eof token)
-    /// will be returned.
+    /// an error of Empty (if the vector is empty) or NoEof (if the last token is not an eof token)
+    /// will be returned. An eof token anywhere before the end is an UnexpectedToken error.
     ///
     /// More likely, if the tokens pass are invalid Lua 5.1 code, an
     /// UnexpectedToken error will be returned.
     pub fn from_tokens(tokens: Vec<Token<'a>>) -> Result<Ast<'a>, AstError<'a>> {
         if *tokens.last().ok_or(AstError::Empty)?.token_type() != TokenType::Eof {
             Err(AstError::NoEof)
+        } else if let Some(eof) = tokens[..tokens.len() - 1]
+            .iter()
+            .find(|token| *token.token_type() == TokenType::Eof)
+        {
+            Err(AstError::UnexpectedToken {
+                token: eof.clone(),
+                additional: Some(Cow::Borrowed("eof token before the end of the tokens")),
+            })
         } else {
             let mut tokens = extract_token_references(tokens);
             let mut state = ParserState::new(&tokens);
@@ -2165,35 +3245,14 @@ impl<'a> Ast<'a> {
                 state = state.advance().unwrap();
             }
 
-            match parsers::ParseBlock.parse(state) {
-                Ok((state, block)) => {
-                    if state.index == tokens.len() - 1 {
-                        Ok(Ast {
-                            nodes: block,
-                            eof: tokens.pop().expect(
-                                "(internal full-moon error) No EOF in tokens after checking for EOF."
-                            ),
-                        })
-                    } else {
-                        Err(AstError::UnexpectedToken {
-                            token: state.peek().token.clone(),
-                            additional: Some(Cow::Borrowed("leftover token")),
-                        })
-                    }
-                }
+            let block = parse_all(state, parsers::ParseBlock)?;
 
-                Err(InternalAstError::NoMatch) => Err(AstError::UnexpectedToken {
-                    token: state.peek().token.clone(),
-                    additional: None,
-                }),
-
-                Err(InternalAstError::UnexpectedToken { token, additional }) => {
-                    Err(AstError::UnexpectedToken {
-                        token: token.token,
-                        additional: additional.map(Cow::Borrowed),
-                    })
-                }
-            }
+            Ok(Ast {
+                nodes: block,
+                eof: tokens
+                    .pop()
+                    .expect("(internal full-moon error) No EOF in tokens after checking for EOF."),
+            })
         }
     }
 
@@ -2202,7 +3261,18 @@ impl<'a> Ast<'a> {
         Self { nodes, ..self }
     }
 
-    /// Returns a new Ast with the given EOF token
+    /// Returns a new Ast with the given EOF token. The EOF token has no text of its own,
+    /// so this is how the comments and whitespace at the end of the code are replaced.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Token, TokenReference, TokenType};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1\n\n\n-- end of file")?;
+    /// let eof = TokenReference::new(Vec::new(), ast.eof().token().clone(), Vec::new());
+    /// assert_eq!(full_moon::print(&ast.with_eof(eof)), "local x = 1\n");
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn with_eof(self, eof: TokenReference<'a>) -> Self {
         Self { eof, ..self }
     }
@@ -2224,10 +3294,183 @@ impl<'a> Ast<'a> {
         &mut self.nodes
     }
 
-    /// The EOF token at the end of every Ast
+    /// The EOF token at the end of every Ast. Its leading trivia is the comments and whitespace after the
+    /// last statement, except for the trivia on the same line as it, which belongs to the last token of the
+    /// statement. See [`trailing_trivia`](Ast::trailing_trivia) for all of it. Code that is only comments
+    /// and whitespace has no statements, and all of it is the leading trivia of the EOF token.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1 -- one\n\n-- end of file")?;
+    /// assert_eq!(ast.eof().to_string(), "\n-- end of file");
+    /// assert_eq!(ast.eof().start_position().line(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn eof(&self) -> &TokenReference<'a> {
         &self.eof
     }
+
+    /// The trivia after the last statement: the trailing trivia of its last token, such as a comment
+    /// on the same line, followed by the leading trivia of the [EOF token](Ast::eof).
+    /// If there are no statements, this is all of the trivia in the code.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1 -- one\n\n-- end of file")?;
+    /// let trivia = ast.trailing_trivia().map(ToString::to_string).collect::<Vec<_>>();
+    /// assert_eq!(trivia, vec![" ", "-- one", "\n", "\n", "-- end of file"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn trailing_trivia(&self) -> impl Iterator<Item = &Token<'a>> {
+        crate::node::Node::last_token(&self.nodes)
+            .into_iter()
+            .flat_map(TokenReference::trailing_trivia)
+            .chain(self.eof.leading_trivia())
+    }
+
+    /// Every token of the Ast, including the EOF token, in the order they are printed.
+    /// Unlike [`Node::tokens`](crate::node::Node::tokens), the EOF token is included, so
+    /// the tokens written one after another are the same as [`print`](crate::print).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("call(x) -- comment")?;
+    /// let tokens = ast.all_tokens().map(ToString::to_string).collect::<Vec<_>>();
+    /// assert_eq!(tokens, vec!["call", "(", "x", ") -- comment", ""]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all_tokens<'b>(&'b self) -> impl Iterator<Item = &'b TokenReference<'a>> {
+        crate::query::<TokenReference>(self)
+    }
+
+    /// Calls the given function on every token of the Ast along with its index in [`all_tokens`](Ast::all_tokens),
+    /// allowing them to be edited in place.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Token, TokenType};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local x = y")?;
+    /// let ast = ast.map_tokens(|index, token| {
+    ///     if index == 3 {
+    ///         *token = token.with_token(Token::new(TokenType::Identifier { identifier: "z".into() }));
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(full_moon::print(&ast), "local x = z");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn map_tokens(self, mut map: impl FnMut(usize, &mut TokenReference<'a>)) -> Self {
+        let mut index = 0;
+        crate::query_mut(self, |token: &mut TokenReference<'a>| {
+            map(index, token);
+            index += 1;
+        })
+    }
+
+    /// A compact dump of the structure of the Ast as an s-expression, meant for tests and bug reports.
+    /// Every node is written as its name in snake_case followed by its children,
+    /// and every token as its kind followed by its text and, optionally, its position.
+    /// Nodes containing only tokens are written on a single line.
+    ///
+    /// ```rust
+    /// # use full_moon::debug::SexprOptions;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("return 1")?;
+    /// assert_eq!(
+    ///     ast.to_sexpr(&SexprOptions::new().with_positions(false)),
+    ///     concat!(
+    ///         "(ast\n",
+    ///         "  (block\n",
+    ///         "    (last_stmt\n",
+    ///         "      (return\n",
+    ///         "        (symbol \"return\")\n",
+    ///         "        (expression\n",
+    ///         "          (value (number \"1\"))))))\n",
+    ///         "  (eof \"\"))\n",
+    ///     )
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_sexpr(&self, options: &crate::debug::SexprOptions) -> String {
+        crate::debug::to_sexpr(self, options)
+    }
+}
+
+fn is_multi_value(expression: &Expression) -> bool {
+    match expression {
+        // A type assertion only keeps the first value
+        #[cfg(feature = "roblox")]
+        Expression::Value {
+            type_assertion: Some(_),
+            ..
+        } => false,
+
+        Expression::Value { value, .. } => match &**value {
+            Value::FunctionCall(_) => true,
+            Value::Symbol(token) => {
+                token.token_type()
+                    == &TokenType::Symbol {
+                        symbol: Symbol::Ellipse,
+                    }
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn identifier_str<'b>(token: &'b TokenReference) -> Option<&'b str> {
+    match token.token_type() {
+        TokenType::Identifier { identifier } => Some(identifier),
+        _ => None,
+    }
+}
+
+fn suffixes_path<'a, 'b>(
+    prefix: &'b Prefix<'a>,
+    suffixes: impl Iterator<Item = &'b Suffix<'a>>,
+    include_string_keys: bool,
+) -> Option<Vec<&'b str>> {
+    let mut path = prefix.to_path()?;
+
+    for suffix in suffixes {
+        match suffix {
+            Suffix::Index(Index::Dot { name, .. }) => path.push(identifier_str(name)?),
+            Suffix::Index(Index::Brackets {
+                expression:
+                    Expression::Value {
+                        value,
+                        #[cfg(feature = "roblox")]
+                            type_assertion: None,
+                    },
+                ..
+            }) if include_string_keys => match &**value {
+                // Only keys whose value is the literal as written can be borrowed, so escape sequences,
+                // and line breaks, which long strings drop after their opening brackets, aren't included
+                Value::String(token) => match token.token_type() {
+                    TokenType::StringLiteral {
+                        literal,
+                        multi_line,
+                        ..
+                    } if !literal.contains(&['\n', '\r'][..])
+                        && (multi_line.is_some() || !literal.contains('\\')) =>
+                    {
+                        path.push(literal)
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+
+    Some(path)
 }
 
 /// Extracts leading and trailing trivia from tokens
@@ -2266,6 +3509,7 @@ pub(crate) fn extract_token_references(mut tokens: Vec<Token>) -> Vec<TokenRefer
                 leading_trivia: leading_trivia.drain(..).collect(),
                 trailing_trivia: trailing_trivia.drain(..).collect(),
                 token,
+                origin: Origin::Source,
             });
         }
     }
@@ -2273,6 +3517,46 @@ pub(crate) fn extract_token_references(mut tokens: Vec<Token>) -> Vec<TokenRefer
     references
 }
 
+// Parses the tokens from the state with the parser, which has to parse every token before the eof
+pub(crate) fn parse_all<'a, P: Parser<'a>>(
+    state: ParserState<'a, '_>,
+    parser: P,
+) -> Result<P::Item, AstError<'a>> {
+    match parser.parse(state) {
+        Ok((state, item)) => {
+            if state.index == state.len - 1 {
+                Ok(item)
+            } else {
+                Err(AstError::UnexpectedToken {
+                    token: state.peek().token.clone(),
+                    additional: Some(Cow::Borrowed("leftover token")),
+                })
+            }
+        }
+
+        Err(InternalAstError::NoMatch) => Err(AstError::UnexpectedToken {
+            token: state.peek().token.clone(),
+            additional: None,
+        }),
+
+        Err(InternalAstError::UnexpectedToken { token, additional }) => {
+            Err(AstError::UnexpectedToken {
+                token: token.token,
+                additional: additional.map(Cow::Borrowed),
+            })
+        }
+    }
+}
+
+// The largest sizes of the nodes that are stored the most, checked whenever the crate is built.
+// There is a little room above the current sizes, so a variant growing by a whole node fails the
+// build. Every TokenReference has 8 bytes for its origin, since a token has no padding to keep it in.
+const _: () = assert!(std::mem::size_of::<Expression>() <= 200);
+const _: () = assert!(std::mem::size_of::<Value>() <= 232);
+const _: () = assert!(std::mem::size_of::<Stmt>() <= 416);
+#[cfg(feature = "roblox")]
+const _: () = assert!(std::mem::size_of::<types::TypeInfo>() <= 384);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2311,6 +3595,31 @@ mod tests {
         assert_eq!(references[4].trailing_trivia[0].to_string(), " ");
     }
 
+    #[test]
+    fn test_from_tokens_malformed() {
+        let mut tokens = tokens("local x = 1").unwrap();
+        let eof = tokens.pop().unwrap();
+
+        assert!(matches!(Ast::from_tokens(Vec::new()), Err(AstError::Empty)));
+        assert!(matches!(
+            Ast::from_tokens(tokens.clone()),
+            Err(AstError::NoEof)
+        ));
+
+        tokens.insert(1, eof.clone());
+        tokens.push(eof);
+        match Ast::from_tokens(tokens) {
+            Err(AstError::UnexpectedToken { token, additional }) => {
+                assert_eq!(token.token_type(), &TokenType::Eof);
+                assert_eq!(
+                    additional.as_deref(),
+                    Some("eof token before the end of the tokens")
+                );
+            }
+            other => panic!("expected unexpected eof, got {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn test_with_eof_safety() {
         let new_ast = {
@@ -2387,8 +3696,499 @@ mod tests {
         );
         NumericFor::new(token.clone(), expression.clone(), expression.clone());
         Repeat::new(expression.clone());
-        Return::new();
+        Return::new(vec![expression.clone()]);
         TableConstructor::new();
         While::new(expression.clone());
     }
+
+    #[test]
+    fn test_var_to_path() {
+        fn first_var_path(code: &str, include_string_keys: bool) -> Option<String> {
+            let ast = parse(code).unwrap();
+            let path = match ast.nodes().stmts().next() {
+                Some(Stmt::Assignment(assignment)) => assignment
+                    .variables()
+                    .iter()
+                    .next()
+                    .unwrap()
+                    .to_path_string(include_string_keys),
+                other => panic!("expected assignment, got {:?}", other),
+            };
+            path
+        }
+
+        assert_eq!(first_var_path("a = 1", false).as_deref(), Some("a"));
+        assert_eq!(first_var_path("a.b.c = 1", false).as_deref(), Some("a.b.c"));
+        assert_eq!(first_var_path("a[\"b\"].c = 1", false), None);
+        assert_eq!(
+            first_var_path("a[\"b\"].c = 1", true).as_deref(),
+            Some("a.b.c")
+        );
+        assert_eq!(
+            first_var_path("a[ [[b]] ].c = 1", true).as_deref(),
+            Some("a.b.c")
+        );
+        assert_eq!(first_var_path("a[\"\\120\"] = 1", true), None);
+        assert_eq!(first_var_path("a[\"\\\n\"] = 1", true), None);
+        assert_eq!(first_var_path("a[ [[\nb]] ] = 1", true), None);
+        assert_eq!(first_var_path("a[b].c = 1", true), None);
+        assert_eq!(first_var_path("a:b().c = 1", true), None);
+        assert_eq!(first_var_path("(a).b = 1", true), None);
+    }
+
+    #[test]
+    fn test_function_call_to_path() {
+        fn first_call_path(code: &str) -> Option<String> {
+            let ast = parse(code).unwrap();
+            let path = match ast.nodes().stmts().next() {
+                Some(Stmt::FunctionCall(call)) => call.to_path_string(false),
+                other => panic!("expected function call, got {:?}", other),
+            };
+            path
+        }
+
+        assert_eq!(first_call_path("print()").as_deref(), Some("print"));
+        assert_eq!(
+            first_call_path("game:GetService()").as_deref(),
+            Some("game.GetService")
+        );
+        assert_eq!(first_call_path("a.b.c()").as_deref(), Some("a.b.c"));
+        assert_eq!(first_call_path("a()()"), None);
+        assert_eq!(first_call_path("a:b().c()"), None);
+    }
+
+    fn trimmed(node: impl fmt::Display) -> String {
+        node.to_string().trim().to_owned()
+    }
+
+    #[test]
+    fn test_name_value_pairs() {
+        type Pairs = (Vec<(String, Option<String>)>, Vec<String>, bool);
+
+        fn pairs(code: &str) -> Pairs {
+            let ast = parse(code).unwrap();
+            let result = match ast.nodes().stmts().next() {
+                Some(Stmt::LocalAssignment(local_assignment)) => (
+                    local_assignment
+                        .name_value_pairs()
+                        .map(|pair| (trimmed(pair.0), pair.1.map(trimmed)))
+                        .collect(),
+                    local_assignment.extra_values().map(trimmed).collect(),
+                    local_assignment.is_last_value_multi(),
+                ),
+                other => panic!("expected local assignment, got {:?}", other),
+            };
+            result
+        }
+
+        let pair = |name: &str, value: Option<&str>| (name.to_owned(), value.map(str::to_owned));
+
+        assert_eq!(
+            pairs("local a, b = 1, 2"),
+            (
+                vec![pair("a", Some("1")), pair("b", Some("2"))],
+                vec![],
+                false
+            )
+        );
+        assert_eq!(
+            pairs("local a, b, c = 1, f()"),
+            (
+                vec![
+                    pair("a", Some("1")),
+                    pair("b", Some("f()")),
+                    pair("c", None)
+                ],
+                vec![],
+                true
+            )
+        );
+        assert_eq!(
+            pairs("local a = 1, 2, ..."),
+            (
+                vec![pair("a", Some("1"))],
+                vec!["2".to_owned(), "...".to_owned()],
+                true
+            )
+        );
+        assert_eq!(pairs("local a"), (vec![pair("a", None)], vec![], false));
+    }
+
+    #[cfg(feature = "roblox")]
+    #[test]
+    fn test_name_value_pairs_type_specifiers() {
+        let ast = parse("local a: number, b = 1").unwrap();
+        match ast.nodes().stmts().next() {
+            Some(Stmt::LocalAssignment(local_assignment)) => {
+                let mut pairs = local_assignment.name_value_pairs();
+                let (name, value, type_specifier) = pairs.next().unwrap();
+                assert_eq!(trimmed(name), "a");
+                assert_eq!(value.unwrap().to_string(), "1");
+                assert_eq!(trimmed(type_specifier.unwrap().type_info()), "number");
+                let (name, value, type_specifier) = pairs.next().unwrap();
+                assert_eq!(trimmed(name), "b");
+                assert_eq!(value, None);
+                assert_eq!(type_specifier, None);
+                assert!(pairs.next().is_none());
+            }
+            other => panic!("expected local assignment, got {:?}", other),
+        };
+    }
+
+    #[cfg(feature = "roblox")]
+    #[test]
+    fn test_is_last_value_multi_type_assertion() {
+        let ast = parse("local a, b = f() :: number\nlocal c, d = f()").unwrap();
+        let multi = ast
+            .nodes()
+            .stmts()
+            .map(|stmt| match stmt {
+                Stmt::LocalAssignment(local_assignment) => local_assignment.is_last_value_multi(),
+                other => panic!("expected local assignment, got {:?}", other),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(multi, vec![false, true]);
+    }
+
+    #[test]
+    fn test_var_value_pairs() {
+        let ast = parse("a, b.c = 1, 2, 3").unwrap();
+        match ast.nodes().stmts().next() {
+            Some(Stmt::Assignment(assignment)) => {
+                let pairs = assignment
+                    .var_value_pairs()
+                    .map(|(var, value)| (trimmed(var), value.map(trimmed)))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    pairs,
+                    vec![
+                        ("a".to_owned(), Some("1".to_owned())),
+                        ("b.c".to_owned(), Some("2".to_owned()))
+                    ]
+                );
+                assert_eq!(
+                    assignment.extra_values().map(trimmed).collect::<Vec<_>>(),
+                    vec!["3"]
+                );
+                assert!(!assignment.is_last_value_multi());
+            }
+            other => panic!("expected assignment, got {:?}", other),
+        };
+    }
+
+    #[test]
+    fn test_call_arguments() {
+        fn first_call<'a, 'b>(ast: &'b Ast<'a>) -> &'b FunctionCall<'a> {
+            match ast.nodes().stmts().next() {
+                Some(Stmt::FunctionCall(call)) => call,
+                other => panic!("expected function call, got {:?}", other),
+            }
+        }
+
+        let ast = parse("f\"str\"").unwrap();
+        let mut arguments = first_call(&ast).call_arguments().unwrap();
+        assert_eq!(arguments.len(), 1);
+        assert!(
+            matches!(arguments.next(), Some(ArgumentRef::String(string)) if string.to_string() == "\"str\"")
+        );
+        assert!(arguments.is_empty());
+
+        let ast = parse("f{1,2}").unwrap();
+        let mut arguments = first_call(&ast).call_arguments().unwrap();
+        assert_eq!(arguments.len(), 1);
+        assert!(
+            matches!(arguments.next(), Some(ArgumentRef::TableConstructor(table)) if table.fields().len() == 2)
+        );
+        assert_eq!(arguments.next(), None);
+
+        let ast = parse("obj:m(a, b)").unwrap();
+        let call = first_call(&ast);
+        assert_eq!(call.method_name().unwrap().to_string(), "m");
+        let arguments = call
+            .call_arguments()
+            .unwrap()
+            .map(|argument| argument.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(arguments, vec!["a", "b"]);
+
+        let ast = parse("f(1)()").unwrap();
+        let call = first_call(&ast);
+        assert_eq!(call.method_name(), None);
+        assert!(call.call_arguments().unwrap().is_empty());
+    }
+
+    fn return_values(code: &str) -> Vec<Expression<'static>> {
+        let code = format!("return {}", code);
+        let ast = parse(&code).unwrap();
+        match ast.nodes().last_stmt() {
+            Some(LastStmt::Return(r#return)) => {
+                r#return.returns().iter().map(owned::Owned::owned).collect()
+            }
+            other => panic!("expected return, got {:?}", other),
+        }
+    }
+
+    // The table must print the expected code, which must parse back into the same code
+    fn assert_table(table: &TableConstructor, expected: &str) {
+        assert_eq!(table.to_string(), expected);
+        let reparsed = return_values(expected);
+        assert_eq!(reparsed.len(), 1);
+        assert_eq!(reparsed[0].to_string(), expected);
+    }
+
+    #[test]
+    fn test_table_constructor_builder() {
+        let values = return_values("1, 2, 3");
+
+        assert_table(&TableConstructor::from_pairs(Vec::<(&str, _)>::new()), "{}");
+        assert_table(
+            &TableConstructor::from_pairs(vec![("a", values[0].clone())]),
+            "{ a = 1 }",
+        );
+        assert_table(
+            &TableConstructor::from_pairs(vec![
+                ("a", values[0].clone()),
+                ("b", values[1].clone()),
+                ("end", values[2].clone()),
+                ("a \"b\"", values[0].clone()),
+            ]),
+            r#"{ a = 1, b = 2, ["end"] = 3, ["a \"b\""] = 1 }"#,
+        );
+
+        let mut table = TableConstructor::new();
+        table.push_field(Field::expression_key(values[0].clone(), values[1].clone()));
+        assert_table(&table, "{ [1] = 2 }");
+        table.push_field(Field::NoKey(values[2].clone()));
+        assert_table(&table, "{ [1] = 2, 3 }");
+        assert_eq!(table.fields().len(), 2);
+    }
+
+    #[test]
+    fn test_table_constructor_push_field() {
+        let push = |code: &str| {
+            let mut table = match return_values(code).remove(0) {
+                Expression::Value { value, .. } => match *value {
+                    Value::TableConstructor(table) => *table,
+                    other => panic!("expected table, got {:?}", other),
+                },
+                other => panic!("expected value, got {:?}", other),
+            };
+
+            table.push_field(Field::name_value("x", return_values("true").remove(0)));
+            table.to_string()
+        };
+
+        assert_eq!(push("{1}"), "{1, x = true}");
+        assert_eq!(push("{ 1, 2, }"), "{ 1, 2, x = true, }");
+        assert_eq!(push("{ 1; 2; }"), "{ 1; 2; x = true; }");
+        assert_eq!(push("{}"), "{x = true}");
+    }
+
+    #[test]
+    fn test_if_branches() {
+        let ast = parse("local function f()\n    if a then\n        b()\n    end\nend\n").unwrap();
+        let mut r#if = match ast.nodes().stmts().next() {
+            Some(Stmt::LocalFunction(function)) => match function.body().block().stmts().next() {
+                Some(Stmt::If(r#if)) => r#if.clone(),
+                other => panic!("expected if, got {:?}", other),
+            },
+            other => panic!("expected local function, got {:?}", other),
+        };
+
+        let block = |code: &'static str| parse(code).unwrap().nodes().clone();
+        let condition = return_values("x").remove(0);
+
+        r#if.push_else_if(ElseIf::new(condition.clone()).with_block(block("        c()\n")));
+        r#if.push_else_if(ElseIf::new(condition).with_block(block("        d()\n")));
+        assert_eq!(
+            r#if.to_string(),
+            "    if a then\n        b()\n    elseif x then\n        c()\n    elseif x then\n        d()\n    end\n"
+        );
+
+        r#if.set_else(block("        e()\n"));
+        assert_eq!(
+            r#if.to_string(),
+            "    if a then\n        b()\n    elseif x then\n        c()\n    elseif x then\n        d()\n    else\n        e()\n    end\n"
+        );
+
+        // Setting the block again keeps the existing `else` token
+        r#if.set_else(block("        f()\n"));
+        let (else_token, else_block) = r#if.remove_else().unwrap();
+        assert_eq!(else_token.to_string(), "    else\n");
+        assert_eq!(else_block.to_string(), "        f()\n");
+        assert!(r#if.remove_else().is_none());
+        assert_eq!(r#if.else_block(), None);
+        assert_eq!(
+            r#if.to_string(),
+            "    if a then\n        b()\n    elseif x then\n        c()\n    elseif x then\n        d()\n    end\n"
+        );
+    }
+
+    #[test]
+    fn test_return_builder() {
+        let values = return_values("a, b");
+
+        assert_eq!(Return::new(Vec::new()).to_string(), "return");
+        assert_eq!(Return::new(vec![values[0].clone()]).to_string(), "return a");
+        assert_eq!(Return::new(values.clone()).to_string(), "return a, b");
+
+        let mut r#return = Return::default();
+        r#return.push_expression(values[1].clone());
+        r#return.push_expression(values[0].clone());
+        assert_eq!(r#return.to_string(), "return b, a");
+    }
+
+    #[test]
+    fn test_set_last_stmt() {
+        let ast = parse("do\n    local function f()\n        x()\n    end\nend\n").unwrap();
+        let function = match ast.nodes().stmts().next() {
+            Some(Stmt::Do(r#do)) => match r#do.block().stmts().next() {
+                Some(Stmt::LocalFunction(function)) => function.clone(),
+                other => panic!("expected local function, got {:?}", other),
+            },
+            other => panic!("expected do, got {:?}", other),
+        };
+
+        // The value is followed by a new line, so the `end` token keeps its own indentation
+        let mut r#return = Return::new(return_values("x\n"));
+        let mut token = r#return.token().clone();
+        token.set_leading_whitespace("        ").unwrap();
+        r#return = r#return.with_token(token);
+
+        let mut block = function.body().block().clone();
+        assert!(block.take_last_stmt().is_none());
+        block.set_last_stmt(LastStmt::Return(r#return), None);
+
+        let function = function
+            .clone()
+            .with_body(function.body().clone().with_block(block));
+        assert_eq!(
+            function.to_string(),
+            "    local function f()\n        x()\n        return x\n    end\n"
+        );
+
+        let mut block = function.body().block().clone();
+        let (last_stmt, semicolon) = block.take_last_stmt().unwrap();
+        assert_eq!(last_stmt.to_string(), "        return x\n");
+        assert!(semicolon.is_none());
+        assert!(block.last_stmt().is_none());
+    }
+
+    #[test]
+    fn test_remove_semicolons() {
+        struct RemoveSemicolons;
+
+        impl<'ast> VisitorMut<'ast> for RemoveSemicolons {
+            fn visit_block(&mut self, mut block: Block<'ast>) -> Block<'ast> {
+                for index in 0..block.stmts().count() {
+                    block.set_semicolon(index, None);
+                }
+
+                block.set_last_stmt_semicolon(None);
+                block
+            }
+        }
+
+        let code = "local a = 1; local b = 2 ; -- two\ndo print(a); end;\nlocal function f() return a; end\n";
+        let ast = RemoveSemicolons.visit_ast(parse(code).unwrap());
+        let output = print(&ast);
+
+        assert_eq!(
+            output,
+            "local a = 1 local b = 2  -- two\ndo print(a) end\nlocal function f() return a end\n"
+        );
+        assert_eq!(print(&parse(&output).unwrap()), output);
+    }
+
+    #[test]
+    fn test_add_semicolons() {
+        let semicolon = || Some(TokenReference::symbol(";").unwrap());
+
+        // Statements on the same line, such as the output of a minifier
+        let ast = parse("local x = 1 y = x print(y) return y").unwrap();
+        let mut block = ast.nodes().clone();
+        for index in 0..block.stmts().count() {
+            block.set_semicolon(index, semicolon());
+        }
+
+        block.set_last_stmt_semicolon(semicolon());
+        assert_eq!(block.to_string(), "local x = 1; y = x; print(y); return y;");
+
+        // The semicolon goes before the comment, and the next statement keeps its indentation
+        let ast = parse("do\n    f() -- call f\n    g()\nend").unwrap();
+        let mut block = match ast.nodes().stmts().next() {
+            Some(Stmt::Do(r#do)) => r#do.block().clone(),
+            other => panic!("expected do, got {:?}", other),
+        };
+
+        block.set_semicolon(0, semicolon());
+        assert_eq!(block.to_string(), "    f(); -- call f\n    g()\n");
+
+        // Replacing a semicolon keeps the trivia of the previous one
+        block.set_semicolon(0, semicolon());
+        assert_eq!(block.to_string(), "    f(); -- call f\n    g()\n");
+    }
+
+    #[test]
+    fn test_sort_requires() {
+        let code = r#"-- Dependencies
+
+local Roact = require(script.Roact) -- UI
+-- Utilities
+local Llama = require(script.Llama)
+
+local Cryo = require(script.Cryo);
+--[[ Networking ]] local Net = require(script.Net)
+return nil
+"#;
+
+        let name = |stmt: &Stmt| match stmt {
+            Stmt::LocalAssignment(local) => local.names().iter().next().unwrap().to_string(),
+            other => panic!("expected a local assignment, got {:?}", other),
+        };
+
+        let mut block = parse(code).unwrap().nodes().clone();
+        block.sort_stmts_by(|a, b| name(a).cmp(&name(b)));
+
+        assert_eq!(
+            block.to_string(),
+            r#"-- Dependencies
+
+local Cryo = require(script.Cryo);
+-- Utilities
+local Llama = require(script.Llama)
+--[[ Networking ]] local Net = require(script.Net)
+local Roact = require(script.Roact) -- UI
+return nil
+"#
+        );
+
+        // The separators stay where they are, and comments that end the line get a new line
+        let mut block = parse("local b = 2 -- b\nlocal a = 1")
+            .unwrap()
+            .nodes()
+            .clone();
+        block.reorder_stmts(&[1, 0]);
+        assert_eq!(block.to_string(), "local a = 1\nlocal b = 2 -- b\n");
+
+        let mut block = parse("c() b() -- b\na()").unwrap().nodes().clone();
+        block.reorder_stmts(&[2, 0, 1]);
+        assert_eq!(block.to_string(), "a() c()\nb() -- b\n");
+
+        let block = parse("do\n    b()\n\n    a()\nend").unwrap();
+        let mut inner = match block.nodes().stmts().next() {
+            Some(Stmt::Do(r#do)) => r#do.block().clone(),
+            other => panic!("expected do, got {:?}", other),
+        };
+        inner.reorder_stmts(&[1, 0]);
+        assert_eq!(inner.to_string(), "    a()\n    b()\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "used more than once")]
+    fn test_reorder_stmts_not_permutation() {
+        let mut block = parse("a() b()").unwrap().nodes().clone();
+        block.reorder_stmts(&[0, 0]);
+    }
 }
@@ -121,6 +121,30 @@ impl Owned for TokenType<'_> {
     }
 }
 
+impl Owned for Origin {
+    type Owned = Origin;
+
+    fn owned(&self) -> Self::Owned {
+        *self
+    }
+}
+
+impl Owned for bool {
+    type Owned = bool;
+
+    fn owned(&self) -> Self::Owned {
+        *self
+    }
+}
+
+impl Owned for u32 {
+    type Owned = u32;
+
+    fn owned(&self) -> Self::Owned {
+        *self
+    }
+}
+
 impl<T> Owned for Box<T>
 where
     T: Owned,
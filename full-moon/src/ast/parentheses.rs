@@ -0,0 +1,635 @@
+use super::{
+    punctuated::{Pair, Punctuated},
+    span::ContainedSpan,
+    Call, Expression, Field, FunctionArgs, FunctionCall, Index, Prefix, Suffix, TableConstructor,
+    Value, Var, VarExpression,
+};
+use crate::{
+    node::Node,
+    tokenizer::{Symbol, Token, TokenKind, TokenReference, TokenType},
+    util::{map_first_token, map_last_token},
+};
+
+// Where an expression is, which decides whether parentheses around it change its meaning
+#[derive(Clone, Copy)]
+enum Context {
+    // A whole expression that could also be an operand or be indexed, such as when it's by itself
+    Unknown,
+    // Where only the first value is used, such as arguments before the last
+    Single,
+    // Where every value is used, such as the last argument
+    Multiple,
+    // One side of a binary operator
+    Operand {
+        precedence: u8,
+        right_associative: bool,
+        is_rhs: bool,
+    },
+    // What a unary operator is used on
+    UnaryOperand {
+        precedence: u8,
+    },
+    // Before calls and indexes, such as `(a).b`, or a type assertion
+    Prefix,
+}
+
+// The characters right before and after an expression, when there's no trivia between them
+#[derive(Clone, Copy, Default)]
+struct Surroundings {
+    previous: Option<char>,
+    next: Option<char>,
+}
+
+impl Surroundings {
+    fn between(previous: Option<&TokenReference>, next: Option<&TokenReference>) -> Self {
+        Self {
+            previous: previous
+                .filter(|token| token.trailing_trivia.is_empty())
+                .and_then(|token| token.token().to_string().chars().last()),
+            next: next
+                .filter(|token| token.leading_trivia.is_empty())
+                .and_then(|token| token.token().to_string().chars().next()),
+        }
+    }
+}
+
+impl<'a> Expression<'a> {
+    /// Whether the parentheses of an expression such as `(a)` can be removed without changing what it means.
+    /// Only the parentheses around the whole expression are checked, including ones in a
+    /// [`Value::ParenthesesExpression`].
+    ///
+    /// An expression doesn't know where it's used, so `None` is returned when that decides it:
+    /// - `(a + b)` needs the parentheses in `(a + b) * c`, but not in `local x = (a + b)`.
+    /// - `(f())` and `(...)` only use the first value, which matters where multiple values
+    ///   could be used, such as `return (f())`, but not in `local x = (f())`.
+    /// - `("x")` needs the parentheses in `("x"):upper()`.
+    ///
+    /// `None` is also returned if the expression is not in parentheses.
+    /// Use [`without_redundant_parentheses`](Expression::without_redundant_parentheses) to check the
+    /// parentheses inside of an expression, where it's known how they're used.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("return (x), ((a + b)), (f())")?;
+    /// let return_values = match ast.nodes().last_stmt() {
+    ///     Some(full_moon::ast::LastStmt::Return(r#return)) => r#return.returns(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let redundant = return_values
+    ///     .iter()
+    ///     .map(|value| value.parentheses_are_redundant())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(redundant, vec![Some(true), Some(true), None]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parentheses_are_redundant(&self) -> Option<bool> {
+        redundant(parenthesized(self)?, Context::Unknown)
+    }
+
+    /// Removes the parentheses that don't change what the expression means, such as those in `(a) + b`,
+    /// keeping those needed by the precedence of operators, such as in `(a + b) * c`,
+    /// and those that only use the first value where multiple values could be used, such as the last argument
+    /// in `f((g()))`. Parentheses in function arguments, indexes, and tables are removed too,
+    /// but not those in the bodies of anonymous functions.
+    ///
+    /// Parentheses around the whole expression are removed the same as
+    /// [`parentheses_are_redundant`](Expression::parentheses_are_redundant), so `(f())` is kept.
+    /// Comments inside of removed parentheses are kept, and a space is added where removing them would
+    /// join tokens together, such as in `a-(-b)`, but not before or after the whole expression.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Expression;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut expression = full_moon::ast::builders::expr::name("x");
+    /// expression.splice("((a) + b) * (c + (d * e)) .. f((g()), (h()))")?;
+    ///
+    /// assert_eq!(
+    ///     expression.without_redundant_parentheses().to_string(),
+    ///     "(a + b) * (c + d * e) .. f(g(), (h()))"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn without_redundant_parentheses(self) -> Self {
+        rewrite(self, Context::Unknown, Surroundings::default())
+    }
+}
+
+// The expression inside of the parentheses, if the expression is in parentheses
+fn parenthesized<'a, 'b>(expression: &'b Expression<'a>) -> Option<&'b Expression<'a>> {
+    match expression {
+        Expression::Parentheses { expression, .. } => Some(expression),
+
+        #[cfg(feature = "roblox")]
+        Expression::Value {
+            type_assertion: Some(_),
+            ..
+        } => None,
+
+        Expression::Value { value, .. } => match &**value {
+            Value::ParenthesesExpression(expression) => parenthesized(expression),
+            _ => None,
+        },
+
+        _ => None,
+    }
+}
+
+// Whether the parentheses around the inner expression can be removed where they are
+fn redundant(inner: &Expression, context: Context) -> Option<bool> {
+    if parenthesized(inner).is_some() {
+        return Some(true);
+    }
+
+    match inner {
+        Expression::BinaryOperator { binop, .. } => match context {
+            Context::Operand {
+                precedence,
+                right_associative,
+                is_rhs,
+            } => Some(
+                binop.precedence() > precedence
+                    || (binop.precedence() == precedence && right_associative == is_rhs),
+            ),
+            Context::UnaryOperand { precedence } => Some(binop.precedence() > precedence),
+            Context::Prefix => Some(false),
+            Context::Unknown => None,
+            Context::Single | Context::Multiple => Some(true),
+        },
+
+        Expression::UnaryOperator { unop, .. } => match context {
+            // Unary operators on the right are always parsed as part of the right hand side
+            Context::Operand { is_rhs: true, .. } | Context::UnaryOperand { .. } => Some(true),
+            Context::Operand { precedence, .. } => Some(unop.precedence() > precedence),
+            Context::Prefix => Some(false),
+            Context::Unknown => None,
+            Context::Single | Context::Multiple => Some(true),
+        },
+
+        // Already handled by `parenthesized`
+        Expression::Parentheses { .. } => Some(true),
+
+        Expression::Value {
+            value,
+            #[cfg(feature = "roblox")]
+            type_assertion,
+        } => {
+            // Types are parsed as far as they can go, such as the `<` in `(x :: T) < y`
+            #[cfg(feature = "roblox")]
+            if type_assertion.is_some() {
+                return match context {
+                    Context::Single | Context::Multiple => Some(true),
+                    _ => None,
+                };
+            }
+
+            let is_prefix = matches!(**value, Value::FunctionCall(_) | Value::Var(_));
+            let is_multiple = match &**value {
+                Value::FunctionCall(_) => true,
+                Value::Symbol(token) => {
+                    token.token_type()
+                        == &TokenType::Symbol {
+                            symbol: Symbol::Ellipse,
+                        }
+                }
+                _ => false,
+            };
+
+            match context {
+                Context::Prefix => Some(is_prefix),
+                Context::Multiple => Some(!is_multiple),
+                // Values such as strings could be indexed, such as `("x"):upper()`
+                Context::Unknown if is_multiple || !is_prefix => None,
+                _ => Some(true),
+            }
+        }
+    }
+}
+
+fn rewrite<'a>(
+    expression: Expression<'a>,
+    context: Context,
+    surroundings: Surroundings,
+) -> Expression<'a> {
+    if parenthesized(&expression).and_then(|inner| redundant(inner, context)) == Some(true) {
+        let expression = remove_parentheses(expression, surroundings);
+        return rewrite(expression, context, surroundings);
+    }
+
+    match expression {
+        Expression::BinaryOperator { lhs, binop, rhs } => {
+            let (precedence, right_associative) =
+                (binop.precedence(), binop.is_right_associative());
+            let lhs_surroundings = Surroundings {
+                next: Surroundings::between(None, Some(binop.token())).next,
+                ..surroundings
+            };
+            let rhs_surroundings = Surroundings {
+                previous: Surroundings::between(Some(binop.token()), None).previous,
+                ..surroundings
+            };
+
+            let lhs = rewrite(
+                *lhs,
+                Context::Operand {
+                    precedence,
+                    right_associative,
+                    is_rhs: false,
+                },
+                lhs_surroundings,
+            );
+            let rhs = rewrite(
+                *rhs,
+                Context::Operand {
+                    precedence,
+                    right_associative,
+                    is_rhs: true,
+                },
+                rhs_surroundings,
+            );
+
+            Expression::BinaryOperator {
+                lhs: Box::new(lhs),
+                binop,
+                rhs: Box::new(rhs),
+            }
+        }
+
+        Expression::UnaryOperator { unop, expression } => {
+            let surroundings = Surroundings {
+                previous: Surroundings::between(Some(unop.token()), None).previous,
+                ..surroundings
+            };
+            let expression = rewrite(
+                *expression,
+                Context::UnaryOperand {
+                    precedence: unop.precedence(),
+                },
+                surroundings,
+            );
+
+            Expression::UnaryOperator {
+                unop,
+                expression: Box::new(expression),
+            }
+        }
+
+        Expression::Parentheses {
+            contained,
+            expression,
+        } => {
+            let (left, right) = ContainedSpan::tokens(&contained);
+            let expression = rewrite(
+                *expression,
+                Context::Single,
+                Surroundings::between(Some(left), Some(right)),
+            );
+
+            Expression::Parentheses {
+                contained,
+                expression: Box::new(expression),
+            }
+        }
+
+        Expression::Value {
+            value,
+            #[cfg(feature = "roblox")]
+            type_assertion,
+        } => {
+            #[cfg(feature = "roblox")]
+            let surroundings = match &type_assertion {
+                Some(type_assertion) => Surroundings {
+                    next: Surroundings::between(None, type_assertion.first_token()).next,
+                    ..surroundings
+                },
+                None => surroundings,
+            };
+
+            let value = match *value {
+                #[cfg(feature = "roblox")]
+                Value::ParenthesesExpression(expression) if type_assertion.is_some() => {
+                    Value::ParenthesesExpression(rewrite(expression, Context::Prefix, surroundings))
+                }
+
+                Value::ParenthesesExpression(expression) => {
+                    Value::ParenthesesExpression(rewrite(expression, context, surroundings))
+                }
+
+                Value::FunctionCall(call) => {
+                    let (prefix, suffixes) =
+                        rewrite_prefix_suffixes(call.prefix, call.suffixes, surroundings);
+                    Value::FunctionCall(FunctionCall { prefix, suffixes })
+                }
+
+                Value::Var(Var::Expression(var)) => {
+                    let (prefix, suffixes) =
+                        rewrite_prefix_suffixes(var.prefix, var.suffixes, surroundings);
+                    Value::Var(Var::Expression(VarExpression { prefix, suffixes }))
+                }
+
+                Value::TableConstructor(table) => {
+                    Value::TableConstructor(Box::new(rewrite_table(*table)))
+                }
+
+                other => other,
+            };
+
+            Expression::Value {
+                value: Box::new(value),
+                #[cfg(feature = "roblox")]
+                type_assertion,
+            }
+        }
+    }
+}
+
+// Removes the parentheses around the expression, keeping the trivia around them
+// and any comments inside of them
+fn remove_parentheses(expression: Expression, surroundings: Surroundings) -> Expression {
+    let (contained, inner) = match expression {
+        Expression::Parentheses {
+            contained,
+            expression,
+        } => (contained, *expression),
+
+        Expression::Value { value, .. } => match *value {
+            Value::ParenthesesExpression(expression) => {
+                return remove_parentheses(expression, surroundings)
+            }
+            _ => unreachable!("the expression isn't in parentheses"),
+        },
+
+        _ => unreachable!("the expression isn't in parentheses"),
+    };
+
+    let (left, right) = ContainedSpan::tokens(&contained);
+    let mut leading_trivia = left.leading_trivia.clone();
+    leading_trivia.extend(with_comments(&left.trailing_trivia));
+    let mut trailing_trivia = with_comments(&right.leading_trivia);
+    trailing_trivia.extend(right.trailing_trivia.iter().cloned());
+
+    let inner = map_first_token(inner, |mut token| {
+        let joins = match (
+            surroundings.previous,
+            token.token().to_string().chars().next(),
+        ) {
+            (Some(previous), Some(next)) => leading_trivia.is_empty() && would_join(previous, next),
+            _ => false,
+        };
+
+        if joins {
+            leading_trivia.push(Token::new(TokenType::spaces(1)));
+        }
+
+        leading_trivia.append(&mut token.leading_trivia);
+        token.leading_trivia = leading_trivia;
+        token
+    });
+
+    map_last_token(inner, |mut token| {
+        let joins = match (token.token().to_string().chars().last(), surroundings.next) {
+            (Some(previous), Some(next)) => {
+                trailing_trivia.is_empty()
+                    && token.trailing_trivia.is_empty()
+                    && would_join(previous, next)
+            }
+            _ => false,
+        };
+
+        if joins {
+            trailing_trivia.push(Token::new(TokenType::spaces(1)));
+        }
+
+        token.trailing_trivia.append(&mut trailing_trivia);
+        token
+    })
+}
+
+// The trivia inside of parentheses is only kept if there's a comment in it
+fn with_comments<'a>(trivia: &[Token<'a>]) -> Vec<Token<'a>> {
+    let has_comment = trivia.iter().any(|trivia| {
+        matches!(
+            trivia.token_kind(),
+            TokenKind::SingleLineComment | TokenKind::MultiLineComment
+        )
+    });
+
+    if has_comment {
+        trivia.to_vec()
+    } else {
+        Vec::new()
+    }
+}
+
+// Whether the two characters would be read as part of the same token, or start a comment
+fn would_join(previous: char, next: char) -> bool {
+    let is_identifier = |character: char| character.is_alphanumeric() || character == '_';
+
+    (is_identifier(previous) && is_identifier(next))
+        || (previous.is_ascii_digit() && next == '.')
+        || matches!(
+            (previous, next),
+            ('-', '-') | ('[', '[') | ('[', '=') | ('.', '.')
+        )
+        || (previous == '.' && next.is_ascii_digit())
+}
+
+fn rewrite_prefix_suffixes<'a>(
+    prefix: Prefix<'a>,
+    suffixes: Vec<Suffix<'a>>,
+    surroundings: Surroundings,
+) -> (Prefix<'a>, Vec<Suffix<'a>>) {
+    let prefix_surroundings = Surroundings {
+        next: Surroundings::between(None, suffixes.first().and_then(Node::first_token)).next,
+        ..surroundings
+    };
+
+    let prefix = match prefix {
+        Prefix::Expression(expression)
+            if parenthesized(&expression).and_then(|inner| redundant(inner, Context::Prefix))
+                == Some(true) =>
+        {
+            let (prefix, mut inner_suffixes) =
+                match remove_parentheses(expression, prefix_surroundings) {
+                    Expression::Value { value, .. } => match *value {
+                        Value::Var(Var::Name(name)) => (Prefix::Name(name), Vec::new()),
+                        Value::Var(Var::Expression(var)) => (var.prefix, var.suffixes),
+                        Value::FunctionCall(call) => (call.prefix, call.suffixes),
+                        other => (
+                            Prefix::Expression(Expression::Value {
+                                value: Box::new(other),
+                                #[cfg(feature = "roblox")]
+                                type_assertion: None,
+                            }),
+                            Vec::new(),
+                        ),
+                    },
+                    other => (Prefix::Expression(other), Vec::new()),
+                };
+
+            inner_suffixes.extend(suffixes);
+            return rewrite_prefix_suffixes(prefix, inner_suffixes, surroundings);
+        }
+
+        Prefix::Expression(expression) => {
+            Prefix::Expression(rewrite(expression, Context::Prefix, prefix_surroundings))
+        }
+
+        name => name,
+    };
+
+    let suffixes = suffixes
+        .into_iter()
+        .map(|suffix| match suffix {
+            Suffix::Call(Call::AnonymousCall(args)) => {
+                Suffix::Call(Call::AnonymousCall(rewrite_args(args)))
+            }
+
+            Suffix::Call(Call::MethodCall(mut method_call)) => {
+                method_call.args = rewrite_args(method_call.args);
+                Suffix::Call(Call::MethodCall(method_call))
+            }
+
+            Suffix::Index(Index::Brackets {
+                brackets,
+                expression,
+            }) => {
+                let (left, right) = brackets.tokens();
+                let expression = rewrite(
+                    expression,
+                    Context::Single,
+                    Surroundings::between(Some(left), Some(right)),
+                );
+
+                Suffix::Index(Index::Brackets {
+                    brackets,
+                    expression,
+                })
+            }
+
+            other => other,
+        })
+        .collect();
+
+    (prefix, suffixes)
+}
+
+fn rewrite_args(args: FunctionArgs) -> FunctionArgs {
+    match args {
+        FunctionArgs::Parentheses {
+            parentheses,
+            arguments,
+        } => {
+            let (left, right) = parentheses.tokens();
+            let arguments =
+                rewrite_punctuated(arguments, left, right, |argument, context, surroundings| {
+                    rewrite(argument, context, surroundings)
+                });
+
+            FunctionArgs::Parentheses {
+                parentheses,
+                arguments,
+            }
+        }
+
+        other => other,
+    }
+}
+
+fn rewrite_table(table: TableConstructor) -> TableConstructor {
+    let (left, right) = table.braces.tokens();
+    let fields =
+        rewrite_punctuated(
+            table.fields,
+            left,
+            right,
+            |field, context, surroundings| match field {
+                Field::ExpressionKey {
+                    brackets,
+                    key,
+                    equal,
+                    value,
+                } => {
+                    let (left, right) = brackets.tokens();
+                    let key = rewrite(
+                        key,
+                        Context::Single,
+                        Surroundings::between(Some(left), Some(right)),
+                    );
+                    let value = rewrite(
+                        value,
+                        Context::Single,
+                        Surroundings {
+                            previous: Surroundings::between(Some(&equal), None).previous,
+                            ..surroundings
+                        },
+                    );
+
+                    Field::ExpressionKey {
+                        brackets,
+                        key,
+                        equal,
+                        value,
+                    }
+                }
+
+                Field::NameKey { key, equal, value } => {
+                    let value = rewrite(
+                        value,
+                        Context::Single,
+                        Surroundings {
+                            previous: Surroundings::between(Some(&equal), None).previous,
+                            ..surroundings
+                        },
+                    );
+
+                    Field::NameKey { key, equal, value }
+                }
+
+                Field::NoKey(value) => Field::NoKey(rewrite(value, context, surroundings)),
+            },
+        );
+
+    TableConstructor {
+        braces: table.braces,
+        fields,
+    }
+}
+
+// Rewrites every value of a list between the two tokens. Only the last value
+// is where multiple values can be used
+fn rewrite_punctuated<'a, T>(
+    punctuated: Punctuated<'a, T>,
+    left: &TokenReference<'a>,
+    right: &TokenReference<'a>,
+    mut rewrite: impl FnMut(T, Context, Surroundings) -> T,
+) -> Punctuated<'a, T> {
+    let length = punctuated.len();
+    let mut previous = Surroundings::between(Some(left), None).previous;
+
+    punctuated
+        .into_pairs()
+        .enumerate()
+        .map(|(index, pair)| {
+            let (value, punctuation) = pair.into_tuple();
+            let context = if index + 1 == length {
+                Context::Multiple
+            } else {
+                Context::Single
+            };
+
+            let surroundings = Surroundings {
+                previous,
+                next: Surroundings::between(None, Some(punctuation.as_ref().unwrap_or(right))).next,
+            };
+
+            previous = punctuation
+                .as_ref()
+                .and_then(|punctuation| Surroundings::between(Some(punctuation), None).previous);
+
+            Pair::new(rewrite(value, context, surroundings), punctuation)
+        })
+        .collect()
+}
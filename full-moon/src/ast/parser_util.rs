@@ -39,9 +39,6 @@ impl<'a, 'b> ParserState<'a, 'b> {
         }
     }
 
-    // TODO: This is bad, containing a mandatory clone on every call so that everything is
-    // backwards compatible, since it SHOULD just borrow. It is only like this because of a failure
-    // to tackle lifetimes.
     pub fn peek(&self) -> &TokenReference<'a> {
         if self.index >= self.len {
             panic!("peek failed, when there should always be an eof");
@@ -83,12 +80,31 @@ macro_rules! make_op {
         $(#[$outer])*
         #[display(fmt = "{}")]
         pub enum $enum<'a> {
-            #[cfg_attr(feature = "serde", serde(borrow))]
             $(
                 #[allow(missing_docs)]
                 $operator(TokenReference<'a>),
             )+
         }
+
+        impl<'a> $enum<'a> {
+            /// The token of the operator, such as `+`
+            pub fn token(&self) -> &TokenReference<'a> {
+                match self {
+                    $(
+                        $enum::$operator(token) => token,
+                    )+
+                }
+            }
+
+            /// Returns the same operator with the given token
+            pub fn with_token(self, token: TokenReference<'a>) -> Self {
+                match self {
+                    $(
+                        $enum::$operator(_) => $enum::$operator(token),
+                    )+
+                }
+            }
+        }
     };
 }
 
@@ -123,9 +139,10 @@ macro_rules! parse_first_of {
         $(
             $(#[$meta])?
             {
-                let parser_result = $parser.parse($state).map(|(state, node)| (state, $constructor(node)));
-                if parser_result != Err(InternalAstError::NoMatch) {
-                    return parser_result;
+                match $parser.parse($state) {
+                    Ok((state, node)) => return Ok((state, $constructor(node))),
+                    Err(InternalAstError::NoMatch) => {}
+                    Err(other) => return Err(other),
                 }
             }
         )+
@@ -218,7 +235,6 @@ macro_rules! define_lua52_parser {
 pub enum InternalAstError<'a> {
     NoMatch,
     UnexpectedToken {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         token: TokenReference<'a>,
         additional: Option<&'a str>,
     },
@@ -298,28 +314,28 @@ where
     ) -> Result<(ParserState<'a, 'b>, Punctuated<'a, T>), InternalAstError<'a>> {
         let mut nodes = Punctuated::new();
 
-        if let Ok((new_state, node)) = keep_going!(self.0.parse(state)) {
+        // The last node is only pushed once it's known whether a delimiter follows it
+        let mut last_node = if let Ok((new_state, node)) = keep_going!(self.0.parse(state)) {
             state = new_state;
-            nodes.push(Pair::End(node));
+            node
         } else {
-            return Ok((state, Punctuated::new()));
-        }
+            return Ok((state, nodes));
+        };
 
         while let Ok((new_state, delimiter)) = keep_going!(self.1.parse(state)) {
-            let last_value = nodes.pop().unwrap().into_value();
-            nodes.push(Pair::Punctuated(last_value, delimiter));
-
             state = new_state;
 
             match self.0.parse(state) {
                 Ok((new_state, node)) => {
                     state = new_state;
-                    nodes.push(Pair::End(node));
+                    nodes.push(Pair::Punctuated(last_node, delimiter));
+                    last_node = node;
                 }
 
                 Err(InternalAstError::NoMatch) => {
                     if self.2 {
-                        break;
+                        nodes.push(Pair::Punctuated(last_node, delimiter));
+                        return Ok((state, nodes));
                     } else {
                         return Err(InternalAstError::UnexpectedToken {
                             token: state.peek().clone(),
@@ -334,6 +350,8 @@ where
             }
         }
 
+        nodes.push(Pair::End(last_node));
+
         if !self.2 {
             test_pairs_logic!(nodes, "ZeroOrMoreDelimited");
         }
@@ -365,22 +383,21 @@ where
         state: ParserState<'a, 'b>,
     ) -> Result<(ParserState<'a, 'b>, Punctuated<'a, ItemParser::Item>), InternalAstError<'a>> {
         let mut nodes = Punctuated::new();
-        let (mut state, node) = self.0.parse(state)?;
-        nodes.push(Pair::End(node));
+        // The last node is only pushed once it's known whether a delimiter follows it
+        let (mut state, mut last_node) = self.0.parse(state)?;
 
         while let Ok((new_state, delimiter)) = self.1.parse(state) {
-            let last_value = nodes.pop().unwrap().into_value();
-            nodes.push(Pair::Punctuated(last_value, delimiter));
-
             match self.0.parse(new_state) {
                 Ok((new_state, node)) => {
                     state = new_state;
-                    nodes.push(Pair::End(node));
+                    nodes.push(Pair::Punctuated(last_node, delimiter));
+                    last_node = node;
                 }
 
                 Err(InternalAstError::NoMatch) => {
                     if self.2 {
-                        state = new_state;
+                        nodes.push(Pair::Punctuated(last_node, delimiter));
+                        return Ok((new_state, nodes));
                     }
 
                     break;
@@ -392,10 +409,9 @@ where
             }
         }
 
-        if !self.2 {
-            let last_value = nodes.pop().unwrap().into_value();
-            nodes.push(Pair::End(last_value));
+        nodes.push(Pair::End(last_node));
 
+        if !self.2 {
             test_pairs_logic!(nodes, "OneOrMore");
         }
 
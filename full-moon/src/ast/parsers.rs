@@ -243,7 +243,7 @@ define_parser!(ParseParenExpression, Expression<'a>, |_, state| {
     Ok((
         state,
         Expression::Parentheses {
-            contained: ContainedSpan::new(left_paren, right_paren),
+            contained: Box::new(ContainedSpan::new(left_paren, right_paren)),
             expression: Box::new(expression),
         },
     ))
@@ -256,7 +256,7 @@ define_parser!(ParseValueExpression, Expression<'a>, |_, state| {
     #[cfg(feature = "roblox")]
     let (state, type_assertion) =
         if let Ok((state, type_assertion)) = keep_going!(ParseTypeAssertion.parse(state)) {
-            (state, Some(type_assertion))
+            (state, Some(Box::new(type_assertion)))
         } else {
             (state, None)
         };
@@ -324,7 +324,7 @@ define_parser!(
 );
 
 #[derive(Clone, Debug, PartialEq)]
-struct ParseExpression;
+pub struct ParseExpression;
 define_parser!(ParseExpression, Expression<'a>, |_, state| {
     ParseExpressionAtPrecedence(1).parse(state)
 });
@@ -358,37 +358,36 @@ define_parser!(ParseValue, Value<'a>, |_, state| parse_first_of!(state, {
     ParseNumber => Value::Number,
     ParseStringLiteral => Value::String,
     ParseSymbol(Symbol::Ellipse) => Value::Symbol,
-    ParseFunction => Value::Function,
-    ParseTableConstructor => Value::TableConstructor,
-    ParseFunctionCall => Value::FunctionCall,
-    ParseVar => Value::Var,
-    ParseParenExpression => Value::ParenthesesExpression,
+    ParseFunction => |function| Value::Function(Box::new(function)),
+    @#[cfg(feature = "roblox")]
+    ParseAttributedFunction => |function| Value::Function(Box::new(function)),
+    ParseTableConstructor => |table| Value::TableConstructor(Box::new(table)),
+    ParsePrefixValue => std::convert::identity,
 }));
 
 #[derive(Clone, Debug, Default, PartialEq)]
 struct ParseStmt;
 define_parser!(ParseStmt, Stmt<'a>, |_, state| parse_first_of!(state, {
-    ParseAssignment => Stmt::Assignment,
-    ParseFunctionCall => Stmt::FunctionCall,
-    ParseDo => Stmt::Do,
-    ParseWhile => Stmt::While,
-    ParseRepeat => Stmt::Repeat,
-    ParseIf => Stmt::If,
-    ParseNumericFor => Stmt::NumericFor,
-    ParseGenericFor => Stmt::GenericFor,
-    ParseFunctionDeclaration => Stmt::FunctionDeclaration,
-    ParseLocalFunction => Stmt::LocalFunction,
+    ParseAssignmentOrFunctionCall => std::convert::identity,
+    ParseDo => |node| Stmt::Do(Box::new(node)),
+    ParseWhile => |node| Stmt::While(Box::new(node)),
+    ParseRepeat => |node| Stmt::Repeat(Box::new(node)),
+    ParseIf => |node| Stmt::If(Box::new(node)),
+    ParseNumericFor => |node| Stmt::NumericFor(Box::new(node)),
+    ParseGenericFor => |node| Stmt::GenericFor(Box::new(node)),
+    ParseFunctionDeclaration => |node| Stmt::FunctionDeclaration(Box::new(node)),
+    ParseLocalFunction => |node| Stmt::LocalFunction(Box::new(node)),
     ParseLocalAssignment => Stmt::LocalAssignment,
     @#[cfg(feature = "roblox")]
-    ParseCompoundAssignment => Stmt::CompoundAssignment,
+    ParseExportedTypeDeclaration => |node| Stmt::ExportedTypeDeclaration(Box::new(node)),
     @#[cfg(feature = "roblox")]
-    ParseExportedTypeDeclaration => Stmt::ExportedTypeDeclaration,
+    ParseAttributedFunctionStmt => std::convert::identity,
     @#[cfg(feature = "roblox")]
-    ParseTypeDeclaration => Stmt::TypeDeclaration,
+    ParseTypeDeclaration => |node| Stmt::TypeDeclaration(Box::new(node)),
     @#[cfg(feature = "lua52")]
     ParseGoto => Stmt::Goto,
     @#[cfg(feature = "lua52")]
-    ParseLabel => Stmt::Label,
+    ParseLabel => |node| Stmt::Label(Box::new(node)),
 }));
 
 #[derive(Clone, Debug, PartialEq)]
@@ -846,25 +845,30 @@ define_parser!(ParseFunctionBody, FunctionBody<'a>, |_, state| {
             type_specifiers,
             #[cfg(feature = "roblox")]
             return_type,
+            implicit_self: false,
         },
     ))
 });
 
 #[derive(Clone, Debug, PartialEq)]
 struct ParseFunction;
-define_parser!(
-    ParseFunction,
-    (TokenReference<'a>, FunctionBody<'a>),
-    |_, state| {
-        let (state, token) = ParseSymbol(Symbol::Function).parse(state)?;
-        let (state, body) = expect!(
-            state,
-            ParseFunctionBody.parse(state),
-            "expected function body"
-        );
-        Ok((state, (token, body)))
-    }
-);
+define_parser!(ParseFunction, AnonymousFunction<'a>, |_, state| {
+    let (state, function_token) = ParseSymbol(Symbol::Function).parse(state)?;
+    let (state, body) = expect!(
+        state,
+        ParseFunctionBody.parse(state),
+        "expected function body"
+    );
+    Ok((
+        state,
+        AnonymousFunction {
+            #[cfg(feature = "roblox")]
+            attributes: Vec::new(),
+            function_token,
+            body,
+        },
+    ))
+});
 
 #[derive(Clone, Debug, PartialEq)]
 struct ParseSuffix;
@@ -873,11 +877,42 @@ define_parser!(ParseSuffix, Suffix<'a>, |_, state| parse_first_of!(state, {
     ParseIndex => Suffix::Index,
 }));
 
+// Function calls and variables both start with a prefix followed by suffixes, and are told apart by the last suffix.
+// Parsers that accept either parse these once, rather than trying each alternative from the start.
+#[derive(Clone, Debug, PartialEq)]
+struct ParsePrefixSuffixes;
+define_parser!(
+    ParsePrefixSuffixes,
+    (Prefix<'a>, Vec<Suffix<'a>>),
+    |_, state| {
+        let (state, prefix) = ParsePrefix.parse(state)?;
+        let (state, suffixes) = ZeroOrMore(ParseSuffix).parse(state)?;
+        Ok((state, (prefix, suffixes)))
+    }
+);
+
+// A function call, a variable, or an expression in parentheses
+#[derive(Clone, Debug, PartialEq)]
+struct ParsePrefixValue;
+define_parser!(ParsePrefixValue, Value<'a>, |_, state| {
+    let (state, (prefix, suffixes)) = ParsePrefixSuffixes.parse(state)?;
+
+    let value = match suffixes.last() {
+        Some(Suffix::Call(_)) => Value::FunctionCall(FunctionCall { prefix, suffixes }),
+        Some(Suffix::Index(_)) => Value::Var(Var::Expression(VarExpression { prefix, suffixes })),
+        None => match prefix {
+            Prefix::Name(name) => Value::Var(Var::Name(name)),
+            Prefix::Expression(expression) => Value::ParenthesesExpression(expression),
+        },
+    };
+
+    Ok((state, value))
+});
+
 #[derive(Clone, Debug, PartialEq)]
 struct ParseVarExpression;
 define_parser!(ParseVarExpression, VarExpression<'a>, |_, state| {
-    let (state, prefix) = ParsePrefix.parse(state)?;
-    let (state, suffixes) = ZeroOrMore(ParseSuffix).parse(state)?;
+    let (state, (prefix, suffixes)) = ParsePrefixSuffixes.parse(state)?;
 
     if let Some(Suffix::Index(_)) = suffixes.last() {
         Ok((state, VarExpression { prefix, suffixes }))
@@ -893,10 +928,56 @@ define_parser!(ParseVar, Var<'a>, |_, state| parse_first_of!(state, {
     ParseIdentifier => Var::Name,
 }));
 
+// An assignment, a compound assignment, or a function call, which all start with a variable or function call
 #[derive(Clone, Debug, Default, PartialEq)]
-struct ParseAssignment;
-define_parser!(ParseAssignment, Assignment<'a>, |_, state| {
-    let (state, var_list) = OneOrMore(ParseVar, ParseSymbol(Symbol::Comma), false).parse(state)?;
+struct ParseAssignmentOrFunctionCall;
+define_parser!(ParseAssignmentOrFunctionCall, Stmt<'a>, |_, state| {
+    let (mut state, (prefix, suffixes)) = ParsePrefixSuffixes.parse(state)?;
+
+    let var = match suffixes.last() {
+        Some(Suffix::Call(_)) => {
+            return Ok((state, Stmt::FunctionCall(FunctionCall { prefix, suffixes })))
+        }
+        Some(Suffix::Index(_)) => Var::Expression(VarExpression { prefix, suffixes }),
+        None => match prefix {
+            Prefix::Name(name) => Var::Name(name),
+            Prefix::Expression(_) => return Err(InternalAstError::NoMatch),
+        },
+    };
+
+    #[cfg(feature = "roblox")]
+    {
+        if let Ok((state, compound_operator)) = ParseCompoundOp.parse(state) {
+            let (state, rhs) = expect!(state, ParseExpression.parse(state), "expected value");
+
+            return Ok((
+                state,
+                Stmt::CompoundAssignment(Box::new(CompoundAssignment {
+                    lhs: var,
+                    compound_operator,
+                    rhs,
+                })),
+            ));
+        }
+    }
+
+    let mut var_list = Punctuated::new();
+    let mut last_var = var;
+    while let Ok((new_state, comma)) = ParseSymbol(Symbol::Comma).parse(state) {
+        match ParseVar.parse(new_state) {
+            Ok((new_state, var)) => {
+                var_list.push(Pair::Punctuated(last_var, comma));
+                last_var = var;
+                state = new_state;
+            }
+
+            Err(InternalAstError::NoMatch) => break,
+            Err(other) => return Err(other),
+        }
+    }
+
+    var_list.push(Pair::End(last_var));
+
     let (state, equal_token) = ParseSymbol(Symbol::Equal).parse(state)?;
     let (state, expr_list) = expect!(
         state,
@@ -906,11 +987,11 @@ define_parser!(ParseAssignment, Assignment<'a>, |_, state| {
 
     Ok((
         state,
-        Assignment {
+        Stmt::Assignment(Assignment {
             var_list,
             equal_token,
             expr_list,
-        },
+        }),
     ))
 });
 
@@ -924,6 +1005,8 @@ define_parser!(ParseLocalFunction, LocalFunction<'a>, |_, state| {
     Ok((
         state,
         LocalFunction {
+            #[cfg(feature = "roblox")]
+            attributes: Vec::new(),
             local_token,
             function_token,
             name,
@@ -1014,19 +1097,6 @@ define_parser!(ParseDo, Do<'a>, |_, state| {
     ))
 });
 
-#[derive(Clone, Debug, PartialEq)]
-struct ParseFunctionCall;
-define_parser!(ParseFunctionCall, FunctionCall<'a>, |_, state| {
-    let (state, prefix) = ParsePrefix.parse(state)?;
-    let (state, suffixes) = ZeroOrMore(ParseSuffix).parse(state)?;
-
-    if let Some(Suffix::Call(_)) = suffixes.last() {
-        Ok((state, FunctionCall { prefix, suffixes }))
-    } else {
-        Err(InternalAstError::NoMatch)
-    }
-});
-
 #[derive(Clone, Debug, PartialEq)]
 struct ParseFunctionName;
 define_parser!(ParseFunctionName, FunctionName<'a>, |_, state| {
@@ -1063,9 +1133,14 @@ define_parser!(
         Ok((
             state,
             FunctionDeclaration {
+                #[cfg(feature = "roblox")]
+                attributes: Vec::new(),
                 function_token,
+                body: FunctionBody {
+                    implicit_self: name.method_name().is_some(),
+                    ..body
+                },
                 name,
-                body,
             },
         ))
     }
@@ -1145,32 +1220,6 @@ define_roblox_parser!(
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "roblox")] {
-        // Roblox Compound Assignment
-        #[derive(Clone, Debug, Default, PartialEq)]
-        struct ParseCompoundAssignment;
-        define_parser!(
-            ParseCompoundAssignment,
-            CompoundAssignment<'a>,
-            |_, state| {
-                let (state, lhs) = ParseVar.parse(state)?;
-                let (state, compound_operator) = ParseCompoundOp.parse(state)?;
-                let (state, rhs) = expect!(
-                    state,
-                    ParseExpression.parse(state),
-                    "expected value"
-                );
-
-                Ok((
-                    state,
-                    CompoundAssignment {
-                        lhs,
-                        compound_operator,
-                        rhs,
-                    },
-                ))
-            }
-        );
-
         #[derive(Clone, Debug, PartialEq)]
         struct ParseTypeDeclaration;
         define_parser!(
@@ -1298,6 +1347,46 @@ cfg_if::cfg_if! {
         #[derive(Clone, Debug, PartialEq)]
         struct ParseSingleTypeInfo(TypeInfoContext);
         define_parser!(ParseSingleTypeInfo, TypeInfo<'a>, |this, state| {
+            // Generic parameters of a function type, such as the `<T>` in `<T>(T) -> T`.
+            // `<` can't be a comparison here, as no type has been parsed yet
+            let (state, generics) = if let Ok((state, start_arrow)) = ParseSymbol(Symbol::LessThan).parse(state) {
+                let (state, generics) = expect!(
+                    state,
+                    OneOrMore(ParseIdentifier, ParseSymbol(Symbol::Comma), false).parse(state),
+                    "expected type parameters"
+                );
+
+                // GenericDeclaration only holds names, so packs such as the `U...` in `<T, U...>` can't be kept
+                if let Ok((_, ellipse)) = ParseSymbol(Symbol::Ellipse).parse(state) {
+                    return Err(InternalAstError::UnexpectedToken {
+                        token: ellipse,
+                        additional: Some("generic type packs are not supported in function types"),
+                    });
+                }
+
+                let (state, end_arrow) = expect!(
+                    state,
+                    ParseSymbol(Symbol::GreaterThan).parse(state),
+                    "expected `>` to match `<`"
+                );
+
+                expect!(
+                    state,
+                    ParseSymbol(Symbol::LeftParen).parse(state),
+                    "expected `(` after type parameters of function type"
+                );
+
+                (
+                    state,
+                    Some(Box::new(GenericDeclaration {
+                        arrows: ContainedSpan::new(start_arrow, end_arrow),
+                        generics,
+                    })),
+                )
+            } else {
+                (state, None)
+            };
+
             let (mut state, mut base_type) = if let Ok((state, identifier)) = {
                 ParseIdentifier
                     .parse(state)
@@ -1326,7 +1415,7 @@ cfg_if::cfg_if! {
                         state,
                         TypeInfo::Typeof {
                             typeof_token: identifier,
-                            parentheses: ContainedSpan::new(start_parenthese, end_parenthese),
+                            parentheses: Box::new(ContainedSpan::new(start_parenthese, end_parenthese)),
                             inner: Box::new(expression),
                         },
                     )
@@ -1364,7 +1453,7 @@ cfg_if::cfg_if! {
                         state,
                         TypeInfo::Generic {
                             base: identifier,
-                            arrows: ContainedSpan::new(start_arrow, end_arrow),
+                            arrows: Box::new(ContainedSpan::new(start_arrow, end_arrow)),
                             generics,
                         },
                     )
@@ -1387,32 +1476,22 @@ cfg_if::cfg_if! {
                     "expected `)` to match `(`"
                 );
 
-                if let TypeInfoContext::ReturnType = this.0 {
-                    // Tuples are only permitted as the return type of a function
-                    if let Ok((state, arrow)) = ParseSymbol(Symbol::ThinArrow).parse(state) {
-                        let (state, return_value) = expect!(
-                            state,
-                            ParseTypeInfo(TypeInfoContext::ReturnType).parse(state),
-                            "expected return type after `->`"
-                        );
-                        (
-                            state,
-                            TypeInfo::Callback {
-                                arguments: types,
-                                parentheses: ContainedSpan::new(start_parenthese, end_parenthese),
-                                arrow,
-                                return_type: Box::new(return_value),
-                            },
-                        )
-                    } else {
-                        (
-                            state,
-                            TypeInfo::Tuple {
-                                parentheses: ContainedSpan::new(start_parenthese, end_parenthese),
-                                types,
-                            },
-                        )
-                    }
+                // Tuples are only permitted as the return type of a function, other than a single type
+                // in parentheses, such as the `(A & B)` in `(A & B) | C`
+                let is_tuple = generics.is_none()
+                    && ParseSymbol(Symbol::ThinArrow).parse(state).is_err()
+                    && (matches!(this.0, TypeInfoContext::ReturnType)
+                        || (types.len() == 1
+                            && !matches!(types.iter().next(), Some(TypeInfo::Variadic { .. }))));
+
+                if is_tuple {
+                    (
+                        state,
+                        TypeInfo::Tuple {
+                            parentheses: ContainedSpan::new(start_parenthese, end_parenthese),
+                            types,
+                        },
+                    )
                 } else {
                     let (state, arrow) = expect!(
                         state,
@@ -1429,8 +1508,9 @@ cfg_if::cfg_if! {
                     (
                         state,
                         TypeInfo::Callback {
+                            generics,
                             arguments: types,
-                            parentheses: ContainedSpan::new(start_parenthese, end_parenthese),
+                            parentheses: Box::new(ContainedSpan::new(start_parenthese, end_parenthese)),
                             arrow,
                             return_type: Box::new(return_value),
                         },
@@ -1497,7 +1577,8 @@ cfg_if::cfg_if! {
                 return Err(InternalAstError::NoMatch);
             };
 
-            if let Ok((new_state, question_mark)) = ParseSymbol(Symbol::QuestionMark).parse(state) {
+            // `string??` is the same as `string?`, but every `?` is kept as its own optional type
+            while let Ok((new_state, question_mark)) = ParseSymbol(Symbol::QuestionMark).parse(state) {
                 base_type = TypeInfo::Optional {
                     base: Box::new(base_type),
                     question_mark,
@@ -1520,6 +1601,15 @@ cfg_if::cfg_if! {
                     ParseTypeInfo(this.0).parse(state),
                     "expected type after `|` for union type"
                 );
+
+                // Luau requires parentheses to mix unions and intersections, such as `A | (B & C)`
+                if let TypeInfo::Intersection { ampersand, .. } = &right {
+                    return Err(InternalAstError::UnexpectedToken {
+                        token: ampersand.clone(),
+                        additional: Some("cannot mix `&` and `|` without parentheses"),
+                    });
+                }
+
                 Ok((
                     state,
                     TypeInfo::Union {
@@ -1534,6 +1624,14 @@ cfg_if::cfg_if! {
                     ParseTypeInfo(this.0).parse(state),
                     "expected type after `&` for intersection type"
                 );
+
+                if let TypeInfo::Union { pipe, .. } = &right {
+                    return Err(InternalAstError::UnexpectedToken {
+                        token: pipe.clone(),
+                        additional: Some("cannot mix `&` and `|` without parentheses"),
+                    });
+                }
+
                 Ok((
                     state,
                     TypeInfo::Intersection {
@@ -1602,6 +1700,74 @@ cfg_if::cfg_if! {
                 Err(InternalAstError::NoMatch)
             }
         });
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseAttribute;
+        define_parser!(ParseAttribute, Attribute<'a>, |_, state| {
+            let (state, at_sign) = ParseSymbol(Symbol::At).parse(state)?;
+            let (state, name) = expect!(
+                state,
+                ParseIdentifier.parse(state),
+                "expected attribute name after `@`"
+            );
+            Ok((state, Attribute { at_sign, name }))
+        });
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseAttributes;
+        define_parser!(ParseAttributes, Vec<Attribute<'a>>, |_, state| {
+            let (state, attributes) = ZeroOrMore(ParseAttribute).parse(state)?;
+            if attributes.is_empty() {
+                Err(InternalAstError::NoMatch)
+            } else {
+                Ok((state, attributes))
+            }
+        });
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseAttributedFunction;
+        define_parser!(ParseAttributedFunction, AnonymousFunction<'a>, |_, state| {
+            let (state, attributes) = ParseAttributes.parse(state)?;
+            let (state, function) = expect!(
+                state,
+                ParseFunction.parse(state),
+                "expected function after attributes"
+            );
+            Ok((state, AnonymousFunction { attributes, ..function }))
+        });
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseAttributedFunctionStmt;
+        define_parser!(ParseAttributedFunctionStmt, Stmt<'a>, |_, state| {
+            let (state, attributes) = ParseAttributes.parse(state)?;
+
+            match ParseFunctionDeclaration.parse(state) {
+                Ok((state, declaration)) => {
+                    return Ok((
+                        state,
+                        Stmt::FunctionDeclaration(Box::new(FunctionDeclaration {
+                            attributes,
+                            ..declaration
+                        })),
+                    ))
+                }
+                Err(InternalAstError::NoMatch) => {}
+                Err(other) => return Err(other),
+            }
+
+            let (state, local_function) = expect!(
+                state,
+                ParseLocalFunction.parse(state),
+                "expected function after attributes"
+            );
+            Ok((
+                state,
+                Stmt::LocalFunction(Box::new(LocalFunction {
+                    attributes,
+                    ..local_function
+                })),
+            ))
+        });
     }
 }
 
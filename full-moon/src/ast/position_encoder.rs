@@ -0,0 +1,139 @@
+use crate::{
+    ast::{Ast, LineIndex},
+    tokenizer::{display_width, Position},
+};
+
+/// Converts [`Position`]s into columns counted in UTF-16 code units, as used by the
+/// Language Server Protocol, created with [`Ast::position_encoder`] or [`PositionEncoder::new`].
+///
+/// [`Position::character`] counts characters, so the two differ for characters outside of the
+/// Basic Multilingual Plane, such as emoji, which take up two UTF-16 code units.
+pub struct PositionEncoder {
+    code: String,
+    line_index: LineIndex,
+}
+
+impl PositionEncoder {
+    /// Creates an encoder for positions in the given code
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.to_owned(),
+            line_index: LineIndex::new(code),
+        }
+    }
+
+    /// The column of the position in UTF-16 code units. Like [`Position::character`], this starts at 1,
+    /// so subtract 1 from it for the Language Server Protocol.
+    /// Only the byte offset of the position is used, so positions past the end of the code are
+    /// clamped to the end, and positions inside of a character are moved to its start.
+    ///
+    /// ```rust
+    /// # use full_moon::node::Node;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("local emoji = '😀' .. x")?;
+    /// let x = ast.nodes().tokens().last().unwrap();
+    /// let position = x.start_position().unwrap();
+    ///
+    /// assert_eq!(position.character(), 22);
+    /// assert_eq!(ast.position_encoder().character_utf16(position), 23);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn character_utf16(&self, position: Position) -> usize {
+        let mut bytes = position.bytes().min(self.code.len());
+        while !self.code.is_char_boundary(bytes) {
+            bytes -= 1;
+        }
+
+        let (_, byte_column) = self
+            .line_index
+            .line_column(bytes)
+            .expect("offset was clamped to the end of the code");
+
+        self.code[bytes - byte_column..bytes]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>()
+            + 1
+    }
+
+    /// The column of the position with tabs expanded to the next multiple of `tab_width`,
+    /// as shown by editors and terminals. See [`Position::display_column`].
+    /// Positions are clamped the same way as [`character_utf16`](PositionEncoder::character_utf16).
+    pub fn display_column(&self, position: Position, tab_width: usize) -> usize {
+        let mut bytes = position.bytes().min(self.code.len());
+        while !self.code.is_char_boundary(bytes) {
+            bytes -= 1;
+        }
+
+        let (_, byte_column) = self
+            .line_index
+            .line_column(bytes)
+            .expect("offset was clamped to the end of the code");
+
+        display_width(&self.code[bytes - byte_column..bytes], tab_width) + 1
+    }
+}
+
+impl Ast<'_> {
+    /// Creates a [`PositionEncoder`] from the code of the Ast, to convert positions into UTF-16 columns.
+    /// The positions of tokens must be up to date, so use [`update_positions`](Ast::update_positions)
+    /// first if the Ast has been changed.
+    pub fn position_encoder(&self) -> PositionEncoder {
+        PositionEncoder::new(&crate::print(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{node::Node, parse};
+
+    #[test]
+    fn test_character_utf16() {
+        let ast =
+            parse("local s = \"😀é\" -- 𝄞 ü\nlocal y = nil\nreturn s, --[[ 😀😀 ]] x").unwrap();
+        let encoder = ast.position_encoder();
+        let columns = ast
+            .nodes()
+            .tokens()
+            .map(|token| {
+                let position = token.start_position().unwrap();
+                (
+                    token.token().to_string(),
+                    position.line(),
+                    position.character(),
+                    encoder.character_utf16(position),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            ("local", 1, 1, 1),
+            ("s", 1, 7, 7),
+            ("=", 1, 9, 9),
+            ("\"😀é\"", 1, 11, 11),
+            ("local", 2, 1, 1),
+            ("y", 2, 7, 7),
+            ("=", 2, 9, 9),
+            ("nil", 2, 11, 11),
+            ("return", 3, 1, 1),
+            ("s", 3, 8, 8),
+            (",", 3, 9, 9),
+            ("x", 3, 22, 24),
+        ];
+
+        assert_eq!(
+            columns,
+            expected
+                .into_iter()
+                .map(|(text, line, character, utf16)| (text.to_owned(), line, character, utf16))
+                .collect::<Vec<_>>()
+        );
+
+        // The end of the string, after two characters taking up three UTF-16 code units
+        let string = ast.nodes().tokens().nth(3).unwrap();
+        let end = string.token().end_position();
+        assert_eq!(end.character(), 15);
+        assert_eq!(encoder.character_utf16(end), 16);
+    }
+}
@@ -15,11 +15,11 @@
 //! # }
 //! ```
 use crate::{
-    node::{Node, TokenItem, Tokens},
+    node::{Node, NodeKind, TokenItem, Tokens},
     private::Sealed,
     tokenizer::{Position, TokenReference},
     util,
-    visitors::{Visit, VisitMut, Visitor, VisitorMut},
+    visitors::{Visit, VisitMut, VisitorMut, VisitorRef},
 };
 use derive_more::Display;
 #[cfg(feature = "serde")]
@@ -34,7 +34,6 @@ use std::{fmt::Display, iter::FromIterator};
 #[display(bound = "T: Display")]
 #[display(fmt = "{}", "util::join_vec(pairs)")]
 pub struct Punctuated<'a, T> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pairs: Vec<Pair<'a, T>>,
 }
 
@@ -193,10 +192,25 @@ impl<'a, T: Node<'a>> Node<'a> for Punctuated<'a, T> {
     fn tokens<'b>(&'b self) -> Tokens<'a, 'b> {
         self.pairs.tokens()
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.pairs.first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.pairs.last_token()
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Punctuated
+    }
 }
 
 impl<'a, T: Visit<'a>> Visit<'a> for Punctuated<'a, T> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         self.pairs.visit(visitor);
     }
 }
@@ -282,8 +296,14 @@ impl<'a, 'b, T> Iterator for Iter<'a, 'b, T> {
     fn next(&mut self) -> Option<Self::Item> {
         Some(self.inner.next()?.value())
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
 }
 
+impl<'a, 'b, T> ExactSizeIterator for Iter<'a, 'b, T> {}
+
 /// An iterator over borrowed values of type `&mut T`.
 /// Refer to the [module documentation](index.html) for more details.
 pub struct IterMut<'a, 'b, T> {
@@ -311,10 +331,7 @@ pub enum Pair<'a, T> {
     /// A node `T` followed by punctuation (in the form of a
     /// [`TokenReference`](crate::tokenizer::TokenReference))
     #[display(fmt = "{}{}", "_0", "_1")]
-    Punctuated(
-        T,
-        #[cfg_attr(feature = "serde", serde(borrow))] TokenReference<'a>,
-    ),
+    Punctuated(T, TokenReference<'a>),
 }
 
 impl<'a, T> Pair<'a, T> {
@@ -437,10 +454,28 @@ impl<'a, T: Node<'a>> Node<'a> for Pair<'a, T> {
             Pair::End(node) => node.tokens(),
         }
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.value().first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.punctuation().or_else(|| self.value().last_token())
+    }
+
+    fn kind(&self) -> NodeKind {
+        match self {
+            Pair::End(_) => NodeKind::PairEnd,
+            Pair::Punctuated(..) => NodeKind::PairPunctuated,
+        }
+    }
 }
 
 impl<'a, T: Visit<'a>> Visit<'a> for Pair<'a, T> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         match self {
             Pair::End(value) => value.visit(visitor),
             Pair::Punctuated(value, punctuation) => {
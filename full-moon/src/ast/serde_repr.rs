@@ -0,0 +1,351 @@
+// Serialize and Deserialize implementations for nodes that use representations which only work
+// with self-describing formats, such as untagged enums or skipped fields.
+// Human readable formats, such as JSON, use those representations, while other formats, such as
+// bincode or MessagePack, use a plain representation that can be read without looking ahead.
+use super::content::{Content, ContentDeserializer};
+use super::*;
+use crate::tokenizer::{StringLiteralQuoteType, Symbol};
+use serde::{
+    de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserializer, Serializer,
+};
+use std::marker::PhantomData;
+
+#[cfg(feature = "roblox")]
+use super::types::*;
+
+macro_rules! impl_serde {
+    ($type:ident, $human_readable:ident, $plain:ident) => {
+        impl Serialize for $type<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    $human_readable::serialize(self, serializer)
+                } else {
+                    $plain::serialize(self, serializer)
+                }
+            }
+        }
+
+        impl<'de, 'a> Deserialize<'de> for $type<'a> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    $human_readable::deserialize(deserializer)
+                } else {
+                    $plain::deserialize(deserializer)
+                }
+            }
+        }
+    };
+}
+
+impl_serde!(Block, HumanReadableBlock, PlainBlock);
+impl_serde!(Expression, HumanReadableExpression, PlainExpression);
+impl_serde!(FunctionBody, HumanReadableFunctionBody, PlainFunctionBody);
+impl_serde!(TokenType, HumanReadableTokenType, PlainTokenType);
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Block", rename = "Block")]
+struct HumanReadableBlock<'a> {
+    stmts: Vec<(Stmt<'a>, Option<TokenReference<'a>>)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_stmt: Option<(LastStmt<'a>, Option<TokenReference<'a>>)>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Block", rename = "Block")]
+struct PlainBlock<'a> {
+    stmts: Vec<(Stmt<'a>, Option<TokenReference<'a>>)>,
+    last_stmt: Option<(LastStmt<'a>, Option<TokenReference<'a>>)>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Expression", rename = "Expression", untagged)]
+enum HumanReadableExpression<'a> {
+    BinaryOperator {
+        lhs: Box<Expression<'a>>,
+        binop: BinOp<'a>,
+        rhs: Box<Expression<'a>>,
+    },
+
+    Parentheses {
+        contained: Box<ContainedSpan<'a>>,
+        expression: Box<Expression<'a>>,
+    },
+
+    UnaryOperator {
+        unop: UnOp<'a>,
+        expression: Box<Expression<'a>>,
+    },
+
+    Value {
+        value: Box<Value<'a>>,
+        #[cfg(feature = "roblox")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_assertion: Option<Box<TypeAssertion<'a>>>,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Expression", rename = "Expression")]
+enum PlainExpression<'a> {
+    BinaryOperator {
+        lhs: Box<Expression<'a>>,
+        binop: BinOp<'a>,
+        rhs: Box<Expression<'a>>,
+    },
+
+    Parentheses {
+        contained: Box<ContainedSpan<'a>>,
+        expression: Box<Expression<'a>>,
+    },
+
+    UnaryOperator {
+        unop: UnOp<'a>,
+        expression: Box<Expression<'a>>,
+    },
+
+    Value {
+        value: Box<Value<'a>>,
+        #[cfg(feature = "roblox")]
+        type_assertion: Option<Box<TypeAssertion<'a>>>,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "FunctionBody", rename = "FunctionBody")]
+struct HumanReadableFunctionBody<'a> {
+    parameters_parentheses: ContainedSpan<'a>,
+    parameters: Punctuated<'a, Parameter<'a>>,
+
+    #[cfg(feature = "roblox")]
+    type_specifiers: Vec<Option<TypeSpecifier<'a>>>,
+
+    #[cfg(feature = "roblox")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    return_type: Option<TypeSpecifier<'a>>,
+
+    block: Block<'a>,
+    end_token: TokenReference<'a>,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    implicit_self: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "FunctionBody", rename = "FunctionBody")]
+struct PlainFunctionBody<'a> {
+    parameters_parentheses: ContainedSpan<'a>,
+    parameters: Punctuated<'a, Parameter<'a>>,
+
+    #[cfg(feature = "roblox")]
+    type_specifiers: Vec<Option<TypeSpecifier<'a>>>,
+
+    #[cfg(feature = "roblox")]
+    return_type: Option<TypeSpecifier<'a>>,
+
+    block: Block<'a>,
+    end_token: TokenReference<'a>,
+    implicit_self: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "TokenType", rename = "TokenType", tag = "type")]
+enum HumanReadableTokenType<'a> {
+    Eof,
+    Identifier {
+        identifier: Cow<'a, str>,
+    },
+    MultiLineComment {
+        blocks: usize,
+        comment: Cow<'a, str>,
+    },
+    Number {
+        text: Cow<'a, str>,
+    },
+    Shebang {
+        line: Cow<'a, str>,
+    },
+    SingleLineComment {
+        comment: Cow<'a, str>,
+    },
+    StringLiteral {
+        literal: Cow<'a, str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        multi_line: Option<usize>,
+        quote_type: StringLiteralQuoteType,
+    },
+    Symbol {
+        symbol: Symbol,
+    },
+    Whitespace {
+        characters: Cow<'a, str>,
+    },
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "TokenType", rename = "TokenType")]
+enum PlainTokenType<'a> {
+    Eof,
+    Identifier {
+        identifier: Cow<'a, str>,
+    },
+    MultiLineComment {
+        blocks: usize,
+        comment: Cow<'a, str>,
+    },
+    Number {
+        text: Cow<'a, str>,
+    },
+    Shebang {
+        line: Cow<'a, str>,
+    },
+    SingleLineComment {
+        comment: Cow<'a, str>,
+    },
+    StringLiteral {
+        literal: Cow<'a, str>,
+        multi_line: Option<usize>,
+        quote_type: StringLiteralQuoteType,
+    },
+    Symbol {
+        symbol: Symbol,
+    },
+    Whitespace {
+        characters: Cow<'a, str>,
+    },
+}
+
+// Asts are serialized with their version first, so that it's read before any nodes that could have changed.
+// Once the version doesn't match, the rest is skipped over instead of being deserialized.
+// Enums keep the tags serde gives them rather than explicit ones: human readable formats already tag variants
+// by name, and formats that aren't use the position of the variant, which serde has no way to name explicitly,
+// so adding a variant bumps AST_VERSION instead.
+impl Serialize for Ast<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Ast", 3)?;
+        state.serialize_field("version", &AST_VERSION)?;
+        state.serialize_field("nodes", &self.nodes)?;
+        state.serialize_field("eof", &self.eof)?;
+        state.end()
+    }
+}
+
+impl<'de, 'a> Deserialize<'de> for Ast<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        VersionedAst::deserialize(deserializer)?
+            .into_ast()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<'de, 'a> Deserialize<'de> for VersionedAst<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_struct(
+            "Ast",
+            &["version", "nodes", "eof"],
+            VersionedAstVisitor {
+                human_readable,
+                ast: PhantomData,
+            },
+        )
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum AstField {
+    Version,
+    Nodes,
+    Eof,
+    #[serde(other)]
+    Other,
+}
+
+struct VersionedAstVisitor<'a> {
+    // Fields read before the version are deserialized later, as the same kind of format
+    human_readable: bool,
+    ast: PhantomData<Ast<'a>>,
+}
+
+impl<'de, 'a> Visitor<'de> for VersionedAstVisitor<'a> {
+    type Value = VersionedAst<'a>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct Ast")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        if version != AST_VERSION {
+            return Ok(VersionedAst { version, ast: None });
+        }
+
+        let nodes = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let eof = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+
+        Ok(VersionedAst {
+            version,
+            ast: Some(Ast { nodes, eof }),
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        // Fields before the version, such as when the keys were sorted, are kept until the version is known.
+        // Asts serialized before versions were added have no version at all.
+        let mut buffered = Vec::new();
+        let version = loop {
+            match map.next_key()? {
+                Some(AstField::Version) => break map.next_value()?,
+                Some(field) => buffered.push((field, map.next_value::<Content>()?)),
+                None => break 0,
+            }
+        };
+
+        if version != AST_VERSION {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            return Ok(VersionedAst { version, ast: None });
+        }
+
+        let (mut nodes, mut eof) = (None, None);
+        for (field, content) in buffered {
+            let deserializer = ContentDeserializer::new(content, self.human_readable);
+            match field {
+                AstField::Nodes => nodes = Some(Block::deserialize(deserializer)?),
+                AstField::Eof => eof = Some(TokenReference::deserialize(deserializer)?),
+                AstField::Version | AstField::Other => {}
+            }
+        }
+
+        while let Some(field) = map.next_key()? {
+            match field {
+                AstField::Version => return Err(de::Error::duplicate_field("version")),
+                AstField::Nodes => nodes = Some(map.next_value()?),
+                AstField::Eof => eof = Some(map.next_value()?),
+                AstField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(VersionedAst {
+            version,
+            ast: Some(Ast {
+                nodes: nodes.ok_or_else(|| de::Error::missing_field("nodes"))?,
+                eof: eof.ok_or_else(|| de::Error::missing_field("eof"))?,
+            }),
+        })
+    }
+}
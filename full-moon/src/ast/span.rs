@@ -7,10 +7,11 @@
 //!
 //! Contained spans don't contain the inner data, just the start and end bounds.
 use crate::{
-    node::{Node, Tokens},
+    node::{Node, NodeKind, Tokens},
     private::Sealed,
-    tokenizer::{Position, TokenReference},
+    tokenizer::{Position, Symbol, Token, TokenReference, TokenType, TokenizerErrorType},
 };
+use std::borrow::Cow;
 
 use full_moon_derive::{Owned, Visit};
 #[cfg(feature = "serde")]
@@ -21,7 +22,6 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, PartialEq, Owned, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ContainedSpan<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) tokens: (TokenReference<'a>, TokenReference<'a>),
 }
 
@@ -33,10 +33,108 @@ impl<'a> ContainedSpan<'a> {
         }
     }
 
+    /// Creates a contained span of parentheses, `(` and `)`, with no spacing inside
+    pub fn parentheses() -> Self {
+        Self::from_symbols(Symbol::LeftParen, Symbol::RightParen)
+    }
+
+    /// Creates a contained span of braces, `{ ` and ` }`, with a space inside of both,
+    /// the same as [`TableConstructor::new`](crate::ast::TableConstructor::new)
+    pub fn braces() -> Self {
+        Self::new(
+            TokenReference::spaced_symbol("", Symbol::LeftBrace, " "),
+            TokenReference::spaced_symbol(" ", Symbol::RightBrace, ""),
+        )
+    }
+
+    /// Creates a contained span of brackets, `[` and `]`, with no spacing inside
+    pub fn brackets() -> Self {
+        Self::from_symbols(Symbol::LeftBracket, Symbol::RightBracket)
+    }
+
+    /// Creates a contained span of angle brackets, `<` and `>`, with no spacing inside,
+    /// such as for generics
+    pub fn angle_brackets() -> Self {
+        Self::from_symbols(Symbol::LessThan, Symbol::GreaterThan)
+    }
+
+    fn from_symbols(start: Symbol, end: Symbol) -> Self {
+        Self::new(
+            TokenReference::from_symbol(start),
+            TokenReference::from_symbol(end),
+        )
+    }
+
     /// Returns the start and end bounds in a tuple as references
     pub fn tokens(&self) -> (&TokenReference<'a>, &TokenReference<'a>) {
         (&self.tokens.0, &self.tokens.1)
     }
+
+    /// Returns the start and end bounds in a tuple as mutable references
+    pub fn tokens_mut(&mut self) -> (&mut TokenReference<'a>, &mut TokenReference<'a>) {
+        (&mut self.tokens.0, &mut self.tokens.1)
+    }
+
+    /// Returns a new ContainedSpan with the given start and end bounds
+    pub fn with_tokens(self, start: TokenReference<'a>, end: TokenReference<'a>) -> Self {
+        Self {
+            tokens: (start, end),
+        }
+    }
+
+    /// Replaces the whitespace directly after the start bound with `leading`, and the whitespace
+    /// directly before the end bound with `trailing`. Comments are kept, and an empty string
+    /// removes the whitespace.
+    ///
+    /// Whitespace after the last token inside of the span is usually the trailing trivia of that token,
+    /// not the leading trivia of the end bound, and is not changed.
+    ///
+    /// # Errors
+    /// Returns an error if either text contains anything other than whitespace.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::span::ContainedSpan;
+    /// # fn main() -> Result<(), full_moon::tokenizer::TokenizerErrorType> {
+    /// let mut braces = ContainedSpan::braces();
+    /// braces.set_inner_padding("", "\n")?;
+    ///
+    /// let (start, end) = braces.tokens();
+    /// assert_eq!(format!("{}{}", start, end), "{\n}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_inner_padding(
+        &mut self,
+        leading: &str,
+        trailing: &str,
+    ) -> Result<(), TokenizerErrorType> {
+        if let Some(character) = leading
+            .chars()
+            .find(|character| !character.is_ascii_whitespace())
+        {
+            return Err(TokenizerErrorType::UnexpectedToken(character));
+        }
+
+        self.tokens.1.set_leading_whitespace(trailing)?;
+
+        let trailing_trivia = &mut self.tokens.0.trailing_trivia;
+        let whitespace = trailing_trivia
+            .iter()
+            .take_while(|token| matches!(token.token_type(), TokenType::Whitespace { .. }))
+            .count();
+        trailing_trivia.drain(..whitespace);
+
+        if !leading.is_empty() {
+            trailing_trivia.insert(
+                0,
+                Token::new(TokenType::Whitespace {
+                    characters: Cow::Owned(leading.to_owned()),
+                }),
+            );
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a> Node<'a> for ContainedSpan<'a> {
@@ -55,6 +153,18 @@ impl<'a> Node<'a> for ContainedSpan<'a> {
     fn tokens<'b>(&'b self) -> Tokens<'a, 'b> {
         self.tokens.tokens()
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        Some(&self.tokens.0)
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        Some(&self.tokens.1)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::ContainedSpan
+    }
 }
 
 impl<'a> Sealed for ContainedSpan<'a> {}
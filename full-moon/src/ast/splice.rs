@@ -0,0 +1,324 @@
+use super::{
+    extract_token_references, owned::Owned, parse_all, parser_util::ParserState, parsers, Block,
+    Expression,
+};
+use crate::{
+    ast::AstError,
+    node::Node,
+    tokenizer::{self, Token, TokenKind, TokenReference, TokenType},
+    util::{map_first_token, map_last_token},
+    visitors::{VisitMut, VisitorMut},
+    Error,
+};
+use std::borrow::Cow;
+
+impl<'a> Block<'a> {
+    /// Parses the snippet of code and inserts its statements before the statement at the given index,
+    /// or at the end of the block if the index is the amount of statements.
+    ///
+    /// The snippet is moved from its own indentation to that of the statements around it,
+    /// and is put on its own lines if they are on their own lines. The positions of its tokens are cleared,
+    /// the same as tokens created with [`Token::new`], so use [`Ast::update_positions`](crate::ast::Ast::update_positions)
+    /// if you need them.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("do\n    print(x)\nend\n")?;
+    /// let mut r#do = match ast.nodes().stmts().next() {
+    ///     Some(full_moon::ast::Stmt::Do(r#do)) => r#do.clone(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let mut block = r#do.block().clone();
+    /// block.splice(0, "local x = 1")?;
+    ///
+    /// assert_eq!(r#do.with_block(block).to_string(), "do\n    local x = 1\n    print(x)\nend\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the snippet can't be parsed, with positions relative to the snippet.
+    /// A snippet that ends with a last statement, such as `return`, can only be spliced into the end of a block
+    /// that doesn't have one, otherwise an UnexpectedToken error is returned.
+    ///
+    /// # Panics
+    /// Panics if the index is greater than the amount of statements.
+    pub fn splice(&mut self, index: usize, snippet: &str) -> Result<(), Error<'static>> {
+        assert!(
+            index <= self.stmts.len(),
+            "splice index {} is out of bounds for a block of {} statements",
+            index,
+            self.stmts.len()
+        );
+
+        let snippet_block = crate::parse(snippet)
+            .map_err(|error| error.owned())?
+            .owned()
+            .nodes;
+
+        if let Some((last_stmt, _)) = &snippet_block.last_stmt {
+            if index < self.stmts.len() || self.last_stmt.is_some() {
+                return Err(Error::AstError(AstError::UnexpectedToken {
+                    token: last_stmt.first_token().unwrap().token().clone(),
+                    additional: Some(Cow::Borrowed(
+                        "a last statement can only be spliced into the end of a block",
+                    )),
+                }));
+            }
+        }
+
+        if snippet_block.stmts.is_empty() && snippet_block.last_stmt.is_none() {
+            return Ok(());
+        }
+
+        let on_own_lines = self.stmts_on_own_lines();
+        let indent = self.indentation_at(index);
+
+        // Statements that are right next to each other need something between them
+        if let Some((stmt, semicolon)) = index.checked_sub(1).map(|index| &mut self.stmts[index]) {
+            let separator = whitespace(if on_own_lines { "\n" } else { " " });
+            match semicolon {
+                Some(semicolon) if semicolon.trailing_trivia.is_empty() => {
+                    semicolon.trailing_trivia.push(separator);
+                }
+
+                Some(_) => {}
+
+                None if matches!(stmt.last_token(), Some(token) if token.trailing_trivia.is_empty()) =>
+                {
+                    *stmt = map_last_token(stmt.clone(), |mut token| {
+                        token.trailing_trivia.push(separator);
+                        token
+                    });
+                }
+
+                None => {}
+            }
+        }
+
+        let snippet_block =
+            snippet_block.visit_mut(&mut Reindent::new(if on_own_lines { &indent } else { "" }));
+
+        // The statements after the snippet go on the next line, or after a space
+        let snippet_block = map_last_token(snippet_block, |mut token| {
+            while let Some(Token {
+                token_type: TokenType::Whitespace { .. },
+                ..
+            }) = token.trailing_trivia.last()
+            {
+                token.trailing_trivia.pop();
+            }
+
+            let ends_with_comment = matches!(
+                token.trailing_trivia.last().map(Token::token_kind),
+                Some(TokenKind::SingleLineComment)
+            );
+
+            token
+                .trailing_trivia
+                .push(whitespace(if on_own_lines || ends_with_comment {
+                    "\n"
+                } else {
+                    " "
+                }));
+
+            token
+        });
+
+        self.stmts.splice(index..index, snippet_block.stmts);
+        if snippet_block.last_stmt.is_some() {
+            self.last_stmt = snippet_block.last_stmt;
+        }
+
+        Ok(())
+    }
+
+    // Whether any statement is followed by a new line, rather than all being on the same line
+    fn stmts_on_own_lines(&self) -> bool {
+        let stmts = self
+            .stmts
+            .iter()
+            .map(|(stmt, semicolon)| last_token(stmt, semicolon));
+        let last_stmt = self
+            .last_stmt
+            .iter()
+            .map(|(last_stmt, semicolon)| last_token(last_stmt, semicolon));
+
+        stmts.chain(last_stmt).flatten().any(|token| {
+            token
+                .trailing_trivia()
+                .any(|trivia| trivia.to_string().contains('\n'))
+        })
+    }
+
+    // The whitespace before the statement at the index, or the one before it if there isn't one
+    fn indentation_at(&self, index: usize) -> String {
+        let first_token = match self.stmts.get(index).or_else(|| self.stmts.last()) {
+            Some((stmt, _)) => stmt.first_token(),
+            None => match &self.last_stmt {
+                Some((last_stmt, _)) => last_stmt.first_token(),
+                None => None,
+            },
+        };
+
+        match first_token.and_then(|token| token.leading_trivia.last()) {
+            Some(Token {
+                token_type: TokenType::Whitespace { characters },
+                ..
+            }) => characters
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .to_owned(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl<'a> Expression<'a> {
+    /// Parses the snippet of code as an expression and replaces this expression with it,
+    /// keeping the trivia before and after this expression, such as the space in `local x = 1`.
+    /// The positions of its tokens are cleared, the same as [`Block::splice`].
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut expression = full_moon::ast::builders::expr::name("x");
+    /// expression.splice("x + 1")?;
+    /// assert_eq!(expression.to_string(), "x + 1");
+    /// assert!(expression.splice("x +").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the snippet is not a single expression, with positions relative to the snippet.
+    pub fn splice(&mut self, snippet: &str) -> Result<(), Error<'static>> {
+        let tokens = tokenizer::tokens(snippet).map_err(Error::TokenizerError)?;
+        let references = extract_token_references(tokens);
+        let expression = parse_all(ParserState::new(&references), parsers::ParseExpression)
+            .map_err(|error| Error::AstError(error).owned())?
+            .owned();
+
+        let leading_trivia = self
+            .first_token()
+            .map(|token| token.leading_trivia.clone())
+            .unwrap_or_default();
+        let trailing_trivia = self
+            .last_token()
+            .map(|token| token.trailing_trivia.clone())
+            .unwrap_or_default();
+
+        let expression = expression.visit_mut(&mut Reindent::new(""));
+        let expression = map_first_token(expression, |mut token| {
+            token.leading_trivia = leading_trivia;
+            token
+        });
+
+        *self = map_last_token(expression, |mut token| {
+            token.trailing_trivia = trailing_trivia;
+            token
+        });
+
+        Ok(())
+    }
+}
+
+fn last_token<'a, 'b, N: Node<'a>>(
+    node: &'b N,
+    semicolon: &'b Option<TokenReference<'a>>,
+) -> Option<&'b TokenReference<'a>> {
+    semicolon.as_ref().or_else(|| node.last_token())
+}
+
+fn whitespace(characters: &str) -> Token<'static> {
+    Token::new(TokenType::Whitespace {
+        characters: Cow::Owned(characters.to_owned()),
+    })
+}
+
+// Moves code from its own indentation to the given one, and clears the positions of every token.
+// The indentation of the first line is replaced on every line, so lines indented further stay that way.
+struct Reindent<'i> {
+    indent: &'i str,
+    base: String,
+    started: bool,
+    line_start: bool,
+}
+
+impl<'i> Reindent<'i> {
+    fn new(indent: &'i str) -> Self {
+        Self {
+            indent,
+            base: String::new(),
+            started: false,
+            line_start: true,
+        }
+    }
+
+    fn push_indent<'a>(&mut self, output: &mut Vec<Token<'a>>) {
+        if self.line_start && !self.indent.is_empty() {
+            output.push(whitespace(self.indent));
+        }
+
+        self.started = true;
+        self.line_start = false;
+    }
+
+    fn push_trivia<'a>(&mut self, trivia: Token<'a>, output: &mut Vec<Token<'a>>) {
+        let characters = match &trivia.token_type {
+            TokenType::Whitespace { characters } => characters,
+            _ => {
+                self.push_indent(output);
+                output.push(Token::new(trivia.token_type));
+                return;
+            }
+        };
+
+        if !self.started {
+            // Whitespace before the code is dropped, but tells us how far the first line is indented
+            self.base = characters
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .to_owned();
+        } else if !self.line_start {
+            self.line_start = characters.ends_with('\n');
+            output.push(Token::new(trivia.token_type));
+        } else if characters.contains('\n') {
+            // An empty line, which doesn't need any indentation
+            let new_line =
+                characters.trim_start_matches(|character| !matches!(character, '\r' | '\n'));
+            output.push(whitespace(new_line));
+        } else {
+            let relative = characters
+                .strip_prefix(self.base.as_str())
+                .unwrap_or_else(|| characters.trim_start());
+
+            output.push(whitespace(&format!("{}{}", self.indent, relative)));
+            self.line_start = false;
+        }
+    }
+}
+
+impl<'a> VisitorMut<'a> for Reindent<'_> {
+    fn visit_token_reference(&mut self, token: TokenReference<'a>) -> TokenReference<'a> {
+        let mut leading_trivia = Vec::new();
+        for trivia in token.leading_trivia {
+            self.push_trivia(trivia, &mut leading_trivia);
+        }
+
+        self.push_indent(&mut leading_trivia);
+
+        let mut trailing_trivia = Vec::new();
+        for trivia in token.trailing_trivia {
+            self.push_trivia(trivia, &mut trailing_trivia);
+        }
+
+        TokenReference::new(
+            leading_trivia,
+            Token::new(token.token.token_type),
+            trailing_trivia,
+        )
+    }
+}
@@ -1,7 +1,7 @@
 // Implementations of Visit and VisitMut that are not able to be automatically derived yet.
 // Ideally everything would be derived.
 use super::*;
-use crate::visitors::{Visit, VisitMut, Visitor, VisitorMut};
+use crate::visitors::{Visit, VisitMut, VisitorMut, VisitorRef};
 
 // The following have `ContainedSpan`, which when automatically derived will visit the tokens containing
 // before they visit what they're actually containing.
@@ -9,7 +9,10 @@ use crate::visitors::{Visit, VisitMut, Visitor, VisitorMut};
 // Then visitors will visit this as `()foo`.
 // This is fixed for structs with `#[visit(contains = "...")], but this is not supported on enums.
 impl<'a> Visit<'a> for TypeInfo<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_type_info(self);
         match self {
             TypeInfo::Array { braces, type_info } => {
@@ -21,11 +24,13 @@ impl<'a> Visit<'a> for TypeInfo<'a> {
                 __self_0.visit(visitor);
             }
             TypeInfo::Callback {
+                generics,
                 parentheses,
                 arguments,
                 arrow,
                 return_type,
             } => {
+                generics.visit(visitor);
                 parentheses.tokens.0.visit(visitor);
                 arguments.visit(visitor);
                 parentheses.tokens.1.visit(visitor);
@@ -117,11 +122,13 @@ impl<'a> VisitMut<'a> for TypeInfo<'a> {
             }
             TypeInfo::Basic(__self_0) => TypeInfo::Basic(__self_0.visit_mut(visitor)),
             TypeInfo::Callback {
+                mut generics,
                 mut parentheses,
                 mut arguments,
                 mut arrow,
                 mut return_type,
             } => {
+                generics = generics.visit_mut(visitor);
                 parentheses.tokens.0 = parentheses.tokens.0.visit_mut(visitor);
                 arguments = arguments.visit_mut(visitor);
                 parentheses.tokens.1 = parentheses.tokens.1.visit_mut(visitor);
@@ -129,6 +136,7 @@ impl<'a> VisitMut<'a> for TypeInfo<'a> {
                 return_type = return_type.visit_mut(visitor);
 
                 TypeInfo::Callback {
+                    generics,
                     parentheses,
                     arguments,
                     arrow,
@@ -243,7 +251,10 @@ impl<'a> VisitMut<'a> for TypeInfo<'a> {
 }
 
 impl<'a> Visit<'a> for IndexedTypeInfo<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_indexed_type_info(self);
         match self {
             IndexedTypeInfo::Basic(__self_0) => {
@@ -293,7 +304,10 @@ impl<'a> VisitMut<'a> for IndexedTypeInfo<'a> {
 }
 
 impl<'a> Visit<'a> for TypeFieldKey<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_type_field_key(self);
         match self {
             TypeFieldKey::Name(__self_0) => {
@@ -1,7 +1,10 @@
 //! Contains the types necessary to parse [Roblox's typed Lua](https://devforum.roblox.com/t/luau-type-checking-beta/435382).
 //! Only usable when the "roblox" feature flag is enabled.
 use super::{punctuated::Punctuated, span::ContainedSpan, *};
-use crate::util::display_option;
+use crate::{
+    util::{display_option, map_first_token, map_last_token},
+    visitors::{Visit, Visitor},
+};
 use derive_more::Display;
 
 /// Any type, such as `string`, `boolean?`, `number | boolean`, etc.
@@ -13,20 +16,20 @@ pub enum TypeInfo<'a> {
     #[display(fmt = "{}{}{}", "braces.tokens().0", "type_info", "braces.tokens().1")]
     Array {
         /// The braces (`{}`) containing the type info.
-        #[cfg_attr(feature = "serde", serde(borrow))]
+        #[node(full_range)]
         braces: ContainedSpan<'a>,
         /// The type info for the values in the Array
-        #[cfg_attr(feature = "serde", serde(borrow))]
         type_info: Box<TypeInfo<'a>>,
     },
 
     /// A standalone type, such as `string` or `Foo`.
     #[display(fmt = "{}", "_0")]
-    Basic(#[cfg_attr(feature = "serde", serde(borrow))] TokenReference<'a>),
+    Basic(TokenReference<'a>),
 
     /// A callback type, such as `(string, number) => boolean`.
     #[display(
-        fmt = "{}{}{}{}{}",
+        fmt = "{}{}{}{}{}{}",
+        "display_option(generics)",
         "parentheses.tokens().0",
         "arguments",
         "parentheses.tokens().1",
@@ -34,17 +37,15 @@ pub enum TypeInfo<'a> {
         "return_type"
     )]
     Callback {
+        /// The generic parameters of the callback, such as `<T>` in `<T>(T) -> T`.
+        generics: Option<Box<GenericDeclaration<'a>>>,
         /// The parentheses for the arguments.
-        #[cfg_attr(feature = "serde", serde(borrow))]
-        parentheses: ContainedSpan<'a>,
+        parentheses: Box<ContainedSpan<'a>>,
         /// The argument types: `(string, number)`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         arguments: Punctuated<'a, TypeInfo<'a>>,
         /// The "thin arrow" (`->`) in between the arguments and the return type.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         arrow: TokenReference<'a>,
         /// The return type: `boolean`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         return_type: Box<TypeInfo<'a>>,
     },
 
@@ -58,13 +59,11 @@ pub enum TypeInfo<'a> {
     )]
     Generic {
         /// The type that has generics: `map`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         base: TokenReference<'a>,
         /// The arrows (`<>`) containing the type parameters.
-        #[cfg_attr(feature = "serde", serde(borrow))]
-        arrows: ContainedSpan<'a>,
+        #[node(contains = "generics")]
+        arrows: Box<ContainedSpan<'a>>,
         /// The type parameters: `number, string`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         generics: Punctuated<'a, TypeInfo<'a>>,
     },
 
@@ -72,13 +71,10 @@ pub enum TypeInfo<'a> {
     #[display(fmt = "{}{}{}", "left", "ampersand", "right")]
     Intersection {
         /// The left hand side: `string`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         left: Box<TypeInfo<'a>>,
         /// The ampersand (`&`) to separate the types.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         ampersand: TokenReference<'a>,
         /// The right hand side: `number`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         right: Box<TypeInfo<'a>>,
     },
 
@@ -86,24 +82,20 @@ pub enum TypeInfo<'a> {
     #[display(fmt = "{}{}{}", "module", "punctuation", "type_info")]
     Module {
         /// The module the type is coming from: `module`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         module: TokenReference<'a>,
         /// The punctuation (`.`) to index the module.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         punctuation: TokenReference<'a>,
         /// The indexed type info: `Foo`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         type_info: Box<IndexedTypeInfo<'a>>,
     },
 
     /// An optional type, such as `string?`.
+    /// Repeated `?`s, such as `string??`, are optional types nested in each other.
     #[display(fmt = "{}{}", "base", "question_mark")]
     Optional {
         /// The type that is optional: `string`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         base: Box<TypeInfo<'a>>,
         /// The question mark: `?`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         question_mark: TokenReference<'a>,
     },
 
@@ -111,10 +103,9 @@ pub enum TypeInfo<'a> {
     #[display(fmt = "{}{}{}", "braces.tokens().0", "fields", "braces.tokens().1")]
     Table {
         /// The braces (`{}`) containing the fields.
-        #[cfg_attr(feature = "serde", serde(borrow))]
+        #[node(full_range)]
         braces: ContainedSpan<'a>,
         /// The fields: `foo: number, bar: string`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         fields: Punctuated<'a, TypeField<'a>>,
     },
 
@@ -128,17 +119,16 @@ pub enum TypeInfo<'a> {
     )]
     Typeof {
         /// The token `typeof`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         typeof_token: TokenReference<'a>,
         /// The parentheses used to contain the expression.
-        #[cfg_attr(feature = "serde", serde(borrow))]
-        parentheses: ContainedSpan<'a>,
+        #[node(contains = "inner")]
+        parentheses: Box<ContainedSpan<'a>>,
         /// The inner expression: `foo`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         inner: Box<Expression<'a>>,
     },
 
     /// A tuple expression: `(string, number)`.
+    /// A single type in parentheses, such as `(A & B)` in `(A & B) | C`, is a tuple of one type.
     #[display(
         fmt = "{}{}{}",
         "parentheses.tokens().0",
@@ -147,10 +137,9 @@ pub enum TypeInfo<'a> {
     )]
     Tuple {
         /// The parentheses used to contain the types
-        #[cfg_attr(feature = "serde", serde(borrow))]
+        #[node(full_range)]
         parentheses: ContainedSpan<'a>,
         /// The types: `(string, number)`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         types: Punctuated<'a, TypeInfo<'a>>,
     },
 
@@ -158,13 +147,10 @@ pub enum TypeInfo<'a> {
     #[display(fmt = "{}{}{}", "left", "pipe", "right")]
     Union {
         /// The left hand side: `string`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         left: Box<TypeInfo<'a>>,
         /// The pipe (`|`) to separate the types.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         pipe: TokenReference<'a>,
         /// The right hand side: `number`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         right: Box<TypeInfo<'a>>,
     },
 
@@ -172,14 +158,607 @@ pub enum TypeInfo<'a> {
     #[display(fmt = "{}{}", "ellipse", "type_info")]
     Variadic {
         /// The ellipse: `...`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         ellipse: TokenReference<'a>,
         /// The type that is variadic: `number`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         type_info: Box<TypeInfo<'a>>,
     },
 }
 
+impl<'a> TypeInfo<'a> {
+    /// The type written on one line with normalized spacing and without comments, such as
+    /// `{ foo: number, bar: string }`, `string | number` or `(number, string) -> boolean`.
+    /// Useful for showing types in messages, since [`Display`](std::fmt::Display) keeps
+    /// the whitespace and comments of the code the type was parsed from.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{types::TypeInfo, Stmt};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("type T = {\n\tfoo: number, -- the foo\n\tbar: string?\n}")?;
+    /// if let Some(Stmt::TypeDeclaration(declaration)) = ast.nodes().stmts().next() {
+    ///     assert_eq!(
+    ///         declaration.type_definition().to_canonical_string(),
+    ///         "{ foo: number, bar: string? }"
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_canonical_string(&self) -> String {
+        let mut string = String::new();
+        write_canonical_type(&mut string, self);
+        string
+    }
+
+    /// Simplifies the type without changing what it means, using the default [`SimplifyOptions`]:
+    /// - Nested unions and intersections are flattened, such as `A | (B | C)` into `A | B | C`.
+    /// - Repeated members of unions and intersections are removed, keeping the first one,
+    ///   such as `number | string | number` into `number | string`.
+    /// - Repeated `?`s are collapsed, such as `(string?)?` into `string?`.
+    /// - Parentheses that aren't needed around members and optional types are removed,
+    ///   such as `(A) | B` into `A | B`. Parentheses that are needed, such as in `(A | B) & C`, are kept.
+    ///
+    /// Members are compared by their [canonical string](TypeInfo::to_canonical_string), so
+    /// trivia doesn't matter. This also means that `typeof(x) | typeof(x)` becomes `typeof(x)`,
+    /// even though the two expressions aren't always the same type.
+    ///
+    /// The trivia of removed members and inside of removed parentheses is removed with them,
+    /// and any tokens that are added are spaced canonically.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{types::TypeInfo, Stmt};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let ast = full_moon::parse("type T = { number | string | number }")?;
+    /// if let Some(Stmt::TypeDeclaration(declaration)) = ast.nodes().stmts().next() {
+    ///     let simplified = declaration.type_definition().simplify();
+    ///     assert_eq!(simplified.to_canonical_string(), "{ number | string }");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn simplify(&self) -> TypeInfo<'a> {
+        self.simplify_with_options(&SimplifyOptions::new())
+    }
+
+    /// Simplifies the type the same way as [`simplify`](TypeInfo::simplify),
+    /// with the given options
+    pub fn simplify_with_options(&self, options: &SimplifyOptions) -> TypeInfo<'a> {
+        simplify_type(self.clone(), options)
+    }
+}
+
+/// Options for [`TypeInfo::simplify_with_options`]
+#[derive(Clone, Debug, Default)]
+pub struct SimplifyOptions {
+    nil_to_optional: bool,
+}
+
+impl SimplifyOptions {
+    /// Creates the default options, which leave `nil` in unions as it is
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether unions containing `nil` are written with `?` instead,
+    /// such as `string | nil` into `string?`, and `A | nil | B` into `A | B?`
+    pub fn nil_to_optional(&self) -> bool {
+        self.nil_to_optional
+    }
+
+    /// Returns new options with `nil` in unions either written with `?` or not
+    pub fn with_nil_to_optional(mut self, nil_to_optional: bool) -> Self {
+        self.nil_to_optional = nil_to_optional;
+        self
+    }
+}
+
+fn simplify_list<'a>(
+    list: Punctuated<'a, TypeInfo<'a>>,
+    options: &SimplifyOptions,
+) -> Punctuated<'a, TypeInfo<'a>> {
+    list.into_pairs()
+        .map(|pair| pair.map(|type_info| simplify_type(type_info, options)))
+        .collect()
+}
+
+fn simplify_type<'a>(type_info: TypeInfo<'a>, options: &SimplifyOptions) -> TypeInfo<'a> {
+    match type_info {
+        TypeInfo::Array { braces, type_info } => TypeInfo::Array {
+            braces,
+            type_info: Box::new(simplify_type(*type_info, options)),
+        },
+
+        TypeInfo::Callback {
+            generics,
+            parentheses,
+            arguments,
+            arrow,
+            return_type,
+        } => TypeInfo::Callback {
+            generics,
+            parentheses,
+            arguments: simplify_list(arguments, options),
+            arrow,
+            return_type: Box::new(simplify_type(*return_type, options)),
+        },
+
+        TypeInfo::Generic {
+            base,
+            arrows,
+            generics,
+        } => TypeInfo::Generic {
+            base,
+            arrows,
+            generics: simplify_list(generics, options),
+        },
+
+        TypeInfo::Module {
+            module,
+            punctuation,
+            type_info,
+        } => TypeInfo::Module {
+            module,
+            punctuation,
+            type_info: Box::new(match *type_info {
+                IndexedTypeInfo::Generic {
+                    base,
+                    arrows,
+                    generics,
+                } => IndexedTypeInfo::Generic {
+                    base,
+                    arrows,
+                    generics: simplify_list(generics, options),
+                },
+
+                other => other,
+            }),
+        },
+
+        TypeInfo::Optional {
+            base,
+            question_mark,
+        } => {
+            let mut base = simplify_type(*base, options);
+            let base = loop {
+                base = match base {
+                    TypeInfo::Optional { base: inner, .. } => *inner,
+                    other if is_parentheses_around(&other, can_be_optional) => {
+                        unwrap_parentheses(other)
+                    }
+                    other => break other,
+                };
+            };
+
+            TypeInfo::Optional {
+                base: Box::new(base),
+                question_mark,
+            }
+        }
+
+        TypeInfo::Table { braces, fields } => TypeInfo::Table {
+            braces,
+            fields: fields
+                .into_pairs()
+                .map(|pair| {
+                    pair.map(|field| TypeField {
+                        key: match field.key {
+                            TypeFieldKey::IndexSignature { brackets, inner } => {
+                                TypeFieldKey::IndexSignature {
+                                    brackets,
+                                    inner: simplify_type(inner, options),
+                                }
+                            }
+
+                            other => other,
+                        },
+                        colon: field.colon,
+                        value: simplify_type(field.value, options),
+                    })
+                })
+                .collect(),
+        },
+
+        TypeInfo::Tuple { parentheses, types } => TypeInfo::Tuple {
+            parentheses,
+            types: simplify_list(types, options),
+        },
+
+        TypeInfo::Variadic { ellipse, type_info } => TypeInfo::Variadic {
+            ellipse,
+            type_info: Box::new(simplify_type(*type_info, options)),
+        },
+
+        union @ TypeInfo::Union { .. } => simplify_members(union, Symbol::Pipe, options),
+        intersection @ TypeInfo::Intersection { .. } => {
+            simplify_members(intersection, Symbol::Ampersand, options)
+        }
+
+        other => other,
+    }
+}
+
+// Whether `?` can be put after a type without parentheses and mean the same thing
+fn can_be_optional(type_info: &TypeInfo) -> bool {
+    !matches!(
+        type_info,
+        TypeInfo::Union { .. }
+            | TypeInfo::Intersection { .. }
+            | TypeInfo::Callback { .. }
+            | TypeInfo::Variadic { .. }
+    )
+}
+
+// Whether the type is parentheses around a single type that `can_unwrap` allows, such as `(string?)`
+fn is_parentheses_around(type_info: &TypeInfo, can_unwrap: impl Fn(&TypeInfo) -> bool) -> bool {
+    match type_info {
+        TypeInfo::Tuple { types, .. } if types.len() == 1 => {
+            matches!(types.last(), Some(Pair::End(inner)) if can_unwrap(inner))
+        }
+
+        _ => false,
+    }
+}
+
+// Removes the parentheses around a single type, checked with `is_parentheses_around`. The trivia
+// around the parentheses is kept around the type, and the trivia inside of them is removed with them.
+fn unwrap_parentheses(type_info: TypeInfo) -> TypeInfo {
+    let (parentheses, types) = match type_info {
+        TypeInfo::Tuple { parentheses, types } => (parentheses, types),
+        _ => unreachable!("only parentheses can be unwrapped"),
+    };
+
+    let (open, close) = parentheses.tokens();
+    let leading_trivia: Vec<_> = open.leading_trivia().cloned().collect();
+    let trailing_trivia: Vec<_> = close.trailing_trivia().cloned().collect();
+
+    let inner = types.into_pairs().next().unwrap().into_value();
+    let inner = map_first_token(inner, |mut token| {
+        token.leading_trivia.splice(0..0, leading_trivia);
+        token
+    });
+
+    map_last_token(inner, |mut token| {
+        token.trailing_trivia.extend(trailing_trivia);
+        token
+    })
+}
+
+fn take_trailing_trivia<'a>(type_info: TypeInfo<'a>) -> (TypeInfo<'a>, Vec<Token<'a>>) {
+    let mut trivia = Vec::new();
+    let type_info = map_last_token(type_info, |mut token| {
+        trivia = token.take_trailing_trivia();
+        token
+    });
+
+    (type_info, trivia)
+}
+
+// Flattens a chain of unions or intersections into its members, along with the `|` or `&` before them.
+// Parentheses around a chain of the same kind, or around a member that doesn't need them, are removed.
+fn flatten_members<'a>(
+    type_info: TypeInfo<'a>,
+    separator: Option<TokenReference<'a>>,
+    symbol: Symbol,
+    options: &SimplifyOptions,
+    members: &mut Vec<(Option<TokenReference<'a>>, TypeInfo<'a>)>,
+) {
+    // Simplified first, so that parentheses which become unnecessary, such as `(A | A)`, are removed
+    let type_info = match type_info {
+        tuple @ TypeInfo::Tuple { .. } => simplify_type(tuple, options),
+        other => other,
+    };
+
+    let unneeded = is_parentheses_around(&type_info, |inner| match inner {
+        TypeInfo::Union { .. } => symbol == Symbol::Pipe,
+        TypeInfo::Intersection { .. } => symbol == Symbol::Ampersand,
+        other => can_be_optional(other),
+    });
+
+    if unneeded {
+        return flatten_members(
+            unwrap_parentheses(type_info),
+            separator,
+            symbol,
+            options,
+            members,
+        );
+    }
+
+    match type_info {
+        TypeInfo::Union { left, pipe, right } if symbol == Symbol::Pipe => {
+            flatten_members(*left, separator, symbol, options, members);
+            flatten_members(*right, Some(pipe), symbol, options, members);
+        }
+
+        TypeInfo::Intersection {
+            left,
+            ampersand,
+            right,
+        } if symbol == Symbol::Ampersand => {
+            flatten_members(*left, separator, symbol, options, members);
+            flatten_members(*right, Some(ampersand), symbol, options, members);
+        }
+
+        other => members.push((separator, other)),
+    }
+}
+
+fn is_nil(type_info: &TypeInfo) -> bool {
+    matches!(type_info, TypeInfo::Basic(token) if token.as_symbol() == Some(Symbol::Nil))
+}
+
+fn simplify_members<'a>(
+    type_info: TypeInfo<'a>,
+    symbol: Symbol,
+    options: &SimplifyOptions,
+) -> TypeInfo<'a> {
+    let mut flattened = Vec::new();
+    flatten_members(type_info, None, symbol, options, &mut flattened);
+
+    // The trivia after the last member stays at the end, even if that member is removed
+    let (last_separator, last) = flattened.pop().expect("types always have a member");
+    let (last, end_trivia) = take_trailing_trivia(last);
+    flattened.push((last_separator, last));
+
+    let mut seen = Vec::new();
+    let mut members = Vec::new();
+    for (separator, member) in flattened {
+        let member = simplify_type(member, options);
+        let canonical = member.to_canonical_string();
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            members.push((separator, member));
+        }
+    }
+
+    if symbol == Symbol::Pipe && options.nil_to_optional() && members.len() > 1 {
+        if let Some(nil_index) = members.iter().position(|(_, member)| is_nil(member)) {
+            let already_optional = members
+                .iter()
+                .any(|(_, member)| matches!(member, TypeInfo::Optional { .. }));
+
+            // `?` after an intersection or callback would only apply to its last part,
+            // and can't be used on tuples at all
+            let optional_index = members.iter().rposition(|(_, member)| {
+                !is_nil(member)
+                    && !matches!(
+                        member,
+                        TypeInfo::Intersection { .. }
+                            | TypeInfo::Callback { .. }
+                            | TypeInfo::Tuple { .. }
+                    )
+            });
+
+            if already_optional || optional_index.is_some() {
+                members.remove(nil_index);
+            }
+
+            if let (false, Some(index)) = (already_optional, optional_index) {
+                let index = if index > nil_index { index - 1 } else { index };
+                let (separator, member) = members.remove(index);
+
+                // The `?` goes right after the member, before the trivia that was after it
+                let (member, trailing_trivia) = take_trailing_trivia(member);
+                let mut question_mark = TokenReference::from_symbol(Symbol::QuestionMark);
+                question_mark.trailing_trivia = trailing_trivia;
+
+                members.insert(
+                    index,
+                    (
+                        separator,
+                        TypeInfo::Optional {
+                            base: Box::new(member),
+                            question_mark,
+                        },
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut members = members.into_iter().rev();
+    let (mut separator, type_info) = members.next().expect("types always have a member");
+    let (mut type_info, _) = take_trailing_trivia(type_info);
+    type_info = map_last_token(type_info, |mut token| {
+        token.trailing_trivia = end_trivia;
+        token
+    });
+
+    for (previous_separator, left) in members {
+        let operator = separator.unwrap_or_else(|| TokenReference::spaced_symbol(" ", symbol, " "));
+
+        type_info = if symbol == Symbol::Pipe {
+            TypeInfo::Union {
+                left: Box::new(left),
+                pipe: operator,
+                right: Box::new(type_info),
+            }
+        } else {
+            TypeInfo::Intersection {
+                left: Box::new(left),
+                ampersand: operator,
+                right: Box::new(type_info),
+            }
+        };
+
+        separator = previous_separator;
+    }
+
+    type_info
+}
+
+// Writes the tokens of an expression in order. Tokens that were separated by any trivia are
+// separated by a single space, except around brackets and indexing, and commas are always
+// followed by one.
+struct CanonicalTokens<'s> {
+    string: &'s mut String,
+    separated: bool,
+    previous: Option<Option<Symbol>>,
+}
+
+impl<'ast> Visitor<'ast> for CanonicalTokens<'_> {
+    fn visit_token_reference(&mut self, token: &TokenReference<'ast>) {
+        let symbol = token.as_symbol();
+        let tight_before = matches!(
+            symbol,
+            Some(Symbol::RightParen)
+                | Some(Symbol::RightBracket)
+                | Some(Symbol::Dot)
+                | Some(Symbol::Colon)
+                | Some(Symbol::Comma)
+        );
+
+        let space = match self.previous {
+            None => false,
+            Some(Some(Symbol::Comma)) => !tight_before,
+            Some(Some(Symbol::LeftParen))
+            | Some(Some(Symbol::LeftBracket))
+            | Some(Some(Symbol::Dot))
+            | Some(Some(Symbol::Colon)) => false,
+            Some(_) => (self.separated || token.leading_trivia().next().is_some()) && !tight_before,
+        };
+
+        if space {
+            self.string.push(' ');
+        }
+
+        self.string.push_str(&token.token().to_string());
+        self.separated = token.trailing_trivia().next().is_some();
+        self.previous = Some(symbol);
+    }
+}
+
+fn write_canonical_list<T>(
+    string: &mut String,
+    list: &Punctuated<T>,
+    mut write: impl FnMut(&mut String, &T),
+) {
+    for (index, item) in list.iter().enumerate() {
+        if index > 0 {
+            string.push_str(", ");
+        }
+
+        write(string, item);
+    }
+}
+
+fn write_canonical_type(string: &mut String, type_info: &TypeInfo) {
+    match type_info {
+        TypeInfo::Array { type_info, .. } => {
+            string.push_str("{ ");
+            write_canonical_type(string, type_info);
+            string.push_str(" }");
+        }
+
+        TypeInfo::Basic(token) => string.push_str(&token.token().to_string()),
+
+        TypeInfo::Callback {
+            generics,
+            arguments,
+            return_type,
+            ..
+        } => {
+            if let Some(generics) = generics {
+                string.push('<');
+                write_canonical_list(string, generics.generics(), |string, name| {
+                    string.push_str(&name.token().to_string())
+                });
+                string.push('>');
+            }
+
+            string.push('(');
+            write_canonical_list(string, arguments, write_canonical_type);
+            string.push_str(") -> ");
+            write_canonical_type(string, return_type);
+        }
+
+        TypeInfo::Generic { base, generics, .. } => {
+            string.push_str(&base.token().to_string());
+            string.push('<');
+            write_canonical_list(string, generics, write_canonical_type);
+            string.push('>');
+        }
+
+        TypeInfo::Intersection { left, right, .. } => {
+            write_canonical_type(string, left);
+            string.push_str(" & ");
+            write_canonical_type(string, right);
+        }
+
+        TypeInfo::Module {
+            module, type_info, ..
+        } => {
+            string.push_str(&module.token().to_string());
+            string.push('.');
+            match &**type_info {
+                IndexedTypeInfo::Basic(token) => string.push_str(&token.token().to_string()),
+                IndexedTypeInfo::Generic { base, generics, .. } => {
+                    string.push_str(&base.token().to_string());
+                    string.push('<');
+                    write_canonical_list(string, generics, write_canonical_type);
+                    string.push('>');
+                }
+            }
+        }
+
+        TypeInfo::Optional { base, .. } => {
+            write_canonical_type(string, base);
+            string.push('?');
+        }
+
+        TypeInfo::Table { fields, .. } => {
+            if fields.is_empty() {
+                string.push_str("{}");
+                return;
+            }
+
+            string.push_str("{ ");
+            write_canonical_list(string, fields, |string, field| {
+                match field.key() {
+                    TypeFieldKey::Name(name) => string.push_str(&name.token().to_string()),
+                    TypeFieldKey::IndexSignature { inner, .. } => {
+                        string.push('[');
+                        write_canonical_type(string, inner);
+                        string.push(']');
+                    }
+                }
+
+                string.push_str(": ");
+                write_canonical_type(string, field.value());
+            });
+            string.push_str(" }");
+        }
+
+        TypeInfo::Typeof { inner, .. } => {
+            string.push_str("typeof(");
+
+            let mut writer = CanonicalTokens {
+                string,
+                separated: false,
+                previous: None,
+            };
+            inner.visit(&mut writer);
+            string.push(')');
+        }
+
+        TypeInfo::Tuple { types, .. } => {
+            string.push('(');
+            write_canonical_list(string, types, write_canonical_type);
+            string.push(')');
+        }
+
+        TypeInfo::Union { left, right, .. } => {
+            write_canonical_type(string, left);
+            string.push_str(" | ");
+            write_canonical_type(string, right);
+        }
+
+        TypeInfo::Variadic { type_info, .. } => {
+            string.push_str("...");
+            write_canonical_type(string, type_info);
+        }
+    }
+}
+
 /// A subset of TypeInfo that consists of items which can only be used as an index, such as `Foo` and `Foo<Bar>`,
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -187,7 +766,7 @@ pub enum TypeInfo<'a> {
 pub enum IndexedTypeInfo<'a> {
     /// A standalone type, such as `string` or `Foo`.
     #[display(fmt = "{}", "_0")]
-    Basic(#[cfg_attr(feature = "serde", serde(borrow))] TokenReference<'a>),
+    Basic(TokenReference<'a>),
 
     /// A type using generics, such as `map<number, string>`.
     #[display(
@@ -199,13 +778,11 @@ pub enum IndexedTypeInfo<'a> {
     )]
     Generic {
         /// The type that has generics: `map`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         base: TokenReference<'a>,
         /// The arrows (`<>`) containing the type parameters.
-        #[cfg_attr(feature = "serde", serde(borrow))]
+        #[node(contains = "generics")]
         arrows: ContainedSpan<'a>,
         /// The type parameters: `number, string`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         generics: Punctuated<'a, TypeInfo<'a>>,
     },
 }
@@ -216,11 +793,8 @@ pub enum IndexedTypeInfo<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "key", "colon", "value")]
 pub struct TypeField<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) key: TypeFieldKey<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) colon: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) value: TypeInfo<'a>,
 }
 
@@ -229,11 +803,16 @@ impl<'a> TypeField<'a> {
     pub fn new(key: TypeFieldKey<'a>, value: TypeInfo<'a>) -> Self {
         Self {
             key,
-            colon: TokenReference::symbol(": ").unwrap(),
+            colon: TokenReference::spaced_symbol("", Symbol::Colon, " "),
             value,
         }
     }
 
+    /// Creates a new TypeField with an index signature, such as `[string]: number`
+    pub fn indexer(key_type: TypeInfo<'a>, value_type: TypeInfo<'a>) -> Self {
+        Self::new(TypeFieldKey::index_signature(key_type), value_type)
+    }
+
     /// The key of the field, `foo` in `foo: number`.
     pub fn key(&self) -> &TypeFieldKey<'a> {
         &self.key
@@ -281,23 +860,61 @@ pub enum TypeFieldKey<'a> {
     #[display(fmt = "{}{}{}", "brackets.tokens().0", "inner", "brackets.tokens().1")]
     IndexSignature {
         /// The brackets (`[]`) used to contain the type.
-        #[cfg_attr(feature = "serde", serde(borrow))]
+        #[node(full_range)]
         brackets: ContainedSpan<'a>,
 
         /// The type for the index signature, `number` in `[number]`.
-        #[cfg_attr(feature = "serde", serde(borrow))]
         inner: TypeInfo<'a>,
     },
 }
 
+impl<'a> TypeFieldKey<'a> {
+    /// Creates a name key, such as `foo`.
+    ///
+    /// # Panics
+    /// Panics if the name is not a valid identifier, such as a keyword.
+    pub fn name(name: &str) -> Self {
+        TypeFieldKey::Name(
+            builders::identifier(name)
+                .unwrap_or_else(|| panic!("{:?} is not a valid identifier", name)),
+        )
+    }
+
+    /// Creates an index signature key, such as `[string]`.
+    pub fn index_signature(inner: TypeInfo<'a>) -> Self {
+        TypeFieldKey::IndexSignature {
+            brackets: ContainedSpan::new(
+                TokenReference::from_symbol(Symbol::LeftBracket),
+                TokenReference::from_symbol(Symbol::RightBracket),
+            ),
+            inner,
+        }
+    }
+
+    /// The name of the key, `foo` in `foo: number`, or `None` if it's an index signature.
+    pub fn as_name(&self) -> Option<&TokenReference<'a>> {
+        match self {
+            TypeFieldKey::Name(name) => Some(name),
+            TypeFieldKey::IndexSignature { .. } => None,
+        }
+    }
+
+    /// The brackets and the type of an index signature, `[string]` in `[string]: number`,
+    /// or `None` if it's a name.
+    pub fn as_index_signature(&self) -> Option<(&ContainedSpan<'a>, &TypeInfo<'a>)> {
+        match self {
+            TypeFieldKey::IndexSignature { brackets, inner } => Some((brackets, inner)),
+            TypeFieldKey::Name(_) => None,
+        }
+    }
+}
+
 /// A type assertion using `::`, such as `:: number`.
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", "assertion_op", "cast_to")]
 pub struct TypeAssertion<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) assertion_op: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) cast_to: TypeInfo<'a>,
 }
 
@@ -305,7 +922,7 @@ impl<'a> TypeAssertion<'a> {
     /// Creates a new TypeAssertion from the given cast to TypeInfo
     pub fn new(cast_to: TypeInfo<'a>) -> Self {
         Self {
-            assertion_op: TokenReference::symbol("::").unwrap(),
+            assertion_op: TokenReference::spaced_symbol("", Symbol::TwoColons, ""),
             cast_to,
         }
     }
@@ -334,6 +951,46 @@ impl<'a> TypeAssertion<'a> {
     }
 }
 
+/// An attribute on a function, such as `@native` in `@native function fast() end`.
+/// Unknown attribute names are accepted, as Luau is free to add more of them.
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[display(fmt = "{}{}", "at_sign", "name")]
+pub struct Attribute<'a> {
+    pub(crate) at_sign: TokenReference<'a>,
+    pub(crate) name: TokenReference<'a>,
+}
+
+impl<'a> Attribute<'a> {
+    /// Creates a new Attribute from the given name, such as `native`
+    pub fn new(name: TokenReference<'a>) -> Self {
+        Self {
+            at_sign: TokenReference::spaced_symbol("", Symbol::At, ""),
+            name,
+        }
+    }
+
+    /// The token `@`.
+    pub fn at_sign(&self) -> &TokenReference<'a> {
+        &self.at_sign
+    }
+
+    /// The name of the attribute, `native` in `@native`.
+    pub fn name(&self) -> &TokenReference<'a> {
+        &self.name
+    }
+
+    /// Returns a new Attribute with the given `@` token
+    pub fn with_at_sign(self, at_sign: TokenReference<'a>) -> Self {
+        Self { at_sign, ..self }
+    }
+
+    /// Returns a new Attribute with the given name
+    pub fn with_name(self, name: TokenReference<'a>) -> Self {
+        Self { name, ..self }
+    }
+}
+
 /// A type declaration, such as `type Meters = number`
 #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -346,15 +1003,10 @@ impl<'a> TypeAssertion<'a> {
     "declare_as"
 )]
 pub struct TypeDeclaration<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) type_token: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) base: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) generics: Option<GenericDeclaration<'a>>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) equal_token: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) declare_as: TypeInfo<'a>,
 }
 
@@ -362,10 +1014,16 @@ impl<'a> TypeDeclaration<'a> {
     /// Creates a new TypeDeclaration from the given type name and type declaration
     pub fn new(type_name: TokenReference<'a>, type_definition: TypeInfo<'a>) -> Self {
         Self {
-            type_token: TokenReference::symbol("type ").unwrap(),
+            type_token: TokenReference::new(
+                Vec::new(),
+                Token::new(TokenType::Identifier {
+                    identifier: "type".into(),
+                }),
+                vec![Token::new(TokenType::spaces(1))],
+            ),
             base: type_name,
             generics: None,
-            equal_token: TokenReference::symbol(" = ").unwrap(),
+            equal_token: TokenReference::spaced_symbol(" ", Symbol::Equal, " "),
             declare_as: type_definition,
         }
     }
@@ -435,10 +1093,9 @@ impl<'a> TypeDeclaration<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "arrows.tokens().0", "generics", "arrows.tokens().1")]
 pub struct GenericDeclaration<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
+    #[node(full_range)]
     #[visit(contains = "generics")]
     pub(crate) arrows: ContainedSpan<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) generics: Punctuated<'a, TokenReference<'a>>,
 }
 
@@ -447,8 +1104,8 @@ impl<'a> GenericDeclaration<'a> {
     pub fn new() -> Self {
         Self {
             arrows: ContainedSpan::new(
-                TokenReference::symbol("<").unwrap(),
-                TokenReference::symbol(">").unwrap(),
+                TokenReference::spaced_symbol("", Symbol::LessThan, ""),
+                TokenReference::spaced_symbol("", Symbol::GreaterThan, ""),
             ),
             generics: Punctuated::new(),
         }
@@ -480,9 +1137,7 @@ impl<'a> GenericDeclaration<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", "punctuation", "type_info")]
 pub struct TypeSpecifier<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) punctuation: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) type_info: TypeInfo<'a>,
 }
 
@@ -490,7 +1145,7 @@ impl<'a> TypeSpecifier<'a> {
     /// Creates a new TypeSpecifier with the given type info
     pub fn new(type_info: TypeInfo<'a>) -> Self {
         Self {
-            punctuation: TokenReference::symbol(": ").unwrap(),
+            punctuation: TokenReference::spaced_symbol("", Symbol::Colon, " "),
             type_info,
         }
     }
@@ -525,9 +1180,7 @@ impl<'a> TypeSpecifier<'a> {
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}", "export_token", "type_declaration")]
 pub struct ExportedTypeDeclaration<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) export_token: TokenReference<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) type_declaration: TypeDeclaration<'a>,
 }
 
@@ -591,7 +1244,6 @@ make_op!(CompoundOp,
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[display(fmt = "{}{}{}", "lhs", "compound_operator", "rhs")]
 pub struct CompoundAssignment<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) lhs: Var<'a>,
     pub(crate) compound_operator: CompoundOp<'a>,
     pub(crate) rhs: Expression<'a>,
@@ -8,6 +8,7 @@ use crate::{
 struct UpdatePositionsRewriter {
     start_position: Position,
     next_is_new_line: bool,
+    only_synthesized: bool,
 }
 
 impl UpdatePositionsRewriter {
@@ -22,6 +23,7 @@ impl UpdatePositionsRewriter {
                     self.next_is_new_line = false;
                     end_position.line += 1;
                     end_position.character = 1;
+                    end_position.byte_column = 0;
                 }
 
                 if character == '\n' {
@@ -31,6 +33,7 @@ impl UpdatePositionsRewriter {
                 }
 
                 end_position.bytes += character.len_utf8();
+                end_position.byte_column += character.len_utf8();
             }
         }
 
@@ -44,6 +47,7 @@ impl UpdatePositionsRewriter {
             self.next_is_new_line = false;
             end_position.line += 1;
             end_position.character = 1;
+            end_position.byte_column = 0;
         }
 
         self.start_position = end_position;
@@ -54,17 +58,26 @@ impl UpdatePositionsRewriter {
 
 impl<'ast> VisitorMut<'ast> for UpdatePositionsRewriter {
     fn visit_token_reference(&mut self, token: TokenReference<'ast>) -> TokenReference<'ast> {
-        TokenReference::new(
-            token
+        let updated = TokenReference {
+            leading_trivia: token
                 .leading_trivia()
                 .map(|token| self.update_token(token))
                 .collect(),
-            self.update_token(token.token()),
-            token
+            token: self.update_token(token.token()),
+            trailing_trivia: token
                 .trailing_trivia()
                 .map(|token| self.update_token(token))
                 .collect(),
-        )
+            origin: token.origin(),
+        };
+
+        // Tokens from the source are still passed through, so that the tokens after them are
+        // positioned correctly
+        if self.only_synthesized && token.is_from_source() {
+            token
+        } else {
+            updated
+        }
     }
 }
 
@@ -72,12 +85,26 @@ impl Ast<'_> {
     /// Will update the positions of all the tokens in the tree
     /// Necessary if you are both mutating the tree and need the positions of the tokens
     pub fn update_positions(self) -> Self {
+        self.update_positions_with(false)
+    }
+
+    /// Updates the positions of only the [synthesized](crate::tokenizer::Origin::Synthesized) tokens
+    /// in the tree, such as ones created with builders, to where they are in the printed code.
+    /// Tokens from the source keep their positions, so that diagnostics on them still point into
+    /// the original code.
+    pub fn update_synthesized_positions(self) -> Self {
+        self.update_positions_with(true)
+    }
+
+    fn update_positions_with(self, only_synthesized: bool) -> Self {
         let mut rewriter = UpdatePositionsRewriter {
             start_position: Position {
                 bytes: 0,
                 character: 1,
                 line: 1,
+                byte_column: 0,
             },
+            only_synthesized,
 
             ..Default::default()
         };
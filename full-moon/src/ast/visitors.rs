@@ -1,7 +1,7 @@
 // Implementations of Visit and VisitMut that are not able to be automatically derived yet.
 // Ideally everything would be derived.
 use super::*;
-use crate::visitors::{Visit, VisitMut, Visitor, VisitorMut};
+use crate::visitors::{Visit, VisitMut, VisitorMut, VisitorRef};
 
 // The following have `ContainedSpan`, which when automatically derived will visit the tokens containing
 // before they visit what they're actually containing.
@@ -10,7 +10,10 @@ use crate::visitors::{Visit, VisitMut, Visitor, VisitorMut};
 // This is fixed for structs with `#[visit(contains = "...")], but this is not supported on enums.
 
 impl<'a> Visit<'a> for Field<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_field(self);
         match self {
             Field::ExpressionKey {
@@ -79,7 +82,10 @@ impl<'a> VisitMut<'a> for Field<'a> {
 }
 
 impl<'a> Visit<'a> for Expression<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_expression(self);
         match self {
             Expression::BinaryOperator { lhs, binop, rhs } => {
@@ -161,7 +167,10 @@ impl<'a> VisitMut<'a> for Expression<'a> {
 }
 
 impl<'a> Visit<'a> for Index<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_index(self);
         match self {
             Index::Brackets {
@@ -212,7 +221,10 @@ impl<'a> VisitMut<'a> for Index<'a> {
 }
 
 impl<'a> Visit<'a> for FunctionArgs<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_function_args(self);
         match self {
             FunctionArgs::Parentheses {
@@ -264,7 +276,10 @@ impl<'a> VisitMut<'a> for FunctionArgs<'a> {
 
 // The following contain type signatures, which are addendums to previous identities
 impl<'a> Visit<'a> for FunctionBody<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_function_body(self);
         self.parameters_parentheses.tokens.0.visit(visitor);
 
@@ -278,12 +293,18 @@ impl<'a> Visit<'a> for FunctionBody<'a> {
         #[cfg(not(feature = "roblox"))]
         {
             // TODO: Option<!>, and implement Visit for !
-            type_specifiers = std::iter::repeat::<Option<Self>>(None);
+            type_specifiers = std::iter::repeat::<Option<&Self>>(None);
         }
 
-        for parameter in &self.parameters {
-            parameter.visit(visitor);
-            type_specifiers.next().visit(visitor);
+        for parameter in self.parameters.pairs() {
+            parameter.value().visit(visitor);
+            if let Some(type_specifier) = type_specifiers.next().flatten() {
+                type_specifier.visit(visitor);
+            }
+
+            if let Some(punctuation) = parameter.punctuation() {
+                punctuation.visit(visitor);
+            }
         }
 
         self.parameters_parentheses.tokens.1.visit(visitor);
@@ -356,7 +377,10 @@ impl<'a> VisitMut<'a> for FunctionBody<'a> {
 }
 
 impl<'a> Visit<'a> for LocalAssignment<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_local_assignment(self);
         self.local_token.visit(visitor);
 
@@ -370,12 +394,18 @@ impl<'a> Visit<'a> for LocalAssignment<'a> {
         #[cfg(not(feature = "roblox"))]
         {
             // TODO: Option<!>, and implement Visit for !
-            type_specifiers = std::iter::repeat::<Option<Self>>(None);
+            type_specifiers = std::iter::repeat::<Option<&Self>>(None);
         }
 
-        for name in &self.name_list {
-            name.visit(visitor);
-            type_specifiers.next().visit(visitor);
+        for name in self.name_list.pairs() {
+            name.value().visit(visitor);
+            if let Some(type_specifier) = type_specifiers.next().flatten() {
+                type_specifier.visit(visitor);
+            }
+
+            if let Some(punctuation) = name.punctuation() {
+                punctuation.visit(visitor);
+            }
         }
 
         self.equal_token.visit(visitor);
@@ -433,7 +463,10 @@ impl<'a> VisitMut<'a> for LocalAssignment<'a> {
 }
 
 impl<'a> Visit<'a> for GenericFor<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_generic_for(self);
         self.for_token.visit(visitor);
 
@@ -447,12 +480,18 @@ impl<'a> Visit<'a> for GenericFor<'a> {
         #[cfg(not(feature = "roblox"))]
         {
             // TODO: Option<!>, and implement Visit for !
-            type_specifiers = std::iter::repeat::<Option<Self>>(None);
+            type_specifiers = std::iter::repeat::<Option<&Self>>(None);
         }
 
-        for name in &self.names {
-            name.visit(visitor);
-            type_specifiers.next().visit(visitor);
+        for name in self.names.pairs() {
+            name.value().visit(visitor);
+            if let Some(type_specifier) = type_specifiers.next().flatten() {
+                type_specifier.visit(visitor);
+            }
+
+            if let Some(punctuation) = name.punctuation() {
+                punctuation.visit(visitor);
+            }
         }
 
         self.in_token.visit(visitor);
@@ -518,7 +557,10 @@ impl<'a> VisitMut<'a> for GenericFor<'a> {
 }
 
 impl<'a> Visit<'a> for NumericFor<'a> {
-    fn visit<V: Visitor<'a>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'a>>(&'b self, visitor: &mut V)
+    where
+        'a: 'b,
+    {
         visitor.visit_numeric_for(self);
         self.for_token.visit(visitor);
         self.index_variable.visit(visitor);
@@ -0,0 +1,257 @@
+use super::tree::{debug_tree, DebugNode, DebugToken};
+use crate::ast::Ast;
+use std::fmt::Write;
+
+/// Options for [`to_dot_with_options`]
+#[derive(Clone, Debug, Default)]
+pub struct DotOptions {
+    include_trivia: bool,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+}
+
+impl DotOptions {
+    /// Creates the default options: no trivia, and no limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether trivia, such as whitespace and comments, is included as children of the tokens it surrounds
+    pub fn include_trivia(&self) -> bool {
+        self.include_trivia
+    }
+
+    /// The maximum depth of nodes included, where the [`Ast`] itself is at a depth of 0
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// The maximum amount of nodes included, not counting the nodes marking truncation
+    pub fn max_nodes(&self) -> Option<usize> {
+        self.max_nodes
+    }
+
+    /// Returns new options with trivia either included or not
+    pub fn with_trivia(self, include_trivia: bool) -> Self {
+        Self {
+            include_trivia,
+            ..self
+        }
+    }
+
+    /// Returns new options with the given maximum depth.
+    /// The children of nodes at this depth are replaced with a single `...` node.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    /// Returns new options with the given maximum amount of nodes.
+    /// Once reached, the rest of the tree is replaced with a single `...` node.
+    pub fn with_max_nodes(self, max_nodes: usize) -> Self {
+        Self {
+            max_nodes: Some(max_nodes),
+            ..self
+        }
+    }
+}
+
+struct DotWriter<'a> {
+    options: &'a DotOptions,
+    output: String,
+    next_id: usize,
+    written: usize,
+    truncated: bool,
+}
+
+enum Child<'b> {
+    Node(&'b DebugNode),
+    Trivia(&'b DebugToken, &'static str),
+}
+
+impl DotWriter<'_> {
+    // Writes a node along with the edge from its parent, returning its id
+    fn write_node(
+        &mut self,
+        label: &str,
+        shape: Option<&str>,
+        parent: Option<(usize, &str)>,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        write!(self.output, "    n{} [label=\"{}\"", id, escape(label)).unwrap();
+        if let Some(shape) = shape {
+            write!(self.output, ", shape={}", shape).unwrap();
+        }
+        self.output.push_str("];\n");
+
+        if let Some((parent, attributes)) = parent {
+            writeln!(self.output, "    n{} -> n{}{};", parent, id, attributes).unwrap();
+        }
+
+        id
+    }
+
+    fn write_ellipsis(&mut self, parent: usize) {
+        self.write_node("...", Some("plaintext"), Some((parent, "")));
+    }
+
+    // Whether another node can be written, marking the output as truncated if not
+    fn has_room(&mut self) -> bool {
+        if matches!(self.options.max_nodes, Some(max) if self.written >= max) {
+            self.truncated = true;
+        }
+
+        if self.truncated {
+            return false;
+        }
+
+        self.written += 1;
+        true
+    }
+
+    // Writes the node and its children, returning false if the node limit was reached
+    fn write_tree(&mut self, node: &DebugNode, parent: Option<usize>, depth: usize) -> bool {
+        if !self.has_room() {
+            return false;
+        }
+
+        let parent = parent.map(|parent| (parent, ""));
+
+        match node {
+            DebugNode::Node {
+                type_name,
+                children,
+                ..
+            } => {
+                let id = self.write_node(type_name, None, parent);
+                self.write_children(id, children.iter().map(Child::Node), depth);
+            }
+
+            DebugNode::Token {
+                token,
+                leading_trivia,
+                trailing_trivia,
+            } => {
+                let id = self.write_node(&token_label(token), Some("ellipse"), parent);
+
+                if self.options.include_trivia {
+                    let trivia = leading_trivia
+                        .iter()
+                        .map(|trivia| Child::Trivia(trivia, "leading"))
+                        .chain(
+                            trailing_trivia
+                                .iter()
+                                .map(|trivia| Child::Trivia(trivia, "trailing")),
+                        );
+
+                    self.write_children(id, trivia, depth);
+                }
+            }
+        }
+
+        true
+    }
+
+    fn write_children<'b>(
+        &mut self,
+        parent: usize,
+        mut children: impl Iterator<Item = Child<'b>>,
+        depth: usize,
+    ) {
+        if self.options.max_depth == Some(depth) {
+            if children.next().is_some() {
+                self.write_ellipsis(parent);
+            }
+
+            return;
+        }
+
+        for child in children {
+            let written = match child {
+                Child::Node(node) => self.write_tree(node, Some(parent), depth + 1),
+                Child::Trivia(trivia, position) => {
+                    let written = self.has_room();
+                    if written {
+                        self.write_node(
+                            &token_label(trivia),
+                            Some("ellipse"),
+                            Some((parent, &format!(" [style=dashed, label=\"{}\"]", position))),
+                        );
+                    }
+
+                    written
+                }
+            };
+
+            if !written {
+                self.write_ellipsis(parent);
+                return;
+            }
+        }
+    }
+}
+
+fn token_label(token: &DebugToken) -> String {
+    format!("{} \"{}\"", token.type_name(), token.text)
+}
+
+// Escapes text to be put inside of a quoted DOT string
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\\\n"),
+            '\r' => escaped.push_str("\\\\r"),
+            '\t' => escaped.push_str("\\\\t"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+/// Creates a [Graphviz](https://graphviz.org/) digraph of the [`Ast`] using the default [`DotOptions`].
+/// Every node is labeled with the name of its type, and every token with its kind and text.
+///
+/// ```rust
+/// let ast = full_moon::parse("local x = 1")?;
+/// let dot = full_moon::debug::to_dot(&ast);
+/// assert!(dot.starts_with("digraph ast {"));
+/// assert!(dot.contains(r#"[label="LocalAssignment"]"#));
+/// assert!(dot.contains(r#"[label="Identifier \"x\"", shape=ellipse]"#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn to_dot(ast: &Ast) -> String {
+    to_dot_with_options(ast, &DotOptions::default())
+}
+
+/// Creates a [Graphviz](https://graphviz.org/) digraph of the [`Ast`] using the given [`DotOptions`].
+/// Edges follow the children of every node in the order they appear in the code.
+///
+/// ```rust
+/// # use full_moon::debug::{to_dot_with_options, DotOptions};
+/// let ast = full_moon::parse("local x = 1 -- comment")?;
+/// let dot = to_dot_with_options(&ast, &DotOptions::new().with_trivia(true).with_max_nodes(100));
+/// assert!(dot.contains(r#"[label="SingleLineComment \"-- comment\"", shape=ellipse]"#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn to_dot_with_options(ast: &Ast, options: &DotOptions) -> String {
+    let mut writer = DotWriter {
+        options,
+        output: String::from("digraph ast {\n    ordering=out;\n    node [shape=box];\n"),
+        next_id: 0,
+        written: 0,
+        truncated: false,
+    };
+
+    writer.write_tree(&debug_tree(ast), None, 0);
+    writer.output.push_str("}\n");
+    writer.output
+}
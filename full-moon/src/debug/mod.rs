@@ -0,0 +1,8 @@
+mod dot;
+mod sexpr;
+mod tree;
+
+pub use dot::*;
+pub use sexpr::SexprOptions;
+
+pub(crate) use sexpr::to_sexpr;
@@ -0,0 +1,140 @@
+use super::tree::{debug_tree, DebugNode, DebugToken};
+use crate::ast::Ast;
+use std::fmt::Write;
+
+/// Options for [`Ast::to_sexpr`]
+#[derive(Clone, Debug)]
+pub struct SexprOptions {
+    include_positions: bool,
+    include_trivia: bool,
+}
+
+impl SexprOptions {
+    /// Creates the default options: positions are included, trivia is not
+    pub fn new() -> Self {
+        Self {
+            include_positions: true,
+            include_trivia: false,
+        }
+    }
+
+    /// Whether every token is followed by its start position, in the format of `line:character`
+    pub fn include_positions(&self) -> bool {
+        self.include_positions
+    }
+
+    /// Whether the leading and trailing trivia of every token is included
+    pub fn include_trivia(&self) -> bool {
+        self.include_trivia
+    }
+
+    /// Returns new options with positions either included or not
+    pub fn with_positions(self, include_positions: bool) -> Self {
+        Self {
+            include_positions,
+            ..self
+        }
+    }
+
+    /// Returns new options with trivia either included or not
+    pub fn with_trivia(self, include_trivia: bool) -> Self {
+        Self {
+            include_trivia,
+            ..self
+        }
+    }
+}
+
+impl Default for SexprOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct SexprWriter<'a> {
+    options: &'a SexprOptions,
+    output: String,
+}
+
+impl SexprWriter<'_> {
+    fn write_tree(&mut self, node: &DebugNode, indent: usize) {
+        match node {
+            DebugNode::Node { name, children, .. } => {
+                write!(self.output, "({}", name).unwrap();
+
+                // Nodes that only contain tokens are kept on one line
+                if children
+                    .iter()
+                    .all(|child| matches!(child, DebugNode::Token { .. }))
+                {
+                    for child in children {
+                        self.output.push(' ');
+                        self.write_tree(child, indent);
+                    }
+                } else {
+                    for child in children {
+                        write!(self.output, "\n{:width$}", "", width = indent + 2).unwrap();
+                        self.write_tree(child, indent + 2);
+                    }
+                }
+
+                self.output.push(')');
+            }
+
+            DebugNode::Token {
+                token,
+                leading_trivia,
+                trailing_trivia,
+            } => {
+                self.output.push('(');
+                self.write_token(token);
+
+                if self.options.include_trivia {
+                    self.write_trivia("leading", leading_trivia);
+                    self.write_trivia("trailing", trailing_trivia);
+                }
+
+                self.output.push(')');
+            }
+        }
+    }
+
+    fn write_token(&mut self, token: &DebugToken) {
+        write!(self.output, "{} {:?}", token.name(), token.text).unwrap();
+
+        if self.options.include_positions {
+            write!(
+                self.output,
+                " {}:{}",
+                token.start_position.line(),
+                token.start_position.character()
+            )
+            .unwrap();
+        }
+    }
+
+    fn write_trivia(&mut self, name: &str, trivia: &[DebugToken]) {
+        if trivia.is_empty() {
+            return;
+        }
+
+        write!(self.output, " ({}", name).unwrap();
+        for token in trivia {
+            self.output.push_str(" (");
+            self.write_token(token);
+            self.output.push(')');
+        }
+        self.output.push(')');
+    }
+}
+
+pub(crate) fn to_sexpr(ast: &Ast, options: &SexprOptions) -> String {
+    let mut writer = SexprWriter {
+        options,
+        output: String::new(),
+    };
+
+    writer.write_tree(&debug_tree(ast), 0);
+    writer.output.push('\n');
+    writer.output
+}
@@ -0,0 +1,203 @@
+use crate::{
+    ast::{span::ContainedSpan, *},
+    tokenizer::{Position, Token, TokenKind, TokenReference},
+    visitors::Visitor,
+};
+
+#[cfg(feature = "lua52")]
+use crate::ast::lua52::*;
+#[cfg(feature = "roblox")]
+use crate::ast::types::*;
+
+// A simplified copy of the structure of an Ast, built from the events of a Visitor
+// so that every node type the visitor knows of is included.
+pub(crate) enum DebugNode {
+    Node {
+        // The name of the type, such as `LocalAssignment`
+        type_name: &'static str,
+        // The name of the type in snake_case, such as `local_assignment`
+        name: &'static str,
+        children: Vec<DebugNode>,
+    },
+
+    Token {
+        token: DebugToken,
+        leading_trivia: Vec<DebugToken>,
+        trailing_trivia: Vec<DebugToken>,
+    },
+}
+
+pub(crate) struct DebugToken {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: String,
+    pub(crate) start_position: Position,
+}
+
+impl DebugToken {
+    fn new(token: &Token) -> Self {
+        Self {
+            kind: token.token_kind(),
+            text: token.to_string(),
+            start_position: token.start_position(),
+        }
+    }
+
+    // The name of the kind of token, such as `Identifier`
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self.kind {
+            TokenKind::Eof => "Eof",
+            TokenKind::Identifier => "Identifier",
+            TokenKind::MultiLineComment => "MultiLineComment",
+            TokenKind::Number => "Number",
+            TokenKind::Shebang => "Shebang",
+            TokenKind::SingleLineComment => "SingleLineComment",
+            TokenKind::StringLiteral => "StringLiteral",
+            TokenKind::Symbol => "Symbol",
+            TokenKind::Whitespace => "Whitespace",
+        }
+    }
+
+    // The name of the kind of token in snake_case, such as `identifier`
+    pub(crate) fn name(&self) -> &'static str {
+        match self.kind {
+            TokenKind::Eof => "eof",
+            TokenKind::Identifier => "identifier",
+            TokenKind::MultiLineComment => "multi_line_comment",
+            TokenKind::Number => "number",
+            TokenKind::Shebang => "shebang",
+            TokenKind::SingleLineComment => "single_line_comment",
+            TokenKind::StringLiteral => "string_literal",
+            TokenKind::Symbol => "symbol",
+            TokenKind::Whitespace => "whitespace",
+        }
+    }
+}
+
+struct TreeBuilder {
+    stack: Vec<DebugNode>,
+}
+
+impl TreeBuilder {
+    fn enter(&mut self, type_name: &'static str, name: &'static str) {
+        self.stack.push(DebugNode::Node {
+            type_name,
+            name,
+            children: Vec::new(),
+        });
+    }
+
+    fn exit(&mut self) {
+        let node = self
+            .stack
+            .pop()
+            .expect("exited more nodes than were entered");
+        self.push(node);
+    }
+
+    fn push(&mut self, node: DebugNode) {
+        match self.stack.last_mut() {
+            Some(DebugNode::Node { children, .. }) => children.push(node),
+            _ => unreachable!("the root of the tree is always a node"),
+        }
+    }
+}
+
+macro_rules! visit_nodes {
+    ($($visit_name:ident => $ast_type:ident,)+) => {
+        paste::item! {
+            $(
+                fn $visit_name(&mut self, _: &$ast_type<'ast>) {
+                    self.enter(
+                        stringify!($ast_type),
+                        &stringify!($visit_name)["visit_".len()..],
+                    );
+                }
+
+                fn [<$visit_name _end>](&mut self, _: &$ast_type<'ast>) {
+                    self.exit();
+                }
+            )+
+        }
+    };
+}
+
+impl<'ast> Visitor<'ast> for TreeBuilder {
+    visit_nodes! {
+        visit_anonymous_function => AnonymousFunction,
+        visit_assignment => Assignment,
+        visit_block => Block,
+        visit_call => Call,
+        visit_contained_span => ContainedSpan,
+        visit_do => Do,
+        visit_else_if => ElseIf,
+        visit_expression => Expression,
+        visit_field => Field,
+        visit_function_args => FunctionArgs,
+        visit_function_body => FunctionBody,
+        visit_function_call => FunctionCall,
+        visit_function_declaration => FunctionDeclaration,
+        visit_function_name => FunctionName,
+        visit_generic_for => GenericFor,
+        visit_if => If,
+        visit_index => Index,
+        visit_local_assignment => LocalAssignment,
+        visit_local_function => LocalFunction,
+        visit_last_stmt => LastStmt,
+        visit_method_call => MethodCall,
+        visit_numeric_for => NumericFor,
+        visit_parameter => Parameter,
+        visit_prefix => Prefix,
+        visit_return => Return,
+        visit_repeat => Repeat,
+        visit_stmt => Stmt,
+        visit_suffix => Suffix,
+        visit_table_constructor => TableConstructor,
+        visit_un_op => UnOp,
+        visit_value => Value,
+        visit_var => Var,
+        visit_var_expression => VarExpression,
+        visit_while => While,
+    }
+
+    #[cfg(feature = "roblox")]
+    visit_nodes! {
+        visit_attribute => Attribute,
+        visit_compound_assignment => CompoundAssignment,
+        visit_compound_op => CompoundOp,
+        visit_exported_type_declaration => ExportedTypeDeclaration,
+        visit_generic_declaration => GenericDeclaration,
+        visit_indexed_type_info => IndexedTypeInfo,
+        visit_type_assertion => TypeAssertion,
+        visit_type_declaration => TypeDeclaration,
+        visit_type_field => TypeField,
+        visit_type_field_key => TypeFieldKey,
+        visit_type_info => TypeInfo,
+        visit_type_specifier => TypeSpecifier,
+    }
+
+    #[cfg(feature = "lua52")]
+    visit_nodes! {
+        visit_goto => Goto,
+        visit_label => Label,
+    }
+
+    // Token references have no end event, as their trivia is visited as plain tokens
+    fn visit_token_reference(&mut self, token: &TokenReference<'ast>) {
+        self.push(DebugNode::Token {
+            token: DebugToken::new(token.token()),
+            leading_trivia: token.leading_trivia().map(DebugToken::new).collect(),
+            trailing_trivia: token.trailing_trivia().map(DebugToken::new).collect(),
+        });
+    }
+}
+
+// Creates the tree for the given Ast, with the Ast itself as the root node
+pub(crate) fn debug_tree(ast: &Ast) -> DebugNode {
+    let mut builder = TreeBuilder { stack: Vec::new() };
+    builder.enter("Ast", "ast");
+    builder.visit_ast(ast);
+
+    let root = builder.stack.pop().expect("the root node was exited");
+    debug_assert!(builder.stack.is_empty(), "not every node was exited");
+    root
+}
@@ -0,0 +1,319 @@
+use crate::{
+    ast::*,
+    node::Node,
+    tokenizer::{Position, Span},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// The most cells the table of a longest common subsequence can have before the whole block
+// is reported as modified instead, so that diffing huge blocks doesn't use huge amounts of memory
+const MAX_TABLE_SIZE: usize = 1 << 22;
+
+/// A change to a statement between two [`Ast`]s, as found by [`diff`](crate::diff).
+/// The spans are of the statements without their surrounding trivia, and their
+/// [byte positions](Position::bytes) can be used to index into the code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[non_exhaustive]
+pub enum AstChange {
+    /// A statement that is only in the new Ast
+    Added {
+        /// The span of the statement in the new code
+        new: Span,
+    },
+    /// A statement that is only in the old Ast
+    Removed {
+        /// The span of the statement in the old code
+        old: Span,
+    },
+    /// A statement that was changed. Statements that contain blocks, such as `if`s and functions,
+    /// are only modified when something other than their blocks was changed, otherwise the changes
+    /// are reported for the statements inside of the blocks.
+    /// Blocks that are too large to compare are modified as a whole.
+    Modified {
+        /// The span of the statement in the old code
+        old: Span,
+        /// The span of the statement in the new code
+        new: Span,
+    },
+}
+
+impl AstChange {
+    /// The span in the old code, unless the statement was added
+    pub fn old_span(&self) -> Option<Span> {
+        match self {
+            AstChange::Added { .. } => None,
+            AstChange::Removed { old } | AstChange::Modified { old, .. } => Some(*old),
+        }
+    }
+
+    /// The span in the new code, unless the statement was removed
+    pub fn new_span(&self) -> Option<Span> {
+        match self {
+            AstChange::Removed { .. } => None,
+            AstChange::Added { new } | AstChange::Modified { new, .. } => Some(*new),
+        }
+    }
+}
+
+/// Finds the statements that were added, removed, or modified between two [`Ast`]s, in the order
+/// they appear. Statements are compared with [`Node::similar`], so trivia such as whitespace and comments
+/// and the semicolons between statements are ignored, and reformatted code has no changes.
+///
+/// Both Asts should have positions, such as from [`parse`](crate::parse).
+///
+/// ```rust
+/// # use full_moon::{diff, AstChange};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let old_code = "local x = 1\nif x then\n    print(x)\nend\n";
+/// let new_code = "local x = 1 -- one\nif x then print(x + 1) end\nreturn x\n";
+///
+/// let changes = diff(&full_moon::parse(old_code)?, &full_moon::parse(new_code)?);
+/// assert_eq!(changes.len(), 2);
+///
+/// let (old, new) = match changes[0] {
+///     AstChange::Modified { old, new } => (old, new),
+///     other => panic!("expected a modified statement, got {:?}", other),
+/// };
+/// assert_eq!(&old_code[old.start().bytes()..old.end().bytes()], "print(x)");
+/// assert_eq!(&new_code[new.start().bytes()..new.end().bytes()], "print(x + 1)");
+///
+/// assert!(matches!(changes[1], AstChange::Added { .. }));
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff(old: &Ast, new: &Ast) -> Vec<AstChange> {
+    let mut changes = Vec::new();
+    diff_blocks(old.nodes(), new.nodes(), &mut changes);
+    changes
+}
+
+#[derive(Clone, Copy)]
+enum Statement<'a, 'b> {
+    Stmt(&'b Stmt<'a>),
+    LastStmt(&'b LastStmt<'a>),
+}
+
+impl<'a, 'b> Statement<'a, 'b> {
+    fn similar(self, other: Self) -> bool {
+        match (self, other) {
+            (Statement::Stmt(stmt), Statement::Stmt(other)) => stmt.similar(other),
+            (Statement::LastStmt(last_stmt), Statement::LastStmt(other)) => {
+                last_stmt.similar(other)
+            }
+            _ => false,
+        }
+    }
+
+    fn span(self) -> Span {
+        match self {
+            Statement::Stmt(stmt) => span(stmt),
+            Statement::LastStmt(last_stmt) => span(last_stmt),
+        }
+    }
+}
+
+fn span<'a>(node: &impl Node<'a>) -> Span {
+    node.span()
+        .unwrap_or_else(|| Span::new(Position::default(), Position::default()))
+}
+
+fn statements<'a, 'b>(block: &'b Block<'a>) -> Vec<Statement<'a, 'b>> {
+    block
+        .stmts()
+        .map(Statement::Stmt)
+        .chain(block.last_stmt().map(Statement::LastStmt))
+        .collect()
+}
+
+fn diff_blocks(old: &Block, new: &Block, changes: &mut Vec<AstChange>) {
+    let (old_statements, new_statements) = (statements(old), statements(new));
+
+    // Only the statements between the common start and end have to be compared with each other
+    let prefix = old_statements
+        .iter()
+        .zip(&new_statements)
+        .take_while(|(old, new)| old.similar(**new))
+        .count();
+    let suffix = old_statements[prefix..]
+        .iter()
+        .rev()
+        .zip(new_statements[prefix..].iter().rev())
+        .take_while(|(old, new)| old.similar(**new))
+        .count();
+
+    let old_statements = &old_statements[prefix..old_statements.len() - suffix];
+    let new_statements = &new_statements[prefix..new_statements.len() - suffix];
+
+    if old_statements.is_empty() && new_statements.is_empty() {
+        return;
+    }
+
+    if (old_statements.len() + 1).saturating_mul(new_statements.len() + 1) > MAX_TABLE_SIZE {
+        changes.push(AstChange::Modified {
+            old: span(old),
+            new: span(new),
+        });
+
+        return;
+    }
+
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let width = new_statements.len() + 1;
+    let mut lengths = vec![0; (old_statements.len() + 1) * width];
+    for i in (0..old_statements.len()).rev() {
+        for j in (0..new_statements.len()).rev() {
+            lengths[i * width + j] = if old_statements[i].similar(new_statements[j]) {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+
+    while i < old_statements.len() || j < new_statements.len() {
+        if i < old_statements.len()
+            && j < new_statements.len()
+            && old_statements[i].similar(new_statements[j])
+        {
+            diff_gap(&removed, &added, changes);
+            removed.clear();
+            added.clear();
+
+            i += 1;
+            j += 1;
+        } else if j == new_statements.len()
+            || (i < old_statements.len()
+                && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            removed.push(old_statements[i]);
+            i += 1;
+        } else {
+            added.push(new_statements[j]);
+            j += 1;
+        }
+    }
+
+    diff_gap(&removed, &added, changes);
+}
+
+// The statements between two statements that are the same. Statements at the same place are paired
+// up as modified, and the rest are removed or added. Last statements can only be at the end,
+// and are only paired with each other.
+fn diff_gap(removed: &[Statement], added: &[Statement], changes: &mut Vec<AstChange>) {
+    if let (Some(Statement::LastStmt(old)), Some(Statement::LastStmt(new))) =
+        (removed.last(), added.last())
+    {
+        diff_gap(
+            &removed[..removed.len() - 1],
+            &added[..added.len() - 1],
+            changes,
+        );
+
+        changes.push(AstChange::Modified {
+            old: span(*old),
+            new: span(*new),
+        });
+
+        return;
+    }
+
+    for (old, new) in removed.iter().zip(added) {
+        match (old, new) {
+            (Statement::Stmt(old), Statement::Stmt(new)) => diff_stmts(old, new, changes),
+            _ => {
+                changes.push(AstChange::Removed { old: old.span() });
+                changes.push(AstChange::Added { new: new.span() });
+            }
+        }
+    }
+
+    for old in removed.iter().skip(added.len()) {
+        changes.push(AstChange::Removed { old: old.span() });
+    }
+
+    for new in added.iter().skip(removed.len()) {
+        changes.push(AstChange::Added { new: new.span() });
+    }
+}
+
+// If only the blocks of the statements are different, the changes are inside of the blocks
+fn diff_stmts(old: &Stmt, new: &Stmt, changes: &mut Vec<AstChange>) {
+    let old_blocks = blocks(old);
+    let new_blocks = blocks(new);
+
+    if !old_blocks.is_empty()
+        && old_blocks.len() == new_blocks.len()
+        && without_blocks(old).similar(&without_blocks(new))
+    {
+        for (old, new) in old_blocks.into_iter().zip(new_blocks) {
+            diff_blocks(old, new, changes);
+        }
+    } else {
+        changes.push(AstChange::Modified {
+            old: span(old),
+            new: span(new),
+        });
+    }
+}
+
+fn blocks<'a, 'b>(stmt: &'b Stmt<'a>) -> Vec<&'b Block<'a>> {
+    match stmt {
+        Stmt::Do(r#do) => vec![r#do.block()],
+        Stmt::FunctionDeclaration(declaration) => vec![declaration.body().block()],
+        Stmt::GenericFor(generic_for) => vec![generic_for.block()],
+        Stmt::If(r#if) => std::iter::once(r#if.block())
+            .chain(r#if.else_if().into_iter().flatten().map(ElseIf::block))
+            .chain(r#if.else_block())
+            .collect(),
+        Stmt::LocalFunction(local_function) => vec![local_function.body().block()],
+        Stmt::NumericFor(numeric_for) => vec![numeric_for.block()],
+        Stmt::Repeat(repeat) => vec![repeat.block()],
+        Stmt::While(r#while) => vec![r#while.block()],
+        _ => Vec::new(),
+    }
+}
+
+// A copy of the statement with every block that `blocks` returns emptied
+fn without_blocks<'a>(stmt: &Stmt<'a>) -> Stmt<'a> {
+    match stmt.clone() {
+        Stmt::Do(r#do) => Stmt::Do(Box::new(r#do.with_block(Block::new()))),
+        Stmt::FunctionDeclaration(declaration) => {
+            let body = declaration.body().clone().with_block(Block::new());
+            Stmt::FunctionDeclaration(Box::new(declaration.with_body(body)))
+        }
+        Stmt::GenericFor(generic_for) => {
+            Stmt::GenericFor(Box::new(generic_for.with_block(Block::new())))
+        }
+        Stmt::If(r#if) => {
+            let else_if = r#if.else_if().map(|else_ifs| {
+                else_ifs
+                    .iter()
+                    .map(|else_if| else_if.clone().with_block(Block::new()))
+                    .collect()
+            });
+            let r#else = r#if.else_block().map(|_| Block::new());
+
+            Stmt::If(Box::new(
+                r#if.with_block(Block::new())
+                    .with_else_if(else_if)
+                    .with_else(r#else),
+            ))
+        }
+        Stmt::LocalFunction(local_function) => {
+            let body = local_function.body().clone().with_block(Block::new());
+            Stmt::LocalFunction(Box::new(local_function.with_body(body)))
+        }
+        Stmt::NumericFor(numeric_for) => {
+            Stmt::NumericFor(Box::new(numeric_for.with_block(Block::new())))
+        }
+        Stmt::Repeat(repeat) => Stmt::Repeat(Box::new(repeat.with_block(Block::new()))),
+        Stmt::While(r#while) => Stmt::While(Box::new(r#while.with_block(Block::new()))),
+        other => other,
+    }
+}
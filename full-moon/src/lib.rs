@@ -5,12 +5,28 @@
 //! `full_moon` is a lossless parser for Lua 5.1
 //! Learn more by going to [the repository](https://github.com/Kampfkarren/full-moon)
 
+/// Analysis passes over an [`Ast`](ast::Ast), such as resolving the variable every identifier refers to.
+/// Nothing in here is run while parsing, only when called directly.
+pub mod analysis;
+
+/// Human readable dumps of an [`Ast`](ast::Ast), such as [Graphviz](https://graphviz.org/) digraphs,
+/// meant for debugging and bug reports.
+pub mod debug;
+
 /// Utilities for ASTs (Abstract Syntax Trees). Contains all nodes used by Full Moon (such as blocks).
 pub mod ast;
 
 /// Contains the `Node` trait, implemented on all nodes
 pub mod node;
 
+/// Transformations over an [`Ast`](ast::Ast) that preserve the formatting of the code, such as renaming variables.
+pub mod refactor;
+
+/// Transformations that lower an [`Ast`](ast::Ast) to a different dialect of Lua, such as removing Luau types.
+/// Only available when the "roblox" feature flag is enabled.
+#[cfg(feature = "roblox")]
+pub mod transform;
+
 /// Used for tokenizing, the process of converting the code to individual tokens.
 /// Useful for getting symbols and manually tokenizing without going using an AST.
 pub mod tokenizer;
@@ -18,23 +34,89 @@ pub mod tokenizer;
 /// Used to create visitors that recurse through [`Ast`](ast::Ast) nodes.
 pub mod visitors;
 
+mod diff;
 mod private;
+mod query;
 mod util;
 
+pub use diff::{diff, AstChange};
+pub use query::{query, query_mut, Query};
+
 use full_moon_derive::Owned;
 use std::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
 #[cfg(all(test, not(feature = "serde")))]
 compile_error!("Serde feature must be enabled for tests");
 
 /// An error type that consists of both [`AstError`](ast::AstError) and [`TokenizerError`](tokenizer::TokenizerError)
 /// Used by [`parse`]
+///
+/// When the "serde" feature flag is enabled, errors serialize as a diagnostic for tools such as
+/// CI integrations, which stays the same between versions. It has these fields:
+/// - `code`: the same as [`Error::code`], such as `"unexpected-token"`
+/// - `message`: what went wrong, such as ``"unexpected token `=`: expected name"``
+/// - `start` and `end`: the [`Position`](tokenizer::Position)s of the error, with the
+///   `line`, `character`, and `bytes` it's at, or `null` if it has none
+/// - `token`: the text that caused the error, such as `"="`, or `null` if there is none, such as at the end of the file
+///
+/// For example, to report the error as a [GitHub Actions annotation](https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions#setting-an-error-message):
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let error = full_moon::parse("local = 1").unwrap_err();
+/// let diagnostic = serde_json::to_value(&error)?;
+///
+/// let annotation = format!(
+///     "::error file={},line={},col={},endLine={},endColumn={},title={}::{}",
+///     "init.lua",
+///     diagnostic["start"]["line"],
+///     diagnostic["start"]["character"],
+///     diagnostic["end"]["line"],
+///     diagnostic["end"]["character"],
+///     diagnostic["code"].as_str().unwrap(),
+///     diagnostic["message"].as_str().unwrap(),
+/// );
+///
+/// assert_eq!(
+///     annotation,
+///     "::error file=init.lua,line=1,col=7,endLine=1,endColumn=8,title=unexpected-token::unexpected token `=`: expected name",
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`AstError`](ast::AstError) and [`TokenizerError`](tokenizer::TokenizerError) on their own serialize
+/// every field as is, the same as they always have, so that they can be deserialized back.
+/// Wrap them in an `Error` to serialize them as a diagnostic instead:
+///
+/// ```rust
+/// # use full_moon::{tokenizer::tokens, Error};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let error = tokens("local x = 'unclosed").unwrap_err();
+/// let diagnostic = serde_json::to_value(&Error::TokenizerError(error))?;
+/// assert_eq!(diagnostic["code"], "unclosed-string");
+/// assert_eq!(diagnostic["message"], "unclosed string");
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Clone, Debug, PartialEq, Owned)]
+#[non_exhaustive]
 pub enum Error<'a> {
     /// Triggered if there's an issue creating an AST, but tokenizing must have succeeded
     AstError(ast::AstError<'a>),
     /// Triggered if there's an issue when tokenizing, and an AST can't be made
     TokenizerError(tokenizer::TokenizerError),
+    /// Triggered when an [`Ast`](ast::Ast) that was serialized with a different [`AST_VERSION`](ast::AST_VERSION)
+    /// is deserialized as a [`VersionedAst`](ast::VersionedAst), such as one cached by an older version of full-moon
+    IncompatibleAstVersion {
+        /// The version the Ast was serialized with, or 0 if it was serialized before versions were added
+        found: u32,
+        /// The version that this version of full-moon serializes with
+        expected: u32,
+    },
 }
 
 impl<'a> fmt::Display for Error<'a> {
@@ -46,12 +128,77 @@ impl<'a> fmt::Display for Error<'a> {
             Error::TokenizerError(error) => {
                 write!(formatter, "error occurred while tokenizing: {}", error)
             }
+            Error::IncompatibleAstVersion { found, expected } => write!(
+                formatter,
+                "ast was serialized with version {}, but version {} is expected",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl<'a> Error<'a> {
+    /// A short name for the type of error that stays the same between versions, such as `unexpected-token`
+    /// or `unclosed-string`, so that errors can be told apart without reading their messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::AstError(error) => error.code(),
+            Error::TokenizerError(error) => error.code(),
+            Error::IncompatibleAstVersion { .. } => "incompatible-ast-version",
         }
     }
 }
 
 impl<'a> std::error::Error for Error<'a> {}
 
+#[cfg(feature = "serde")]
+impl Serialize for Error<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (message, range, token) = match self {
+            Error::AstError(ast::AstError::UnexpectedToken { token, additional }) => {
+                let text = match token.token_kind() {
+                    tokenizer::TokenKind::Eof => None,
+                    _ => Some(token.to_string()),
+                };
+
+                let unexpected = match &text {
+                    Some(text) => format!("unexpected token `{}`", text),
+                    None => "unexpected end of file".to_owned(),
+                };
+
+                let message = match additional {
+                    Some(additional) => format!("{}: {}", unexpected, additional),
+                    None => unexpected,
+                };
+
+                (
+                    message,
+                    Some((token.start_position(), token.end_position())),
+                    text,
+                )
+            }
+
+            Error::AstError(error) => (error.to_string(), None, None),
+
+            Error::TokenizerError(error) => (
+                error.error().message(),
+                Some((error.position(), error.position())),
+                error.error().text(),
+            ),
+
+            Error::IncompatibleAstVersion { .. } => (self.to_string(), None, None),
+        };
+
+        let mut state = serializer.serialize_struct("Error", 5)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &message)?;
+        state.serialize_field("start", &range.map(|(start, _)| start))?;
+        state.serialize_field("end", &range.map(|(_, end)| end))?;
+        state.serialize_field("token", &token)?;
+        state.end()
+    }
+}
+
 /// Creates an [`Ast`](ast::Ast) from Lua code
 ///
 /// # Errors
@@ -72,3 +219,29 @@ pub fn parse(code: &str) -> Result<ast::Ast, Error> {
 pub fn print(ast: &ast::Ast) -> String {
     format!("{}{}", ast.nodes(), ast.eof())
 }
+
+/// Writes the Lua code of an [`Ast`](ast::Ast) to an [`io::Write`](std::io::Write), such as a file,
+/// without building the whole code as a string first. The output is the same as [`print`].
+///
+/// Every token is written separately, so wrap writers that are slow to write to,
+/// such as files, in a [`BufWriter`](std::io::BufWriter).
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1 -- comment\n")?;
+/// let mut output = Vec::new();
+/// full_moon::write(&ast, &mut output)?;
+///
+/// assert_eq!(output, b"local x = 1 -- comment\n");
+/// # Ok(())
+/// # }
+/// ```
+pub fn write(ast: &ast::Ast, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+    write!(writer, "{}{}", ast.nodes(), ast.eof())
+}
+
+/// Writes the Lua code of an [`Ast`](ast::Ast) to a [`fmt::Write`](std::fmt::Write), such as a
+/// `String` that already has code in it. The output is the same as [`print`].
+pub fn fmt_to(ast: &ast::Ast, writer: &mut impl std::fmt::Write) -> std::fmt::Result {
+    write!(writer, "{}{}", ast.nodes(), ast.eof())
+}
@@ -1,10 +1,13 @@
 use crate::{
     ast::Ast,
     private,
-    tokenizer::{Position, Token, TokenReference},
+    tokenizer::{Position, Span, Token, TokenReference},
 };
 use std::fmt;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Used to represent nodes such as tokens or function definitions
 ///
 /// This trait is sealed and cannot be implemented for types outside of `full-moon`
@@ -23,11 +26,38 @@ pub trait Node<'ast>: private::Sealed {
     /// The token references that comprise a node
     fn tokens<'b>(&'b self) -> Tokens<'ast, 'b>;
 
+    /// The first token of a node, such as `local` in `local x = 1`, which is where its start position comes from.
+    /// For nodes that start with a [`ContainedSpan`](crate::ast::span::ContainedSpan), this is the opening delimiter.
+    /// None if the node is empty, such as an empty [`Punctuated`](crate::ast::punctuated::Punctuated)
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'ast>>;
+
+    /// The last token of a node, such as `end` in `function x() end`, which is where its end position comes from.
+    /// For nodes that end with a [`ContainedSpan`](crate::ast::span::ContainedSpan), this is the closing delimiter.
+    /// None if the node is empty, such as an empty [`Punctuated`](crate::ast::punctuated::Punctuated)
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'ast>>;
+
+    /// What kind of node this is, such as [`NodeKind::LocalAssignment`] or [`NodeKind::TypeInfoUnion`].
+    /// `Vec`s and tuples of nodes are [`NodeKind::Vec`] and [`NodeKind::Tuple`], and an [`Option`] is the kind
+    /// of the node inside of it, or [`NodeKind::None`]
+    fn kind(&self) -> NodeKind;
+
     /// The full range of a node, if it has both start and end positions
     fn range(&self) -> Option<(Position, Position)> {
         Some((self.start_position()?, self.end_position()?))
     }
 
+    /// The full [`Span`] of a node, if it has both start and end positions.
+    /// None if the end position comes before the start position, which can happen when tokens
+    /// are changed without [updating their positions](Ast::update_positions).
+    fn span(&self) -> Option<Span> {
+        let (start, end) = self.range()?;
+        if start <= end {
+            Some(Span::new(start, end))
+        } else {
+            None
+        }
+    }
+
     /// The tokens surrounding a node that are ignored and not accessible through the node's own accessors.
     /// Use this if you want to get surrounding comments or whitespace.
     /// Returns a tuple of the leading and trailing trivia.
@@ -49,6 +79,241 @@ pub trait Node<'ast>: private::Sealed {
     }
 }
 
+macro_rules! node_kinds {
+    ($($(#[$meta:meta])* $kind:ident,)+) => {
+        /// The kind of a node, as returned by [`Node::kind`]. Structs are named the same as the struct,
+        /// such as `LocalAssignment`, and every variant of an enum is named after both the enum and the variant,
+        /// such as `TypeInfoUnion` for [`TypeInfo::Union`](crate::ast::types::TypeInfo::Union)
+        /// and `StmtLocalAssignment` for [`Stmt::LocalAssignment`](crate::ast::Stmt::LocalAssignment).
+        /// The only exception is [`Var::Expression`](crate::ast::Var::Expression), which is `VarComplex`
+        /// so that it doesn't have the same name as the [`VarExpression`](crate::ast::VarExpression) struct.
+        ///
+        /// ```rust
+        /// # use full_moon::{ast::Stmt, node::{Node, NodeKind}};
+        /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+        /// let ast = full_moon::parse("local x = 1")?;
+        /// let stmt = ast.nodes().stmts().next().unwrap();
+        /// assert_eq!(stmt.kind(), NodeKind::StmtLocalAssignment);
+        ///
+        /// if let Stmt::LocalAssignment(local_assignment) = stmt {
+        ///     assert_eq!(local_assignment.kind().name(), "LocalAssignment");
+        /// }
+        /// # Ok(())
+        /// # }
+        /// ```
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+        #[non_exhaustive]
+        pub enum NodeKind {
+            $(
+                $(#[$meta])*
+                #[allow(missing_docs)]
+                $kind,
+            )+
+        }
+
+        impl NodeKind {
+            /// Every kind, in the order they are declared
+            pub const ALL: &'static [NodeKind] = &[
+                $(
+                    $(#[$meta])*
+                    NodeKind::$kind,
+                )+
+            ];
+
+            /// The name of the kind, the same as the name of the variant, such as `"LocalAssignment"`
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        $(#[$meta])*
+                        NodeKind::$kind => stringify!($kind),
+                    )+
+                }
+            }
+        }
+    };
+}
+
+node_kinds! {
+    Ast,
+    TokenReference,
+    ContainedSpan,
+    Punctuated,
+    PairEnd,
+    PairPunctuated,
+    Vec,
+    Tuple,
+    None,
+
+    Block,
+    LastStmtBreak,
+    #[cfg(feature = "roblox")]
+    LastStmtContinue,
+    LastStmtReturn,
+    Return,
+    FieldExpressionKey,
+    FieldNameKey,
+    FieldNoKey,
+    TableConstructor,
+    ExpressionBinaryOperator,
+    ExpressionParentheses,
+    ExpressionUnaryOperator,
+    ExpressionValue,
+    ValueFunction,
+    ValueFunctionCall,
+    ValueTableConstructor,
+    ValueNumber,
+    ValueParenthesesExpression,
+    ValueString,
+    ValueSymbol,
+    ValueVar,
+    StmtAssignment,
+    StmtDo,
+    StmtFunctionCall,
+    StmtFunctionDeclaration,
+    StmtGenericFor,
+    StmtIf,
+    StmtLocalAssignment,
+    StmtLocalFunction,
+    StmtNumericFor,
+    StmtRepeat,
+    StmtWhile,
+    #[cfg(feature = "roblox")]
+    StmtCompoundAssignment,
+    #[cfg(feature = "roblox")]
+    StmtExportedTypeDeclaration,
+    #[cfg(feature = "roblox")]
+    StmtTypeDeclaration,
+    #[cfg(feature = "lua52")]
+    StmtGoto,
+    #[cfg(feature = "lua52")]
+    StmtLabel,
+    PrefixExpression,
+    PrefixName,
+    IndexBrackets,
+    IndexDot,
+    FunctionArgsParentheses,
+    FunctionArgsString,
+    FunctionArgsTableConstructor,
+    NumericFor,
+    GenericFor,
+    If,
+    ElseIf,
+    While,
+    Repeat,
+    MethodCall,
+    CallAnonymousCall,
+    CallMethodCall,
+    FunctionBody,
+    ParameterEllipse,
+    ParameterName,
+    SuffixCall,
+    SuffixIndex,
+    VarExpression,
+    VarComplex,
+    VarName,
+    Assignment,
+    LocalFunction,
+    AnonymousFunction,
+    LocalAssignment,
+    Do,
+    FunctionCall,
+    FunctionName,
+    FunctionDeclaration,
+    BinOpAnd,
+    BinOpCaret,
+    BinOpGreaterThan,
+    BinOpGreaterThanEqual,
+    BinOpLessThan,
+    BinOpLessThanEqual,
+    BinOpMinus,
+    BinOpOr,
+    BinOpPercent,
+    BinOpPlus,
+    BinOpSlash,
+    BinOpStar,
+    BinOpTildeEqual,
+    BinOpTwoDots,
+    BinOpTwoEqual,
+    UnOpMinus,
+    UnOpNot,
+    UnOpHash,
+
+    #[cfg(feature = "roblox")]
+    TypeInfoArray,
+    #[cfg(feature = "roblox")]
+    TypeInfoBasic,
+    #[cfg(feature = "roblox")]
+    TypeInfoCallback,
+    #[cfg(feature = "roblox")]
+    TypeInfoGeneric,
+    #[cfg(feature = "roblox")]
+    TypeInfoIntersection,
+    #[cfg(feature = "roblox")]
+    TypeInfoModule,
+    #[cfg(feature = "roblox")]
+    TypeInfoOptional,
+    #[cfg(feature = "roblox")]
+    TypeInfoTable,
+    #[cfg(feature = "roblox")]
+    TypeInfoTypeof,
+    #[cfg(feature = "roblox")]
+    TypeInfoTuple,
+    #[cfg(feature = "roblox")]
+    TypeInfoUnion,
+    #[cfg(feature = "roblox")]
+    TypeInfoVariadic,
+    #[cfg(feature = "roblox")]
+    IndexedTypeInfoBasic,
+    #[cfg(feature = "roblox")]
+    IndexedTypeInfoGeneric,
+    #[cfg(feature = "roblox")]
+    TypeField,
+    #[cfg(feature = "roblox")]
+    TypeFieldKeyName,
+    #[cfg(feature = "roblox")]
+    TypeFieldKeyIndexSignature,
+    #[cfg(feature = "roblox")]
+    TypeAssertion,
+    #[cfg(feature = "roblox")]
+    Attribute,
+    #[cfg(feature = "roblox")]
+    TypeDeclaration,
+    #[cfg(feature = "roblox")]
+    GenericDeclaration,
+    #[cfg(feature = "roblox")]
+    TypeSpecifier,
+    #[cfg(feature = "roblox")]
+    ExportedTypeDeclaration,
+    #[cfg(feature = "roblox")]
+    CompoundAssignment,
+    #[cfg(feature = "roblox")]
+    CompoundOpPlusEqual,
+    #[cfg(feature = "roblox")]
+    CompoundOpMinusEqual,
+    #[cfg(feature = "roblox")]
+    CompoundOpStarEqual,
+    #[cfg(feature = "roblox")]
+    CompoundOpSlashEqual,
+    #[cfg(feature = "roblox")]
+    CompoundOpPercentEqual,
+    #[cfg(feature = "roblox")]
+    CompoundOpCaretEqual,
+    #[cfg(feature = "roblox")]
+    CompoundOpTwoDotsEqual,
+
+    #[cfg(feature = "lua52")]
+    Goto,
+    #[cfg(feature = "lua52")]
+    Label,
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.name())
+    }
+}
+
 pub(crate) enum TokenItem<'ast, 'b> {
     MoreTokens(&'b dyn Node<'ast>),
     TokenReference(&'b TokenReference<'ast>),
@@ -123,6 +388,18 @@ impl<'a> Node<'a> for Ast<'a> {
     fn tokens<'b>(&'b self) -> Tokens<'a, 'b> {
         self.nodes().tokens()
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.nodes().first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.nodes().last_token()
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Ast
+    }
 }
 
 impl<'a, T: Node<'a>> Node<'a> for Box<T> {
@@ -141,6 +418,18 @@ impl<'a, T: Node<'a>> Node<'a> for Box<T> {
     fn tokens<'b>(&'b self) -> Tokens<'a, 'b> {
         (**self).tokens()
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        (**self).first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        (**self).last_token()
+    }
+
+    fn kind(&self) -> NodeKind {
+        (**self).kind()
+    }
 }
 
 impl<'a, T: Node<'a>> Node<'a> for &T {
@@ -159,6 +448,18 @@ impl<'a, T: Node<'a>> Node<'a> for &T {
     fn tokens<'b>(&'b self) -> Tokens<'a, 'b> {
         (**self).tokens()
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        (**self).first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        (**self).last_token()
+    }
+
+    fn kind(&self) -> NodeKind {
+        (**self).kind()
+    }
 }
 
 impl<'a, T: Node<'a>> Node<'a> for &mut T {
@@ -177,6 +478,18 @@ impl<'a, T: Node<'a>> Node<'a> for &mut T {
     fn tokens<'b>(&'b self) -> Tokens<'a, 'b> {
         (**self).tokens()
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        (**self).first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        (**self).last_token()
+    }
+
+    fn kind(&self) -> NodeKind {
+        (**self).kind()
+    }
 }
 
 impl<'a> Node<'a> for TokenReference<'a> {
@@ -197,6 +510,18 @@ impl<'a> Node<'a> for TokenReference<'a> {
             items: vec![TokenItem::TokenReference(&self)],
         }
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        Some(self)
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        Some(self)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::TokenReference
+    }
 }
 
 impl<'a, T: Node<'a>> Node<'a> for Option<T> {
@@ -222,6 +547,18 @@ impl<'a, T: Node<'a>> Node<'a> for Option<T> {
             None => Tokens::default(),
         }
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.as_ref()?.first_token()
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.as_ref()?.last_token()
+    }
+
+    fn kind(&self) -> NodeKind {
+        self.as_ref().map_or(NodeKind::None, Node::kind)
+    }
 }
 
 impl<'a, T: Node<'a>> Node<'a> for Vec<T> {
@@ -250,6 +587,18 @@ impl<'a, T: Node<'a>> Node<'a> for Vec<T> {
                 .collect(),
         }
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.iter().find_map(Node::first_token)
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.iter().rev().find_map(Node::last_token)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Vec
+    }
 }
 
 impl<'a, A: Node<'a>, B: Node<'a>> Node<'a> for (A, B) {
@@ -281,4 +630,16 @@ impl<'a, A: Node<'a>, B: Node<'a>> Node<'a> for (A, B) {
 
         Tokens { items }
     }
+
+    fn first_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.0.first_token().or_else(|| self.1.first_token())
+    }
+
+    fn last_token<'b>(&'b self) -> Option<&'b TokenReference<'a>> {
+        self.1.last_token().or_else(|| self.0.last_token())
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Tuple
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     ast::{Ast, AstError},
-    tokenizer::{Token, TokenReference, TokenType, TokenizerError},
+    tokenizer::{Origin, Token, TokenReference, TokenType, TokenizerError},
     Error,
 };
 use std::borrow::Cow;
@@ -13,10 +13,13 @@ impl<T: ToOwned> Sealed for Cow<'_, T> {}
 impl Sealed for Ast<'_> {}
 impl Sealed for AstError<'_> {}
 impl Sealed for Error<'_> {}
+impl Sealed for Origin {}
 impl Sealed for Token<'_> {}
 impl Sealed for TokenizerError {}
 impl Sealed for TokenReference<'_> {}
 impl Sealed for TokenType<'_> {}
+impl Sealed for bool {}
+impl Sealed for u32 {}
 impl<T> Sealed for Box<T> {}
 impl<T> Sealed for Option<T> {}
 impl<T> Sealed for Vec<T> {}
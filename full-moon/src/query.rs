@@ -0,0 +1,178 @@
+use crate::{
+    ast::*,
+    private::Sealed,
+    tokenizer::TokenReference,
+    visitors::{Visit, VisitorMut, VisitorRef},
+};
+
+#[cfg(feature = "lua52")]
+use crate::ast::lua52::*;
+#[cfg(feature = "roblox")]
+use crate::ast::types::*;
+
+/// A node that can be searched for with [`query`](crate::query) and [`query_mut`](crate::query_mut).
+/// This is implemented for every node that has a method in [`Visitor`](crate::visitors::Visitor),
+/// except for [`ContainedSpan`](crate::ast::span::ContainedSpan), as the visitors pass the tokens of spans one by one.
+pub trait Query<'ast>: Sealed + Sized {
+    #[doc(hidden)]
+    fn collect<'b>(ast: &'b Ast<'ast>) -> Vec<&'b Self>;
+
+    #[doc(hidden)]
+    fn edit(ast: Ast<'ast>, edit: &mut dyn FnMut(&mut Self)) -> Ast<'ast>;
+}
+
+macro_rules! impl_query {
+    ($($visit_name:ident => $ast_type:ident,)+) => {
+        $(
+            impl<'ast> Query<'ast> for $ast_type<'ast> {
+                fn collect<'b>(ast: &'b Ast<'ast>) -> Vec<&'b Self> {
+                    struct Collector<'b, 'ast>(Vec<&'b $ast_type<'ast>>);
+
+                    impl<'b, 'ast> VisitorRef<'b, 'ast> for Collector<'b, 'ast> {
+                        fn $visit_name(&mut self, node: &'b $ast_type<'ast>) {
+                            self.0.push(node);
+                        }
+                    }
+
+                    let mut collector = Collector(Vec::new());
+                    ast.nodes().visit(&mut collector);
+                    ast.eof().visit(&mut collector);
+                    collector.0
+                }
+
+                fn edit(ast: Ast<'ast>, edit: &mut dyn FnMut(&mut Self)) -> Ast<'ast> {
+                    struct Editor<'a, 'ast>(&'a mut dyn FnMut(&mut $ast_type<'ast>));
+
+                    impl<'ast> VisitorMut<'ast> for Editor<'_, 'ast> {
+                        fn $visit_name(&mut self, mut node: $ast_type<'ast>) -> $ast_type<'ast> {
+                            (self.0)(&mut node);
+                            node
+                        }
+                    }
+
+                    Editor(edit).visit_ast(ast)
+                }
+            }
+        )+
+    };
+}
+
+impl_query! {
+    visit_anonymous_function => AnonymousFunction,
+    visit_assignment => Assignment,
+    visit_block => Block,
+    visit_call => Call,
+    visit_do => Do,
+    visit_else_if => ElseIf,
+    visit_expression => Expression,
+    visit_field => Field,
+    visit_function_args => FunctionArgs,
+    visit_function_body => FunctionBody,
+    visit_function_call => FunctionCall,
+    visit_function_declaration => FunctionDeclaration,
+    visit_function_name => FunctionName,
+    visit_generic_for => GenericFor,
+    visit_if => If,
+    visit_index => Index,
+    visit_local_assignment => LocalAssignment,
+    visit_local_function => LocalFunction,
+    visit_last_stmt => LastStmt,
+    visit_method_call => MethodCall,
+    visit_numeric_for => NumericFor,
+    visit_parameter => Parameter,
+    visit_prefix => Prefix,
+    visit_return => Return,
+    visit_repeat => Repeat,
+    visit_stmt => Stmt,
+    visit_suffix => Suffix,
+    visit_table_constructor => TableConstructor,
+    visit_token_reference => TokenReference,
+    visit_un_op => UnOp,
+    visit_value => Value,
+    visit_var => Var,
+    visit_var_expression => VarExpression,
+    visit_while => While,
+}
+
+#[cfg(feature = "roblox")]
+impl_query! {
+    visit_attribute => Attribute,
+    visit_compound_assignment => CompoundAssignment,
+    visit_compound_op => CompoundOp,
+    visit_exported_type_declaration => ExportedTypeDeclaration,
+    visit_generic_declaration => GenericDeclaration,
+    visit_indexed_type_info => IndexedTypeInfo,
+    visit_type_assertion => TypeAssertion,
+    visit_type_declaration => TypeDeclaration,
+    visit_type_field => TypeField,
+    visit_type_field_key => TypeFieldKey,
+    visit_type_info => TypeInfo,
+    visit_type_specifier => TypeSpecifier,
+}
+
+#[cfg(feature = "lua52")]
+impl_query! {
+    visit_goto => Goto,
+    visit_label => Label,
+}
+
+/// Returns every node of the given type in the [`Ast`], in the order they appear,
+/// including nodes nested inside of other nodes of the same type.
+///
+/// ```rust
+/// # use full_moon::{ast::{FunctionCall, Stmt}, query, tokenizer::{TokenReference, TokenType}};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse(r#"print("a", { key = f("b") })"#)?;
+///
+/// let calls = query::<FunctionCall>(&ast).count();
+/// assert_eq!(calls, 2);
+///
+/// let strings = query::<TokenReference>(&ast)
+///     .filter(|token| matches!(token.token_type(), TokenType::StringLiteral { .. }))
+///     .map(ToString::to_string)
+///     .collect::<Vec<_>>();
+/// assert_eq!(strings, vec![r#""a""#, r#""b""#]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Type positions are searched as well when the "roblox" feature flag is enabled:
+///
+/// ```rust
+/// # #[cfg(feature = "roblox")]
+/// # {
+/// # use full_moon::{ast::types::TypeInfo, query};
+/// let ast = full_moon::parse("local x: typeof(y) = (z :: typeof(w))").unwrap();
+/// let typeofs = query::<TypeInfo>(&ast)
+///     .filter(|type_info| matches!(type_info, TypeInfo::Typeof { .. }))
+///     .count();
+/// assert_eq!(typeofs, 2);
+/// # }
+/// ```
+pub fn query<'ast, 'b, T>(ast: &'b Ast<'ast>) -> impl Iterator<Item = &'b T>
+where
+    T: Query<'ast> + 'b,
+{
+    T::collect(ast).into_iter()
+}
+
+/// Calls the given function on every node of the given type in the [`Ast`], in the order they appear,
+/// allowing them to be edited in place.
+///
+/// ```rust
+/// # use full_moon::{ast::Value, query_mut, tokenizer::{Token, TokenType}};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1\nlocal y = { 2, 3 }")?;
+/// let ast = query_mut(ast, |value: &mut Value| {
+///     if let Value::Number(number) = value {
+///         *number = number.with_token(Token::new(TokenType::Number { text: "0".into() }));
+///     }
+/// });
+///
+/// assert_eq!(full_moon::print(&ast), "local x = 0\nlocal y = { 0, 0 }");
+/// # Ok(())
+/// # }
+/// ```
+pub fn query_mut<'ast, T: Query<'ast>>(ast: Ast<'ast>, mut edit: impl FnMut(&mut T)) -> Ast<'ast> {
+    T::edit(ast, &mut edit)
+}
@@ -0,0 +1,3 @@
+mod rename;
+
+pub use rename::*;
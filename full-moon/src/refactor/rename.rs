@@ -0,0 +1,196 @@
+use crate::{
+    analysis::{scopes, Resolution, ScopeTree, VariableKind},
+    ast::Ast,
+    node::Node,
+    tokenizer::{self, Token, TokenReference, TokenType},
+    visitors::{Visitor, VisitorMut},
+};
+use std::{collections::HashMap, collections::HashSet, fmt};
+
+/// An error that occurs when a variable cannot be renamed, as returned by [`rename`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenameError {
+    /// The new name is not a valid identifier, such as `1x` or `end`
+    InvalidName,
+    /// The target is not a local variable, such as the `y` in `x.y` or the implicit `self` of a method
+    NotAVariable,
+    /// The target is a global variable
+    Global,
+    /// The new name is already used by another variable that would be shadowed by,
+    /// or would shadow, the renamed variable
+    Capture,
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenameError::InvalidName => write!(formatter, "new name is not a valid identifier"),
+            RenameError::NotAVariable => write!(formatter, "target is not a local variable"),
+            RenameError::Global => write!(formatter, "target is a global variable"),
+            RenameError::Capture => write!(
+                formatter,
+                "new name would change what another identifier refers to"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+// Every token of an Ast in the order visitors see them, which is the order tokens are counted in
+// by IdentifierRenamer
+#[derive(Default)]
+struct TokenOrder<'a>(Vec<*const TokenReference<'a>>);
+
+impl<'a> Visitor<'a> for TokenOrder<'a> {
+    fn visit_token_reference(&mut self, token: &TokenReference<'a>) {
+        self.0.push(token);
+    }
+}
+
+fn token_indices<'a>(ast: &Ast<'a>) -> HashMap<*const TokenReference<'a>, usize> {
+    let mut order = TokenOrder::default();
+    order.visit_ast(ast);
+    order
+        .0
+        .into_iter()
+        .enumerate()
+        .map(|(index, token)| (token, index))
+        .collect()
+}
+
+// Renames the tokens at the given indices. Tokens are picked by their index rather than their position,
+// as tokens that were built or spliced in can share their position with others.
+struct IdentifierRenamer<'a> {
+    indices: HashSet<usize>,
+    index: usize,
+    new_name: &'a str,
+}
+
+impl<'ast> VisitorMut<'ast> for IdentifierRenamer<'_> {
+    fn visit_token_reference(&mut self, mut token: TokenReference<'ast>) -> TokenReference<'ast> {
+        let index = self.index;
+        self.index += 1;
+
+        if !self.indices.contains(&index) {
+            return token;
+        }
+
+        token.token = Token {
+            token_type: TokenType::Identifier {
+                identifier: self.new_name.to_owned().into(),
+            },
+            ..token.token
+        };
+
+        token
+    }
+}
+
+// For every identifier used as a variable, the index of the token it is used at and the index
+// of the token its variable is declared at, if it's not a global
+fn resolutions(ast: &Ast, tree: &ScopeTree) -> Vec<(usize, Option<usize>)> {
+    let indices = token_indices(ast);
+
+    ast.tokens()
+        .filter_map(|token| {
+            let variable = tree.resolve(token)?.variable();
+            Some((
+                indices[&(token as *const TokenReference)],
+                variable.map(|variable| {
+                    indices[&(tree.variable(variable).token() as *const TokenReference)]
+                }),
+            ))
+        })
+        .collect()
+}
+
+/// Renames the variable that the target token either declares or refers to, as well as every
+/// other reference to it. Only the text of the identifiers is changed, trivia is kept as is.
+/// The target is found by its position, so it can be a clone of a token from the [`Ast`]. Tokens that
+/// were built or spliced in have no position of their own, so if the target is one of them, call
+/// [`Ast::update_positions`] first and take the target from the updated [`Ast`].
+///
+/// # Errors
+///
+/// Returns an error if the new name is not a valid identifier, if the target is not a local
+/// variable, or if renaming it would change what any identifier refers to.
+///
+/// ```rust
+/// # use full_moon::{ast::Stmt, refactor::rename};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1 -- comment\nprint(x)")?;
+/// let target = match ast.nodes().stmts().next() {
+///     Some(Stmt::LocalAssignment(local_assignment)) => {
+///         local_assignment.names().iter().next().unwrap().clone()
+///     }
+///     _ => unreachable!(),
+/// };
+///
+/// let ast = rename(ast, &target, "count")?;
+/// assert_eq!(full_moon::print(&ast), "local count = 1 -- comment\nprint(count)");
+/// # Ok(())
+/// # }
+/// ```
+pub fn rename<'a>(
+    ast: Ast<'a>,
+    target: &TokenReference,
+    new_name: &str,
+) -> Result<Ast<'a>, RenameError> {
+    match tokenizer::tokens(new_name).as_deref() {
+        Ok([name, eof])
+            if name.token_type()
+                == &TokenType::Identifier {
+                    identifier: new_name.into(),
+                }
+                && eof.token_type() == &TokenType::Eof => {}
+        _ => return Err(RenameError::InvalidName),
+    }
+
+    let (indices, before) = {
+        let tree = scopes(&ast);
+        let token = ast
+            .tokens()
+            .find(|token| {
+                token.start_position() == target.start_position()
+                    && token.token_type() == target.token_type()
+            })
+            .ok_or(RenameError::NotAVariable)?;
+
+        let variable = match tree.declaration(token) {
+            Some(variable) => variable,
+            None => match tree.resolve(token) {
+                Some(Resolution::Global) => return Err(RenameError::Global),
+                Some(resolution) => resolution.variable().ok_or(RenameError::NotAVariable)?,
+                None => return Err(RenameError::NotAVariable),
+            },
+        };
+
+        let variable = tree.variable(variable);
+        if variable.kind() == VariableKind::ImplicitSelf {
+            return Err(RenameError::NotAVariable);
+        }
+
+        let token_indices = token_indices(&ast);
+        let indices = std::iter::once(variable.token())
+            .chain(variable.references().iter().copied())
+            .map(|token| token_indices[&(token as *const TokenReference)])
+            .collect::<HashSet<_>>();
+
+        (indices, resolutions(&ast, &tree))
+    };
+
+    let ast = IdentifierRenamer {
+        indices,
+        index: 0,
+        new_name,
+    }
+    .visit_ast(ast)
+    .update_positions();
+
+    if resolutions(&ast, &scopes(&ast)) != before {
+        return Err(RenameError::Capture);
+    }
+
+    Ok(ast)
+}
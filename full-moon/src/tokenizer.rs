@@ -1,8 +1,8 @@
-use crate::visitors::{Visit, VisitMut, Visitor, VisitorMut};
+use crate::visitors::{Visit, VisitMut, VisitorMut, VisitorRef};
 
 use full_moon_derive::{symbols, Owned};
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 use std::{borrow::Cow, cmp::Ordering, fmt, str::FromStr};
 
 symbols!(
@@ -41,6 +41,8 @@ symbols!(
     // TODO: This only is valid in Roblox
     Ampersand => "&",
     // TODO: This only is valid in Roblox
+    At => "@",
+    // TODO: This only is valid in Roblox
     ThinArrow => "->",
     // TODO: This only is valid in Roblox and Lua 5.2
     TwoColons => "::",
@@ -79,6 +81,7 @@ symbols!(
 /// The possible errors that can happen while tokenizing.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[non_exhaustive]
 pub enum TokenizerErrorType {
     /// An unclosed multi-line comment was found
     UnclosedComment,
@@ -91,12 +94,38 @@ pub enum TokenizerErrorType {
     /// Symbol passed is not valid
     /// Returned from [`TokenReference::symbol`]
     InvalidSymbol(String),
+    /// Identifier passed is not valid, such as a keyword
+    /// Returned from [`TokenReference::set_identifier`]
+    InvalidIdentifier(String),
+    /// Text passed as a number is not valid, as opposed to a
+    /// [`MalformedNumber`](TokenizerErrorType::MalformedNumber) found while tokenizing
+    /// Returned from [`TokenReference::set_number`]
+    InvalidNumberText(String),
+    /// A number is missing its digits, such as `0x` or `1e+`
+    MalformedNumber {
+        /// The text of the number, such as `1e+`
+        number: String,
+        /// What the number is missing, such as "expected digits after the exponent"
+        reason: String,
+    },
+    /// An escape sequence in a string is not valid in the Lua version being parsed, such as `\q`
+    /// or `\400`. The position of the error is the position of the backslash.
+    InvalidEscape(String),
+    /// The token being changed is not of the kind that was expected, such as trying to
+    /// change the name of a symbol with [`TokenReference::set_identifier`]
+    UnexpectedTokenKind {
+        /// The kind of token that can be changed
+        expected: TokenKind,
+        /// The kind of the token that was found instead
+        found: TokenKind,
+    },
+    /// A token that is not trivia, such as a comment or whitespace, was added as trivia
+    /// Returned from methods such as [`TokenReference::push_leading_trivia`]
+    InvalidTrivia(TokenKind),
 }
 
 /// The type of tokens in parsed code
 #[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[cfg_attr(feature = "serde", serde(tag = "type"))]
 #[non_exhaustive]
 pub enum TokenType<'a> {
     /// End of file, should always be the very last token
@@ -104,7 +133,6 @@ pub enum TokenType<'a> {
 
     /// An identifier, such as `foo`
     Identifier {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The identifier itself
         identifier: Cow<'a, str>,
     },
@@ -114,38 +142,32 @@ pub enum TokenType<'a> {
         /// Number of equals signs, if any, for the multi line comment
         /// For example, `--[=[` would have a `blocks` value of `1`
         blocks: usize,
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The comment itself, ignoring opening and closing tags
         comment: Cow<'a, str>,
     },
 
     /// A literal number, such as `3.3`
     Number {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The text representing the number, includes details such as `0x`
         text: Cow<'a, str>,
     },
 
     /// A shebang line
     Shebang {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The shebang line itself
         line: Cow<'a, str>,
     },
 
     /// A single line comment, such as `-- comment`
     SingleLineComment {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The comment, ignoring initial `--`
         comment: Cow<'a, str>,
     },
 
     /// A literal string, such as "Hello, world"
     StringLiteral {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// The literal itself, ignoring quotation marks
         literal: Cow<'a, str>,
-        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
         /// Number of equals signs used for a multi line string, if it is one
         /// For example, `[=[string]=]` would have a `multi_line` value of Some(1)
         /// `[[string]]` would have a `multi_line` value of Some(0)
@@ -163,7 +185,6 @@ pub enum TokenType<'a> {
 
     /// Whitespace, such as tabs or new lines
     Whitespace {
-        #[cfg_attr(feature = "serde", serde(borrow))]
         /// Characters consisting of the whitespace
         characters: Cow<'a, str>,
     },
@@ -182,6 +203,70 @@ impl<'a> TokenType<'a> {
         )
     }
 
+    /// Returns the symbol if the token is one, such as `local` or `+`
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Symbol, TokenType};
+    /// assert_eq!(TokenType::Symbol { symbol: Symbol::Plus }.as_symbol(), Some(Symbol::Plus));
+    /// assert_eq!(TokenType::spaces(1).as_symbol(), None);
+    /// ```
+    pub fn as_symbol(&self) -> Option<Symbol> {
+        match self {
+            TokenType::Symbol { symbol } => Some(*symbol),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the identifier if the token is one
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::TokenType;
+    /// let identifier = TokenType::Identifier { identifier: "foo".into() };
+    /// assert_eq!(identifier.as_identifier(), Some("foo"));
+    /// ```
+    pub fn as_identifier(&self) -> Option<&str> {
+        match self {
+            TokenType::Identifier { identifier } => Some(identifier),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the number if the token is one, such as `0xFF`
+    pub fn as_number(&self) -> Option<&str> {
+        match self {
+            TokenType::Number { text } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the literal, the type of quotes, and the number of equals signs of multi-line strings
+    /// if the token is a string. The literal is the text inside of the quotes, with its escapes as written.
+    pub fn as_string_literal(&self) -> Option<(&str, StringLiteralQuoteType, Option<usize>)> {
+        match self {
+            TokenType::StringLiteral {
+                literal,
+                multi_line,
+                quote_type,
+            } => Some((literal, *quote_type, *multi_line)),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the comment if the token is a single or multi-line comment,
+    /// without the `--` and brackets
+    pub fn as_comment(&self) -> Option<&str> {
+        match self {
+            TokenType::SingleLineComment { comment }
+            | TokenType::MultiLineComment { comment, .. } => Some(comment),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the token is a keyword symbol, such as `local`, rather than an operator
+    pub fn is_keyword(&self) -> bool {
+        matches!(self, TokenType::Symbol { symbol } if symbol.is_keyword())
+    }
+
     /// Returns the kind of the token type.
     ///
     /// ```rust
@@ -226,6 +311,7 @@ impl<'a> TokenType<'a> {
 
 /// The kind of token. Contains no additional data.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[non_exhaustive]
 pub enum TokenKind {
     /// End of file, should always be the very last token
@@ -254,7 +340,6 @@ pub enum TokenKind {
 pub struct Token<'a> {
     pub(crate) start_position: Position,
     pub(crate) end_position: Position,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) token_type: TokenType<'a>,
 }
 
@@ -289,6 +374,42 @@ impl<'a> Token<'a> {
     pub fn token_kind(&self) -> TokenKind {
         self.token_type().kind()
     }
+
+    /// Returns whether the token can be practically ignored, such as comments and whitespace.
+    /// See [`TokenType::is_trivia`].
+    pub fn is_trivia(&self) -> bool {
+        self.token_type.is_trivia()
+    }
+
+    /// Returns whether the token is a keyword symbol. See [`TokenType::is_keyword`].
+    pub fn is_keyword(&self) -> bool {
+        self.token_type.is_keyword()
+    }
+
+    /// Returns the symbol if the token is one. See [`TokenType::as_symbol`].
+    pub fn as_symbol(&self) -> Option<Symbol> {
+        self.token_type.as_symbol()
+    }
+
+    /// Returns the name of the identifier if the token is one. See [`TokenType::as_identifier`].
+    pub fn as_identifier(&self) -> Option<&str> {
+        self.token_type.as_identifier()
+    }
+
+    /// Returns the text of the number if the token is one. See [`TokenType::as_number`].
+    pub fn as_number(&self) -> Option<&str> {
+        self.token_type.as_number()
+    }
+
+    /// Returns the details of the string if the token is one. See [`TokenType::as_string_literal`].
+    pub fn as_string_literal(&self) -> Option<(&str, StringLiteralQuoteType, Option<usize>)> {
+        self.token_type.as_string_literal()
+    }
+
+    /// Returns the text of the comment if the token is one. See [`TokenType::as_comment`].
+    pub fn as_comment(&self) -> Option<&str> {
+        self.token_type.as_comment()
+    }
 }
 
 impl<'a> fmt::Display for Token<'a> {
@@ -296,32 +417,51 @@ impl<'a> fmt::Display for Token<'a> {
         use self::TokenType::*;
 
         match &*self.token_type() {
-            Eof => "".to_string(),
-            Number { text } => text.to_string(),
-            Identifier { identifier } => identifier.to_string(),
+            Eof => Ok(()),
+            Number { text } => formatter.write_str(text),
+            Identifier { identifier } => formatter.write_str(identifier),
             MultiLineComment { blocks, comment } => {
-                format!("--[{0}[{1}]{0}]", "=".repeat(*blocks), comment)
+                formatter.write_str("--")?;
+                write_long_brackets(formatter, *blocks, comment)
             }
-            Shebang { line } => line.to_string(),
-            SingleLineComment { comment } => format!("--{}", comment),
+            Shebang { line } => formatter.write_str(line),
+            SingleLineComment { comment } => write!(formatter, "--{}", comment),
             StringLiteral {
                 literal,
                 multi_line,
                 quote_type,
             } => {
                 if let Some(blocks) = multi_line {
-                    format!("[{0}[{1}]{0}]", "=".repeat(*blocks), literal.to_string())
+                    write_long_brackets(formatter, *blocks, literal)
                 } else {
-                    format!("{0}{1}{0}", quote_type.to_string(), literal.to_string())
+                    write!(formatter, "{0}{1}{0}", quote_type, literal)
                 }
             }
-            Symbol { symbol } => symbol.to_string(),
-            Whitespace { characters } => characters.to_string(),
+            Symbol { symbol } => formatter.write_str(symbol.as_str()),
+            Whitespace { characters } => formatter.write_str(characters),
         }
-        .fmt(formatter)
     }
 }
 
+// Writes `[==[contents]==]`, with as many equals signs as there are blocks
+fn write_long_brackets(
+    formatter: &mut fmt::Formatter,
+    blocks: usize,
+    contents: &str,
+) -> fmt::Result {
+    formatter.write_str("[")?;
+    for _ in 0..blocks {
+        formatter.write_str("=")?;
+    }
+
+    write!(formatter, "[{}]", contents)?;
+    for _ in 0..blocks {
+        formatter.write_str("=")?;
+    }
+
+    formatter.write_str("]")
+}
+
 impl<'a> PartialEq<Self> for Token<'a> {
     fn eq(&self, rhs: &Self) -> bool {
         self.start_position() == rhs.start_position()
@@ -345,7 +485,10 @@ impl<'a> PartialOrd for Token<'a> {
 }
 
 impl<'ast> Visit<'ast> for Token<'ast> {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         visitor.visit_token(self);
 
         match self.token_kind() {
@@ -380,21 +523,31 @@ impl<'ast> VisitMut<'ast> for Token<'ast> {
     }
 }
 
+/// Where a [`TokenReference`] came from, returned by [`TokenReference::origin`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Origin {
+    /// Tokens read from code by the parser, whose positions point into that code
+    #[default]
+    Source,
+    /// Tokens created by constructors and builders, such as [`TokenReference::new`],
+    /// whose positions are the default until [updated](crate::ast::Ast::update_positions)
+    Synthesized,
+}
+
 /// A reference to a token used by Ast's.
-/// Dereferences to a [`Token`]
+/// Dereferences to a [`Token`], so methods such as [`Token::as_identifier`] can be used on it directly
 #[derive(Clone, Debug, Owned)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TokenReference<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) leading_trivia: Vec<Token<'a>>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) token: Token<'a>,
-    #[cfg_attr(feature = "serde", serde(borrow))]
     pub(crate) trailing_trivia: Vec<Token<'a>>,
+    pub(crate) origin: Origin,
 }
 
 impl<'a> TokenReference<'a> {
-    /// Creates a TokenReference from leading/trailing trivia as well as the leading token
+    /// Creates a TokenReference from leading/trailing trivia as well as the leading token.
+    /// Its [`origin`](TokenReference::origin) is [`Origin::Synthesized`].
     pub fn new(
         leading_trivia: Vec<Token<'a>>,
         token: Token<'a>,
@@ -404,6 +557,7 @@ impl<'a> TokenReference<'a> {
             leading_trivia,
             token,
             trailing_trivia,
+            origin: Origin::Synthesized,
         }
     }
 
@@ -464,9 +618,52 @@ impl<'a> TokenReference<'a> {
             trailing_trivia: vec![Token::new(TokenType::Whitespace {
                 characters: Cow::Owned(trailing_trivia),
             })],
+            origin: Origin::Synthesized,
         })
     }
 
+    /// Creates a TokenReference of the symbol with no trivia. Unlike [`symbol`](TokenReference::symbol),
+    /// this can't fail, and can be used in constants.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Symbol, TokenReference};
+    /// const LOCAL: TokenReference<'static> = TokenReference::from_symbol(Symbol::Local);
+    /// assert_eq!(LOCAL.to_string(), "local");
+    /// ```
+    pub const fn from_symbol(symbol: Symbol) -> Self {
+        const START: Position = Position {
+            bytes: 0,
+            line: 0,
+            character: 0,
+            byte_column: 0,
+        };
+
+        Self {
+            leading_trivia: Vec::new(),
+            token: Token {
+                start_position: START,
+                end_position: START,
+                token_type: TokenType::Symbol { symbol },
+            },
+            trailing_trivia: Vec::new(),
+            origin: Origin::Synthesized,
+        }
+    }
+
+    // The same as `symbol`, for the constructors of nodes, which can't fail
+    pub(crate) fn spaced_symbol(leading: &str, symbol: Symbol, trailing: &str) -> Self {
+        Self {
+            leading_trivia: vec![Token::new(TokenType::Whitespace {
+                characters: Cow::Owned(leading.to_owned()),
+            })],
+            token: Token::new(TokenType::Symbol { symbol }),
+            trailing_trivia: vec![Token::new(TokenType::Whitespace {
+                characters: Cow::Owned(trailing.to_owned()),
+            })],
+            origin: Origin::Synthesized,
+        }
+    }
+
     /// Returns the inner token.
     pub fn token(&self) -> &Token<'a> {
         &self.token
@@ -482,13 +679,365 @@ impl<'a> TokenReference<'a> {
         self.trailing_trivia.iter()
     }
 
+    /// Returns whether the token was read by the parser or created by a constructor or builder.
+    /// Tokens keep their origin when they are changed in place, such as with
+    /// [`set_identifier`](TokenReference::set_identifier).
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Returns whether the token was read by the parser, meaning its positions point into
+    /// the parsed code, at least until the tree is changed and its positions are updated.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Symbol, TokenReference};
+    /// let ast = full_moon::parse("local x = 1").unwrap();
+    /// assert!(ast.eof().is_from_source());
+    /// assert!(!TokenReference::from_symbol(Symbol::Local).is_from_source());
+    /// ```
+    pub fn is_from_source(&self) -> bool {
+        self.origin == Origin::Source
+    }
+
     /// Creates a clone of the current TokenReference with the new inner token, preserving trivia.
+    /// The clone is [`Origin::Synthesized`], since the new token wasn't read from the code.
     pub fn with_token(&self, token: Token<'a>) -> Self {
         Self {
             token,
             leading_trivia: self.leading_trivia.clone(),
             trailing_trivia: self.trailing_trivia.clone(),
+            origin: Origin::Synthesized,
+        }
+    }
+
+    /// Changes the name of an identifier, keeping its trivia and positions.
+    /// Use [`Ast::update_positions`](crate::ast::Ast::update_positions) afterwards if you need
+    /// the positions of tokens.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not an identifier, or if the name is not a valid
+    /// identifier, such as a keyword.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Token, TokenReference, TokenType, TokenizerErrorType};
+    /// # fn main() -> Result<(), TokenizerErrorType> {
+    /// let mut name = TokenReference::new(vec![], Token::new(TokenType::Identifier {
+    ///     identifier: "x".into(),
+    /// }), vec![]);
+    ///
+    /// name.set_identifier("y")?;
+    /// assert_eq!(name.to_string(), "y");
+    /// assert_eq!(
+    ///     name.set_identifier("end"),
+    ///     Err(TokenizerErrorType::InvalidIdentifier("end".to_owned())),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_identifier(&mut self, name: &str) -> Result<(), TokenizerErrorType> {
+        self.expect_kind(TokenKind::Identifier)?;
+
+        match tokens::identifier(name) {
+            Ok(Ok(TokenType::Identifier { .. })) => {
+                self.token.token_type = TokenType::Identifier {
+                    identifier: Cow::Owned(name.to_owned()),
+                };
+                Ok(())
+            }
+
+            _ => Err(TokenizerErrorType::InvalidIdentifier(name.to_owned())),
+        }
+    }
+
+    /// Changes the text of a number, such as `0xFF` or `1e5`, keeping its trivia and positions.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not a number, or if the text is not a valid number.
+    pub fn set_number(&mut self, literal: &str) -> Result<(), TokenizerErrorType> {
+        self.expect_kind(TokenKind::Number)?;
+
+        match tokens::number(literal) {
+            Ok(Ok(TokenType::Number { .. })) => {
+                self.token.token_type = TokenType::Number {
+                    text: Cow::Owned(literal.to_owned()),
+                };
+                Ok(())
+            }
+
+            _ => Err(TokenizerErrorType::InvalidNumberText(literal.to_owned())),
+        }
+    }
+
+    /// Changes the contents of a string, keeping its quotes, trivia, and positions.
+    /// The contents are the value of the string, and are escaped as needed for the quotes.
+    /// Strings using brackets have their level raised if the contents contain the closing brackets.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not a string.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{StringLiteralQuoteType, Token, TokenReference, TokenType};
+    /// # fn main() -> Result<(), full_moon::tokenizer::TokenizerErrorType> {
+    /// let mut string = TokenReference::new(vec![], Token::new(TokenType::StringLiteral {
+    ///     literal: "hello".into(),
+    ///     multi_line: None,
+    ///     quote_type: StringLiteralQuoteType::Double,
+    /// }), vec![]);
+    ///
+    /// string.set_string_contents("say \"hi\"\n")?;
+    /// assert_eq!(string.to_string(), r#""say \"hi\"\n""#);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_string_contents(&mut self, contents: &str) -> Result<(), TokenizerErrorType> {
+        self.expect_kind(TokenKind::StringLiteral)?;
+
+        let quote_type = match &self.token.token_type {
+            TokenType::StringLiteral { quote_type, .. } => *quote_type,
+            _ => unreachable!(),
+        };
+
+        self.token.token_type = match quote_type {
+            StringLiteralQuoteType::Brackets => {
+                let level = match &self.token.token_type {
+                    TokenType::StringLiteral { multi_line, .. } => multi_line.unwrap_or(0),
+                    _ => unreachable!(),
+                };
+
+                // A new line right after the opening brackets is skipped by Lua. It's the same character
+                // as the one the contents start with, since `\n\r` and `\r\n` are skipped as a single line ending
+                let literal = match contents.chars().next() {
+                    Some(line_ending @ ('\n' | '\r')) => format!("{}{}", line_ending, contents),
+                    _ => contents.to_owned(),
+                };
+
+                let level = (level..)
+                    .find(|&level| {
+                        let close = format!("]{}]", "=".repeat(level));
+                        format!("{}{}", literal, close).find(&close) == Some(literal.len())
+                    })
+                    .unwrap();
+
+                TokenType::StringLiteral {
+                    literal: Cow::Owned(literal),
+                    multi_line: Some(level),
+                    quote_type,
+                }
+            }
+
+            _ => {
+                let quote = if quote_type == StringLiteralQuoteType::Double {
+                    '"'
+                } else {
+                    '\''
+                };
+
+                let mut literal = String::with_capacity(contents.len());
+                for character in contents.chars() {
+                    match character {
+                        '\\' => literal.push_str("\\\\"),
+                        '\n' => literal.push_str("\\n"),
+                        '\r' => literal.push_str("\\r"),
+                        '\t' => literal.push_str("\\t"),
+                        _ if character == quote => {
+                            literal.push('\\');
+                            literal.push(character);
+                        }
+                        // Padded so that digits after the escape aren't read as a part of it
+                        _ if character.is_ascii_control() => {
+                            literal.push_str(&format!("\\{:03}", character as u8))
+                        }
+                        _ => literal.push(character),
+                    }
+                }
+
+                TokenType::StringLiteral {
+                    literal: Cow::Owned(literal),
+                    multi_line: None,
+                    quote_type,
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Adds a trivia token to the end of the leading trivia, right before the token.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not trivia, such as comments or whitespace.
+    pub fn push_leading_trivia(&mut self, trivia: Token<'a>) -> Result<(), TokenizerErrorType> {
+        expect_trivia(&trivia)?;
+        self.leading_trivia.push(trivia);
+        Ok(())
+    }
+
+    /// Adds a trivia token to the end of the trailing trivia.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not trivia, such as comments or whitespace.
+    pub fn push_trailing_trivia(&mut self, trivia: Token<'a>) -> Result<(), TokenizerErrorType> {
+        expect_trivia(&trivia)?;
+        self.trailing_trivia.push(trivia);
+        Ok(())
+    }
+
+    /// Inserts a trivia token into the leading trivia at the given index.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not trivia, such as comments or whitespace.
+    ///
+    /// # Panics
+    /// Panics if the index is greater than the amount of leading trivia.
+    pub fn insert_leading_trivia(
+        &mut self,
+        index: usize,
+        trivia: Token<'a>,
+    ) -> Result<(), TokenizerErrorType> {
+        expect_trivia(&trivia)?;
+        self.leading_trivia.insert(index, trivia);
+        Ok(())
+    }
+
+    /// Inserts a trivia token into the trailing trivia at the given index.
+    ///
+    /// # Errors
+    /// Returns an error if the token is not trivia, such as comments or whitespace.
+    ///
+    /// # Panics
+    /// Panics if the index is greater than the amount of trailing trivia.
+    pub fn insert_trailing_trivia(
+        &mut self,
+        index: usize,
+        trivia: Token<'a>,
+    ) -> Result<(), TokenizerErrorType> {
+        expect_trivia(&trivia)?;
+        self.trailing_trivia.insert(index, trivia);
+        Ok(())
+    }
+
+    /// Keeps only the leading trivia for which the function returns `true`
+    pub fn retain_leading_trivia(&mut self, function: impl FnMut(&Token<'a>) -> bool) {
+        self.leading_trivia.retain(function);
+    }
+
+    /// Keeps only the trailing trivia for which the function returns `true`
+    pub fn retain_trailing_trivia(&mut self, function: impl FnMut(&Token<'a>) -> bool) {
+        self.trailing_trivia.retain(function);
+    }
+
+    /// Removes all of the leading trivia and returns it, such as to move it onto another token
+    pub fn take_leading_trivia(&mut self) -> Vec<Token<'a>> {
+        std::mem::take(&mut self.leading_trivia)
+    }
+
+    /// Removes all of the trailing trivia and returns it, such as to move it onto another token
+    /// before removing a node, so that its comments are kept.
+    pub fn take_trailing_trivia(&mut self) -> Vec<Token<'a>> {
+        std::mem::take(&mut self.trailing_trivia)
+    }
+
+    /// Adds a comment on its own line right before the token, indented the same as the token.
+    /// The text does not include the `--`. Text with multiple lines creates a multi-line comment.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::TokenReference;
+    /// # fn main() -> Result<(), full_moon::tokenizer::TokenizerErrorType> {
+    /// let mut token = TokenReference::symbol("\n    local ")?;
+    /// token.prepend_comment(" Hello");
+    /// assert_eq!(token.to_string(), "\n    -- Hello\n    local ");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn prepend_comment(&mut self, text: &str) {
+        // The whitespace after the last new line, directly before the token
+        let indent = match self.leading_trivia.last() {
+            Some(Token {
+                token_type: TokenType::Whitespace { characters },
+                ..
+            }) => characters.rsplit('\n').next().unwrap_or_default(),
+            _ => "",
+        };
+        let new_line = Token::new(TokenType::Whitespace {
+            characters: Cow::Owned(format!("\n{}", indent)),
+        });
+
+        let comment = if text.contains(['\n', '\r']) {
+            let blocks = (0..)
+                .find(|&blocks| {
+                    !format!("{}]", text).contains(&format!("]{}]", "=".repeat(blocks)))
+                })
+                .unwrap();
+
+            TokenType::MultiLineComment {
+                blocks,
+                comment: Cow::Owned(text.to_owned()),
+            }
+        } else {
+            TokenType::SingleLineComment {
+                comment: Cow::Owned(text.to_owned()),
+            }
+        };
+
+        self.leading_trivia.push(Token::new(comment));
+        self.leading_trivia.push(new_line);
+    }
+
+    /// Replaces the whitespace directly before the token, after any comments, with the given whitespace.
+    /// An empty string removes the whitespace.
+    ///
+    /// # Errors
+    /// Returns an error if the text contains anything other than whitespace.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::TokenReference;
+    /// # fn main() -> Result<(), full_moon::tokenizer::TokenizerErrorType> {
+    /// let mut token = TokenReference::symbol("\n    local ")?;
+    /// token.set_leading_whitespace("\t")?;
+    /// assert_eq!(token.to_string(), "\tlocal ");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_leading_whitespace(&mut self, whitespace: &str) -> Result<(), TokenizerErrorType> {
+        if let Some(character) = whitespace
+            .chars()
+            .find(|character| !character.is_ascii_whitespace())
+        {
+            return Err(TokenizerErrorType::UnexpectedToken(character));
+        }
+
+        while let Some(Token {
+            token_type: TokenType::Whitespace { .. },
+            ..
+        }) = self.leading_trivia.last()
+        {
+            self.leading_trivia.pop();
         }
+
+        if !whitespace.is_empty() {
+            self.leading_trivia.push(Token::new(TokenType::Whitespace {
+                characters: Cow::Owned(whitespace.to_owned()),
+            }));
+        }
+
+        Ok(())
+    }
+
+    fn expect_kind(&self, expected: TokenKind) -> Result<(), TokenizerErrorType> {
+        let found = self.token.token_kind();
+        if found == expected {
+            Ok(())
+        } else {
+            Err(TokenizerErrorType::UnexpectedTokenKind { expected, found })
+        }
+    }
+}
+
+fn expect_trivia(token: &Token) -> Result<(), TokenizerErrorType> {
+    if token.token_type.is_trivia() {
+        Ok(())
+    } else {
+        Err(TokenizerErrorType::InvalidTrivia(token.token_kind()))
     }
 }
 
@@ -509,19 +1058,20 @@ impl<'a> std::ops::Deref for TokenReference<'a> {
 impl<'a> fmt::Display for TokenReference<'a> {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         for trivia in &self.leading_trivia {
-            formatter.write_str(&trivia.to_string())?;
+            trivia.fmt(formatter)?;
         }
 
-        formatter.write_str(&self.token.to_string())?;
+        self.token.fmt(formatter)?;
 
         for trivia in &self.trailing_trivia {
-            formatter.write_str(&trivia.to_string())?;
+            trivia.fmt(formatter)?;
         }
 
         Ok(())
     }
 }
 
+// The origin is left out, so that synthesized tokens are equal to the same tokens from the source
 impl<'a> PartialEq<Self> for TokenReference<'a> {
     fn eq(&self, other: &Self) -> bool {
         (**self).eq(other)
@@ -545,7 +1095,11 @@ impl<'a> PartialOrd for TokenReference<'a> {
 }
 
 impl<'ast> Visit<'ast> for TokenReference<'ast> {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
+        visitor.visit_token_reference(self);
         visitor.visit_token(self);
 
         if matches!(self.token().token_kind(), TokenKind::Eof) {
@@ -573,6 +1127,112 @@ impl<'ast> VisitMut<'ast> for TokenReference<'ast> {
     }
 }
 
+// TokenReferences are serialized as only their kind and text when inside of a CompactAst.
+// Otherwise, human readable formats leave out the origin of tokens from the source, which is the default.
+#[cfg(feature = "serde")]
+impl Serialize for TokenReference<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let skip_origin = serializer.is_human_readable() && self.origin == Origin::Source;
+
+        let mut state = serializer.serialize_struct("TokenReference", 4)?;
+        state.serialize_field("leading_trivia", &self.leading_trivia)?;
+        state.serialize_field("token", &self.token)?;
+        state.serialize_field("trailing_trivia", &self.trailing_trivia)?;
+        if skip_origin {
+            state.skip_field("origin")?;
+        } else {
+            state.serialize_field("origin", &self.origin)?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'a> Deserialize<'de> for TokenReference<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{
+            self,
+            value::{MapAccessDeserializer, SeqAccessDeserializer},
+            MapAccess, SeqAccess, Visitor,
+        };
+        use std::marker::PhantomData;
+
+        #[derive(Deserialize)]
+        #[serde(rename = "TokenReference")]
+        struct TokenReferenceFields<'a> {
+            leading_trivia: Vec<Token<'a>>,
+            token: Token<'a>,
+            trailing_trivia: Vec<Token<'a>>,
+            #[serde(default)]
+            origin: Origin,
+        }
+
+        impl<'a> From<TokenReferenceFields<'a>> for TokenReference<'a> {
+            fn from(fields: TokenReferenceFields<'a>) -> Self {
+                TokenReference {
+                    leading_trivia: fields.leading_trivia,
+                    token: fields.token,
+                    trailing_trivia: fields.trailing_trivia,
+                    origin: fields.origin,
+                }
+            }
+        }
+
+        // Compact tokens are a sequence of their kind and text, which human readable formats
+        // never use for full tokens, so they can be told apart even when buffered by an untagged enum.
+        // Other formats can't tell them apart, so a CompactDeserializer gives them as a newtype instead.
+        struct TokenReferenceVisitor<'a> {
+            human_readable: bool,
+            token_reference: PhantomData<TokenReference<'a>>,
+        }
+
+        impl<'de, 'a> Visitor<'de> for TokenReferenceVisitor<'a> {
+            type Value = TokenReference<'a>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("struct TokenReference")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                TokenReferenceFields::deserialize(MapAccessDeserializer::new(map)).map(Into::into)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                if !self.human_readable {
+                    return TokenReferenceFields::deserialize(SeqAccessDeserializer::new(seq))
+                        .map(Into::into);
+                }
+
+                let kind = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &"a token kind and its text"))?;
+                let text: Cow<str> = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &"a token kind and its text"))?;
+                crate::ast::compact::token_reference(kind, &text)
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                let (kind, text) = <(TokenKind, Cow<str>)>::deserialize(deserializer)?;
+                crate::ast::compact::token_reference(kind, &text)
+            }
+        }
+
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_struct(
+            "TokenReference",
+            &["leading_trivia", "token", "trailing_trivia", "origin"],
+            TokenReferenceVisitor {
+                human_readable,
+                token_reference: PhantomData,
+            },
+        )
+    }
+}
+
 /// Used to represent exact positions of tokens in code
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -580,6 +1240,7 @@ pub struct Position {
     pub(crate) bytes: usize,
     pub(crate) line: usize,
     pub(crate) character: usize,
+    pub(crate) byte_column: usize,
 }
 
 impl Position {
@@ -593,12 +1254,57 @@ impl Position {
         self.character
     }
 
+    /// How many bytes into the line this position is, starting from 0.
+    /// The line starts at `bytes() - byte_column()`, and the `\r` of a CRLF line ending counts
+    /// towards the line it ends.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::tokens;
+    /// let code = "local s = 'é'\r\nreturn s";
+    /// let tokens = tokens(code).unwrap();
+    /// let s = tokens.iter().rev().nth(1).unwrap().start_position();
+    ///
+    /// assert_eq!((s.line(), s.character(), s.byte_column()), (2, 8, 7));
+    /// assert_eq!(&code[s.bytes() - s.byte_column()..s.bytes()], "return ");
+    /// ```
+    pub fn byte_column(self) -> usize {
+        self.byte_column
+    }
+
     /// Line the position lies on
     pub fn line(self) -> usize {
         self.line
     }
+
+    /// The column of this position as shown by an editor or terminal, where tabs advance to the
+    /// next multiple of `tab_width` rather than counting as one character. Like [`character`](Position::character),
+    /// this starts at 1. Every other character counts as one column, and a `tab_width` of 0 is treated as 1.
+    ///
+    /// `source_line` is the code of the line the position lies on, from its start, such as
+    /// `&code[position.bytes() - position.byte_column()..]`. Only the part before the position is used.
+    /// [`PositionEncoder::display_column`](crate::ast::PositionEncoder::display_column) finds the line itself.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::tokens;
+    /// let code = "\t  x =\t1";
+    /// let tokens = tokens(code).unwrap();
+    /// let (x, one) = (tokens[1].start_position(), tokens[5].start_position());
+    ///
+    /// assert_eq!((x.character(), x.display_column(4, code), x.display_column(8, code)), (4, 7, 11));
+    /// assert_eq!((one.character(), one.display_column(4, code)), (8, 13));
+    /// ```
+    pub fn display_column(self, tab_width: usize, source_line: &str) -> usize {
+        let mut end = self.byte_column.min(source_line.len());
+        while !source_line.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        display_width(&source_line[..end], tab_width) + 1
+    }
 }
 
+/// Positions are ordered by their byte offset. For positions in the same code, this is
+/// the same as ordering them by their line and then their character.
 impl Ord for Position {
     fn cmp(&self, other: &Self) -> Ordering {
         self.bytes.cmp(&other.bytes)
@@ -611,11 +1317,87 @@ impl PartialOrd for Position {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-struct TokenAdvancement<'a> {
-    pub advance: usize,
-    pub token_type: TokenType<'a>,
-}
+/// The range of code between two positions, such as the one returned by [`Node::span`](crate::node::Node::span).
+/// The start is included in the span, but the end is not, so spans that touch do not overlap.
+///
+/// ```rust
+/// # use full_moon::node::Node;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1\nlocal y = 2")?;
+/// let stmts = ast.nodes().stmts().map(|stmt| stmt.span().unwrap()).collect::<Vec<_>>();
+///
+/// assert!(stmts[0].contains(stmts[0].start()));
+/// assert!(!stmts[0].contains(stmts[0].end()));
+/// assert!(!stmts[0].overlaps(&stmts[1]));
+/// assert_eq!(stmts[0].union(&stmts[1]), ast.nodes().span().unwrap());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Span {
+    start: Position,
+    end: Position,
+}
+
+impl Span {
+    /// Creates a span between the two positions.
+    /// # Panics
+    /// Panics if `end` comes before `start`.
+    pub fn new(start: Position, end: Position) -> Self {
+        assert!(
+            start <= end,
+            "the end of a span can't come before its start"
+        );
+        Self { start, end }
+    }
+
+    /// The position the span starts at, which is included in the span
+    pub fn start(self) -> Position {
+        self.start
+    }
+
+    /// The position the span ends at, which is not included in the span
+    pub fn end(self) -> Position {
+        self.end
+    }
+
+    /// How many bytes the span covers
+    pub fn len(self) -> usize {
+        self.end.bytes - self.start.bytes
+    }
+
+    /// Whether the span covers no code, such as the span of a token that was created
+    /// rather than parsed
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    /// Whether the position is inside of the span. Empty spans contain no positions.
+    pub fn contains(self, position: Position) -> bool {
+        self.start <= position && position < self.end
+    }
+
+    /// Whether the two spans share any code. Spans that only touch do not overlap,
+    /// and an empty span only overlaps spans it is strictly inside of.
+    pub fn overlaps(&self, other: &Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// The smallest span that covers both spans, including anything between them
+    pub fn union(&self, other: &Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct TokenAdvancement<'a> {
+    pub advance: usize,
+    pub token_type: TokenType<'a>,
+}
 
 /// The types of quotes used in a Lua string
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -690,8 +1472,17 @@ peg::parser! {
             }.into()}
             / &multi_line_start() [_]+ { TokenizerErrorType::UnclosedString.into() }
 
+        rule lua52_escapes()
+            = {? if cfg!(any(feature = "lua52", feature = "roblox")) {
+                Ok(())
+            } else {
+                Err("lua52 escapes not enabled")
+            }}
+
+        // `\z` skips the whitespace after it, including new lines
         rule escape()
-            = "\\" [_]
+            = lua52_escapes() "\\z" [' '|'\t'|'\r'|'\n'|'\x0B'|'\x0C']*
+            / "\\" [_]
 
         rule quote_char(quote: &str)
             = !(##parse_string_literal(quote) / ['\r'|'\n'|'\\']) [_]
@@ -701,6 +1492,8 @@ peg::parser! {
               literal:$((quote_char(quote) / escape())+ / )
               ##parse_string_literal(quote)
               { TokenType::StringLiteral { multi_line: None, literal:literal.into(), quote_type }.into() }
+            / ##parse_string_literal(quote) (quote_char(quote) / escape())* "\\" ![_]
+              { TokenizerErrorType::InvalidEscape("\\".to_owned()).into() }
             / ##parse_string_literal(quote) [_]* {TokenizerErrorType::UnclosedString.into() }
 
         rule single_line_quote() -> RawToken<'input>
@@ -715,14 +1508,31 @@ peg::parser! {
             = line:$("#!" (!line_ending() [_])* line_ending())
               {TokenType::Shebang{line:line.into()}.into()}
 
-        pub(super) rule identifier() -> RawToken<'input>
-            = id:$(['_'|'a'..='z'|'A'..='Z'] ['_'|'a'..='z'|'A'..='Z'|'0'..='9']*)
+        rule unicode(allow_unicode_identifiers: bool)
+            = {? if allow_unicode_identifiers {
+                Ok(())
+            } else {
+                Err("unicode identifiers not allowed")
+            }}
+
+        // Any character that isn't ASCII, like LuaJIT
+        rule unicode_character(allow_unicode_identifiers: bool)
+            = unicode(allow_unicode_identifiers) [c if !c.is_ascii() && !c.is_control()]
+
+        rule identifier_with(allow_unicode_identifiers: bool) -> RawToken<'input>
+            = id:$(
+                (['_'|'a'..='z'|'A'..='Z'] / unicode_character(allow_unicode_identifiers))
+                (['_'|'a'..='z'|'A'..='Z'|'0'..='9'] / unicode_character(allow_unicode_identifiers))*
+              )
               { match parse_keyword(id) {
                     Some(symbol) => TokenType::Symbol { symbol }.into(),
                     None => TokenType::Identifier { identifier: id.into() }.into(),
               }}
             / expected!("identifier")
 
+        pub(super) rule identifier() -> RawToken<'input>
+            = identifier_with(false)
+
         pub(super) rule comment() -> RawToken<'input>
             = "--" v:multi_line_block()
               { TokenType::MultiLineComment { blocks: v.0, comment: v.1.into() }.into() }
@@ -748,41 +1558,59 @@ peg::parser! {
             = roblox() n:$(['0'..='9'] ['0'..='9'|'_']*) {n}
             / !roblox() n:$(['0'..='9']+) {n}
 
+        // A `.` followed by another is the `..` operator, such as in `3..x`
+        rule mantissa()
+            = digit_with_separator() ("." !"." digit_with_separator()?)?
+            / "." digit_with_separator()
+
         rule basic_number() -> &'input str
             = $(
-                digit_with_separator()
-                ("." digit_with_separator()?)?
+                mantissa()
                 (['e'|'E'] ['-'|'+']? digit_with_separator())?
             )
 
-        rule no_int_fractional_number() -> &'input str
-            = $(
-                "." digit_with_separator()
-                (['e'|'E'] ['-'|'+']? digit_with_separator())?
-            )
+        rule malformed_number() -> RawToken<'input>
+            = roblox() n:$("0b"/"0B") !['0'|'1'] {
+                TokenizerErrorType::MalformedNumber {
+                    number: n.to_owned(),
+                    reason: "expected binary digits".to_owned(),
+                }.into()
+            }
+            / n:$("0x"/"0X") !['0'..='9'|'a'..='f'|'A'..='F'] {
+                TokenizerErrorType::MalformedNumber {
+                    number: n.to_owned(),
+                    reason: "expected hexadecimal digits".to_owned(),
+                }.into()
+            }
+            / n:$(mantissa() ['e'|'E'] ['-'|'+']?) !['0'..='9'] {
+                TokenizerErrorType::MalformedNumber {
+                    number: n.to_owned(),
+                    reason: "expected digits after the exponent".to_owned(),
+                }.into()
+            }
 
         pub(super) rule number() -> RawToken<'input>
             = n:(
                 roblox_number()
               / hex_number()
-              / basic_number()
-              / no_int_fractional_number()
             ) { TokenType::Number { text:n.into() }.into() }
+            / malformed_number()
+            / n:basic_number() { TokenType::Number { text:n.into() }.into() }
 
         pub(super) rule symbol() -> RawToken<'input> = symbol:##parse_symbol() { TokenType::Symbol{symbol}.into() }
 
-        rule token() -> RawToken<'input>
+        rule token(allow_unicode_identifiers: bool) -> RawToken<'input>
             = whitespace()
             / comment()
             / number()
             / string_literal()
             / "#!" { TokenizerErrorType::UnexpectedShebang.into() }
             / symbol()
-            / identifier()
+            / identifier_with(allow_unicode_identifiers)
 
-        pub(crate) rule tokens() -> Vec<(RawToken<'input>, usize)>
+        pub(crate) rule tokens(allow_unicode_identifiers: bool) -> Vec<(RawToken<'input>, usize)>
             = shebang:(shebang:shebang() pos:position!() {(shebang,pos)})?
-              body:( token:token() pos:position!() {(token,pos)})*
+              body:( token:token(allow_unicode_identifiers) pos:position!() {(token,pos)})*
               {
                   let mut body = body;
                   if let Some(shebang) = shebang {
@@ -813,6 +1641,82 @@ impl TokenizerError {
     pub fn position(&self) -> Position {
         self.position
     }
+
+    /// A short name for the type of error that stays the same between versions, such as `unclosed-string`.
+    /// See [`Error::code`](crate::Error::code).
+    pub fn code(&self) -> &'static str {
+        self.error.code()
+    }
+}
+
+impl TokenizerErrorType {
+    /// A short name for the type of error that stays the same between versions, such as `unclosed-string`.
+    /// See [`Error::code`](crate::Error::code).
+    pub fn code(&self) -> &'static str {
+        match self {
+            TokenizerErrorType::UnclosedComment => "unclosed-comment",
+            TokenizerErrorType::UnclosedString => "unclosed-string",
+            TokenizerErrorType::UnexpectedShebang => "unexpected-shebang",
+            TokenizerErrorType::UnexpectedToken(_) => "unexpected-character",
+            TokenizerErrorType::InvalidSymbol(_) => "invalid-symbol",
+            TokenizerErrorType::InvalidIdentifier(_) => "invalid-identifier",
+            TokenizerErrorType::InvalidNumberText(_) => "invalid-number-text",
+            TokenizerErrorType::MalformedNumber { .. } => "malformed-number",
+            TokenizerErrorType::InvalidEscape(_) => "invalid-escape",
+            TokenizerErrorType::UnexpectedTokenKind { .. } => "unexpected-token-kind",
+            TokenizerErrorType::InvalidTrivia(_) => "invalid-trivia",
+        }
+    }
+
+    // The error without its position, such as "unclosed string"
+    pub(crate) fn message(&self) -> String {
+        match self {
+            TokenizerErrorType::UnclosedComment => "unclosed comment".to_string(),
+            TokenizerErrorType::UnclosedString => "unclosed string".to_string(),
+            TokenizerErrorType::UnexpectedShebang => "unexpected shebang".to_string(),
+            // Control characters such as NUL are escaped, since they can't be seen when printed
+            TokenizerErrorType::UnexpectedToken(character) if character.is_control() => {
+                format!("unexpected character {}", character.escape_default())
+            }
+            TokenizerErrorType::UnexpectedToken(character) => {
+                format!("unexpected character {}", character)
+            }
+            TokenizerErrorType::InvalidSymbol(symbol) => {
+                format!("invalid symbol {}", symbol)
+            }
+            TokenizerErrorType::InvalidIdentifier(identifier) => {
+                format!("invalid identifier {}", identifier)
+            }
+            TokenizerErrorType::InvalidNumberText(number) => {
+                format!("invalid number {}", number)
+            }
+            TokenizerErrorType::MalformedNumber { number, reason } => {
+                format!("malformed number {}, {}", number, reason)
+            }
+            TokenizerErrorType::InvalidEscape(escape) => {
+                format!("invalid escape sequence {}", escape)
+            }
+            TokenizerErrorType::UnexpectedTokenKind { expected, found } => {
+                format!("expected token of kind {:?}, found {:?}", expected, found)
+            }
+            TokenizerErrorType::InvalidTrivia(kind) => {
+                format!("token of kind {:?} is not trivia", kind)
+            }
+        }
+    }
+
+    // The code that caused the error, if there is any
+    pub(crate) fn text(&self) -> Option<String> {
+        match self {
+            TokenizerErrorType::UnexpectedToken(character) => Some(character.to_string()),
+            TokenizerErrorType::InvalidSymbol(text)
+            | TokenizerErrorType::InvalidIdentifier(text)
+            | TokenizerErrorType::InvalidNumberText(text)
+            | TokenizerErrorType::MalformedNumber { number: text, .. }
+            | TokenizerErrorType::InvalidEscape(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for TokenizerError {
@@ -820,17 +1724,7 @@ impl fmt::Display for TokenizerError {
         write!(
             formatter,
             "{} at line {}, column {}",
-            match &self.error {
-                TokenizerErrorType::UnclosedComment => "unclosed comment".to_string(),
-                TokenizerErrorType::UnclosedString => "unclosed string".to_string(),
-                TokenizerErrorType::UnexpectedShebang => "unexpected shebang".to_string(),
-                TokenizerErrorType::UnexpectedToken(character) => {
-                    format!("unexpected character {}", character)
-                }
-                TokenizerErrorType::InvalidSymbol(symbol) => {
-                    format!("invalid symbol {}", symbol)
-                }
-            },
+            self.error.message(),
             self.position.line,
             self.position.character,
         )
@@ -839,24 +1733,244 @@ impl fmt::Display for TokenizerError {
 
 impl std::error::Error for TokenizerError {}
 
-impl From<peg::str::LineCol> for Position {
-    fn from(location: peg::str::LineCol) -> Position {
+// How many columns the text on a single line takes up when tabs advance to the next multiple of `tab_width`
+pub(crate) fn display_width(text: &str, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+
+    text.chars().fold(0, |column, character| match character {
+        '\t' => (column / tab_width + 1) * tab_width,
+        // The end position of a token ending in a new line is still on the line it ends
+        '\n' => column,
+        _ => column + 1,
+    })
+}
+
+impl Position {
+    fn from_line_col(code: &str, location: peg::str::LineCol) -> Position {
+        let line_start = code[..location.offset]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+
         Position {
             bytes: location.offset,
             line: location.line,
             character: location.column,
+            byte_column: location.offset - line_start,
+        }
+    }
+
+    // The position after the text, if the text started at this position
+    fn advanced_by(mut self, text: &str) -> Position {
+        for character in text.chars() {
+            self.bytes += character.len_utf8();
+
+            if character == '\n' {
+                self.line += 1;
+                self.character = 1;
+                self.byte_column = 0;
+            } else {
+                self.character += 1;
+                self.byte_column += character.len_utf8();
+            }
+        }
+
+        self
+    }
+}
+
+// Returns the length in bytes of the escape sequence that `text` starts with, or if it's not valid
+// in the Lua version being parsed, the length of the part of it that was read, such as `\x4` of `\x4z`
+fn escape_length(text: &str) -> Result<usize, usize> {
+    let bytes = text.as_bytes();
+    let count = |start: usize, max: usize, predicate: fn(&u8) -> bool| {
+        bytes[start.min(bytes.len())..]
+            .iter()
+            .take(max)
+            .take_while(|byte| predicate(byte))
+            .count()
+    };
+
+    match bytes.get(1) {
+        Some(
+            b'a' | b'b' | b'f' | b'n' | b'r' | b't' | b'v' | b'\\' | b'"' | b'\'' | b'\n' | b'\r',
+        ) => Ok(2),
+
+        Some(b'0'..=b'9') => {
+            let digits = count(1, 3, u8::is_ascii_digit);
+            match text[1..1 + digits].parse::<u16>() {
+                Ok(byte) if byte <= 255 => Ok(1 + digits),
+                _ => Err(1 + digits),
+            }
+        }
+
+        Some(b'z') if cfg!(any(feature = "lua52", feature = "roblox")) => Ok(2),
+
+        Some(b'x') if cfg!(any(feature = "lua52", feature = "roblox")) => {
+            let digits = count(2, 2, u8::is_ascii_hexdigit);
+            if digits == 2 {
+                Ok(4)
+            } else {
+                Err(2 + digits)
+            }
+        }
+
+        Some(b'u') if cfg!(feature = "roblox") => {
+            if bytes.get(2) != Some(&b'{') {
+                return Err(2);
+            }
+
+            let digits = count(3, usize::MAX, u8::is_ascii_hexdigit);
+            if bytes.get(3 + digits) != Some(&b'}') {
+                return Err(3 + digits);
+            }
+
+            match u32::from_str_radix(&text[3..3 + digits], 16) {
+                Ok(code_point) if code_point <= 0x10FFFF => Ok(4 + digits),
+                _ => Err(4 + digits),
+            }
+        }
+
+        Some(_) => Err(1 + text[1..].chars().next().map_or(0, char::len_utf8)),
+        None => Err(1),
+    }
+}
+
+// Finds every escape sequence in the contents of a quoted string that is not valid,
+// returning their byte offsets and text
+fn find_invalid_escapes(literal: &str) -> Vec<(usize, &str)> {
+    let mut invalid_escapes = Vec::new();
+    let mut index = 0;
+
+    while let Some(offset) = literal[index..].find('\\') {
+        let start = index + offset;
+        match escape_length(&literal[start..]) {
+            Ok(length) => index = start + length,
+            Err(length) => {
+                invalid_escapes.push((start, &literal[start..start + length]));
+                index = start + length;
+            }
+        }
+    }
+
+    invalid_escapes
+}
+
+// The bytes a string literal stands for, with its escape sequences decoded and, for multi line strings,
+// the line ending right after the opening brackets removed. Returns `None` if any escape sequence is invalid
+pub(crate) fn decode_string_literal(literal: &str, multi_line: Option<usize>) -> Option<Vec<u8>> {
+    if multi_line.is_some() {
+        let content = ["\r\n", "\n\r", "\n", "\r"]
+            .iter()
+            .find_map(|line_ending| literal.strip_prefix(line_ending))
+            .unwrap_or(literal);
+
+        return Some(content.as_bytes().to_vec());
+    }
+
+    let mut bytes = Vec::with_capacity(literal.len());
+    let mut rest = literal;
+
+    while let Some(start) = rest.find('\\') {
+        bytes.extend_from_slice(&rest.as_bytes()[..start]);
+
+        let length = escape_length(&rest[start..]).ok()?;
+        let escape = &rest[start..start + length];
+        rest = &rest[start + length..];
+
+        match escape.as_bytes()[1] {
+            b'a' => bytes.push(0x07),
+            b'b' => bytes.push(0x08),
+            b'f' => bytes.push(0x0C),
+            b'n' => bytes.push(b'\n'),
+            b'r' => bytes.push(b'\r'),
+            b't' => bytes.push(b'\t'),
+            b'v' => bytes.push(0x0B),
+            byte @ (b'\\' | b'"' | b'\'') => bytes.push(byte),
+
+            // `\r\n` and `\n\r` after a backslash are a single line ending
+            line_ending @ (b'\n' | b'\r') => {
+                bytes.push(b'\n');
+                let other = if line_ending == b'\n' { "\r" } else { "\n" };
+                rest = rest.strip_prefix(other).unwrap_or(rest);
+            }
+
+            b'0'..=b'9' => bytes.push(escape[1..].parse().ok()?),
+            b'z' => {
+                rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '\x0B')
+            }
+            b'x' => bytes.push(u8::from_str_radix(&escape[2..], 16).ok()?),
+            b'u' => {
+                let code_point = u32::from_str_radix(&escape[3..length - 1], 16).ok()?;
+                let character = std::char::from_u32(code_point)?;
+                bytes.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+
+            _ => return None,
+        }
+    }
+
+    bytes.extend_from_slice(rest.as_bytes());
+    Some(bytes)
+}
+
+/// Options for [`tokens_with_options`]
+#[derive(Clone, Debug, Default)]
+pub struct TokenizerOptions {
+    allow_unicode_identifiers: bool,
+    invalid_escapes_as_warnings: bool,
+}
+
+impl TokenizerOptions {
+    /// Creates the default options, where identifiers are ASCII only and invalid escape sequences are errors
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether identifiers can contain characters that are not ASCII, such as `名前`.
+    /// Like LuaJIT, every character that is not ASCII is allowed anywhere in an identifier,
+    /// while keywords are still only ASCII.
+    pub fn allow_unicode_identifiers(&self) -> bool {
+        self.allow_unicode_identifiers
+    }
+
+    /// Sets whether identifiers can contain characters that are not ASCII
+    pub fn with_allow_unicode_identifiers(self, allow_unicode_identifiers: bool) -> Self {
+        Self {
+            allow_unicode_identifiers,
+            ..self
+        }
+    }
+
+    /// Whether invalid escape sequences in strings, such as `\q`, are returned as warnings
+    /// alongside the tokens instead of failing tokenizing
+    pub fn invalid_escapes_as_warnings(&self) -> bool {
+        self.invalid_escapes_as_warnings
+    }
+
+    /// Sets whether invalid escape sequences in strings are returned as warnings alongside the tokens,
+    /// one for each of them, instead of the first one failing tokenizing
+    pub fn with_invalid_escapes_as_warnings(self, invalid_escapes_as_warnings: bool) -> Self {
+        Self {
+            invalid_escapes_as_warnings,
+            ..self
         }
     }
 }
 
 struct TokenCollector<'input> {
     result: Vec<Token<'input>>,
+    warnings: Vec<TokenizerError>,
+    invalid_escapes_as_warnings: bool,
 }
 
 // Collector
 impl<'input> TokenCollector<'input> {
-    fn new() -> Self {
-        Self { result: Vec::new() }
+    fn new(options: &TokenizerOptions) -> Self {
+        Self {
+            result: Vec::new(),
+            warnings: Vec::new(),
+            invalid_escapes_as_warnings: options.invalid_escapes_as_warnings,
+        }
     }
     fn push(
         &mut self,
@@ -866,6 +1980,29 @@ impl<'input> TokenCollector<'input> {
     ) -> Result<(), TokenizerError> {
         match raw_token {
             Ok(token_type) => {
+                if let TokenType::StringLiteral {
+                    literal,
+                    multi_line: None,
+                    ..
+                } = &token_type
+                {
+                    for (offset, escape) in find_invalid_escapes(literal) {
+                        // Skips the opening quote
+                        let error = TokenizerError {
+                            error: TokenizerErrorType::InvalidEscape(escape.to_owned()),
+                            position: start_position
+                                .advanced_by("\"")
+                                .advanced_by(&literal[..offset]),
+                        };
+
+                        if self.invalid_escapes_as_warnings {
+                            self.warnings.push(error);
+                        } else {
+                            return Err(error);
+                        }
+                    }
+                }
+
                 self.result.push(Token {
                     start_position,
                     end_position,
@@ -873,19 +2010,29 @@ impl<'input> TokenCollector<'input> {
                 });
                 Ok(())
             }
+            // A backslash at the very end of the code, which is the last character of the string
+            Err(error @ TokenizerErrorType::InvalidEscape(_)) => Err(TokenizerError {
+                error,
+                position: Position {
+                    bytes: end_position.bytes - 1,
+                    character: end_position.character - 1,
+                    byte_column: end_position.byte_column - 1,
+                    ..end_position
+                },
+            }),
             Err(error) => Err(TokenizerError {
                 error,
                 position: start_position,
             }),
         }
     }
-    fn finish(mut self, eof_position: Position) -> Vec<Token<'input>> {
+    fn finish(mut self, eof_position: Position) -> (Vec<Token<'input>>, Vec<TokenizerError>) {
         self.result.push(Token {
             start_position: eof_position,
             end_position: eof_position,
             token_type: TokenType::Eof,
         });
-        self.result
+        (self.result, self.warnings)
     }
 }
 
@@ -898,12 +2045,13 @@ fn from_parser_error(
                 "(internal full-moon error) Text overflow while giving unexpected token error",
             ),
         ),
-        position: err.location.into(),
+        position: Position::from_line_col(code, err.location),
     }
 }
 
-/// Returns a list of tokens.
-/// You probably want [`parse`](crate::parse) instead.
+/// Returns a list of tokens, always ending with an eof token.
+/// You probably want [`parse`](crate::parse) instead, or [`Ast::from_tokens`](crate::ast::Ast::from_tokens)
+/// to parse the tokens afterwards.
 ///
 /// # Errors
 ///
@@ -915,11 +2063,32 @@ fn from_parser_error(
 /// assert!(tokens("local x = 1").is_ok());
 /// assert!(tokens("local 4 = end").is_ok()); // tokens does *not* check validity of code, only tokenizing
 /// assert!(tokens("--[[ Unclosed comment!").is_err());
+/// assert!(tokens(r#"print("\q")"#).is_err());
 /// ```
 pub fn tokens(code: &str) -> Result<Vec<Token>, TokenizerError> {
-    let mut tokens = TokenCollector::new();
+    tokens_with_options(code, &TokenizerOptions::new()).map(|(tokens, _)| tokens)
+}
+
+/// Returns a list of tokens, along with any warnings, using the options given.
+/// See [`tokens`].
+///
+/// ```rust
+/// # use full_moon::tokenizer::{tokens_with_options, TokenizerErrorType, TokenizerOptions};
+/// let options = TokenizerOptions::new().with_invalid_escapes_as_warnings(true);
+/// let (tokens, warnings) = tokens_with_options(r#"print("\q")"#, &options).unwrap();
+///
+/// assert_eq!(tokens.len(), 5);
+/// assert_eq!(warnings[0].error(), &TokenizerErrorType::InvalidEscape("\\q".to_owned()));
+/// assert_eq!(warnings[0].position().character(), 8);
+/// ```
+pub fn tokens_with_options<'a>(
+    code: &'a str,
+    options: &TokenizerOptions,
+) -> Result<(Vec<Token<'a>>, Vec<TokenizerError>), TokenizerError> {
+    let mut tokens = TokenCollector::new(options);
 
-    let mut raw_tokens = tokens::tokens(code).map_err(from_parser_error(code))?;
+    let mut raw_tokens =
+        tokens::tokens(code, options.allow_unicode_identifiers).map_err(from_parser_error(code))?;
 
     // rust-peg lets us easily get the offset associated with
     // (the end of) each token, but not the line or column
@@ -931,6 +2100,7 @@ pub fn tokens(code: &str) -> Result<Vec<Token>, TokenizerError> {
         bytes: 0,
         character: 1,
         line: 1,
+        byte_column: 0,
     };
     let mut next_is_new_line = false;
     let mut start_position = position;
@@ -943,6 +2113,7 @@ pub fn tokens(code: &str) -> Result<Vec<Token>, TokenizerError> {
             }
 
             position.bytes += character.len_utf8();
+            position.byte_column += character.len_utf8();
 
             let end_position = position;
 
@@ -950,6 +2121,7 @@ pub fn tokens(code: &str) -> Result<Vec<Token>, TokenizerError> {
                 next_is_new_line = false;
                 position.line += 1;
                 position.character = 1;
+                position.byte_column = 0;
             }
 
             if token_offset == end_position.bytes {
@@ -1065,6 +2237,275 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rule_malformed_numbers() {
+        let malformed = |number: &str, reason: &str| TokenizerErrorType::MalformedNumber {
+            number: number.to_owned(),
+            reason: reason.to_owned(),
+        };
+
+        test_rule!(number("0x"), malformed("0x", "expected hexadecimal digits"));
+        test_rule!(
+            number("1e"),
+            malformed("1e", "expected digits after the exponent")
+        );
+        test_rule!(
+            number("1e+"),
+            malformed("1e+", "expected digits after the exponent")
+        );
+        test_rule!(
+            number(".5E-"),
+            malformed(".5E-", "expected digits after the exponent")
+        );
+
+        #[cfg(feature = "roblox")]
+        test_rule!(number("0b"), malformed("0b", "expected binary digits"));
+
+        let error = tokens("local x = 0x.p").unwrap_err();
+        assert_eq!(
+            error.error(),
+            &malformed("0x", "expected hexadecimal digits")
+        );
+        assert_eq!(error.position().character(), 11);
+    }
+
+    #[test]
+    fn test_invalid_escapes() {
+        let invalid_escape = |code: &str| {
+            tokens(code).err().map(|error| match error.error() {
+                TokenizerErrorType::InvalidEscape(escape) => {
+                    (escape.clone(), error.position().character())
+                }
+                other => panic!("expected invalid escape, got {:?}", other),
+            })
+        };
+
+        assert_eq!(invalid_escape(r#"x = "\q""#), Some((r"\q".to_owned(), 6)));
+        assert_eq!(
+            invalid_escape(r#"x = 'ok\'\400'"#),
+            Some((r"\400".to_owned(), 10))
+        );
+        assert_eq!(invalid_escape(r#"x = "é\é""#), Some((r"\é".to_owned(), 7)));
+        assert_eq!(
+            invalid_escape(r#"x = "\255\0\97\a\b\f\n\r\t\v\\\"\'""#),
+            None
+        );
+        assert_eq!(
+            invalid_escape("x = \"\\\n\\q\""),
+            Some((r"\q".to_owned(), 1))
+        );
+        assert_eq!(invalid_escape(r#"x = "abc\"#), Some((r"\".to_owned(), 9)));
+        assert_eq!(invalid_escape(r#"x = [[\q]]"#), None);
+
+        let escape_of =
+            |code: &str| invalid_escape(&format!("x = '{}'", code)).map(|(escape, _)| escape);
+
+        // Escapes that are only valid in some versions of Lua
+        let lua52 = cfg!(any(feature = "lua52", feature = "roblox"));
+        let roblox = cfg!(feature = "roblox");
+        for (escape, valid) in &[
+            (r"\x41", lua52),
+            (r"\xfF", lua52),
+            (r"\z", lua52),
+            (r"\u{41}", roblox),
+        ] {
+            let expected = if *valid {
+                None
+            } else {
+                Some(escape[..2].to_owned())
+            };
+            assert_eq!(escape_of(escape), expected, "{}", escape);
+        }
+
+        #[cfg(any(feature = "lua52", feature = "roblox"))]
+        assert_eq!(escape_of(r"\x4z"), Some(r"\x4".to_owned()));
+
+        #[cfg(feature = "roblox")]
+        for invalid in &[r"\u{}", r"\u{41", r"\u{110000}"] {
+            assert_eq!(escape_of(invalid), Some(invalid.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_invalid_escapes_as_warnings() {
+        let options = TokenizerOptions::new().with_invalid_escapes_as_warnings(true);
+        let (tokens, warnings) =
+            tokens_with_options("x = '\\q'\ny = \"\\999 \\n \\w\"", &options).unwrap();
+
+        assert_eq!(tokens.len(), 12);
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| (
+                    warning.error().clone(),
+                    warning.position().line(),
+                    warning.position().character()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (TokenizerErrorType::InvalidEscape(r"\q".to_owned()), 1, 6),
+                (TokenizerErrorType::InvalidEscape(r"\999".to_owned()), 2, 6),
+                (TokenizerErrorType::InvalidEscape(r"\w".to_owned()), 2, 14),
+            ]
+        );
+
+        // Without warnings, the first invalid escape is the error
+        assert_eq!(
+            super::tokens("y = \"\\999 \\w\"").unwrap_err().error(),
+            &TokenizerErrorType::InvalidEscape(r"\999".to_owned())
+        );
+
+        // Unfinished strings can't be tokenized, so they're always errors
+        assert!(tokens_with_options("x = '\\", &options).is_err());
+    }
+
+    #[test]
+    fn test_decode_string_literal() {
+        let decode = |literal| decode_string_literal(literal, None);
+
+        assert_eq!(decode(r"plain"), Some(b"plain".to_vec()));
+        assert_eq!(decode(r#"a\tb\n\"\'\\"#), Some(b"a\tb\n\"'\\".to_vec()));
+        assert_eq!(decode(r"\65\0662\255"), Some(b"AB2\xFF".to_vec()));
+        assert_eq!(decode("a\\\r\nb\\\nc"), Some(b"a\nb\nc".to_vec()));
+        assert_eq!(decode(r"\q"), None);
+
+        if cfg!(any(feature = "lua52", feature = "roblox")) {
+            assert_eq!(decode("\\x41\\z  \n  B"), Some(b"AB".to_vec()));
+        }
+
+        if cfg!(feature = "roblox") {
+            assert_eq!(decode(r"\u{48}\u{540D}"), Some("H名".as_bytes().to_vec()));
+        }
+
+        assert_eq!(
+            decode_string_literal("\n[[x]]\n", Some(0)),
+            Some(b"[[x]]\n".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let code = "local 名前 = ifé .. naïve_2\nprint(名前)";
+
+        let error = tokens(code).unwrap_err();
+        assert_eq!(error.error(), &TokenizerErrorType::UnexpectedToken('名'));
+        assert_eq!(error.position().character(), 7);
+
+        let options = TokenizerOptions::new().with_allow_unicode_identifiers(true);
+        let (tokens, _) = tokens_with_options(code, &options).unwrap();
+        assert_eq!(
+            tokens.iter().map(ToString::to_string).collect::<String>(),
+            code
+        );
+
+        let tokens = tokens
+            .into_iter()
+            .filter(|token| !token.token_type().is_trivia())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens[0].token_type(),
+            &TokenType::Symbol {
+                symbol: Symbol::Local
+            }
+        );
+
+        let identifiers = tokens
+            .iter()
+            .filter_map(|token| match token.token_type() {
+                TokenType::Identifier { identifier } => Some((
+                    identifier.to_string(),
+                    token.start_position().character(),
+                    token.end_position().character(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            identifiers,
+            vec![
+                ("名前".to_owned(), 7, 9),
+                ("ifé".to_owned(), 12, 15),
+                ("naïve_2".to_owned(), 19, 26),
+                ("print".to_owned(), 1, 6),
+                ("名前".to_owned(), 7, 9),
+            ]
+        );
+
+        let ast = crate::ast::Ast::from_tokens(tokens_with_options(code, &options).unwrap().0);
+        assert_eq!(crate::print(&ast.unwrap()), code);
+    }
+
+    #[test]
+    fn test_control_characters() {
+        // Control characters are kept inside of comments and strings
+        let code = "--[[ \0 ]] local x = '\x01' -- \x7f\nprint(x)";
+        let kept = tokens(code).unwrap();
+        assert_eq!(
+            kept.iter().map(ToString::to_string).collect::<String>(),
+            code
+        );
+        assert_eq!(kept[2].start_position().character(), 11);
+        assert_eq!(kept.last().unwrap().end_position().character(), 9);
+
+        // ...but nowhere else, even in unicode identifiers
+        let options = TokenizerOptions::new().with_allow_unicode_identifiers(true);
+        for (code, character, column) in [
+            ("\0", '\0', 1),
+            ("local a\0b = 1", '\0', 8),
+            ("local é\u{85} = 1", '\u{85}', 8),
+        ] {
+            let error = tokens_with_options(code, &options).unwrap_err();
+            assert_eq!(
+                error.error(),
+                &TokenizerErrorType::UnexpectedToken(character)
+            );
+            assert_eq!(error.position().character(), column);
+        }
+
+        assert_eq!(
+            tokens("x = 1\0").unwrap_err().to_string(),
+            "unexpected character \\u{0} at line 1, column 6"
+        );
+    }
+
+    #[test]
+    fn test_number_concat() {
+        let token_types = |code| {
+            tokens(code)
+                .unwrap()
+                .into_iter()
+                .map(|token| token.token_type().clone())
+                .filter(|token_type| !token_type.is_trivia())
+                .collect::<Vec<_>>()
+        };
+
+        let number = |text: &'static str| TokenType::Number { text: text.into() };
+        let concat = TokenType::Symbol {
+            symbol: Symbol::TwoDots,
+        };
+
+        assert_eq!(
+            token_types("3..x"),
+            vec![
+                number("3"),
+                concat.clone(),
+                TokenType::Identifier {
+                    identifier: "x".into(),
+                },
+                TokenType::Eof,
+            ]
+        );
+
+        assert_eq!(
+            token_types("3 .. 4"),
+            vec![number("3"), concat, number("4"), TokenType::Eof]
+        );
+
+        assert_eq!(token_types("3."), vec![number("3."), TokenType::Eof]);
+    }
+
     #[test]
     fn test_rule_identifier() {
         test_rule!(
@@ -1201,12 +2642,14 @@ mod tests {
                     bytes: 0,
                     character: 1,
                     line: 1,
+                    byte_column: 0,
                 },
 
                 end_position: Position {
                     bytes: 1,
                     character: 1,
                     line: 1,
+                    byte_column: 1,
                 },
 
                 token_type: TokenType::Whitespace {
@@ -1222,4 +2665,197 @@ mod tests {
         let _ = tokens("̹(");
         let _ = tokens("¹;");
     }
+
+    proptest::proptest! {
+        #[test]
+        fn test_number_soup(code in "[0-9a-fA-FxXeEpPbB._+ -]{0,16}") {
+            if let Ok(tokens) = tokens(&code) {
+                let printed = tokens.iter().map(ToString::to_string).collect::<String>();
+                proptest::prop_assert_eq!(printed, code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_symbol_round_trip() {
+        for &symbol in Symbol::ALL {
+            assert_eq!(symbol.as_str().parse::<Symbol>(), Ok(symbol));
+            assert_eq!(symbol.to_string(), symbol.as_str());
+
+            let token = TokenReference::from_symbol(symbol);
+            assert_eq!(token.token_type().as_symbol(), Some(symbol));
+            assert_eq!(token.to_string(), symbol.as_str());
+            assert_eq!(
+                TokenReference::symbol(symbol.as_str()),
+                Ok(TokenReference::spaced_symbol("", symbol, ""))
+            );
+
+            let tokens = tokens(symbol.as_str()).unwrap();
+            assert_eq!(tokens.len(), 2);
+            assert_eq!(tokens[0].token_type().as_symbol(), Some(symbol));
+        }
+
+        assert_eq!("not a symbol".parse::<Symbol>(), Err(()));
+    }
+
+    #[test]
+    fn test_token_type_extractors() {
+        let tokens =
+            tokens("#!lua\nlocal x = 0xFF .. [=[a]=] -- one\n--[[two]] += -> | & ? :: @").unwrap();
+        let token = |text: &str| {
+            tokens
+                .iter()
+                .find(|token| token.to_string() == text)
+                .unwrap_or_else(|| panic!("no token {}", text))
+        };
+
+        assert!(token("#!lua\n").is_trivia());
+        assert!(token(" ").is_trivia());
+        assert!(!token(" ").is_keyword());
+
+        assert!(token("local").is_keyword());
+        assert_eq!(token("local").as_symbol(), Some(Symbol::Local));
+        assert_eq!(token("local").as_identifier(), None);
+
+        assert_eq!(token("x").as_identifier(), Some("x"));
+        assert!(!token("x").is_keyword());
+        assert_eq!(token("x").as_symbol(), None);
+
+        assert_eq!(token("0xFF").as_number(), Some("0xFF"));
+        assert_eq!(token("x").as_number(), None);
+
+        assert_eq!(
+            token("[=[a]=]").as_string_literal(),
+            Some(("a", StringLiteralQuoteType::Brackets, Some(1)))
+        );
+        assert_eq!(token("x").as_string_literal(), None);
+
+        assert_eq!(token("-- one").as_comment(), Some(" one"));
+        assert_eq!(token("--[[two]]").as_comment(), Some("two"));
+        assert!(token("--[[two]]").is_trivia());
+        assert_eq!(token("x").as_comment(), None);
+
+        for (text, symbol) in &[
+            ("..", Symbol::TwoDots),
+            ("+=", Symbol::PlusEqual),
+            ("->", Symbol::ThinArrow),
+            ("|", Symbol::Pipe),
+            ("&", Symbol::Ampersand),
+            ("?", Symbol::QuestionMark),
+            ("::", Symbol::TwoColons),
+            ("@", Symbol::At),
+        ] {
+            assert_eq!(token(text).as_symbol(), Some(*symbol));
+            assert!(!token(text).is_keyword());
+            assert!(!token(text).is_trivia());
+        }
+
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.token_kind(), TokenKind::Eof);
+        assert!(!eof.is_trivia() && !eof.is_keyword());
+
+        // Token references dereference to their token
+        assert_eq!(parse_token("x ").as_identifier(), Some("x"));
+        assert_eq!(
+            parse_token("'s'").as_string_literal(),
+            Some(("s", StringLiteralQuoteType::Single, None))
+        );
+    }
+
+    fn parse_token(code: &str) -> TokenReference {
+        let mut tokens = tokens(code).unwrap().into_iter();
+        let token = tokens.next().unwrap();
+        TokenReference::new(
+            Vec::new(),
+            token,
+            tokens
+                .filter(|token| token.token_type().is_trivia())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_set_identifier() {
+        let mut token = parse_token("foo -- comment");
+        let position = token.start_position();
+
+        token.set_identifier("bar_2").unwrap();
+        assert_eq!(token.to_string(), "bar_2 -- comment");
+        assert_eq!(token.start_position(), position);
+
+        for invalid in &["local", "2x", "a b", "", "a-b"] {
+            assert_eq!(
+                token.set_identifier(invalid),
+                Err(TokenizerErrorType::InvalidIdentifier(invalid.to_string()))
+            );
+        }
+        assert_eq!(token.to_string(), "bar_2 -- comment");
+
+        assert_eq!(
+            parse_token("local").set_identifier("x"),
+            Err(TokenizerErrorType::UnexpectedTokenKind {
+                expected: TokenKind::Identifier,
+                found: TokenKind::Symbol,
+            })
+        );
+    }
+
+    #[test]
+    fn test_set_number() {
+        let mut token = parse_token("1 ");
+        token.set_number("0xFF").unwrap();
+        assert_eq!(token.to_string(), "0xFF ");
+        token.set_number("1.5e-3").unwrap();
+        assert_eq!(token.to_string(), "1.5e-3 ");
+
+        for invalid in &["x", "1 2", "1e", ""] {
+            assert_eq!(
+                token.set_number(invalid),
+                Err(TokenizerErrorType::InvalidNumberText(invalid.to_string()))
+            );
+        }
+
+        assert!(parse_token("x").set_number("1").is_err());
+    }
+
+    #[test]
+    fn test_set_string_contents() {
+        let cases: &[(&str, &str, &str)] = &[
+            ("'a'", "it's \\ \"fine\"", r#"'it\'s \\ "fine"'"#),
+            ("\"a\"", "it's \"fine\"", r#""it's \"fine\"""#),
+            ("'a'", "line\nnext\ttab\u{0}1", r#"'line\nnext\ttab\0001'"#),
+            ("[[a]]", "x]]y", "[=[x]]y]=]"),
+            ("[[a]]", "x]", "[=[x]]=]"),
+            ("[==[a]==]", "x]]", "[==[x]]]==]"),
+            ("[[a]]", "\nx", "[[\n\nx]]"),
+            ("[[a]]", "\rx", "[[\r\rx]]"),
+            ("[[a]]", "\r\nx", "[[\r\r\nx]]"),
+            ("[[a]]", "\n\rx", "[[\n\n\rx]]"),
+        ];
+
+        for (code, contents, expected) in cases {
+            let mut token = parse_token(code);
+            token.set_string_contents(contents).unwrap();
+            assert_eq!(token.to_string(), *expected);
+
+            // The new string is a single valid token
+            let retokenized = tokens(expected).unwrap();
+            assert_eq!(retokenized.len(), 2);
+            assert_eq!(retokenized[0].token_type(), token.token_type());
+
+            if let TokenType::StringLiteral {
+                literal,
+                multi_line,
+                ..
+            } = token.token_type()
+            {
+                assert_eq!(
+                    decode_string_literal(literal, *multi_line).unwrap(),
+                    contents.as_bytes()
+                );
+            }
+        }
+
+        assert!(parse_token("x").set_string_contents("x").is_err());
+    }
 }
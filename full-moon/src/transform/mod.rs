@@ -0,0 +1,3 @@
+mod strip_types;
+
+pub use strip_types::*;
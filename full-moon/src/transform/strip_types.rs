@@ -0,0 +1,828 @@
+use crate::{
+    ast::{
+        builders::{expr, stmt},
+        punctuated::{Pair, Punctuated},
+        span::ContainedSpan,
+        types::{Attribute, CompoundAssignment, CompoundOp, TypeSpecifier},
+        AnonymousFunction, Assignment, Ast, BinOp, Block, Expression, FunctionBody,
+        FunctionDeclaration, GenericFor, If, LastStmt, LocalAssignment, LocalFunction, NumericFor,
+        Prefix, Repeat, Stmt, Value, Var, While,
+    },
+    tokenizer::{Symbol, Token, TokenKind, TokenReference, TokenType},
+    util::{map_first_token, map_last_token},
+    visitors::{Visit, VisitMut, Visitor, VisitorMut},
+};
+use std::collections::HashSet;
+
+// The flag used to tell a `break` apart from a `continue` when desugaring `continue`
+const BREAK_FLAG: &str = "__break";
+
+/// Options for [`strip_types_with_options`]
+#[derive(Clone, Debug, Default)]
+pub struct StripTypesOptions {
+    desugar_compound_assignments: bool,
+    desugar_continue: bool,
+}
+
+impl StripTypesOptions {
+    /// Creates the default options: only types are removed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether compound assignments, such as `x += 1`, are rewritten as `x = x + 1`
+    pub fn desugar_compound_assignments(&self) -> bool {
+        self.desugar_compound_assignments
+    }
+
+    /// Whether `continue` is rewritten as a `break` out of a `repeat ... until true` loop
+    /// wrapped around the body of the loop
+    pub fn desugar_continue(&self) -> bool {
+        self.desugar_continue
+    }
+
+    /// Returns new options with compound assignments either rewritten or kept
+    pub fn with_desugar_compound_assignments(self, desugar_compound_assignments: bool) -> Self {
+        Self {
+            desugar_compound_assignments,
+            ..self
+        }
+    }
+
+    /// Returns new options with `continue` either rewritten or kept
+    pub fn with_desugar_continue(self, desugar_continue: bool) -> Self {
+        Self {
+            desugar_continue,
+            ..self
+        }
+    }
+}
+
+fn whitespace<'a>(characters: &str) -> Token<'a> {
+    Token::new(TokenType::Whitespace {
+        characters: characters.to_owned().into(),
+    })
+}
+
+fn is_blank(token: &Token) -> bool {
+    matches!(token.token_type(), TokenType::Whitespace { characters } if !characters.contains('\n'))
+}
+
+// The trivia of a removed node in order, with `None` in place of each of its tokens
+#[derive(Default)]
+struct RemovedTrivia<'a>(Vec<Option<Token<'a>>>);
+
+impl<'a> Visitor<'a> for RemovedTrivia<'a> {
+    fn visit_token_reference(&mut self, token: &TokenReference<'a>) {
+        self.0.extend(token.leading_trivia().cloned().map(Some));
+        self.0.push(None);
+        self.0.extend(token.trailing_trivia().cloned().map(Some));
+    }
+}
+
+fn removed_trivia<'a>(node: &impl Visit<'a>) -> Vec<Option<Token<'a>>> {
+    let mut removed = RemovedTrivia::default();
+    node.visit(&mut removed);
+    removed.0
+}
+
+// What has to stay of the trivia of removed code: every comment along with the indentation before it,
+// and every line break, so that line numbers don't change. `after_code` is whether the trivia is placed
+// after code on the same line, in which case a space is kept to separate the code from what comes next.
+fn kept_trivia<'a>(removed: Vec<Option<Token<'a>>>, after_code: bool) -> Vec<Token<'a>> {
+    let mut kept = Vec::new();
+    // The current line, only kept if it has a comment
+    let mut line = Vec::new();
+    let mut has_comment = false;
+    // Whether anything is printed before the current position on this line
+    let mut has_text = after_code;
+    let mut after_removed = false;
+    let mut separated = false;
+
+    for token in removed {
+        let token = match token {
+            Some(token) => token,
+            None => {
+                if has_text {
+                    while line.last().map_or(false, is_blank) {
+                        line.pop();
+                    }
+                }
+
+                after_removed = true;
+                separated = false;
+                continue;
+            }
+        };
+
+        let characters = match token.token_type() {
+            TokenType::Whitespace { characters } => characters.to_string(),
+            _ => {
+                if separated {
+                    line.push(whitespace(" "));
+                }
+
+                line.push(token);
+                has_comment = true;
+                has_text = true;
+                after_removed = false;
+                separated = false;
+                continue;
+            }
+        };
+
+        for piece in characters.split_inclusive('\n') {
+            if piece.ends_with('\n') {
+                if has_comment {
+                    line.push(whitespace(piece));
+                    kept.append(&mut line);
+                } else {
+                    line.clear();
+                    kept.push(whitespace(if piece.ends_with("\r\n") {
+                        "\r\n"
+                    } else {
+                        "\n"
+                    }));
+                }
+
+                has_comment = false;
+                has_text = false;
+                after_removed = false;
+                separated = false;
+            } else if after_removed {
+                separated = has_text;
+            } else {
+                line.push(whitespace(piece));
+            }
+        }
+    }
+
+    if has_comment {
+        let ends_with_comment = matches!(
+            line.last().map(Token::token_kind),
+            Some(TokenKind::SingleLineComment)
+        );
+
+        kept.append(&mut line);
+        if ends_with_comment {
+            kept.push(whitespace("\n"));
+        }
+    }
+
+    if separated {
+        kept.push(whitespace(" "));
+    }
+
+    kept
+}
+
+// Places the trivia that has to stay of a removed node after a token, replacing the whitespace
+// at the end of the token, as the removed node has its own
+fn attach_removed<'a>(
+    mut token: TokenReference<'a>,
+    removed: Vec<Option<Token<'a>>>,
+) -> TokenReference<'a> {
+    while token.trailing_trivia.last().map_or(false, is_blank) {
+        token.trailing_trivia.pop();
+    }
+
+    let after_code = !matches!(
+        token.trailing_trivia.last().map(Token::token_type),
+        Some(TokenType::Whitespace { .. })
+    );
+
+    let kept = kept_trivia(removed, after_code);
+    token.trailing_trivia.extend(kept);
+    token
+}
+
+fn prepend_leading<'a>(
+    mut token: TokenReference<'a>,
+    trivia: Vec<Token<'a>>,
+) -> TokenReference<'a> {
+    token.leading_trivia.splice(0..0, trivia);
+    token
+}
+
+// Removes the attributes of a function, such as `@native`, moving what has to stay of their trivia
+// before the token that came after them
+fn strip_attributes<'a>(
+    attributes: Vec<&Attribute<'a>>,
+    token: &TokenReference<'a>,
+) -> TokenReference<'a> {
+    let removed = attributes.into_iter().flat_map(removed_trivia).collect();
+    prepend_leading(token.clone(), kept_trivia(removed, false))
+}
+
+// Removes the type specifiers of a list, moving what has to stay of their trivia after what they were for
+fn strip_type_specifiers<'a, T: Visit<'a> + VisitMut<'a>>(
+    values: Punctuated<'a, T>,
+    type_specifiers: Vec<Option<&TypeSpecifier<'a>>>,
+) -> Punctuated<'a, T> {
+    values
+        .into_pairs()
+        .enumerate()
+        .map(|(index, pair)| match type_specifiers.get(index) {
+            Some(Some(type_specifier)) => pair.map(|value| {
+                let removed = removed_trivia(*type_specifier);
+                map_last_token(value, |token| attach_removed(token, removed))
+            }),
+            _ => pair,
+        })
+        .collect()
+}
+
+fn is_type_declaration(stmt: &Stmt) -> bool {
+    matches!(
+        stmt,
+        Stmt::TypeDeclaration(_) | Stmt::ExportedTypeDeclaration(_)
+    )
+}
+
+fn symbol_expression<'a>(symbol: Symbol, trailing: &str) -> Expression<'a> {
+    Expression::Value {
+        value: Box::new(Value::Symbol(TokenReference::spaced_symbol(
+            "", symbol, trailing,
+        ))),
+        type_assertion: None,
+    }
+}
+
+fn desugar_compound_assignment(compound_assignment: CompoundAssignment) -> Assignment {
+    let operator = compound_assignment.compound_operator();
+    let binop = match operator {
+        CompoundOp::PlusEqual(_) => BinOp::Plus(TokenReference::from_symbol(Symbol::Plus)),
+        CompoundOp::MinusEqual(_) => BinOp::Minus(TokenReference::from_symbol(Symbol::Minus)),
+        CompoundOp::StarEqual(_) => BinOp::Star(TokenReference::from_symbol(Symbol::Star)),
+        CompoundOp::SlashEqual(_) => BinOp::Slash(TokenReference::from_symbol(Symbol::Slash)),
+        CompoundOp::PercentEqual(_) => BinOp::Percent(TokenReference::from_symbol(Symbol::Percent)),
+        CompoundOp::CaretEqual(_) => BinOp::Caret(TokenReference::from_symbol(Symbol::Caret)),
+        CompoundOp::TwoDotsEqual(_) => BinOp::TwoDots(TokenReference::from_symbol(Symbol::TwoDots)),
+    };
+
+    let equal_token = operator.token().with_token(Token::new(TokenType::Symbol {
+        symbol: Symbol::Equal,
+    }));
+
+    let lhs = compound_assignment.lhs().clone();
+    let value = map_first_token(lhs.clone(), |mut token| {
+        token.leading_trivia.clear();
+        token
+    });
+
+    let value = map_last_token(value, |mut token| {
+        token.trailing_trivia.clear();
+        token
+    });
+
+    let value = Expression::Value {
+        value: Box::new(Value::Var(value)),
+        type_assertion: None,
+    };
+
+    // The trivia after the value goes after the parentheses it might be put in
+    let mut trailing_trivia = Vec::new();
+    let rhs = map_last_token(compound_assignment.rhs().clone(), |mut token| {
+        trailing_trivia = std::mem::take(&mut token.trailing_trivia);
+        token
+    });
+
+    let rhs = map_last_token(expr::binop(value, binop, rhs), |mut token| {
+        token.trailing_trivia = trailing_trivia;
+        token
+    });
+
+    Assignment::new(
+        std::iter::once(Pair::End(lhs)).collect(),
+        std::iter::once(Pair::End(rhs)).collect(),
+    )
+    .with_equal_token(equal_token)
+}
+
+// Whether the block has a last statement that applies to the loop it is directly in,
+// looking into `do` blocks and if statements, but not into nested loops or functions
+fn has_loop_last_stmt(block: &Block, matches: fn(&LastStmt) -> bool) -> bool {
+    block.last_stmt().map_or(false, matches)
+        || block.stmts().any(|stmt| match stmt {
+            Stmt::Do(do_block) => has_loop_last_stmt(do_block.block(), matches),
+            Stmt::If(if_stmt) => {
+                has_loop_last_stmt(if_stmt.block(), matches)
+                    || if_stmt
+                        .else_if()
+                        .into_iter()
+                        .flatten()
+                        .any(|else_if| has_loop_last_stmt(else_if.block(), matches))
+                    || if_stmt
+                        .else_block()
+                        .map_or(false, |block| has_loop_last_stmt(block, matches))
+            }
+            _ => false,
+        })
+}
+
+// The names of the variables an expression uses, including ones that are shadowed inside of it
+#[derive(Default)]
+struct UsedNames(HashSet<String>);
+
+impl<'a> Visitor<'a> for UsedNames {
+    fn visit_prefix(&mut self, prefix: &Prefix<'a>) {
+        if let Prefix::Name(name) = prefix {
+            self.0.insert(name.token().to_string());
+        }
+    }
+
+    fn visit_var(&mut self, var: &Var<'a>) {
+        if let Var::Name(name) = var {
+            self.0.insert(name.token().to_string());
+        }
+    }
+}
+
+// Whether the condition of a `repeat` loop uses a local declared in its body,
+// which would be out of scope once the body is wrapped in another loop
+fn until_uses_body_locals(repeat: &Repeat) -> bool {
+    let mut used = UsedNames::default();
+    repeat.until().visit(&mut used);
+
+    repeat.block().stmts().any(|stmt| match stmt {
+        Stmt::LocalAssignment(local_assignment) => local_assignment
+            .names()
+            .iter()
+            .any(|name| used.0.contains(&name.token().to_string())),
+        Stmt::LocalFunction(local_function) => {
+            used.0.contains(&local_function.name().token().to_string())
+        }
+        _ => false,
+    })
+}
+
+// Turns every `continue` of the loop into a `break` out of the wrapping `repeat ... until true` loop.
+// If `flag_breaks` is set, every `break` of the loop sets the break flag first.
+fn replace_loop_exits(block: Block, flag_breaks: bool) -> Block {
+    let last_stmt = block.last_stmt_with_semicolon().cloned();
+    let mut stmts = block
+        .stmts_with_semicolon()
+        .cloned()
+        .map(|(stmt, semicolon)| {
+            let stmt = match stmt {
+                Stmt::Do(do_block) => {
+                    let inner = replace_loop_exits(do_block.block().clone(), flag_breaks);
+                    Stmt::Do(Box::new(do_block.with_block(inner)))
+                }
+
+                Stmt::If(if_stmt) => {
+                    let inner = replace_loop_exits(if_stmt.block().clone(), flag_breaks);
+                    let else_if = if_stmt.else_if().map(|else_ifs| {
+                        else_ifs
+                            .iter()
+                            .cloned()
+                            .map(|else_if| {
+                                let inner =
+                                    replace_loop_exits(else_if.block().clone(), flag_breaks);
+                                else_if.with_block(inner)
+                            })
+                            .collect()
+                    });
+
+                    let r#else = if_stmt
+                        .else_block()
+                        .cloned()
+                        .map(|block| replace_loop_exits(block, flag_breaks));
+
+                    Stmt::If(Box::new(
+                        if_stmt
+                            .with_block(inner)
+                            .with_else_if(else_if)
+                            .with_else(r#else),
+                    ))
+                }
+
+                other => other,
+            };
+
+            (stmt, semicolon)
+        })
+        .collect::<Vec<_>>();
+
+    let last_stmt = match last_stmt {
+        Some((LastStmt::Continue(token), semicolon)) => {
+            let token = token.with_token(Token::new(TokenType::Symbol {
+                symbol: Symbol::Break,
+            }));
+
+            Some((LastStmt::Break(token), semicolon))
+        }
+
+        Some((LastStmt::Break(mut token), semicolon)) if flag_breaks => {
+            let leading_trivia =
+                std::mem::replace(&mut token.leading_trivia, vec![whitespace(" ")]);
+            let flag = TokenReference::new(
+                leading_trivia,
+                Token::new(TokenType::Identifier {
+                    identifier: BREAK_FLAG.into(),
+                }),
+                Vec::new(),
+            );
+
+            let assignment = Assignment::new(
+                std::iter::once(Pair::End(Var::Name(flag))).collect(),
+                std::iter::once(Pair::End(symbol_expression(Symbol::True, ""))).collect(),
+            );
+
+            stmts.push((Stmt::Assignment(assignment), None));
+            Some((LastStmt::Break(token), semicolon))
+        }
+
+        other => other,
+    };
+
+    block.with_stmts(stmts).with_last_stmt(last_stmt)
+}
+
+// Wraps the body of a loop using `continue` in a `repeat ... until true` loop, turning `continue` into `break`.
+// `repeat` is put on the same line as the start of the body, and `until true` on the same line as its end.
+// When the loop also uses `break`, a flag is set before it, and checked after the inner loop.
+fn desugar_continue<'a>(
+    block: Block<'a>,
+    mut open: TokenReference<'a>,
+    mut close: TokenReference<'a>,
+) -> (Block<'a>, TokenReference<'a>, TokenReference<'a>) {
+    if !has_loop_last_stmt(&block, |last_stmt| {
+        matches!(last_stmt, LastStmt::Continue(_))
+    }) {
+        return (block, open, close);
+    }
+
+    let flag_breaks =
+        has_loop_last_stmt(&block, |last_stmt| matches!(last_stmt, LastStmt::Break(_)));
+
+    let block = replace_loop_exits(block, flag_breaks);
+
+    let repeat_token = TokenReference::new(
+        Vec::new(),
+        Token::new(TokenType::Symbol {
+            symbol: Symbol::Repeat,
+        }),
+        std::mem::replace(&mut open.trailing_trivia, vec![whitespace(" ")]),
+    );
+
+    let until_token = TokenReference::new(
+        std::mem::take(&mut close.leading_trivia),
+        Token::new(TokenType::Symbol {
+            symbol: Symbol::Until,
+        }),
+        vec![whitespace(" ")],
+    );
+
+    let repeat = Repeat::new(symbol_expression(Symbol::True, " "))
+        .with_repeat_token(repeat_token)
+        .with_block(block)
+        .with_until_token(until_token);
+
+    let stmts = if flag_breaks {
+        let check = If::new(expr::name(BREAK_FLAG))
+            .with_then_token(TokenReference::spaced_symbol(" ", Symbol::Then, " "))
+            .with_block(Block::new().with_last_stmt(Some((
+                LastStmt::Break(TokenReference::spaced_symbol("", Symbol::Break, " ")),
+                None,
+            ))))
+            .with_end_token(TokenReference::spaced_symbol("", Symbol::End, " "));
+
+        vec![
+            (
+                stmt::local(
+                    std::iter::once(BREAK_FLAG),
+                    std::iter::once(symbol_expression(Symbol::False, " ")),
+                ),
+                None,
+            ),
+            (Stmt::Repeat(Box::new(repeat)), None),
+            (Stmt::If(Box::new(check)), None),
+        ]
+    } else {
+        vec![(Stmt::Repeat(Box::new(repeat)), None)]
+    };
+
+    (Block::new().with_stmts(stmts), open, close)
+}
+
+struct TypeStripper<'a, 'b> {
+    options: &'b StripTypesOptions,
+    // Trivia of removed statements in an otherwise empty block, placed before the next token
+    pending: Vec<Token<'a>>,
+}
+
+impl<'a> TypeStripper<'a, '_> {
+    fn flush_pending(&mut self, token: TokenReference<'a>) -> TokenReference<'a> {
+        if self.pending.is_empty() {
+            token
+        } else {
+            prepend_leading(token, std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+impl<'a> VisitorMut<'a> for TypeStripper<'a, '_> {
+    fn visit_anonymous_function(
+        &mut self,
+        function: AnonymousFunction<'a>,
+    ) -> AnonymousFunction<'a> {
+        if function.attributes().next().is_none() {
+            return function;
+        }
+
+        let function_token =
+            strip_attributes(function.attributes().collect(), function.function_token());
+
+        function
+            .with_function_token(function_token)
+            .with_attributes(Vec::new())
+    }
+
+    fn visit_block(&mut self, block: Block<'a>) -> Block<'a> {
+        if !block.stmts().any(is_type_declaration) {
+            return block;
+        }
+
+        let mut stmts = Vec::new();
+        let mut removed = Vec::new();
+
+        for (stmt, semicolon) in block.stmts_with_semicolon().cloned() {
+            if is_type_declaration(&stmt) {
+                removed.extend(removed_trivia(&stmt));
+                if let Some(semicolon) = &semicolon {
+                    removed.extend(removed_trivia(semicolon));
+                }
+
+                continue;
+            }
+
+            let stmt = if removed.is_empty() {
+                stmt
+            } else {
+                let kept = kept_trivia(std::mem::take(&mut removed), false);
+                map_first_token(stmt, |token| prepend_leading(token, kept))
+            };
+
+            stmts.push((stmt, semicolon));
+        }
+
+        let mut last_stmt = block.last_stmt_with_semicolon().cloned();
+
+        if !removed.is_empty() {
+            let kept = kept_trivia(removed, false);
+
+            if let Some((stmt, semicolon)) = last_stmt.take() {
+                let stmt = map_first_token(stmt, |token| prepend_leading(token, kept));
+                last_stmt = Some((stmt, semicolon));
+            } else if let Some((stmt, semicolon)) = stmts.pop() {
+                let append = |mut token: TokenReference<'a>| {
+                    token.trailing_trivia.extend(kept);
+                    token
+                };
+
+                stmts.push(match semicolon {
+                    Some(semicolon) => (stmt, Some(append(semicolon))),
+                    None => (map_last_token(stmt, append), None),
+                });
+            } else {
+                self.pending.extend(kept);
+            }
+        }
+
+        block.with_stmts(stmts).with_last_stmt(last_stmt)
+    }
+
+    fn visit_eof(&mut self, token: TokenReference<'a>) -> TokenReference<'a> {
+        self.flush_pending(token)
+    }
+
+    fn visit_expression(&mut self, expression: Expression<'a>) -> Expression<'a> {
+        match expression {
+            Expression::Value {
+                value,
+                type_assertion: Some(type_assertion),
+            } => {
+                let removed = removed_trivia(&type_assertion);
+                let value = map_last_token(*value, |token| attach_removed(token, removed));
+
+                Expression::Value {
+                    value: Box::new(value),
+                    type_assertion: None,
+                }
+            }
+
+            other => other,
+        }
+    }
+
+    fn visit_function_body(&mut self, body: FunctionBody<'a>) -> FunctionBody<'a> {
+        let parameters =
+            strip_type_specifiers(body.parameters().clone(), body.type_specifiers().collect());
+
+        let parameters_parentheses = match body.return_type() {
+            Some(return_type) => {
+                let removed = removed_trivia(return_type);
+                let (open, close) = body.parameters_parentheses().tokens();
+                ContainedSpan::new(open.clone(), attach_removed(close.clone(), removed))
+            }
+
+            None => body.parameters_parentheses().clone(),
+        };
+
+        let type_specifiers = vec![None; parameters.len()];
+        body.with_parameters(parameters)
+            .with_type_specifiers(type_specifiers)
+            .with_return_type(None)
+            .with_parameters_parentheses(parameters_parentheses)
+    }
+
+    fn visit_function_declaration(
+        &mut self,
+        declaration: FunctionDeclaration<'a>,
+    ) -> FunctionDeclaration<'a> {
+        if declaration.attributes().next().is_none() {
+            return declaration;
+        }
+
+        let function_token = strip_attributes(
+            declaration.attributes().collect(),
+            declaration.function_token(),
+        );
+
+        declaration
+            .with_function_token(function_token)
+            .with_attributes(Vec::new())
+    }
+
+    fn visit_generic_for(&mut self, generic_for: GenericFor<'a>) -> GenericFor<'a> {
+        let names = strip_type_specifiers(
+            generic_for.names().clone(),
+            generic_for.type_specifiers().collect(),
+        );
+        let type_specifiers = vec![None; names.len()];
+        let generic_for = generic_for
+            .with_names(names)
+            .with_type_specifiers(type_specifiers);
+
+        if !self.options.desugar_continue {
+            return generic_for;
+        }
+
+        let (block, do_token, end_token) = desugar_continue(
+            generic_for.block().clone(),
+            generic_for.do_token().clone(),
+            generic_for.end_token().clone(),
+        );
+
+        generic_for
+            .with_do_token(do_token)
+            .with_block(block)
+            .with_end_token(end_token)
+    }
+
+    fn visit_local_assignment(
+        &mut self,
+        local_assignment: LocalAssignment<'a>,
+    ) -> LocalAssignment<'a> {
+        let names = strip_type_specifiers(
+            local_assignment.names().clone(),
+            local_assignment.type_specifiers().collect(),
+        );
+
+        let type_specifiers = vec![None; names.len()];
+        local_assignment
+            .with_names(names)
+            .with_type_specifiers(type_specifiers)
+    }
+
+    fn visit_local_function(&mut self, local_function: LocalFunction<'a>) -> LocalFunction<'a> {
+        if local_function.attributes().next().is_none() {
+            return local_function;
+        }
+
+        let local_token = strip_attributes(
+            local_function.attributes().collect(),
+            local_function.local_token(),
+        );
+
+        local_function
+            .with_local_token(local_token)
+            .with_attributes(Vec::new())
+    }
+
+    fn visit_numeric_for(&mut self, numeric_for: NumericFor<'a>) -> NumericFor<'a> {
+        let numeric_for = match numeric_for.type_specifier() {
+            Some(type_specifier) => {
+                let removed = removed_trivia(type_specifier);
+                let index_variable = attach_removed(numeric_for.index_variable().clone(), removed);
+                numeric_for
+                    .with_index_variable(index_variable)
+                    .with_type_specifier(None)
+            }
+
+            None => numeric_for,
+        };
+
+        if !self.options.desugar_continue {
+            return numeric_for;
+        }
+
+        let (block, do_token, end_token) = desugar_continue(
+            numeric_for.block().clone(),
+            numeric_for.do_token().clone(),
+            numeric_for.end_token().clone(),
+        );
+
+        numeric_for
+            .with_do_token(do_token)
+            .with_block(block)
+            .with_end_token(end_token)
+    }
+
+    fn visit_repeat(&mut self, repeat: Repeat<'a>) -> Repeat<'a> {
+        if !self.options.desugar_continue || until_uses_body_locals(&repeat) {
+            return repeat;
+        }
+
+        let (block, repeat_token, until_token) = desugar_continue(
+            repeat.block().clone(),
+            repeat.repeat_token().clone(),
+            repeat.until_token().clone(),
+        );
+
+        repeat
+            .with_repeat_token(repeat_token)
+            .with_block(block)
+            .with_until_token(until_token)
+    }
+
+    fn visit_stmt(&mut self, stmt: Stmt<'a>) -> Stmt<'a> {
+        match stmt {
+            Stmt::CompoundAssignment(compound_assignment)
+                if self.options.desugar_compound_assignments =>
+            {
+                Stmt::Assignment(desugar_compound_assignment(*compound_assignment))
+            }
+
+            other => other,
+        }
+    }
+
+    fn visit_token_reference(&mut self, token: TokenReference<'a>) -> TokenReference<'a> {
+        self.flush_pending(token)
+    }
+
+    fn visit_while(&mut self, while_loop: While<'a>) -> While<'a> {
+        if !self.options.desugar_continue {
+            return while_loop;
+        }
+
+        let (block, do_token, end_token) = desugar_continue(
+            while_loop.block().clone(),
+            while_loop.do_token().clone(),
+            while_loop.end_token().clone(),
+        );
+
+        while_loop
+            .with_do_token(do_token)
+            .with_block(block)
+            .with_end_token(end_token)
+    }
+}
+
+/// Lowers Luau code to Lua 5.1 by removing its types, the same as [`strip_types_with_options`]
+/// with the default options. Compound assignments and `continue` are kept as is.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let ast = full_moon::parse("type Id = number\nlocal id: Id = (next :: any)() -- the id\n")?;
+/// let ast = full_moon::transform::strip_types(ast);
+/// assert_eq!(full_moon::print(&ast), "\nlocal id = (next)() -- the id\n");
+/// # Ok(())
+/// # }
+/// ```
+pub fn strip_types(ast: Ast) -> Ast {
+    strip_types_with_options(ast, &StripTypesOptions::default())
+}
+
+/// Lowers Luau code to Lua 5.1 by removing its types: type declarations, type specifiers on locals,
+/// parameters and for loops, return types, type assertions, keeping the asserted expression, and
+/// function attributes such as `@native`.
+/// Luau syntax that isn't types, compound assignments and `continue`, is rewritten if the options ask for it.
+/// Generic functions are not supported by the parser, so there are no generic declarations on functions to remove.
+///
+/// The code around removed nodes is kept as is. Comments inside of removed nodes are moved to the nearest token
+/// that stays, and line breaks inside of them are kept, so that every line of code stays on the same line.
+///
+/// Compound assignments evaluate the variable assigned to twice once rewritten, such as `t[f()] = t[f()] + 1`.
+/// Desugaring `continue` declares a `__break` local in loops using both `continue` and `break`.
+/// A `repeat` loop whose condition uses a local declared in its body keeps its `continue`, as the body
+/// is put inside of another loop, where its locals would be out of scope of the condition.
+pub fn strip_types_with_options<'a>(ast: Ast<'a>, options: &StripTypesOptions) -> Ast<'a> {
+    TypeStripper {
+        options,
+        pending: Vec::new(),
+    }
+    .visit_ast(ast)
+}
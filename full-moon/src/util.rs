@@ -1,61 +1,174 @@
-use crate::tokenizer::TokenReference;
-use std::{borrow::Borrow, fmt::Display};
+use crate::{
+    tokenizer::TokenReference,
+    visitors::{Visit, VisitMut, Visitor, VisitorMut},
+};
+use std::{
+    borrow::Borrow,
+    fmt::{self, Display},
+    marker::PhantomData,
+};
 
 #[cfg(feature = "roblox")]
 use crate::ast::punctuated::Punctuated;
 
-pub fn display_option<T: Display, O: Borrow<Option<T>>>(option: O) -> String {
-    match option.borrow() {
-        Some(x) => x.to_string(),
-        None => "".to_string(),
+// The helpers below are used by `Display` implementations. They return values that write
+// straight to the formatter, rather than strings, so displaying a node doesn't allocate.
+
+pub struct DisplayOption<T, O>(O, PhantomData<T>);
+
+impl<T: Display, O: Borrow<Option<T>>> Display for DisplayOption<T, O> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.borrow() {
+            Some(x) => x.fmt(formatter),
+            None => Ok(()),
+        }
     }
 }
 
-pub fn display_optional_punctuated<T: Display>(pair: &(T, Option<TokenReference<'_>>)) -> String {
-    format!("{}{}", pair.0, display_option(&pair.1))
+pub fn display_option<T: Display, O: Borrow<Option<T>>>(option: O) -> DisplayOption<T, O> {
+    DisplayOption(option, PhantomData)
 }
 
-pub fn display_optional_punctuated_vec<T: Display>(
-    vec: &[(T, Option<TokenReference<'_>>)],
-) -> String {
-    let mut string = String::new();
+pub struct DisplayOptionalPunctuated<'a, 'b, T>(&'b (T, Option<TokenReference<'a>>));
 
-    for pair in vec {
-        string.push_str(&display_optional_punctuated(pair));
+impl<T: Display> Display for DisplayOptionalPunctuated<'_, '_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}{}", self.0 .0, display_option(&self.0 .1))
     }
+}
 
-    string
+pub fn display_optional_punctuated<'a, 'b, T: Display>(
+    pair: &'b (T, Option<TokenReference<'a>>),
+) -> DisplayOptionalPunctuated<'a, 'b, T> {
+    DisplayOptionalPunctuated(pair)
 }
 
-pub fn join_vec<T: Display, V: AsRef<[T]>>(vec: V) -> String {
-    let mut string = String::new();
+pub struct DisplayOptionalPunctuatedVec<'a, 'b, T>(&'b [(T, Option<TokenReference<'a>>)]);
 
-    for item in vec.as_ref() {
-        string.push_str(&item.to_string());
+impl<T: Display> Display for DisplayOptionalPunctuatedVec<'_, '_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for pair in self.0 {
+            display_optional_punctuated(pair).fmt(formatter)?;
+        }
+
+        Ok(())
     }
+}
 
-    string
+pub fn display_optional_punctuated_vec<'a, 'b, T: Display>(
+    vec: &'b [(T, Option<TokenReference<'a>>)],
+) -> DisplayOptionalPunctuatedVec<'a, 'b, T> {
+    DisplayOptionalPunctuatedVec(vec)
+}
+
+pub struct JoinVec<T, V>(V, PhantomData<T>);
+
+impl<T: Display, V: AsRef<[T]>> Display for JoinVec<T, V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for item in self.0.as_ref() {
+            item.fmt(formatter)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn join_vec<T: Display, V: AsRef<[T]>>(vec: V) -> JoinVec<T, V> {
+    JoinVec(vec, PhantomData)
+}
+
+#[cfg(feature = "roblox")]
+pub struct JoinTypeSpecifiers<'a, 'b, T1, T2> {
+    parameters: &'b Punctuated<'a, T1>,
+    type_specifiers: &'b [Option<T2>],
 }
 
 #[cfg(feature = "roblox")]
-pub fn join_type_specifiers<'a, I: IntoIterator<Item = Option<T2>>, T1: Display, T2: Display>(
-    parameters: &Punctuated<'a, T1>,
-    type_specifiers: I,
-) -> String {
-    let mut string = String::new();
-
-    for (parameter, type_specifier) in parameters.pairs().zip(
-        type_specifiers
-            .into_iter()
-            .chain(std::iter::repeat_with(|| None)),
-    ) {
-        string.push_str(&format!(
-            "{}{}{}",
-            parameter.value(),
-            display_option(type_specifier),
-            display_option(parameter.punctuation())
-        ));
-    }
-
-    string
+impl<T1: Display, T2: Display> Display for JoinTypeSpecifiers<'_, '_, T1, T2> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (index, parameter) in self.parameters.pairs().enumerate() {
+            write!(
+                formatter,
+                "{}{}{}",
+                parameter.value(),
+                display_option(self.type_specifiers.get(index).and_then(Option::as_ref)),
+                display_option(parameter.punctuation())
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "roblox")]
+pub fn join_type_specifiers<'a, 'b, T1: Display, T2: Display>(
+    parameters: &'b Punctuated<'a, T1>,
+    type_specifiers: &'b [Option<T2>],
+) -> JoinTypeSpecifiers<'a, 'b, T1, T2> {
+    JoinTypeSpecifiers {
+        parameters,
+        type_specifiers,
+    }
+}
+
+#[derive(Default)]
+struct TokenCounter(usize);
+
+impl<'ast> Visitor<'ast> for TokenCounter {
+    fn visit_token_reference(&mut self, _: &TokenReference<'ast>) {
+        self.0 += 1;
+    }
+}
+
+// Maps the token at the given index, in the order tokens are visited
+struct TokenMapper<F> {
+    visited: usize,
+    target: usize,
+    map: Option<F>,
+}
+
+impl<'ast, F> VisitorMut<'ast> for TokenMapper<F>
+where
+    F: FnOnce(TokenReference<'ast>) -> TokenReference<'ast>,
+{
+    fn visit_token_reference(&mut self, token: TokenReference<'ast>) -> TokenReference<'ast> {
+        let index = self.visited;
+        self.visited += 1;
+
+        match self.map.take() {
+            Some(map) if index == self.target => map(token),
+            map => {
+                self.map = map;
+                token
+            }
+        }
+    }
+}
+
+pub fn map_first_token<'a, N: VisitMut<'a>>(
+    node: N,
+    map: impl FnOnce(TokenReference<'a>) -> TokenReference<'a>,
+) -> N {
+    node.visit_mut(&mut TokenMapper {
+        visited: 0,
+        target: 0,
+        map: Some(map),
+    })
+}
+
+pub fn map_last_token<'a, N: Visit<'a> + VisitMut<'a>>(
+    node: N,
+    map: impl FnOnce(TokenReference<'a>) -> TokenReference<'a>,
+) -> N {
+    let mut counter = TokenCounter::default();
+    node.visit(&mut counter);
+
+    match counter.0.checked_sub(1) {
+        Some(target) => node.visit_mut(&mut TokenMapper {
+            visited: 0,
+            target,
+            map: Some(map),
+        }),
+        None => node,
+    }
 }
@@ -77,6 +77,65 @@ macro_rules! create_visitor {
             )+
         }
 
+        /// A [`Visitor`] that is given the nodes/tokens for as long as they are borrowed, so that it can keep them,
+        /// such as to [`query`](crate::query) them. Every [`Visitor`] is one as well.
+        #[doc(hidden)]
+        pub trait VisitorRef<'b, 'ast: 'b> {
+            paste::item! {
+                $(
+                    fn $visit_name(&mut self, _node: &'b $ast_type<'ast>) { }
+                    fn [<$visit_name _end>](&mut self, _node: &'b $ast_type<'ast>) { }
+                )+
+
+                $(
+                    $(
+                        #[$meta]
+                        fn $meta_visit_name(&mut self, _node: &'b $meta_ast_type<'ast>) { }
+                        #[$meta]
+                        fn [<$meta_visit_name _end>](&mut self, _node: &'b $meta_ast_type<'ast>) { }
+                    )+
+                )+
+            }
+
+            $(
+                fn $visit_token(&mut self, _token: &'b Token<'ast>) { }
+            )+
+        }
+
+        impl<'b, 'ast: 'b, V: Visitor<'ast>> VisitorRef<'b, 'ast> for V {
+            paste::item! {
+                $(
+                    fn $visit_name(&mut self, node: &'b $ast_type<'ast>) {
+                        Visitor::$visit_name(self, node);
+                    }
+
+                    fn [<$visit_name _end>](&mut self, node: &'b $ast_type<'ast>) {
+                        Visitor::[<$visit_name _end>](self, node);
+                    }
+                )+
+
+                $(
+                    $(
+                        #[$meta]
+                        fn $meta_visit_name(&mut self, node: &'b $meta_ast_type<'ast>) {
+                            Visitor::$meta_visit_name(self, node);
+                        }
+
+                        #[$meta]
+                        fn [<$meta_visit_name _end>](&mut self, node: &'b $meta_ast_type<'ast>) {
+                            Visitor::[<$meta_visit_name _end>](self, node);
+                        }
+                    )+
+                )+
+            }
+
+            $(
+                fn $visit_token(&mut self, token: &'b Token<'ast>) {
+                    Visitor::$visit_token(self, token);
+                }
+            )+
+        }
+
         /// A trait that implements functions to listen for specific nodes/tokens.
         /// Unlike [`Visitor`], nodes/tokens passed are mutable.
         pub trait VisitorMut<'ast> {
@@ -136,7 +195,9 @@ macro_rules! create_visitor {
 
 #[doc(hidden)]
 pub trait Visit<'ast>: Sealed {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V);
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b;
 }
 
 #[doc(hidden)]
@@ -148,19 +209,28 @@ where
 }
 
 impl<'ast, T: Visit<'ast>> Visit<'ast> for &T {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         (**self).visit(visitor);
     }
 }
 
 impl<'ast, T: Visit<'ast>> Visit<'ast> for &mut T {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         (**self).visit(visitor);
     }
 }
 
 impl<'ast, T: Visit<'ast>> Visit<'ast> for Vec<T> {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         for item in self {
             item.visit(visitor);
         }
@@ -176,7 +246,10 @@ impl<'ast, T: VisitMut<'ast>> VisitMut<'ast> for Vec<T> {
 }
 
 impl<'ast, T: Visit<'ast>> Visit<'ast> for Option<T> {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         if let Some(item) = self {
             item.visit(visitor);
         }
@@ -190,7 +263,10 @@ impl<'ast, T: VisitMut<'ast>> VisitMut<'ast> for Option<T> {
 }
 
 impl<'ast, A: Visit<'ast>, B: Visit<'ast>> Visit<'ast> for (A, B) {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         self.0.visit(visitor);
         self.1.visit(visitor);
     }
@@ -203,7 +279,10 @@ impl<'ast, A: VisitMut<'ast>, B: VisitMut<'ast>> VisitMut<'ast> for (A, B) {
 }
 
 impl<'ast, T: Visit<'ast>> Visit<'ast> for Box<T> {
-    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+    fn visit<'b, V: VisitorRef<'b, 'ast>>(&'b self, visitor: &mut V)
+    where
+        'ast: 'b,
+    {
         (**self).visit(visitor);
     }
 }
@@ -216,6 +295,7 @@ impl<'ast, T: VisitMut<'ast>> VisitMut<'ast> for Box<T> {
 
 create_visitor!(ast: {
     visit_anonymous_call => FunctionArgs,
+    visit_anonymous_function => AnonymousFunction,
     visit_assignment => Assignment,
     visit_block => Block,
     visit_call => Call,
@@ -254,6 +334,7 @@ create_visitor!(ast: {
 
     // Types
     #[cfg(feature = "roblox")] {
+        visit_attribute => Attribute,
         visit_compound_assignment => CompoundAssignment,
         visit_compound_op => CompoundOp,
         visit_exported_type_declaration => ExportedTypeDeclaration,
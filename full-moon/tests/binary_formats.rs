@@ -0,0 +1,163 @@
+use full_moon::{
+    ast::{owned::Owned, Ast, VersionedAst, AST_VERSION},
+    parse, print, Error,
+};
+use serde::de::DeserializeOwned;
+use std::{fs, path::Path};
+
+// Owned ASTs can be deserialized without borrowing from the input
+fn assert_deserialize_owned<T: DeserializeOwned>() {}
+
+fn assert_round_trip(ast: &Ast<'static>, restored: Ast<'static>) {
+    assert_eq!(restored.nodes(), ast.nodes());
+    assert_eq!(restored.eof(), ast.eof());
+    assert_eq!(print(&restored), print(ast));
+}
+
+fn test_folder(folder: impl AsRef<Path>) {
+    for entry in fs::read_dir(folder).expect("couldn't read directory") {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(path.join("source.lua")).expect("couldn't read source.lua");
+        let ast = parse(&source).unwrap().owned();
+
+        let bytes = bincode::serialize(&ast).unwrap();
+        assert_round_trip(&ast, bincode::deserialize(&bytes).unwrap());
+
+        let bytes = rmp_serde::to_vec(&ast).unwrap();
+        assert_round_trip(&ast, rmp_serde::from_slice(&bytes).unwrap());
+
+        // Human readable formats still work after being read from a temporary string
+        let json = serde_json::to_string(&ast).unwrap();
+        assert_round_trip(&ast, serde_json::from_str::<Ast>(&json).unwrap().owned());
+    }
+}
+
+#[test]
+fn test_deserialize_owned() {
+    assert_deserialize_owned::<Ast<'static>>();
+}
+
+#[test]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_binary_round_trip() {
+    test_folder("./tests/cases/pass");
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_roblox_binary_round_trip() {
+    test_folder("./tests/roblox_cases/pass");
+}
+
+#[test]
+#[cfg(feature = "lua52")]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_lua52_binary_round_trip() {
+    test_folder("./tests/lua52_cases/pass");
+}
+
+#[test]
+fn test_version() {
+    let ast = parse("local x = 1 -- comment\nprint(x)\n").unwrap().owned();
+
+    let json = serde_json::to_string(&ast).unwrap();
+    assert!(json.starts_with(&format!("{{\"version\":{},", AST_VERSION)));
+
+    let versioned = serde_json::from_str::<VersionedAst>(&json).unwrap();
+    assert_eq!(versioned.version(), AST_VERSION);
+    assert_round_trip(&ast, versioned.into_ast().unwrap().owned());
+
+    // The version is the first thing in formats that aren't human readable, too
+    let mut bytes = bincode::serialize(&ast).unwrap();
+    assert_eq!(bytes[..4], AST_VERSION.to_le_bytes());
+    bytes[..4].copy_from_slice(&(AST_VERSION + 1).to_le_bytes());
+
+    assert_eq!(
+        bincode::deserialize::<VersionedAst>(&bytes)
+            .unwrap()
+            .into_ast()
+            .unwrap_err(),
+        Error::IncompatibleAstVersion {
+            found: AST_VERSION + 1,
+            expected: AST_VERSION,
+        }
+    );
+    assert!(bincode::deserialize::<Ast>(&bytes).is_err());
+}
+
+#[test]
+fn test_version_2_ast() {
+    // Version 2 Asts were serialized before formats that aren't human readable always had the
+    // origin of tokens, so their nodes can't be read anymore
+    let ast = parse("local x = 1\n").unwrap();
+    let mut bytes = bincode::serialize(&ast).unwrap();
+    bytes[..4].copy_from_slice(&2u32.to_le_bytes());
+
+    assert_eq!(
+        bincode::deserialize::<VersionedAst>(&bytes)
+            .unwrap()
+            .into_ast()
+            .unwrap_err(),
+        Error::IncompatibleAstVersion {
+            found: 2,
+            expected: 3,
+        }
+    );
+}
+
+#[test]
+fn test_version_after_nodes() {
+    let ast = parse("local x = { 1, y = \"two\" } -- comment\nprint(x)\n")
+        .unwrap()
+        .owned();
+
+    // Maps in serde_json sort their keys, so the version comes after the nodes
+    let mut value = serde_json::to_value(&ast).unwrap();
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.ends_with(&format!("\"version\":{}}}", AST_VERSION)));
+
+    let versioned = serde_json::from_str::<VersionedAst>(&json).unwrap();
+    assert_round_trip(&ast, versioned.into_ast().unwrap().owned());
+
+    value["version"] = (AST_VERSION + 1).into();
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(
+        serde_json::from_str::<VersionedAst>(&json)
+            .unwrap()
+            .into_ast()
+            .unwrap_err(),
+        Error::IncompatibleAstVersion {
+            found: AST_VERSION + 1,
+            expected: AST_VERSION,
+        }
+    );
+}
+
+#[test]
+fn test_unversioned_ast() {
+    // Serialized with JSON by the version of full-moon before versions were added
+    let json = fs::read_to_string("./tests/fixtures/unversioned_ast.json").unwrap();
+
+    let error = serde_json::from_str::<VersionedAst>(&json)
+        .unwrap()
+        .into_ast()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        Error::IncompatibleAstVersion {
+            found: 0,
+            expected: AST_VERSION,
+        }
+    );
+    assert_eq!(error.code(), "incompatible-ast-version");
+
+    let error = serde_json::from_str::<Ast>(&json).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "ast was serialized with version 0, but version {} is expected",
+            AST_VERSION
+        )
+    );
+}
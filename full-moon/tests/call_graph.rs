@@ -0,0 +1,201 @@
+use full_moon::{
+    analysis::{call_graph, CallGraph, FunctionKind},
+    parse,
+};
+
+// Describes every call as `caller -> callee`, where undefined callees are written as `path?`
+fn calls(graph: &CallGraph) -> Vec<String> {
+    graph
+        .calls()
+        .iter()
+        .map(|call| {
+            let callee = match call.callee() {
+                Some(callee) => graph.function(callee).name().to_owned(),
+                None => format!("{}?", call.path().unwrap_or("<dynamic>")),
+            };
+
+            format!("{} -> {}", graph.function(call.caller()).name(), callee)
+        })
+        .collect()
+}
+
+#[test]
+fn test_definitions() {
+    let ast = parse(
+        r#"function a.b.c() end
+function a:d() end
+local function e() end
+local f = function() end
+g = function() end
+a.h = function() end
+local i = 1, function() end
+call(function() end)"#,
+    )
+    .unwrap();
+    let graph = call_graph(&ast);
+
+    assert_eq!(
+        graph
+            .functions()
+            .map(|(_, function)| (function.name(), function.kind()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("<chunk>", FunctionKind::Chunk),
+            ("a.b.c", FunctionKind::Declaration),
+            ("a:d", FunctionKind::Method),
+            ("e", FunctionKind::LocalFunction),
+            ("f", FunctionKind::Assigned),
+            ("g", FunctionKind::Assigned),
+            ("a.h", FunctionKind::Assigned),
+        ]
+    );
+
+    let position = graph.functions().nth(2).unwrap().1.position().unwrap();
+    assert_eq!((position.line(), position.character()), (2, 10));
+}
+
+#[test]
+fn test_methods() {
+    let ast = parse(
+        r#"local Class = {}
+function Class.new() return setmetatable({}, Class) end
+function Class:a() self:b() self.c() end
+function Class:b() end
+function Class.c() end
+local object = Class.new()
+object:a()
+Class:a()"#,
+    )
+    .unwrap();
+    let graph = call_graph(&ast);
+
+    assert_eq!(
+        calls(&graph),
+        vec![
+            "Class.new -> setmetatable?",
+            "Class:a -> Class:b",
+            "Class:a -> Class.c",
+            "<chunk> -> Class.new",
+            "<chunk> -> object:a?",
+            "<chunk> -> Class:a",
+        ]
+    );
+}
+
+#[test]
+fn test_nested_functions() {
+    let ast = parse(
+        r#"local function outer()
+    local function inner()
+        helper()
+    end
+
+    pcall(function()
+        inner()
+    end)
+end
+
+function helper() end
+outer()"#,
+    )
+    .unwrap();
+    let graph = call_graph(&ast);
+
+    assert_eq!(
+        calls(&graph),
+        vec![
+            "inner -> helper",
+            "outer -> pcall?",
+            "outer -> inner",
+            "<chunk> -> outer",
+        ]
+    );
+
+    let (inner, function) = graph.functions().nth(2).unwrap();
+    assert_eq!(function.name(), "inner");
+    assert_eq!(graph.function(function.parent().unwrap()).name(), "outer");
+    assert_eq!(graph.calls_from(inner).count(), 1);
+}
+
+#[test]
+fn test_recursion() {
+    let ast = parse(
+        r#"local function fact(n) return n * fact(n - 1) end
+local fib = function(n) return fib(n - 1) end
+function even(n) return odd(n - 1) end
+function odd(n) return even(n - 1) end"#,
+    )
+    .unwrap();
+    let graph = call_graph(&ast);
+
+    // `fib` inside of its own definition is a global, as the local isn't declared yet
+    assert_eq!(
+        calls(&graph),
+        vec!["fact -> fact", "fib -> fib?", "even -> odd", "odd -> even"]
+    );
+
+    let edges = graph
+        .edges()
+        .into_iter()
+        .map(|(caller, callee)| (caller == callee, graph.function(callee).name()))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(true, "fact"), (false, "odd"), (false, "even")]);
+}
+
+#[test]
+fn test_shadowed_locals() {
+    let ast = parse(
+        r#"local function f() end
+do
+    local function f() end
+    f()
+end
+f()
+local g = {}
+function g.h() end
+do
+    local g = {}
+    g.h()
+end"#,
+    )
+    .unwrap();
+    let graph = call_graph(&ast);
+
+    let callees = graph
+        .calls()
+        .iter()
+        .map(|call| {
+            call.callee()
+                .map(|callee| graph.function(callee).position().unwrap().line())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(callees, vec![Some(3), Some(1), None]);
+}
+
+#[test]
+fn test_to_dot() {
+    let ast = parse(
+        r#"local t = {}
+function t:run() self:step() print("run") end
+function t:step() print("step") end
+t:run()
+t.callbacks[1]()"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        call_graph(&ast).to_dot(),
+        r#"digraph calls {
+    node [shape=box];
+    f0 [label="<chunk>", shape=plaintext];
+    f1 [label="t:run"];
+    f2 [label="t:step"];
+    u0 [label="print", style=dashed];
+    f1 -> f2;
+    f1 -> u0;
+    f2 -> u0;
+    f0 -> f1;
+}
+"#
+    );
+}
@@ -0,0 +1 @@
+print("\255\256")
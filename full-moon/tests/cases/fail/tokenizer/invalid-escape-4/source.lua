@@ -0,0 +1 @@
+print("unfinished\
\ No newline at end of file
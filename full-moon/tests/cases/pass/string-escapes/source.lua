@@ -0,0 +1,3 @@
+print("\a\b\f\n\r\t\v\\\"\'", '\'\"', "\0\9\65\255\0001")
+print("line\
+next")
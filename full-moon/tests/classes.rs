@@ -0,0 +1,153 @@
+use full_moon::{
+    analysis::{find_class_like_tables, ClassLikeTable},
+    node::Node,
+    parse,
+};
+
+// The name of the class, the line of its `__index` assignment, and the names of its
+// constructors and methods
+fn summarize(class: &ClassLikeTable) -> (String, Option<usize>, Vec<String>, Vec<String>) {
+    (
+        class.name().token().to_string(),
+        class
+            .index_assignment()
+            .and_then(|assignment| assignment.start_position())
+            .map(|position| position.line()),
+        class
+            .constructors()
+            .iter()
+            .map(|constructor| constructor.declaration().name().to_string())
+            .collect(),
+        class
+            .methods()
+            .iter()
+            .map(|method| method.name().to_string())
+            .collect(),
+    )
+}
+
+fn strings(strings: &[&str]) -> Vec<String> {
+    strings.iter().map(|string| string.to_string()).collect()
+}
+
+#[test]
+fn test_signal_fixture() {
+    let ast = parse(include_str!("fixtures/classes/signal.lua")).unwrap();
+    let classes = find_class_like_tables(&ast);
+
+    assert_eq!(
+        classes.iter().map(summarize).collect::<Vec<_>>(),
+        vec![
+            (
+                "Connection".to_owned(),
+                Some(3),
+                strings(&["Connection.new"]),
+                strings(&["Connection:Disconnect"]),
+            ),
+            (
+                "Signal".to_owned(),
+                Some(23),
+                strings(&["Signal.new"]),
+                strings(&["Signal:Connect", "Signal:Fire", "Signal:_remove"]),
+            ),
+        ]
+    );
+
+    // The tokens of every part point back into the source, so diagnostics can be attached
+    let signal = &classes[1];
+    assert_eq!(signal.name().start_position().unwrap().line(), 22);
+    assert_eq!(signal.definition().to_string().trim(), "local Signal = {}");
+    assert_eq!(
+        signal.constructors()[0].setmetatable().to_string().trim(),
+        "setmetatable({}, Signal)"
+    );
+}
+
+#[test]
+fn test_account_fixture() {
+    let ast = parse(include_str!("fixtures/classes/account.lua")).unwrap();
+    let classes = find_class_like_tables(&ast);
+
+    assert_eq!(
+        classes.iter().map(summarize).collect::<Vec<_>>(),
+        vec![
+            (
+                "Account".to_owned(),
+                // `self.__index = self` inside of `Account:new`
+                Some(7),
+                strings(&["Account:new"]),
+                strings(&["Account:new", "Account:deposit", "Account:withdraw"]),
+            ),
+            (
+                "SpecialAccount".to_owned(),
+                Some(23),
+                strings(&["SpecialAccount.new"]),
+                strings(&["SpecialAccount:getLimit"]),
+            ),
+            (
+                "Account".to_owned(),
+                None,
+                strings(&["Account.create"]),
+                Vec::new(),
+            ),
+        ]
+    );
+
+    assert_eq!(
+        classes[0].constructors()[0]
+            .setmetatable()
+            .to_string()
+            .trim(),
+        "setmetatable(o, self)"
+    );
+    assert_eq!(
+        classes[1].constructors()[0]
+            .setmetatable()
+            .to_string()
+            .trim(),
+        "setmetatable(self, SpecialAccount)"
+    );
+    assert_ne!(classes[0].variable(), classes[2].variable());
+}
+
+#[test]
+fn test_not_classes() {
+    for code in [
+        // Tables of functions without a metatable
+        "local M = {}\nfunction M.new() return {} end\nfunction M:method() end",
+        // The `__index` of something else
+        "local M = {}\nlocal Other = {}\nM.__index = Other",
+        "local M = {}\nM.__index = function() end",
+        // Not a table
+        "local M = f()\nM.__index = M",
+        // Globals
+        "M = {}\nM.__index = M\nfunction M.new() return setmetatable({}, M) end",
+        // A local `setmetatable`
+        "local setmetatable = f\nlocal M = {}\nfunction M.new() return setmetatable({}, M) end",
+        // `setmetatable` outside of a function declared on the class
+        "local M = {}\nlocal function new() return setmetatable({}, M) end",
+        "local M = {}\nfunction M.a.new() return setmetatable({}, M) end",
+    ] {
+        let ast = parse(code).unwrap();
+        assert!(find_class_like_tables(&ast).is_empty(), "{}", code);
+    }
+}
+
+#[test]
+fn test_nested_constructor_calls() {
+    let ast = parse(
+        "local M = {}\nfunction M.new()\n\tlocal function make() return setmetatable({}, M) end\n\treturn make()\nend\nfunction M:clone() return setmetatable({}, M) end",
+    )
+    .unwrap();
+    let classes = find_class_like_tables(&ast);
+
+    assert_eq!(
+        classes.iter().map(summarize).collect::<Vec<_>>(),
+        vec![(
+            "M".to_owned(),
+            None,
+            strings(&["M.new", "M:clone"]),
+            strings(&["M:clone"]),
+        )]
+    );
+}
@@ -0,0 +1,113 @@
+use full_moon::{
+    ast::{Ast, CompactAst},
+    node::Node,
+    parse, print,
+    tokenizer::{tokens_with_options, TokenizerOptions},
+};
+
+const DATE: &str = include_str!("../../benches/date.lua");
+const T: &str = include_str!("../../benches/t.lua");
+
+#[test]
+fn test_compact_size() {
+    let ast = parse(DATE).unwrap();
+    let full = serde_json::to_string(&ast.nodes()).unwrap();
+    let compact = serde_json::to_string(&ast.to_compact()).unwrap();
+
+    assert!(
+        full.len() > compact.len() * 5,
+        "compact form is {} bytes, full form is {} bytes",
+        compact.len(),
+        full.len()
+    );
+}
+
+#[test]
+fn test_compact_round_trip() {
+    for source in &[DATE, T] {
+        let ast = parse(source).unwrap();
+        let json = serde_json::to_string(&ast.to_compact()).unwrap();
+
+        let restored = serde_json::from_str::<CompactAst>(&json)
+            .unwrap()
+            .into_ast();
+        let code = print(&restored);
+
+        // The restored code is still valid, and has the same structure as the original
+        let reparsed = parse(&code).unwrap();
+        assert_eq!(serde_json::to_string(&reparsed.to_compact()).unwrap(), json);
+        assert_eq!(print(&reparsed), code);
+    }
+}
+
+#[test]
+fn test_compact_positions() {
+    let json = serde_json::to_string(&parse("local x = 1").unwrap().to_compact()).unwrap();
+    assert!(json.contains(r#"["Identifier","x"]"#));
+    assert!(!json.contains("position"));
+    assert!(!json.contains("trivia"));
+
+    let ast = serde_json::from_str::<CompactAst>(&json)
+        .unwrap()
+        .into_ast();
+    assert_eq!(print(&ast), "local x = 1 ");
+    let one = ast.nodes().tokens().nth(3).unwrap();
+    assert_eq!(one.to_string(), "1 ");
+    assert_eq!(one.start_position().unwrap().bytes(), 10);
+}
+
+#[test]
+fn test_compact_invalid_token() {
+    let json = serde_json::to_string(&parse("local x = 1").unwrap().to_compact()).unwrap();
+    for invalid in &[r#"["Identifier","local"]"#, r#"["Identifier","x y"]"#] {
+        let json = json.replace(r#"["Identifier","x"]"#, invalid);
+        assert!(serde_json::from_str::<CompactAst>(&json).is_err());
+    }
+}
+
+#[test]
+fn test_compact_options() {
+    // Tokens that were only allowed by the options they were tokenized with are still restored
+    let options = TokenizerOptions::new()
+        .with_allow_unicode_identifiers(true)
+        .with_invalid_escapes_as_warnings(true);
+    let (tokens, _) = tokens_with_options("local 名前 = '\\q'", &options).unwrap();
+    let ast = Ast::from_tokens(tokens).unwrap();
+    let json = serde_json::to_string(&ast.to_compact()).unwrap();
+
+    let restored = serde_json::from_str::<CompactAst>(&json)
+        .unwrap()
+        .into_ast();
+    assert_eq!(print(&restored), "local 名前 = '\\q' ");
+}
+
+#[test]
+fn test_compact_bincode() {
+    let ast = parse(DATE).unwrap();
+    let bytes = bincode::serialize(&ast.to_compact()).unwrap();
+    assert!(bytes.len() < bincode::serialize(&ast).unwrap().len());
+
+    let restored = bincode::deserialize::<CompactAst>(&bytes)
+        .unwrap()
+        .into_ast();
+    assert_eq!(
+        serde_json::to_string(&restored.to_compact()).unwrap(),
+        serde_json::to_string(&ast.to_compact()).unwrap()
+    );
+}
+
+#[test]
+fn test_compact_inside_full() {
+    // Only the tokens inside of the CompactAst are compact, even when it's serialized
+    // in the middle of other values
+    let ast = parse("local x = 1").unwrap();
+    let json = serde_json::to_value((&ast, ast.to_compact(), &ast)).unwrap();
+    assert_eq!(json[0], json[2]);
+    assert_eq!(json[0], serde_json::to_value(&ast).unwrap());
+    assert!(!json[1].to_string().contains("position"));
+
+    let (first, compact, last): (Ast, CompactAst, Ast) = serde_json::from_value(json).unwrap();
+    assert_eq!(first.nodes(), ast.nodes());
+    assert_eq!(last.nodes(), ast.nodes());
+    assert_eq!(print(&compact.into_ast()), "local x = 1 ");
+}
@@ -0,0 +1,88 @@
+use full_moon::{
+    debug::{to_dot, to_dot_with_options, DotOptions, SexprOptions},
+    parse,
+};
+
+const SOURCE: &str = "local x = \"a\\\"b\" -- comment\nprint(x)\n";
+
+#[test]
+fn test_to_dot() {
+    insta::assert_snapshot!("dot", to_dot(&parse(SOURCE).unwrap()));
+}
+
+#[test]
+fn test_to_dot_trivia() {
+    let options = DotOptions::new().with_trivia(true);
+    insta::assert_snapshot!(
+        "dot_trivia",
+        to_dot_with_options(&parse(SOURCE).unwrap(), &options)
+    );
+}
+
+#[test]
+fn test_to_dot_limits() {
+    let ast = parse(SOURCE).unwrap();
+
+    let dot = to_dot_with_options(&ast, &DotOptions::new().with_max_depth(2));
+    assert!(!dot.contains("LocalAssignment"));
+    assert_eq!(dot.matches("[label=\"...\"").count(), 2);
+
+    let dot = to_dot_with_options(&ast, &DotOptions::new().with_max_nodes(5));
+    assert_eq!(dot.matches("shape=plaintext").count(), 3);
+    assert_eq!(dot.matches("[label=").count(), 8);
+
+    // The limits are never reached
+    assert_eq!(
+        to_dot_with_options(
+            &ast,
+            &DotOptions::new().with_max_depth(100).with_max_nodes(1000)
+        ),
+        to_dot(&ast)
+    );
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+fn test_to_dot_roblox() {
+    let ast = parse("local x: { number } = y :: any").unwrap();
+    let dot = to_dot(&ast);
+
+    assert!(dot.contains("[label=\"TypeSpecifier\"]"));
+    assert!(dot.contains("[label=\"TypeAssertion\"]"));
+}
+
+#[test]
+fn test_to_sexpr() {
+    let ast = parse(SOURCE).unwrap();
+    insta::assert_snapshot!("sexpr", ast.to_sexpr(&SexprOptions::new()));
+    insta::assert_snapshot!(
+        "sexpr_no_positions",
+        ast.to_sexpr(&SexprOptions::new().with_positions(false))
+    );
+    insta::assert_snapshot!(
+        "sexpr_trivia",
+        ast.to_sexpr(&SexprOptions::new().with_trivia(true))
+    );
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+fn test_to_sexpr_roblox() {
+    let ast =
+        parse("type T<U> = { [string]: U }\nlocal x: T<number>? = y :: any\nx += 1\n").unwrap();
+    insta::assert_snapshot!("sexpr_roblox", ast.to_sexpr(&SexprOptions::new()));
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+fn test_to_sexpr_attributes() {
+    let ast = parse("@native function f() end\nlocal g = @checked function() end\n").unwrap();
+    insta::assert_snapshot!("sexpr_attributes", ast.to_sexpr(&SexprOptions::new()));
+}
+
+#[test]
+#[cfg(feature = "lua52")]
+fn test_to_sexpr_lua52() {
+    let ast = parse("::top:: goto top\n").unwrap();
+    insta::assert_snapshot!("sexpr_lua52", ast.to_sexpr(&SexprOptions::new()));
+}
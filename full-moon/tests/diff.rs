@@ -0,0 +1,164 @@
+use full_moon::{
+    diff, parse, print,
+    tokenizer::{Token, TokenReference, TokenType},
+    visitors::VisitorMut,
+    AstChange,
+};
+use std::{fs, path::Path};
+
+mod common;
+use common::run_test_folder;
+
+// Replaces all of the trivia between tokens with a single space, removing comments
+struct Reformat;
+
+impl<'ast> VisitorMut<'ast> for Reformat {
+    fn visit_token_reference(&mut self, mut token: TokenReference<'ast>) -> TokenReference<'ast> {
+        let space = || {
+            Token::new(TokenType::Whitespace {
+                characters: " ".into(),
+            })
+        };
+
+        if !token.take_leading_trivia().is_empty() {
+            token.push_leading_trivia(space()).unwrap();
+        }
+
+        if !token.take_trailing_trivia().is_empty() {
+            token.push_trailing_trivia(space()).unwrap();
+        }
+
+        token
+    }
+}
+
+// Describes every change as the code of the old and new statements
+fn changes(old_code: &str, new_code: &str) -> Vec<(Option<String>, Option<String>)> {
+    let code = |code: &str, span: Option<full_moon::tokenizer::Span>| {
+        span.map(|span| code[span.start().bytes()..span.end().bytes()].to_owned())
+    };
+
+    diff(&parse(old_code).unwrap(), &parse(new_code).unwrap())
+        .iter()
+        .map(|change| {
+            (
+                code(old_code, change.old_span()),
+                code(new_code, change.new_span()),
+            )
+        })
+        .collect()
+}
+
+fn test_reformatted_case(path: &Path) {
+    let source = fs::read_to_string(path.join("source.lua")).expect("couldn't read source.lua");
+    let ast = parse(&source).unwrap();
+    assert_eq!(diff(&ast, &ast), Vec::new());
+
+    let reformatted = print(&Reformat.visit_ast(ast.clone()));
+    let reformatted_ast = parse(&reformatted)
+        .unwrap_or_else(|error| panic!("couldn't parse {:?}: {}", reformatted, error));
+    assert_eq!(diff(&ast, &reformatted_ast), Vec::new());
+}
+
+#[test]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_reformatted_pass_cases() {
+    run_test_folder("./tests/cases/pass", test_reformatted_case);
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_reformatted_roblox_pass_cases() {
+    run_test_folder("./tests/roblox_cases/pass", test_reformatted_case);
+}
+
+#[test]
+#[cfg(feature = "lua52")]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_reformatted_lua52_pass_cases() {
+    run_test_folder("./tests/lua52_cases/pass", test_reformatted_case);
+}
+
+#[test]
+fn test_statement_changes() {
+    let old_code = "local a = 1\nlocal b = 2\nprint(a, b)\nreturn a";
+    let new_code = "local a = 1; local c = 3\nprint(a,b)\nprint(c)\nreturn a, c";
+
+    assert_eq!(
+        changes(old_code, new_code),
+        vec![
+            (
+                Some("local b = 2".to_owned()),
+                Some("local c = 3".to_owned())
+            ),
+            (None, Some("print(c)".to_owned())),
+            (Some("return a".to_owned()), Some("return a, c".to_owned())),
+        ]
+    );
+
+    assert_eq!(
+        changes("a() b() c()", "a() c()"),
+        vec![(Some("b()".to_owned()), None)]
+    );
+}
+
+#[test]
+fn test_nested_changes() {
+    let old_code = r#"local function f(x)
+    if x then
+        print("x")
+    elseif y then
+        print("y")
+    end
+end
+
+while true do
+    break
+end"#;
+
+    // Only the statements inside of the blocks are changed, not the headers
+    let new_code = r#"local function f(x)
+    if x then print("x") elseif y then
+        print("y", 1)
+        return
+    end
+end
+
+while false do
+    break
+end"#;
+
+    assert_eq!(
+        changes(old_code, new_code),
+        vec![
+            (
+                Some(r#"print("y")"#.to_owned()),
+                Some(r#"print("y", 1)"#.to_owned())
+            ),
+            (None, Some("return".to_owned())),
+            (
+                Some("while true do\n    break\nend".to_owned()),
+                Some("while false do\n    break\nend".to_owned())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_large_block_fallback() {
+    let old_code = (0..2048).map(|i| format!("a{}()\n", i)).collect::<String>();
+    let new_code = (0..2048).map(|i| format!("b{}()\n", i)).collect::<String>();
+
+    let changes = diff(&parse(&old_code).unwrap(), &parse(&new_code).unwrap());
+    assert_eq!(changes.len(), 1);
+
+    match changes[0] {
+        AstChange::Modified { old, new } => {
+            assert_eq!(old.start().bytes(), 0);
+            assert_eq!(old.end().bytes(), old_code.trim_end().len());
+            assert_eq!(new.end().bytes(), new_code.trim_end().len());
+        }
+        other => panic!("expected the whole block to be modified, got {:?}", other),
+    }
+}
@@ -0,0 +1,205 @@
+use full_moon::{
+    ast::{Expression, Stmt, TableKey, Value},
+    node::Node,
+    parse,
+};
+
+// Every duplicate in the table assigned by `code`, as the key, the first field, and the duplicate
+fn duplicate_keys(code: &str) -> Vec<(TableKey, String, String)> {
+    let code = format!("local t = {}", code);
+    let ast = parse(&code).unwrap();
+    let value = match ast.nodes().stmts().next() {
+        Some(Stmt::LocalAssignment(assignment)) => match assignment.expressions().iter().next() {
+            Some(Expression::Value { value, .. }) => value,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+
+    match &**value {
+        Value::TableConstructor(table) => table
+            .duplicate_keys()
+            .into_iter()
+            .map(|duplicate| {
+                (
+                    duplicate.key().clone(),
+                    duplicate.first().to_string().trim().to_owned(),
+                    duplicate.duplicate().to_string().trim().to_owned(),
+                )
+            })
+            .collect(),
+        _ => panic!("{} is not a table constructor", code),
+    }
+}
+
+fn string(key: &str) -> TableKey {
+    TableKey::String(key.as_bytes().to_vec())
+}
+
+#[test]
+fn test_string_keys() {
+    for (code, first, duplicate) in [
+        ("{ x = 1, x = 2 }", "x = 1", "x = 2"),
+        ("{ x = 1, [\"x\"] = 2 }", "x = 1", "[\"x\"] = 2"),
+        ("{ ['x'] = 1, x = 2 }", "['x'] = 1", "x = 2"),
+        (
+            "{ [\"x\"] = 1, [ [[x]] ] = 2 }",
+            "[\"x\"] = 1",
+            "[ [[x]] ] = 2",
+        ),
+        ("{ x = 1, [\"\\120\"] = 2 }", "x = 1", "[\"\\120\"] = 2"),
+        ("{ x = 1, [(\"x\")] = 2 }", "x = 1", "[(\"x\")] = 2"),
+    ] {
+        assert_eq!(
+            duplicate_keys(code),
+            vec![(string("x"), first.to_owned(), duplicate.to_owned())],
+            "{}",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_number_keys() {
+    for (code, key) in [
+        ("{ [1] = 1, [1] = 2 }", 1.0),
+        ("{ [1] = 1, [1.0] = 2 }", 1.0),
+        ("{ [1] = 1, [1e0] = 2 }", 1.0),
+        ("{ [16] = 1, [0x10] = 2 }", 16.0),
+        ("{ [0.5] = 1, [.5] = 2 }", 0.5),
+        ("{ [-1] = 1, [-1.0] = 2 }", -1.0),
+        ("{ [-1] = 1, [-(1)] = 2 }", -1.0),
+        ("{ [100] = 1, [1e2] = 2 }", 100.0),
+        ("{ [0] = 1, [-0] = 2 }", 0.0),
+        ("{ [0] = 1, [-0.0] = 2 }", 0.0),
+    ] {
+        let duplicates = duplicate_keys(code);
+        assert_eq!(duplicates.len(), 1, "{}", code);
+        assert_eq!(duplicates[0].0, TableKey::Number(key), "{}", code);
+    }
+}
+
+#[test]
+fn test_implicit_indices() {
+    assert_eq!(
+        duplicate_keys("{ \"a\", [1] = \"b\" }"),
+        vec![(
+            TableKey::Number(1.0),
+            "\"a\"".to_owned(),
+            "[1] = \"b\"".to_owned()
+        )]
+    );
+
+    assert_eq!(
+        duplicate_keys("{ [2] = \"b\", \"a\", f() }"),
+        vec![(
+            TableKey::Number(2.0),
+            "[2] = \"b\"".to_owned(),
+            "f()".to_owned()
+        )]
+    );
+
+    assert_eq!(
+        duplicate_keys("{ ..., [1] = 2 }"),
+        vec![(
+            TableKey::Number(1.0),
+            "...".to_owned(),
+            "[1] = 2".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn test_every_duplicate_is_reported() {
+    let duplicates = duplicate_keys("{ x = 1, y = 2, x = 3, x = 4 }");
+    assert_eq!(
+        duplicates
+            .iter()
+            .map(|(_, first, duplicate)| (first.as_str(), duplicate.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("x = 1", "x = 3"), ("x = 1", "x = 4")]
+    );
+}
+
+#[test]
+fn test_distinct_keys() {
+    for code in [
+        "{}",
+        "{ x = 1, y = 2 }",
+        "{ x = 1, [\"X\"] = 2 }",
+        "{ [\"1\"] = 1, [1] = 2 }",
+        "{ [1] = 1, [-1] = 2 }",
+        "{ \"a\", \"b\", [3] = \"c\" }",
+        // Keys that aren't constants are never duplicates
+        "{ [x] = 1, [x] = 2 }",
+        "{ [f()] = 1, [f()] = 2 }",
+        "{ [\"a\" .. \"b\"] = 1, [\"ab\"] = 2 }",
+        "{ [1 + 1] = 1, [2] = 2 }",
+    ] {
+        let duplicates = duplicate_keys(code);
+        assert!(duplicates.is_empty(), "{}: {:?}", code, duplicates);
+    }
+}
+
+#[test]
+fn test_positions() {
+    let ast = parse("local t = {\n\tx = 1,\n\tx = 2,\n}").unwrap();
+    let table = match ast.nodes().stmts().next() {
+        Some(Stmt::LocalAssignment(assignment)) => match assignment.expressions().iter().next() {
+            Some(Expression::Value { value, .. }) => match &**value {
+                Value::TableConstructor(table) => table,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+
+    let duplicates = table.duplicate_keys();
+    let lines = (
+        duplicates[0].first().start_position().unwrap().line(),
+        duplicates[0].duplicate().start_position().unwrap().line(),
+    );
+    assert_eq!(lines, (2, 3));
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_type_keys() {
+    for (code, expected) in [
+        ("{ x: number, x: string }", vec![("x: number", "x: string")]),
+        ("{ x: number, y: string }", vec![]),
+        ("{ [string]: number, [string]: string }", vec![]),
+        (
+            "{ x: number, y: number, x: string, x: boolean }",
+            vec![("x: number", "x: string"), ("x: number", "x: boolean")],
+        ),
+    ] {
+        let source = format!("type T = {}", code);
+        let ast = parse(&source).unwrap();
+        let type_info = match ast.nodes().stmts().next() {
+            Some(Stmt::TypeDeclaration(declaration)) => declaration.type_definition(),
+            _ => unreachable!(),
+        };
+
+        let duplicates = type_info.duplicate_keys();
+        assert!(duplicates
+            .iter()
+            .all(|duplicate| duplicate.key() == &string("x")));
+        assert_eq!(
+            duplicates
+                .iter()
+                .map(|duplicate| (
+                    duplicate.first().to_string().trim().to_owned(),
+                    duplicate.duplicate().to_string().trim().to_owned()
+                ))
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|(first, duplicate)| (first.to_string(), duplicate.to_string()))
+                .collect::<Vec<_>>(),
+            "{}",
+            code
+        );
+    }
+}
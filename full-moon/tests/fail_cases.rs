@@ -1,5 +1,5 @@
-use full_moon::{ast, tokenizer};
-use insta::assert_yaml_snapshot;
+use full_moon::{ast, tokenizer, Error};
+use insta::{assert_json_snapshot, assert_yaml_snapshot};
 use std::fs;
 
 mod common;
@@ -19,6 +19,7 @@ fn test_parser_fail_cases() {
             Ok(_) => panic!("fail case passed for {:?}", path),
             Err(error) => {
                 println!("error {:#?}", error);
+                assert_json_snapshot!("error_json", Error::AstError(error.clone()));
                 assert_yaml_snapshot!("error", error);
             }
         }
@@ -34,6 +35,7 @@ fn test_tokenizer_fail_cases() {
         match tokenizer::tokens(&source) {
             Ok(_) => panic!("fail case passed for {:?}", path),
             Err(error) => {
+                assert_json_snapshot!("error_json", Error::TokenizerError(error.clone()));
                 assert_yaml_snapshot!("error", error);
             }
         }
@@ -55,6 +57,7 @@ fn test_roblox_parser_fail_cases() {
             Ok(_) => panic!("fail case passed for {:?}", path),
             Err(error) => {
                 println!("error {:#?}", error);
+                assert_json_snapshot!("error_json", Error::AstError(error.clone()));
                 assert_yaml_snapshot!("error", error);
             }
         }
@@ -76,6 +79,7 @@ fn test_lua52_parser_fail_cases() {
             Ok(_) => panic!("fail case passed for {:?}", path),
             Err(error) => {
                 println!("error {:#?}", error);
+                assert_json_snapshot!("error_json", Error::AstError(error.clone()));
                 assert_yaml_snapshot!("error", error);
             }
         }
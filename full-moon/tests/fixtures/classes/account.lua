@@ -0,0 +1,44 @@
+-- Classes in the style of Programming in Lua
+local Account = { balance = 0 }
+
+function Account:new(o)
+	o = o or {}
+	setmetatable(o, self)
+	self.__index = self
+	return o
+end
+
+function Account:deposit(v)
+	self.balance = self.balance + v
+end
+
+function Account:withdraw(v)
+	if v > self.balance then
+		error("insufficient funds")
+	end
+	self.balance = self.balance - v
+end
+
+local SpecialAccount = setmetatable({}, { __index = Account })
+SpecialAccount.__index = SpecialAccount
+
+function SpecialAccount.new(limit)
+	local self = Account:new({ limit = limit or 1000 })
+	setmetatable(self, SpecialAccount)
+	return self
+end
+
+function SpecialAccount:getLimit()
+	return self.limit or 0
+end
+
+do
+	-- A different local with the same name is a different class
+	local Account = {}
+
+	function Account.create()
+		return setmetatable({}, Account)
+	end
+end
+
+return { Account = Account, SpecialAccount = SpecialAccount }
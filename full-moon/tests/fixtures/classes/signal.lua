@@ -0,0 +1,59 @@
+-- A Roblox style signal, with connections that can be disconnected
+local Connection = {}
+Connection.__index = Connection
+
+function Connection.new(signal, callback)
+	return setmetatable({
+		_signal = signal,
+		_callback = callback,
+		connected = true,
+	}, Connection)
+end
+
+function Connection:Disconnect()
+	if not self.connected then
+		return
+	end
+
+	self.connected = false
+	self._signal:_remove(self)
+end
+
+local Signal = {}
+Signal.__index = Signal
+
+function Signal.new()
+	local self = setmetatable({}, Signal)
+	self._connections = {}
+	return self
+end
+
+function Signal.is(object)
+	return type(object) == "table" and getmetatable(object) == Signal
+end
+
+function Signal:Connect(callback)
+	local connection = Connection.new(self, callback)
+	table.insert(self._connections, connection)
+	return connection
+end
+
+function Signal:Fire(...)
+	for _, connection in ipairs(self._connections) do
+		task.spawn(connection._callback, ...)
+	end
+end
+
+function Signal:_remove(connection)
+	local index = table.find(self._connections, connection)
+	if index then
+		table.remove(self._connections, index)
+	end
+end
+
+-- Not a class, only a table of functions
+local util = {}
+
+function util.noop() end
+
+return Signal
@@ -0,0 +1,31 @@
+-- A fixture for analysis::stats
+local Queue = {}
+
+--[[
+	Adds an item to the end of the queue
+]]
+function Queue:push(item, ...)
+	if item == nil then
+		return
+	end
+
+	for _, extra in ipairs({ ... }) do
+		while extra do
+			extra = nil
+		end
+	end
+
+	self[#self + 1] = item -- append
+end
+
+local function sum(a, b)
+	return a + b
+end
+
+local callback = function() end
+
+do
+	print(sum(1, 2), callback)
+end
+
+return Queue
@@ -0,0 +1,5 @@
+print("\a\b\f\n\r\t\v\\\"\'", '\'\"', "\0\9\65\255\0001")
+print("line\
+next")
+print("\x41\xfF\x00", "skip \z
+      whitespace")
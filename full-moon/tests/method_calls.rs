@@ -0,0 +1,162 @@
+use full_moon::{
+    ast::{AnonymousFunction, Ast, FunctionCall, FunctionName, LocalFunction, Stmt},
+    parse,
+};
+
+fn call<'a>(ast: &'a Ast) -> &'a FunctionCall<'a> {
+    match ast.nodes().stmts().next() {
+        Some(Stmt::FunctionCall(call)) => call,
+        _ => panic!("{:?} is not a function call", full_moon::print(ast)),
+    }
+}
+
+fn to_dot_call(code: &str) -> Option<String> {
+    Some(call(&parse(code).unwrap()).to_dot_call()?.to_string())
+}
+
+fn to_method_call(code: &str) -> Option<String> {
+    Some(call(&parse(code).unwrap()).to_method_call()?.to_string())
+}
+
+#[test]
+fn test_method_declarations() {
+    let ast =
+        parse("function a.b:c() end\nfunction a.b.c(self) end\nlocal function d() end").unwrap();
+    let stmts = ast.nodes().stmts().collect::<Vec<_>>();
+
+    let (method, function) = match (stmts[0], stmts[1]) {
+        (Stmt::FunctionDeclaration(method), Stmt::FunctionDeclaration(function)) => {
+            (method, function)
+        }
+        _ => unreachable!(),
+    };
+
+    assert!(method.is_method());
+    assert_eq!(method.name().method_name().unwrap().to_string(), "c");
+    assert_eq!(method.name().method_colon().unwrap().to_string(), ":");
+    assert!(method.body().has_implicit_self());
+
+    assert!(!function.is_method());
+    assert!(!function.body().has_implicit_self());
+
+    match stmts[2] {
+        Stmt::LocalFunction(local_function) => {
+            assert!(!local_function.body().has_implicit_self())
+        }
+        _ => unreachable!(),
+    }
+
+    // The body follows the name, and bodies taken out of methods lose their implicit self
+    let renamed = method
+        .clone()
+        .with_name(FunctionName::new(function.name().names().clone()));
+    assert!(!renamed.body().has_implicit_self());
+
+    let method_name = Some((
+        method.name().method_colon().unwrap().clone(),
+        method.name().method_name().unwrap().clone(),
+    ));
+    let renamed = function
+        .clone()
+        .with_name(function.name().clone().with_method(method_name));
+    assert!(renamed.is_method());
+    assert!(renamed.body().has_implicit_self());
+
+    let local_function = LocalFunction::new(method.name().method_name().unwrap().clone())
+        .with_body(method.body().clone());
+    assert!(!local_function.body().has_implicit_self());
+    assert!(!AnonymousFunction::new()
+        .with_body(method.body().clone())
+        .body()
+        .has_implicit_self());
+}
+
+#[test]
+fn test_is_method_call() {
+    for (code, method_name) in [
+        ("obj:m()", Some("m")),
+        ("obj.m(obj)", None),
+        ("obj:m():n()", Some("n")),
+        ("obj:m()()", None),
+    ] {
+        let ast = parse(code).unwrap();
+        let call = call(&ast);
+
+        assert_eq!(call.is_method_call(), method_name.is_some(), "{}", code);
+        assert_eq!(
+            call.method_name().map(|name| name.to_string()).as_deref(),
+            method_name
+        );
+    }
+}
+
+#[test]
+fn test_to_dot_call() {
+    for (code, expected) in [
+        ("obj:m()", "obj.m(obj)"),
+        ("obj:m(a, b)", "obj.m(obj, a, b)"),
+        ("a.b.c:m(x)", "a.b.c.m(a.b.c, x)"),
+        ("obj : m (a) -- comment", "obj . m (obj, a) -- comment"),
+        ("obj:m \"x\"", "obj.m(obj, \"x\")"),
+        ("obj:m { 1 }", "obj.m(obj, { 1 })"),
+        ("obj:m{ 1 }\nx = 1", "obj.m(obj, { 1 })\n"),
+    ] {
+        assert_eq!(to_dot_call(code).as_deref(), Some(expected), "{}", code);
+    }
+}
+
+#[test]
+fn test_to_dot_call_refused() {
+    for code in [
+        // Not a method call
+        "obj.m(obj)",
+        "obj:m()()",
+        // Receivers that would be evaluated twice
+        "obj:m(a):n(b)",
+        "f():m()",
+        "obj[key]:m()",
+        "(obj):m()",
+        "(\"text\"):upper()",
+    ] {
+        assert_eq!(to_dot_call(code), None, "{}", code);
+    }
+}
+
+#[test]
+fn test_to_method_call() {
+    for (code, expected) in [
+        ("obj.m(obj)", "obj:m()"),
+        ("obj.m(obj, a, b)", "obj:m(a, b)"),
+        ("a.b.c.m(a.b.c, x)", "a.b.c:m(x)"),
+        ("obj . m (obj, a) -- comment", "obj : m (a) -- comment"),
+    ] {
+        assert_eq!(to_method_call(code).as_deref(), Some(expected), "{}", code);
+    }
+}
+
+#[test]
+fn test_to_method_call_refused() {
+    for code in [
+        "obj:m()",
+        "obj.m()",
+        "obj.m(other)",
+        "obj.m(obj.field)",
+        "obj.m \"x\"",
+        "m(obj)",
+        // Receivers with side effects
+        "f().m(f())",
+        "obj[key].m(obj[key])",
+        "obj:n().m(obj)",
+        "f():g().m(obj)",
+    ] {
+        assert_eq!(to_method_call(code), None, "{}", code);
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    for code in ["obj:m(a, b)", "self.items:insert(item)", "x:y()"] {
+        let dot_call = to_dot_call(code).unwrap();
+        assert_eq!(to_method_call(&dot_call).as_deref(), Some(code));
+    }
+}
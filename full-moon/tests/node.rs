@@ -1,4 +1,11 @@
-use full_moon::{node::Node, parse};
+#[cfg(feature = "roblox")]
+use full_moon::ast::types::TypeInfo;
+use full_moon::{
+    ast::{self, punctuated::Punctuated},
+    node::{Node, NodeKind},
+    parse, query,
+    tokenizer::{Span, TokenReference},
+};
 
 #[test]
 fn surrounding_trivia() {
@@ -36,3 +43,382 @@ fn test_tokens_back() {
     let mut tokens = source.nodes().tokens();
     assert_eq!(tokens.next_back().unwrap().to_string(), "1");
 }
+
+#[test]
+fn test_span() {
+    let ast = parse("local x = 1\nlocal y = 2").unwrap();
+    let stmts = ast.nodes().stmts().collect::<Vec<_>>();
+    let first = stmts[0].span().unwrap();
+    let second = stmts[1].span().unwrap();
+
+    assert_eq!(first.start(), stmts[0].start_position().unwrap());
+    assert_eq!(first.end(), stmts[0].end_position().unwrap());
+    assert_eq!(first.len(), "local x = 1".len());
+    assert!(first.start() < second.start());
+
+    assert!(first.contains(first.start()));
+    assert!(!first.contains(first.end()));
+    assert!(!first.contains(second.start()));
+    assert!(first.overlaps(&first));
+
+    let union = first.union(&second);
+    assert_eq!(union, second.union(&first));
+    assert_eq!(union, ast.nodes().span().unwrap());
+    assert!(union.overlaps(&first) && union.overlaps(&second));
+}
+
+#[test]
+fn test_span_touching() {
+    let ast = parse("x=y").unwrap();
+    let tokens = ast
+        .nodes()
+        .tokens()
+        .map(|token| token.span().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(tokens[0].end(), tokens[1].start());
+    assert!(!tokens[0].overlaps(&tokens[1]));
+    assert!(!tokens[1].overlaps(&tokens[0]));
+    assert!(!tokens[0].contains(tokens[1].start()));
+    assert_eq!(tokens[0].union(&tokens[2]).len(), 3);
+}
+
+#[test]
+fn test_span_empty() {
+    let synthesized = TokenReference::symbol("local").unwrap().span().unwrap();
+    assert!(synthesized.is_empty());
+    assert_eq!(synthesized.len(), 0);
+    assert!(!synthesized.contains(synthesized.start()));
+    assert!(!synthesized.overlaps(&synthesized));
+
+    let ast = parse("local x = 1").unwrap();
+    let tokens = ast.nodes().tokens().collect::<Vec<_>>();
+    let name = tokens[1].token().end_position();
+    let inside = Span::new(name, name);
+    let statement = ast.nodes().span().unwrap();
+
+    assert!(inside.overlaps(&statement) && statement.overlaps(&inside));
+    assert!(!Span::new(statement.start(), statement.start()).overlaps(&statement));
+    assert!(!Span::new(statement.end(), statement.end()).overlaps(&statement));
+    assert_eq!(inside.union(&statement), statement);
+}
+
+fn boundary_tokens<'a>(node: &impl Node<'a>) -> (Option<String>, Option<String>) {
+    (
+        node.first_token().map(|token| token.token().to_string()),
+        node.last_token().map(|token| token.token().to_string()),
+    )
+}
+
+fn some(first: &str, last: &str) -> (Option<String>, Option<String>) {
+    (Some(first.to_owned()), Some(last.to_owned()))
+}
+
+#[test]
+fn test_first_last_token() {
+    let ast = parse(
+        r#"local x, y = 1, (2)
+function t.a:b(c, d) return c end
+call { 1, 2, }
+while x do end"#,
+    )
+    .unwrap();
+    let stmts = ast.nodes().stmts().collect::<Vec<_>>();
+
+    assert_eq!(boundary_tokens(ast.nodes()), some("local", "end"));
+    assert_eq!(boundary_tokens(&ast), some("local", "end"));
+
+    let local_assignment = match stmts[0] {
+        ast::Stmt::LocalAssignment(local_assignment) => local_assignment,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(local_assignment), some("local", ")"));
+    assert_eq!(boundary_tokens(local_assignment.names()), some("x", "y"));
+
+    let mut expressions = local_assignment.expressions().iter();
+    assert_eq!(boundary_tokens(expressions.next().unwrap()), some("1", "1"));
+    // Parentheses return their delimiters
+    assert_eq!(boundary_tokens(expressions.next().unwrap()), some("(", ")"));
+    // Pairs include their punctuation
+    assert_eq!(
+        boundary_tokens(local_assignment.expressions().pairs().next().unwrap()),
+        some("1", ",")
+    );
+
+    let declaration = match stmts[1] {
+        ast::Stmt::FunctionDeclaration(declaration) => declaration,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(declaration), some("function", "end"));
+    assert_eq!(boundary_tokens(declaration.name()), some("t", "b"));
+    assert_eq!(boundary_tokens(declaration.body()), some("(", "end"));
+    assert_eq!(
+        boundary_tokens(declaration.body().parameters_parentheses()),
+        some("(", ")")
+    );
+    assert_eq!(
+        boundary_tokens(declaration.body().block()),
+        some("return", "c")
+    );
+
+    let call = match stmts[2] {
+        ast::Stmt::FunctionCall(call) => call,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(call), some("call", "}"));
+    assert_eq!(
+        boundary_tokens(call.suffixes().next().unwrap()),
+        some("{", "}")
+    );
+
+    let while_loop = match stmts[3] {
+        ast::Stmt::While(while_loop) => while_loop,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(while_loop), some("while", "end"));
+}
+
+#[test]
+fn test_first_last_token_empty() {
+    let ast = parse("").unwrap();
+    assert_eq!(boundary_tokens(ast.nodes()), (None, None));
+
+    let ast = parse("local function x() end").unwrap();
+    let body = match ast.nodes().stmts().next().unwrap() {
+        ast::Stmt::LocalFunction(local_function) => local_function.body(),
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(body.parameters()), (None, None));
+    assert_eq!(boundary_tokens(body.block()), (None, None));
+    assert_eq!(
+        boundary_tokens(&Punctuated::<ast::Expression>::new()),
+        (None, None)
+    );
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_first_last_token_types() {
+    let ast = parse(
+        r#"type Callback<T> = (T, ...string) -> { [string]: number? }
+local x: typeof(y) = z :: any"#,
+    )
+    .unwrap();
+    let stmts = ast.nodes().stmts().collect::<Vec<_>>();
+
+    let declaration = match stmts[0] {
+        ast::Stmt::TypeDeclaration(declaration) => declaration,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(declaration), some("type", "}"));
+    assert_eq!(boundary_tokens(&declaration.generics()), some("<", ">"));
+    assert_eq!(
+        boundary_tokens(declaration.type_definition()),
+        some("(", "}")
+    );
+
+    let return_type = match declaration.type_definition() {
+        TypeInfo::Callback { return_type, .. } => return_type,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(return_type), some("{", "}"));
+
+    let field = match &**return_type {
+        TypeInfo::Table { fields, .. } => fields.iter().next().unwrap(),
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(field), some("[", "?"));
+    assert_eq!(boundary_tokens(field.key()), some("[", "]"));
+
+    let local_assignment = match stmts[1] {
+        ast::Stmt::LocalAssignment(local_assignment) => local_assignment,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(local_assignment), some("local", "any"));
+
+    let type_specifier = local_assignment.type_specifiers().next().unwrap();
+    assert_eq!(boundary_tokens(&type_specifier), some(":", ")"));
+
+    let type_assertion = match local_assignment.expressions().iter().next().unwrap() {
+        ast::Expression::Value {
+            type_assertion: Some(type_assertion),
+            ..
+        } => type_assertion,
+        _ => unreachable!(),
+    };
+    assert_eq!(boundary_tokens(type_assertion), some("::", "any"));
+}
+
+#[test]
+fn test_kind() {
+    let ast = parse("local x = a.b(1, 2)\nprint(-x)\nt.y = x").unwrap();
+
+    let mut counts = std::collections::HashMap::new();
+    for kind in query::<ast::Expression>(&ast).map(Node::kind) {
+        *counts.entry(kind).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts[&NodeKind::ExpressionValue], 5);
+    assert_eq!(counts[&NodeKind::ExpressionUnaryOperator], 1);
+
+    let kinds = query::<ast::Var>(&ast)
+        .map(|var| var.kind().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, vec!["VarName", "VarComplex", "VarName"]);
+
+    assert_eq!(ast.kind(), NodeKind::Ast);
+    assert_eq!(
+        ast.nodes().stmts().collect::<Vec<_>>().kind(),
+        NodeKind::Vec
+    );
+    assert_eq!(None::<TokenReference>.kind(), NodeKind::None);
+    assert_eq!(ast.nodes().last_stmt().kind(), NodeKind::None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_kind_serialize() {
+    assert_eq!(
+        serde_json::to_string(&NodeKind::LocalAssignment).unwrap(),
+        r#""LocalAssignment""#
+    );
+}
+
+// Every kind in NodeKind comes from a node, so the list can't keep kinds that no node has anymore
+#[test]
+fn test_every_kind() {
+    let mut code = String::from(
+        r#"
+local a, b = 1, "s", {}
+local function f(x, ...) return x end
+function t.m:n(y) end
+x = function() end
+t.y, t["z"], (f)().w = #a, not b, true
+do break end
+for i = 1, 10, 2 do end
+for k, v in pairs(t) do end
+if a then elseif b then else end
+while a do end
+repeat until a
+print(a .. b, -a, a + b - c * d / e % f ^ g, a == b, a ~= b, a < b, a <= b, a > b, a >= b, a and b or c, (a))
+f "s"
+f { 1, x = 2, [3] = 4 }
+t:m(1)(2)
+return f(...)
+"#,
+    );
+
+    if cfg!(feature = "roblox") {
+        code.insert_str(
+            0,
+            r#"
+type T<U> = { [string]: number, x: string?, y: (number, ...string) -> (a | b) & c, z: M.T, w: M.T<U> }
+export type V = typeof(x) | { number } | U<number>
+local y: number = a :: any
+@native function g() end
+a += 1 a -= 1 a *= 1 a /= 1 a %= 1 a ^= 1 a ..= "s"
+while a do continue end
+"#,
+        );
+    }
+
+    if cfg!(feature = "lua52") {
+        code.insert_str(0, "goto label\n::label::\n");
+    }
+
+    let ast = parse(&code).unwrap();
+    let mut kinds = std::collections::HashSet::new();
+    kinds.insert(ast.kind());
+
+    macro_rules! collect_kinds {
+        ($($node:ty,)+) => {
+            $(
+                kinds.extend(query::<$node>(&ast).map(Node::kind));
+            )+
+        };
+    }
+
+    collect_kinds! {
+        ast::AnonymousFunction,
+        ast::Assignment,
+        ast::Block,
+        ast::Call,
+        ast::Do,
+        ast::ElseIf,
+        ast::Expression,
+        ast::Field,
+        ast::FunctionArgs,
+        ast::FunctionBody,
+        ast::FunctionCall,
+        ast::FunctionDeclaration,
+        ast::FunctionName,
+        ast::GenericFor,
+        ast::If,
+        ast::Index,
+        ast::LocalAssignment,
+        ast::LocalFunction,
+        ast::LastStmt,
+        ast::MethodCall,
+        ast::NumericFor,
+        ast::Parameter,
+        ast::Prefix,
+        ast::Return,
+        ast::Repeat,
+        ast::Stmt,
+        ast::Suffix,
+        ast::TableConstructor,
+        TokenReference,
+        ast::UnOp,
+        ast::Value,
+        ast::Var,
+        ast::VarExpression,
+        ast::While,
+    }
+
+    #[cfg(feature = "roblox")]
+    collect_kinds! {
+        ast::types::Attribute,
+        ast::types::CompoundAssignment,
+        ast::types::CompoundOp,
+        ast::types::ExportedTypeDeclaration,
+        ast::types::GenericDeclaration,
+        ast::types::IndexedTypeInfo,
+        ast::types::TypeAssertion,
+        ast::types::TypeDeclaration,
+        ast::types::TypeField,
+        ast::types::TypeFieldKey,
+        ast::types::TypeInfo,
+        ast::types::TypeSpecifier,
+    }
+
+    #[cfg(feature = "lua52")]
+    collect_kinds! {
+        ast::lua52::Goto,
+        ast::lua52::Label,
+    }
+
+    // Binary operators, spans, and the containers of nodes aren't searched for on their own
+    for expression in query::<ast::Expression>(&ast) {
+        if let ast::Expression::BinaryOperator { binop, .. } = expression {
+            kinds.insert(binop.kind());
+        }
+    }
+
+    for local_assignment in query::<ast::LocalAssignment>(&ast) {
+        let names = local_assignment.names();
+        kinds.insert(names.kind());
+        kinds.extend(names.pairs().map(Node::kind));
+    }
+
+    let table = query::<ast::TableConstructor>(&ast).next().unwrap();
+    kinds.insert(table.braces().kind());
+    kinds.insert(ast.nodes().stmts().collect::<Vec<_>>().kind());
+    kinds.insert((table.braces(), table.fields()).kind());
+    kinds.insert(ast.nodes().last_stmt().and(None::<TokenReference>).kind());
+
+    let missing = NodeKind::ALL
+        .iter()
+        .filter(|kind| !kinds.contains(kind))
+        .collect::<Vec<_>>();
+    assert!(missing.is_empty(), "no node has the kinds {:?}", missing);
+}
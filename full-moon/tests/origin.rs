@@ -0,0 +1,98 @@
+use full_moon::{
+    ast::{builders::expr, Ast, Expression, Value, Var},
+    node::Node,
+    parse, print,
+    tokenizer::{Origin, TokenReference},
+    visitors::VisitorMut,
+};
+
+// Replaces the variable `y` with a variable of the given name created with builders
+struct ReplaceY(&'static str);
+
+impl<'ast> VisitorMut<'ast> for ReplaceY {
+    fn visit_expression(&mut self, expression: Expression<'ast>) -> Expression<'ast> {
+        match &expression {
+            Expression::Value { value, .. } => match &**value {
+                Value::Var(Var::Name(name)) if name.token().to_string() == "y" => {
+                    expr::name(self.0)
+                }
+                _ => expression,
+            },
+
+            _ => expression,
+        }
+    }
+}
+
+fn replaced() -> Ast<'static> {
+    ReplaceY("z").visit_ast(parse("local x = 1\nprint(x + y)\n").unwrap())
+}
+
+fn synthesized(ast: &Ast) -> Vec<String> {
+    ast.all_tokens()
+        .filter(|token| !token.is_from_source())
+        .map(|token| token.to_string())
+        .collect()
+}
+
+#[test]
+fn test_parsed_tokens_are_from_source() {
+    let ast = parse("local x = 1\nprint(x + y)\n").unwrap();
+    assert!(ast.all_tokens().all(TokenReference::is_from_source));
+    assert_eq!(ast.eof().origin(), Origin::Source);
+}
+
+#[test]
+fn test_builders_are_synthesized() {
+    let ast = replaced();
+    assert_eq!(print(&ast), "local x = 1\nprint(x + z)\n");
+    assert_eq!(synthesized(&ast), vec!["z"]);
+
+    // The origin doesn't change how tokens compare, once their positions are the same
+    let parsed = parse("local x = 1\nprint(x + z)\n").unwrap();
+    assert_eq!(ast.update_positions().nodes(), parsed.nodes());
+}
+
+#[test]
+fn test_update_positions_keeps_origin() {
+    let ast = replaced().update_positions();
+    assert_eq!(synthesized(&ast), vec!["z"]);
+}
+
+#[test]
+fn test_update_synthesized_positions() {
+    let ast = ReplaceY("longer").visit_ast(parse("print(y, abc)").unwrap());
+    assert_eq!(print(&ast), "print(longer, abc)");
+
+    let position = |ast: &Ast, name: &str| {
+        let token = ast
+            .all_tokens()
+            .find(|token| token.token().to_string() == name)
+            .unwrap();
+        let token = token.token();
+        (token.start_position().bytes(), token.end_position().bytes())
+    };
+
+    // Tokens from the source keep pointing into the original code
+    let only_synthesized = ast.clone().update_synthesized_positions();
+    assert_eq!(position(&only_synthesized, "longer"), (6, 12));
+    assert_eq!(position(&only_synthesized, "abc"), (9, 12));
+
+    let all = ast.update_positions();
+    assert_eq!(position(&all, "longer"), (6, 12));
+    assert_eq!(position(&all, "abc"), (14, 17));
+}
+
+#[test]
+fn test_origin_serde() {
+    let ast = replaced();
+
+    let json = serde_json::to_string(&ast).unwrap();
+    assert_eq!(json.matches("\"origin\"").count(), 1);
+    let restored: Ast = serde_json::from_str(&json).unwrap();
+    assert_eq!(synthesized(&restored), vec!["z"]);
+
+    let bytes = bincode::serialize(&ast).unwrap();
+    let restored: Ast = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(synthesized(&restored), vec!["z"]);
+}
@@ -0,0 +1,141 @@
+use full_moon::ast::{builders::expr, Expression, LastStmt};
+
+fn expression(code: &str) -> Expression<'static> {
+    let mut expression = expr::name("x");
+    expression.splice(code).unwrap();
+    expression
+}
+
+fn without_redundant_parentheses(code: &str) -> String {
+    expression(code).without_redundant_parentheses().to_string()
+}
+
+#[test]
+fn test_parentheses_are_redundant() {
+    for (code, redundant) in [
+        ("(x)", Some(true)),
+        ("(a.b)", Some(true)),
+        ("((a + b))", Some(true)),
+        ("((f()))", Some(true)),
+        // These depend on where the expression is used
+        ("(a + b)", None),
+        ("(not a)", None),
+        ("(f())", None),
+        ("(...)", None),
+        ("(\"x\")", None),
+        ("x", None),
+        ("(a) + b", None),
+    ] {
+        assert_eq!(
+            expression(code).parentheses_are_redundant(),
+            redundant,
+            "{}",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_return_call_is_not_redundant() {
+    let ast = full_moon::parse("return (f())").unwrap();
+    let returns = match ast.nodes().last_stmt() {
+        Some(LastStmt::Return(r#return)) => r#return.returns(),
+        _ => unreachable!(),
+    };
+
+    let value = returns.iter().next().unwrap();
+    assert_eq!(value.parentheses_are_redundant(), None);
+    assert_eq!(
+        value.clone().without_redundant_parentheses().to_string(),
+        "(f())"
+    );
+}
+
+#[test]
+fn test_precedence() {
+    for (code, expected) in [
+        ("(a + b) * c", "(a + b) * c"),
+        ("(a) + b", "a + b"),
+        ("a * (b * c)", "a * (b * c)"),
+        ("(a * b) * c", "a * b * c"),
+        ("a + (b * c)", "a + b * c"),
+        ("a - (b - c)", "a - (b - c)"),
+        ("a ^ (b ^ c)", "a ^ b ^ c"),
+        ("(a ^ b) ^ c", "(a ^ b) ^ c"),
+        ("a .. (b .. c)", "a .. b .. c"),
+        ("-(a ^ b)", "-a ^ b"),
+        ("-(a * b)", "-(a * b)"),
+        ("(-a) ^ b", "(-a) ^ b"),
+        ("(not a) == b", "not a == b"),
+        ("a ^ (-b)", "a ^ -b"),
+        ("((a + b)) * c", "(a + b) * c"),
+        ("(((a))) and b", "a and b"),
+    ] {
+        assert_eq!(without_redundant_parentheses(code), expected, "{}", code);
+    }
+}
+
+#[test]
+fn test_multiple_values() {
+    for (code, expected) in [
+        ("f((g()))", "f((g()))"),
+        ("f((g()), (...))", "f(g(), (...))"),
+        ("f((a))", "f(a)"),
+        ("a:b((g()), (g()))", "a:b(g(), (g()))"),
+        ("{ (f()) }", "{ (f()) }"),
+        ("{ (f()), (...), 1 }", "{ f(), ..., 1 }"),
+        ("{ x = (f()), [(g())] = (...) }", "{ x = f(), [g()] = ... }"),
+        ("t[(f())]", "t[f()]"),
+        ("(f()) + 1", "f() + 1"),
+        ("(f())", "(f())"),
+    ] {
+        assert_eq!(without_redundant_parentheses(code), expected, "{}", code);
+    }
+}
+
+#[test]
+fn test_prefixes() {
+    for (code, expected) in [
+        ("(\"x\"):upper()", "(\"x\"):upper()"),
+        ("({}).x", "({}).x"),
+        ("(a + b).x", "(a + b).x"),
+        ("(a).b", "a.b"),
+        ("(a.b)(c)", "a.b(c)"),
+        ("((f())):g()", "f():g()"),
+        ("((a + (b))).c", "(a + b).c"),
+    ] {
+        assert_eq!(without_redundant_parentheses(code), expected, "{}", code);
+    }
+}
+
+#[test]
+fn test_trivia() {
+    for (code, expected) in [
+        ("a-(-b)", "a- -b"),
+        ("not(x)", "not x"),
+        ("(x)and y", "x and y"),
+        ("(1)..x", "1 ..x"),
+        ("t[([[s]])]", "t[ [[s]]]"),
+        ("f((a),(b))", "f(a,b)"),
+        // Whitespace is only kept next to comments
+        ("( --[[ a ]] a ) + b", " --[[ a ]] a  + b"),
+        ("( a ) + b", "a  + b"),
+        ("(a -- a\n) + b", "a -- a\n + b"),
+    ] {
+        assert_eq!(without_redundant_parentheses(code), expected, "{:?}", code);
+    }
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+fn test_type_assertions() {
+    for (code, expected) in [
+        ("(x :: T) < y", "(x :: T) < y"),
+        ("(x :: T).y", "(x :: T).y"),
+        ("f((x :: T))", "f(x :: T)"),
+        ("(a + b) :: T", "(a + b) :: T"),
+        ("(a) :: T", "a :: T"),
+    ] {
+        assert_eq!(without_redundant_parentheses(code), expected, "{}", code);
+    }
+}
@@ -1,12 +1,12 @@
 use full_moon::{
     ast,
-    node::Node,
-    print,
+    node::{Node, NodeKind},
+    print, query,
     tokenizer::{self, Token, TokenReference},
 };
 use insta::assert_yaml_snapshot;
 use pretty_assertions::assert_eq;
-use std::{fmt, fs, path::Path};
+use std::{collections::HashMap, fmt, fs, path::Path};
 
 mod common;
 use common::run_test_folder;
@@ -40,6 +40,10 @@ fn test_pass_case(path: &Path) {
     let ast = ast::Ast::from_tokens(tokens)
         .unwrap_or_else(|error| panic!("couldn't make ast for {:?} - {:?}", path, error));
 
+    let parsed = full_moon::parse(&source).expect("couldn't parse");
+    assert_eq!(parsed.nodes(), ast.nodes());
+    assert_eq!(parsed.eof(), ast.eof());
+
     let old_positions: Vec<_> = ast.tokens().flat_map(unpack_token_reference).collect();
     let ast = ast.update_positions();
     assert_eq!(
@@ -51,6 +55,119 @@ fn test_pass_case(path: &Path) {
 
     assert_yaml_snapshot!("ast", ast.nodes());
     assert_eq!(PrettyString(&print(&ast)), PrettyString(&source));
+
+    let mut written = Vec::new();
+    full_moon::write(&ast, &mut written).unwrap();
+    assert_eq!(written, source.as_bytes());
+
+    let mut formatted = String::new();
+    full_moon::fmt_to(&ast, &mut formatted).unwrap();
+    assert_eq!(formatted, source);
+    assert_eq!(
+        PrettyString(
+            &ast.all_tokens()
+                .map(ToString::to_string)
+                .collect::<String>()
+        ),
+        PrettyString(&source),
+    );
+
+    for stmt in query::<ast::Stmt>(&ast) {
+        assert_boundary_tokens(stmt);
+    }
+
+    for expression in query::<ast::Expression>(&ast) {
+        assert_boundary_tokens(expression);
+    }
+
+    assert_node_kinds(&ast);
+}
+
+// Every node has a kind, and no two kinds have the same name
+fn assert_node_kinds(ast: &ast::Ast) {
+    let mut names = HashMap::new();
+    names.insert(NodeKind::Ast.name(), ast.kind());
+
+    macro_rules! collect_kinds {
+        ($($node:ty,)+) => {
+            $(
+                for node in query::<$node>(ast) {
+                    let kind = node.kind();
+                    assert_eq!(*names.entry(kind.name()).or_insert(kind), kind);
+                }
+            )+
+        };
+    }
+
+    collect_kinds! {
+        ast::AnonymousFunction,
+        ast::Assignment,
+        ast::Block,
+        ast::Call,
+        ast::Do,
+        ast::ElseIf,
+        ast::Expression,
+        ast::Field,
+        ast::FunctionArgs,
+        ast::FunctionBody,
+        ast::FunctionCall,
+        ast::FunctionDeclaration,
+        ast::FunctionName,
+        ast::GenericFor,
+        ast::If,
+        ast::Index,
+        ast::LocalAssignment,
+        ast::LocalFunction,
+        ast::LastStmt,
+        ast::MethodCall,
+        ast::NumericFor,
+        ast::Parameter,
+        ast::Prefix,
+        ast::Return,
+        ast::Repeat,
+        ast::Stmt,
+        ast::Suffix,
+        ast::TableConstructor,
+        TokenReference,
+        ast::UnOp,
+        ast::Value,
+        ast::Var,
+        ast::VarExpression,
+        ast::While,
+    }
+
+    #[cfg(feature = "roblox")]
+    collect_kinds! {
+        ast::types::Attribute,
+        ast::types::CompoundAssignment,
+        ast::types::CompoundOp,
+        ast::types::ExportedTypeDeclaration,
+        ast::types::GenericDeclaration,
+        ast::types::IndexedTypeInfo,
+        ast::types::TypeAssertion,
+        ast::types::TypeDeclaration,
+        ast::types::TypeField,
+        ast::types::TypeFieldKey,
+        ast::types::TypeInfo,
+        ast::types::TypeSpecifier,
+    }
+
+    #[cfg(feature = "lua52")]
+    collect_kinds! {
+        ast::lua52::Goto,
+        ast::lua52::Label,
+    }
+}
+
+fn assert_boundary_tokens<'a>(node: &impl Node<'a>) {
+    assert_eq!(
+        node.first_token().and_then(Node::start_position),
+        node.start_position()
+    );
+    assert_eq!(
+        node.last_token().and_then(Node::end_position),
+        node.end_position()
+    );
 }
 
 #[test]
@@ -0,0 +1,248 @@
+use full_moon::{
+    ast::{LineIndex, PositionEncoder},
+    parse,
+    tokenizer::{tokens, Position, Token, TokenType},
+};
+use proptest::prelude::*;
+use std::{fs, path::Path};
+
+// Every position of the token must agree with the line index and the code
+fn check_token(code: &str, index: &LineIndex, token: &Token) {
+    let start = token.start_position();
+    let end = token.end_position();
+
+    assert_eq!(&code[start.bytes()..end.bytes()], token.to_string());
+
+    for position in [start, end] {
+        check_position(code, index, position);
+    }
+
+    // End positions of tokens ending in a new line are still on the line they end
+    assert_eq!(
+        index.line_column(start.bytes()),
+        Some((start.line(), start.byte_column()))
+    );
+}
+
+fn check_position(code: &str, index: &LineIndex, position: Position) {
+    let line_start = index.line_start(position.line()).unwrap();
+    assert_eq!(line_start + position.byte_column(), position.bytes());
+    assert_eq!(index.position_offset(position), Some(position.bytes()));
+
+    let line = &code[line_start..position.bytes()];
+    let newlines = line.matches('\n').count();
+    assert!(newlines == 0 || (newlines == 1 && line.ends_with('\n')));
+    assert_eq!(
+        line.chars().filter(|&character| character != '\n').count() + 1,
+        position.character()
+    );
+
+    // With a tab width of 1, every character is one column
+    assert_eq!(
+        position.display_column(1, &code[line_start..]),
+        position.character()
+    );
+}
+
+fn check_code(code: &str) {
+    let index = LineIndex::new(code);
+    for token in tokens(code).unwrap() {
+        check_token(code, &index, &token);
+    }
+}
+
+fn test_folder(folder: impl AsRef<Path>) {
+    for entry in fs::read_dir(folder).expect("couldn't read directory") {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(path.join("source.lua")).expect("couldn't read source.lua");
+
+        for code in [source.clone(), source.replace('\n', "\r\n")] {
+            // Strings continued onto the next line with a backslash can't end lines with CRLF
+            if tokens(&code).is_err() {
+                continue;
+            }
+
+            check_code(&code);
+
+            let ast = parse(&code).unwrap();
+            let index = ast.line_index();
+            assert_eq!(index, LineIndex::new(&code));
+
+            let ast = ast.update_positions();
+            for token_reference in ast.all_tokens() {
+                for token in token_reference
+                    .leading_trivia()
+                    .chain(std::iter::once(token_reference.token()))
+                    .chain(token_reference.trailing_trivia())
+                {
+                    check_token(&code, &index, token);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_byte_column() {
+    let code = "local s = '😀é'\r\n-- ü\r\nreturn s";
+    let positions = tokens(code)
+        .unwrap()
+        .iter()
+        .map(|token| {
+            let position = token.start_position();
+            (
+                position.line(),
+                position.character(),
+                position.byte_column(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        positions,
+        vec![
+            (1, 1, 0),
+            (1, 6, 5),
+            (1, 7, 6),
+            (1, 8, 7),
+            (1, 9, 8),
+            (1, 10, 9),
+            (1, 11, 10),
+            (1, 15, 18),
+            (2, 1, 0),
+            (2, 5, 5),
+            (3, 1, 0),
+            (3, 7, 6),
+            (3, 8, 7),
+            (3, 9, 8),
+        ]
+    );
+}
+
+#[test]
+fn test_display_column() {
+    // Tabs before the tokens, inside of the string, and after spaces that don't reach a tab stop
+    let code = "local a\n\t  x\t= '\ty' .. z";
+    let encoder = PositionEncoder::new(code);
+
+    let columns = tokens(code)
+        .unwrap()
+        .iter()
+        .filter(|token| {
+            token.start_position().line() == 2
+                && !token.token_type().is_trivia()
+                && *token.token_type() != TokenType::Eof
+        })
+        .flat_map(|token| [token.start_position(), token.end_position()])
+        .map(|position| {
+            let line = &code[position.bytes() - position.byte_column()..];
+            let columns = (
+                position.character(),
+                position.display_column(4, line),
+                position.display_column(8, line),
+            );
+
+            assert_eq!(encoder.display_column(position, 4), columns.1);
+            assert_eq!(encoder.display_column(position, 8), columns.2);
+            columns
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        columns,
+        vec![
+            // x
+            (4, 7, 11),
+            (5, 8, 12),
+            // =
+            (6, 9, 17),
+            (7, 10, 18),
+            // '\ty'
+            (8, 11, 19),
+            (12, 15, 27),
+            // ..
+            (13, 16, 28),
+            (15, 18, 30),
+            // z
+            (16, 19, 31),
+            (17, 20, 32),
+        ]
+    );
+
+    // A tab width of 0 is treated as 1
+    let x = tokens("\t  x").unwrap()[1].start_position();
+    assert_eq!(x.display_column(0, "\t  x"), 4);
+}
+
+#[test]
+fn test_line_index() {
+    let index = LineIndex::new("a\r\n\nbc");
+    assert_eq!(index.line_starts(), &[0, 3, 4]);
+    assert_eq!(index.line_count(), 3);
+    assert_eq!(index.line_start(0), None);
+    assert_eq!(index.line_start(4), None);
+
+    assert_eq!(index.line_column(0), Some((1, 0)));
+    assert_eq!(index.line_column(2), Some((1, 2)));
+    assert_eq!(index.line_column(3), Some((2, 0)));
+    assert_eq!(index.line_column(6), Some((3, 2)));
+    assert_eq!(index.line_column(7), None);
+
+    assert_eq!(index.offset(1, 3), Some(3));
+    assert_eq!(index.offset(1, 4), None);
+    assert_eq!(index.offset(3, 2), Some(6));
+    assert_eq!(index.offset(3, 3), None);
+}
+
+#[test]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_pass_cases() {
+    test_folder("./tests/cases/pass");
+}
+
+#[test]
+#[cfg(feature = "roblox")]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_roblox_pass_cases() {
+    test_folder("./tests/roblox_cases/pass");
+}
+
+#[test]
+#[cfg(feature = "lua52")]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_lua52_pass_cases() {
+    test_folder("./tests/lua52_cases/pass");
+}
+
+fn piece() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(vec![
+        "local",
+        "x",
+        "=",
+        "..",
+        "1",
+        "0x1F",
+        "'é'",
+        "\"😀\\\"\"",
+        "[[𝄞\r\nü]]",
+        "-- ü",
+        "--[==[ 😀\n]==]",
+    ])
+}
+
+fn separator() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(vec![" ", "\t", "\n", "\r\n", "\r\n\r\n", " \n "])
+}
+
+proptest! {
+    #[test]
+    fn test_positions_slice_source(pieces in prop::collection::vec((piece(), separator()), 0..40)) {
+        let code = pieces
+            .into_iter()
+            .flat_map(|(piece, separator)| [piece, separator])
+            .collect::<String>();
+
+        prop_assume!(tokens(&code).is_ok());
+        check_code(&code);
+    }
+}
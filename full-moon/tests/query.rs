@@ -0,0 +1,62 @@
+use full_moon::{
+    ast::{Expression, FunctionCall, FunctionDeclaration},
+    parse, print, query, query_mut,
+};
+
+#[test]
+fn test_query_document_order() {
+    let ast = parse(
+        "function a() function b() end end\nlocal t = { function() function c() end end }\nfunction d() end",
+    )
+    .unwrap();
+
+    let names = query::<FunctionDeclaration>(&ast)
+        .map(|declaration| declaration.name().to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["a", "b", "c", "d"]);
+}
+
+#[test]
+fn test_query_nested_expressions() {
+    let ast = parse("x = f(g(1), { h() })(i())").unwrap();
+
+    let calls = query::<FunctionCall>(&ast)
+        .map(|call| call.to_string().trim().to_owned())
+        .collect::<Vec<_>>();
+
+    assert_eq!(calls, vec!["f(g(1), { h() })(i())", "g(1)", "h()", "i()"]);
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_query_types() {
+    use full_moon::ast::types::TypeInfo;
+
+    let ast = parse("type T = { a: string?, b: (number) -> typeof(x) }").unwrap();
+    assert_eq!(
+        query::<TypeInfo>(&ast)
+            .filter(|type_info| matches!(type_info, TypeInfo::Optional { .. }))
+            .count(),
+        1
+    );
+
+    assert_eq!(query::<Expression>(&ast).count(), 1);
+}
+
+#[test]
+fn test_query_mut() {
+    let ast = parse("print((1), ((2)), {(3)})").unwrap();
+    let ast = query_mut(ast, |expression: &mut Expression| {
+        while let Expression::Parentheses {
+            expression: inner, ..
+        } = expression
+        {
+            *expression = (**inner).clone();
+        }
+    });
+
+    assert_eq!(print(&ast), "print(1, 2, {3})");
+    assert!(!query::<Expression>(&ast)
+        .any(|expression| matches!(expression, Expression::Parentheses { .. })));
+}
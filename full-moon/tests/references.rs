@@ -0,0 +1,85 @@
+use full_moon::{analysis::references, node::Node, parse, tokenizer::TokenReference};
+
+// Finds the identifier tokens with the given name, in the order they appear
+fn find<'a, 'b>(ast: &'b full_moon::ast::Ast<'a>, name: &str) -> Vec<&'b TokenReference<'a>> {
+    ast.nodes()
+        .tokens()
+        .filter(|token| token.token().to_string() == name)
+        .collect()
+}
+
+fn lines(tokens: &[&TokenReference]) -> Vec<usize> {
+    tokens
+        .iter()
+        .map(|token| token.token().start_position().line())
+        .collect()
+}
+
+#[test]
+fn test_shadowing() {
+    let ast = parse("local x = 1\nprint(x)\nlocal x = x\nprint(x)").unwrap();
+    let index = references(&ast);
+    let x = find(&ast, "x");
+    assert_eq!(x.len(), 5);
+
+    assert_eq!(lines(index.references_of(x[0])), vec![1, 2, 3]);
+    assert_eq!(lines(index.references_of(x[1])), vec![1, 2, 3]);
+    assert_eq!(lines(index.references_of(x[2])), vec![3, 4]);
+    assert_eq!(index.definition_of(x[3]), Some(x[0]));
+    assert_eq!(index.definition_of(x[4]), Some(x[2]));
+}
+
+#[test]
+fn test_sibling_scopes() {
+    let ast = parse("do local a = 1 print(a) end\ndo local a = 2 print(a) end").unwrap();
+    let index = references(&ast);
+    let a = find(&ast, "a");
+
+    assert_eq!(lines(index.references_of(a[1])), vec![1, 1]);
+    assert_eq!(lines(index.references_of(a[3])), vec![2, 2]);
+    assert_eq!(index.definition_of(a[3]), Some(a[2]));
+}
+
+#[test]
+fn test_globals() {
+    let ast = parse("print(1)\nlocal function f() print(2) end\nprint = nil").unwrap();
+    let index = references(&ast);
+    let print = find(&ast, "print");
+
+    assert_eq!(lines(index.references_of(print[1])), vec![1, 2, 3]);
+    assert_eq!(index.definition_of(print[0]), None);
+}
+
+#[test]
+fn test_definition_sites() {
+    let ast = parse(
+        "local t = {}\nfunction t:m(a)\nfor i = 1, a do end\nfor k in self do end\nreturn self, a\nend",
+    )
+    .unwrap();
+    let index = references(&ast);
+
+    let a = find(&ast, "a");
+    assert_eq!(index.definition_of(a[2]), Some(a[0]));
+    assert_eq!(lines(index.references_of(a[0])), vec![2, 3, 5]);
+
+    let i = find(&ast, "i");
+    assert_eq!(index.definition_of(i[0]), Some(i[0]));
+
+    let k = find(&ast, "k");
+    assert_eq!(index.definition_of(k[0]), Some(k[0]));
+
+    let m = find(&ast, "m");
+    let this = find(&ast, "self");
+    assert_eq!(index.definition_of(this[0]), Some(m[0]));
+    assert_eq!(lines(index.references_of(this[1])), vec![4, 5]);
+}
+
+#[test]
+fn test_not_a_variable() {
+    let ast = parse("local t = {}\nprint(t.x)").unwrap();
+    let index = references(&ast);
+    let x = find(&ast, "x");
+
+    assert!(index.references_of(x[0]).is_empty());
+    assert_eq!(index.definition_of(x[0]), None);
+}
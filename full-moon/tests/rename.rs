@@ -0,0 +1,124 @@
+use full_moon::{
+    parse, print,
+    refactor::{rename, RenameError},
+    tokenizer::TokenReference,
+};
+
+// Renames the nth identifier token with the given name
+fn rename_nth(code: &str, name: &str, nth: usize, new_name: &str) -> Result<String, RenameError> {
+    let ast = parse(code).unwrap();
+    let target: TokenReference = ast
+        .all_tokens()
+        .filter(|token| token.token().to_string() == name)
+        .nth(nth)
+        .unwrap()
+        .clone();
+
+    rename(ast, &target, new_name).map(|ast| print(&ast))
+}
+
+#[test]
+fn test_rename_preserves_trivia() {
+    let code = "local x --[[ the x ]] = 1\nlocal function f(x)\n\treturn x -- inner\nend\nprint( x , f(x) )\n";
+
+    assert_eq!(
+        rename_nth(code, "x", 0, "value").unwrap(),
+        "local value --[[ the x ]] = 1\nlocal function f(x)\n\treturn x -- inner\nend\nprint( value , f(value) )\n"
+    );
+
+    assert_eq!(
+        rename_nth(code, "x", 2, "argument").unwrap(),
+        "local x --[[ the x ]] = 1\nlocal function f(argument)\n\treturn argument -- inner\nend\nprint( x , f(x) )\n"
+    );
+}
+
+#[test]
+fn test_rename_from_reference() {
+    assert_eq!(
+        rename_nth("for i = 1, 10 do print(i) end", "i", 1, "index").unwrap(),
+        "for index = 1, 10 do print(index) end"
+    );
+}
+
+#[test]
+fn test_rename_capture() {
+    // The new name is used by a reference inside the scope of the variable
+    assert_eq!(
+        rename_nth("local a = 1\nlocal b = 2\nprint(a, b)", "a", 0, "b"),
+        Err(RenameError::Capture)
+    );
+
+    // The renamed variable would shadow a global
+    assert_eq!(
+        rename_nth("local a = 1\nprint(a, b)", "a", 0, "b"),
+        Err(RenameError::Capture)
+    );
+
+    // An inner variable would shadow the renamed variable
+    assert_eq!(
+        rename_nth("local a = 1\ndo local b = 2 print(a) end", "a", 0, "b"),
+        Err(RenameError::Capture)
+    );
+
+    // A sibling scope using the same name is fine
+    assert_eq!(
+        rename_nth(
+            "do local a = 1 print(a) end\ndo local b = 2 print(b) end",
+            "a",
+            0,
+            "b"
+        )
+        .unwrap(),
+        "do local b = 1 print(b) end\ndo local b = 2 print(b) end"
+    );
+}
+
+#[test]
+fn test_rename_errors() {
+    assert_eq!(
+        rename_nth("print(x)", "x", 0, "y"),
+        Err(RenameError::Global)
+    );
+    assert_eq!(
+        rename_nth("local t = {}\nprint(t.x)", "x", 0, "y"),
+        Err(RenameError::NotAVariable)
+    );
+    assert_eq!(
+        rename_nth(
+            "local t = {}\nfunction t:m() return self end",
+            "self",
+            0,
+            "this"
+        ),
+        Err(RenameError::NotAVariable)
+    );
+    assert_eq!(
+        rename_nth("local x = 1", "x", 0, "end"),
+        Err(RenameError::InvalidName)
+    );
+    assert_eq!(
+        rename_nth("local x = 1", "x", 0, "1x"),
+        Err(RenameError::InvalidName)
+    );
+    assert_eq!(
+        rename_nth("local x = 1", "x", 0, "a b"),
+        Err(RenameError::InvalidName)
+    );
+}
+
+#[test]
+fn test_rename_after_splice() {
+    // The spliced in tokens have their positions cleared, so they aren't where they are printed
+    let ast = parse("local x = 1\nprint(x)").unwrap();
+    let mut block = ast.nodes().clone();
+    block.splice(1, "local y = x").unwrap();
+    let ast = ast.with_nodes(block);
+
+    let target = ast.all_tokens().nth(1).unwrap().clone();
+    assert_eq!(target.to_string(), "x ");
+
+    assert_eq!(
+        print(&rename(ast, &target, "z").unwrap()),
+        "local z = 1\nlocal y = z\nprint(z)"
+    );
+}
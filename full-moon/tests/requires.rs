@@ -0,0 +1,123 @@
+use full_moon::{
+    analysis::{requires, RequireArgument, RequireNode},
+    ast::Ast,
+    parse,
+};
+
+fn arguments<'b>(ast: &'b Ast) -> Vec<RequireArgument<'b>> {
+    requires(ast)
+        .iter()
+        .map(|require| require.argument().clone())
+        .collect()
+}
+
+#[test]
+fn test_string_requires() {
+    let ast =
+        parse(r#"local a = require("a") require 'b.c' require [[d]] require("\101\102")"#).unwrap();
+    assert_eq!(
+        arguments(&ast),
+        vec![
+            RequireArgument::String("a".to_owned()),
+            RequireArgument::String("b.c".to_owned()),
+            RequireArgument::String("d".to_owned()),
+            RequireArgument::String("ef".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_path_requires() {
+    let ast = parse(r#"local Roact = require(script.Parent.Packages["Roact"])"#).unwrap();
+    assert_eq!(
+        arguments(&ast),
+        vec![RequireArgument::Path(vec![
+            "script", "Parent", "Packages", "Roact"
+        ])]
+    );
+}
+
+#[test]
+fn test_dynamic_requires() {
+    let ast = parse(r#"require(name .. ".lua") require(modules[index]) require(get()) require()"#)
+        .unwrap();
+    assert_eq!(arguments(&ast), vec![RequireArgument::Dynamic; 4]);
+}
+
+#[test]
+fn test_nested_requires() {
+    let code = r#"local modules = {
+    json = require("json"),
+    [1] = require("one").value,
+}
+
+local function load()
+    return (require("lazy"))
+end
+
+local ok, result = pcall(require, "optional")
+pcall(print, "not a require")"#;
+
+    let ast = parse(code).unwrap();
+    let found = requires(&ast);
+
+    assert_eq!(
+        found
+            .iter()
+            .map(|require| {
+                let position = require.position().unwrap();
+                (
+                    match require.node() {
+                        RequireNode::FunctionCall(call) => call.to_string(),
+                        RequireNode::VarExpression(var_expression) => var_expression.to_string(),
+                        _ => unreachable!(),
+                    }
+                    .trim_end()
+                    .to_owned(),
+                    position.line(),
+                    require.is_protected(),
+                )
+            })
+            .collect::<Vec<_>>(),
+        vec![
+            (r#"require("json")"#.to_owned(), 2, false),
+            (r#"require("one").value"#.to_owned(), 3, false),
+            (r#"require("lazy")"#.to_owned(), 7, false),
+            (r#"pcall(require, "optional")"#.to_owned(), 10, true),
+        ]
+    );
+
+    assert_eq!(
+        found[3].argument(),
+        &RequireArgument::String("optional".to_owned())
+    );
+    assert!(found[0].call().is_some());
+    assert!(found[1].call().is_none());
+}
+
+#[test]
+fn test_shadowed_require() {
+    let code = r#"local require = require
+require("local")
+
+local function f(pcall)
+    pcall(require, "local")
+end
+
+do
+    local require = nil
+end
+
+require("global")"#;
+
+    // `require` is a local for the rest of the chunk, so the last call isn't to the global either
+    let ast = parse(code).unwrap();
+    assert_eq!(arguments(&ast), Vec::<RequireArgument>::new());
+
+    let ast =
+        parse("do local require = nil end require('global') pcall(require, 'global')").unwrap();
+    assert_eq!(
+        arguments(&ast),
+        vec![RequireArgument::String("global".to_owned()); 2]
+    );
+}
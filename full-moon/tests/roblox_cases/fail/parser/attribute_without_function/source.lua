@@ -0,0 +1 @@
+@native local x = 1
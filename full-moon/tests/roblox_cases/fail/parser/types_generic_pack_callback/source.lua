@@ -0,0 +1 @@
+type F = <T, U...>(T, U...) -> T
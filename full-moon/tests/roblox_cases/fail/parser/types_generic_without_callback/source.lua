@@ -0,0 +1 @@
+type Broken = <T> T
@@ -0,0 +1 @@
+type A = B & C | D
@@ -0,0 +1,17 @@
+@native
+function fast(x: number): number
+	return x * 2
+end
+
+@native @checked local function checked(value)
+	return value
+end
+
+@unknown
+local function custom() end
+
+local callback = @native function(x)
+	return x
+end
+
+call(@native  function() end)
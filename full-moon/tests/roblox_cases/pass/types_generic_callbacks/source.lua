@@ -0,0 +1,12 @@
+type Id = <T>(T) -> T
+type Map = < K, V >({ [K]: V }, (V) -> V) -> { [K]: V }
+type MaybeCallback = (<T>(T) -> T) | nil
+type Either = <T>(T) -> T | <U>() -> U
+type Callbacks = {
+	transform: <T>(T, number) -> T,
+	[string]: <A, B>(A) -> (B, A)?,
+}
+
+local function apply(callback: <T>(T) -> T, value: number): <U>(U) -> U
+	return callback
+end
@@ -0,0 +1,5 @@
+local a: string?? = nil
+local b: { number }? ? = nil
+type Union = string | number?
+type Intersection = A & B??
+type Callback = () -> string??
@@ -0,0 +1,5 @@
+type A = (B & C) | D
+type E = B & (C | D)
+type F = (B | C)?
+local x: ((string) -> number) | nil
+type G = (A & B) -> (C | D) & E
@@ -0,0 +1,170 @@
+use full_moon::{
+    analysis::{scopes, Resolution, ScopeKind, ScopeTree, VariableKind},
+    parse,
+};
+
+// Describes every usage as `name -> kind line:character`, where the position is that of the declaration
+fn usages(tree: &ScopeTree) -> Vec<String> {
+    tree.usages()
+        .map(|(token, resolution)| {
+            let name = token.token().to_string();
+            let declared_at = |id| {
+                let position = tree.variable(id).token().start_position();
+                format!("{}:{}", position.line(), position.character())
+            };
+
+            match resolution {
+                Resolution::Local(id) => format!("{} -> local {}", name, declared_at(id)),
+                Resolution::Upvalue(id) => format!("{} -> upvalue {}", name, declared_at(id)),
+                Resolution::Global => format!("{} -> global", name),
+                _ => unreachable!(),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_shadowing() {
+    let ast = parse("local x = 1\nlocal x = x\ndo local x = x end\nprint(x)").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec![
+            "x -> local 1:7",
+            "x -> local 2:7",
+            "print -> global",
+            "x -> local 2:7",
+        ]
+    );
+}
+
+#[test]
+fn test_upvalues() {
+    let ast = parse("local x = 1\nlocal function f(y)\nreturn x, y, f, z\nend").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec![
+            "x -> upvalue 1:7",
+            "y -> local 2:18",
+            "f -> upvalue 2:16",
+            "z -> global",
+        ]
+    );
+}
+
+#[test]
+fn test_local_function_recursion() {
+    let ast = parse("local function f() f() end\nlocal g = function() g() end").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(usages(&tree), vec!["f -> upvalue 1:16", "g -> global"]);
+}
+
+#[test]
+fn test_repeat_until_sees_locals() {
+    let ast =
+        parse("local done = false\nrepeat\nlocal done = true\nuntil done\nprint(done)").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec!["done -> local 3:7", "print -> global", "done -> local 1:7"]
+    );
+
+    let repeat = tree.scope(tree.root()).children()[0];
+    assert_eq!(tree.scope(repeat).kind(), ScopeKind::Repeat);
+    assert_eq!(tree.scope(repeat).variables().len(), 1);
+}
+
+#[test]
+fn test_numeric_for() {
+    let ast = parse("local i = 1\nfor i = i, i + 10, i do\nprint(i)\nend\nprint(i)").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec![
+            "i -> local 1:7",
+            "i -> local 1:7",
+            "i -> local 1:7",
+            "print -> global",
+            "i -> local 2:5",
+            "print -> global",
+            "i -> local 1:7",
+        ]
+    );
+
+    let numeric_for = tree.scope(tree.root()).children()[0];
+    assert_eq!(tree.scope(numeric_for).kind(), ScopeKind::NumericFor);
+
+    let index = tree.scope(numeric_for).variables()[0];
+    assert_eq!(tree.variable(index).kind(), VariableKind::LoopVariable);
+    assert_eq!(tree.variable(index).references().len(), 1);
+}
+
+#[test]
+fn test_generic_for() {
+    let ast = parse("local t = {}\nfor k, t in pairs(t) do\nprint(k, t)\nend").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec![
+            "pairs -> global",
+            "t -> local 1:7",
+            "print -> global",
+            "k -> local 2:5",
+            "t -> local 2:8",
+        ]
+    );
+}
+
+#[test]
+fn test_implicit_self() {
+    let ast = parse("local t = {}\nfunction t:m()\nreturn self\nend\nreturn self").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec!["t -> local 1:7", "self -> local 2:12", "self -> global"]
+    );
+
+    let (_, self_variable) = tree
+        .variables()
+        .find(|(_, variable)| variable.kind() == VariableKind::ImplicitSelf)
+        .unwrap();
+    assert_eq!(self_variable.name(), "self");
+}
+
+#[test]
+fn test_resolve_and_declaration() {
+    let ast = parse("local x = 1\nprint(x.y)").unwrap();
+    let tree = scopes(&ast);
+
+    let (x, _) = tree.variables().next().unwrap();
+    let usage = tree.variable(x).references()[0];
+    assert_eq!(tree.resolve(usage), Some(Resolution::Local(x)));
+    assert_eq!(tree.declaration(tree.variable(x).token()), Some(x));
+    assert_eq!(tree.resolve(tree.variable(x).token()), None);
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_typeof() {
+    let ast = parse("local x = 1\nlocal y: typeof(x) = (x :: typeof(z))").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(
+        usages(&tree),
+        vec!["x -> local 1:7", "z -> global", "x -> local 1:7"]
+    );
+
+    // Both the key and the value of an index signature are types
+    let ast = parse("local x = 1\nlocal t: { [typeof(x)]: typeof(y) } = {}").unwrap();
+    let tree = scopes(&ast);
+
+    assert_eq!(usages(&tree), vec!["x -> local 1:7", "y -> global"]);
+}
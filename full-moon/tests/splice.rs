@@ -0,0 +1,148 @@
+use full_moon::{
+    ast::{AstError, Block, Expression, LocalFunction},
+    parse, print, query_mut, Error,
+};
+
+// Splices the snippet into the body of every local function named `f`
+fn splice_into_function(code: &str, index: usize, snippet: &str) -> Result<String, Error<'static>> {
+    let mut result = Ok(());
+    let ast = query_mut(parse(code).unwrap(), |function: &mut LocalFunction| {
+        if function.name().token().to_string() == "f" {
+            let mut block = function.body().block().clone();
+            result = block.splice(index, snippet);
+            *function = function
+                .clone()
+                .with_body(function.body().clone().with_block(block));
+        }
+    });
+
+    result.map(|_| print(&ast))
+}
+
+// The line and character of the unexpected token
+fn error_position(error: Error) -> (usize, usize) {
+    match error {
+        Error::AstError(AstError::UnexpectedToken { token, .. }) => (
+            token.start_position().line(),
+            token.start_position().character(),
+        ),
+        other => panic!("expected an unexpected token, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_splice_guard_clause() {
+    let code = r#"local function outer()
+    do
+        local function f(x)
+            local y = x * 2
+            return y
+        end
+    end
+end
+"#;
+
+    assert_eq!(
+        splice_into_function(code, 0, "if not x then\n    return\nend").unwrap(),
+        r#"local function outer()
+    do
+        local function f(x)
+            if not x then
+                return
+            end
+            local y = x * 2
+            return y
+        end
+    end
+end
+"#
+    );
+
+    // The snippet's own indentation is replaced, keeping lines that are further indented
+    assert_eq!(
+        splice_into_function(
+            code,
+            1,
+            "\n\t\tprint(y)\n\t\tif y then\n\t\t\tprint(x) -- x\n\t\tend\n\n"
+        )
+        .unwrap(),
+        r#"local function outer()
+    do
+        local function f(x)
+            local y = x * 2
+            print(y)
+            if y then
+            	print(x) -- x
+            end
+            return y
+        end
+    end
+end
+"#
+    );
+}
+
+#[test]
+fn test_splice_same_line() {
+    assert_eq!(
+        splice_into_function("local function f() a() end", 1, "b() c()").unwrap(),
+        "local function f() a() b() c() end"
+    );
+
+    assert_eq!(
+        splice_into_function("local function f() a(); end", 1, "b() -- b").unwrap(),
+        "local function f() a(); b() -- b\nend"
+    );
+
+    assert_eq!(
+        splice_into_function("local function f() end", 0, "return 1").unwrap(),
+        "local function f() return 1 end"
+    );
+}
+
+#[test]
+fn test_splice_last_stmt() {
+    let code = "local function f()\n    a()\n    return\nend";
+
+    let error = splice_into_function(code, 1, "return 1").unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("a last statement can only be spliced into the end of a block"));
+    assert_eq!(error_position(error), (1, 1));
+
+    assert_eq!(
+        splice_into_function("local function f()\n    a()\nend", 1, "return 1").unwrap(),
+        "local function f()\n    a()\n    return 1\nend"
+    );
+}
+
+#[test]
+fn test_splice_errors() {
+    // Positions are relative to the snippet
+    let error =
+        splice_into_function("local function f()\n    a()\nend", 0, "b()\nlocal = 1").unwrap_err();
+    assert_eq!(error_position(error), (2, 7));
+
+    let mut block = Block::new();
+    assert!(block.splice(0, "\"unfinished").is_err());
+}
+
+#[test]
+fn test_splice_expression() {
+    let ast = parse("local x = --[[ old ]] 1 -- one\n").unwrap();
+    let mut spliced = false;
+    let ast = query_mut(ast, |expression: &mut Expression| {
+        // The new expression is visited too, so only the first one is replaced
+        if !spliced {
+            expression.splice("(y +\n\t2)").unwrap();
+            spliced = true;
+        }
+    });
+
+    assert_eq!(print(&ast), "local x = --[[ old ]] (y +\n\t2) -- one\n");
+
+    let mut expression = full_moon::ast::builders::expr::name("x");
+    let error = expression.splice("x y").unwrap_err();
+    assert_eq!(error_position(error), (1, 3));
+    assert_eq!(expression.to_string(), "x");
+}
@@ -0,0 +1,83 @@
+use full_moon::{analysis::stats, parse};
+
+#[test]
+fn test_stats_fixture() {
+    let ast = parse(include_str!("fixtures/stats.lua")).unwrap();
+    let stats = stats(&ast);
+
+    // local Queue, function Queue:push, if, return, for, while, assignment, assignment,
+    // local function sum, return, local callback, do, call, return Queue
+    assert_eq!(stats.statements(), 14);
+    // The `while` inside of the `for` inside of `Queue:push`
+    assert_eq!(stats.max_depth(), 3);
+    assert_eq!(stats.tokens(), 86);
+    assert_eq!(stats.lines(), 31);
+    // Lines 1, 4 to 6, and 18
+    assert_eq!(stats.comment_lines(), 5);
+    assert_eq!(stats.comment_density(), 5.0 / 31.0);
+
+    assert_eq!(stats.function_count(), 3);
+    assert_eq!(stats.longest_function_statements(), 6);
+    assert_eq!(stats.longest_function_lines(), 13);
+
+    let functions = stats
+        .functions()
+        .iter()
+        .map(|function| {
+            (
+                function.name(),
+                function.line(),
+                function.parameters(),
+                function.statements(),
+                function.lines(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        functions,
+        vec![
+            (Some("Queue:push"), 7, 2, 6, 13),
+            (Some("sum"), 21, 2, 1, 3),
+            (None, 25, 0, 0, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_stats_empty() {
+    let empty = stats(&parse("").unwrap());
+    assert_eq!(empty.statements(), 0);
+    assert_eq!(empty.max_depth(), 0);
+    assert_eq!(empty.lines(), 0);
+    assert_eq!(empty.comment_density(), 0.0);
+    assert_eq!(empty.longest_function_lines(), 0);
+
+    let one_line = stats(&parse("local x = 1").unwrap());
+    assert_eq!((one_line.lines(), one_line.tokens()), (1, 4));
+}
+
+#[test]
+fn test_stats_nested_functions() {
+    let ast = parse("local function outer()\n\tlocal inner = function(...)\n\t\treturn ...\n\tend\n\treturn inner\nend").unwrap();
+    let stats = stats(&ast);
+
+    // `inner`'s return belongs to the anonymous function only
+    let functions = stats.functions();
+    assert_eq!((functions[0].statements(), functions[0].lines()), (2, 6));
+    assert_eq!(
+        (functions[1].parameters(), functions[1].statements()),
+        (1, 1)
+    );
+    assert_eq!(stats.max_depth(), 2);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_stats_serialize() {
+    let stats = stats(&parse("local function f(a) end").unwrap());
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["functions"][0]["name"], "f");
+    assert_eq!(json["functions"][0]["parameters"], 1);
+    assert_eq!(json["statements"], 1);
+}
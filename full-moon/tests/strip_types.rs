@@ -0,0 +1,277 @@
+#![cfg(feature = "roblox")]
+use full_moon::{
+    ast::{
+        types::{Attribute, CompoundAssignment, TypeAssertion, TypeInfo, TypeSpecifier},
+        LastStmt,
+    },
+    parse, print,
+    transform::{strip_types, strip_types_with_options, StripTypesOptions},
+    visitors::Visitor,
+};
+
+#[derive(Default)]
+struct LuauNodes(Vec<String>);
+
+impl<'ast> Visitor<'ast> for LuauNodes {
+    fn visit_attribute(&mut self, node: &Attribute<'ast>) {
+        self.0.push(node.to_string());
+    }
+
+    fn visit_compound_assignment(&mut self, node: &CompoundAssignment<'ast>) {
+        self.0.push(node.to_string());
+    }
+
+    fn visit_last_stmt(&mut self, node: &LastStmt<'ast>) {
+        if let LastStmt::Continue(_) = node {
+            self.0.push(node.to_string());
+        }
+    }
+
+    fn visit_type_assertion(&mut self, node: &TypeAssertion<'ast>) {
+        self.0.push(node.to_string());
+    }
+
+    fn visit_type_info(&mut self, node: &TypeInfo<'ast>) {
+        self.0.push(node.to_string());
+    }
+
+    fn visit_type_specifier(&mut self, node: &TypeSpecifier<'ast>) {
+        self.0.push(node.to_string());
+    }
+}
+
+fn strip(code: &str, options: &StripTypesOptions) -> String {
+    let output = print(&strip_types_with_options(parse(code).unwrap(), options));
+
+    let mut nodes = LuauNodes::default();
+    nodes.visit_ast(&parse(&output).expect("output doesn't parse"));
+    assert_eq!(nodes.0, Vec::<String>::new(), "{}", output);
+    assert_eq!(
+        output.lines().count(),
+        code.lines().count(),
+        "line count changed:\n{}",
+        output
+    );
+
+    output
+}
+
+fn all_options() -> StripTypesOptions {
+    StripTypesOptions::new()
+        .with_desugar_compound_assignments(true)
+        .with_desugar_continue(true)
+}
+
+#[test]
+fn test_strip_types() {
+    let code = r#"--!strict
+type Point = { x: number, y: number }
+export type Callback<T> = (T) -> ()
+
+local function distance(a: Point, b: Point, ...: any): number
+    local dx: number, dy = a.x - b.x, a.y - b.y
+    return math.sqrt(dx * dx + dy * dy)
+end
+
+for index: number = 1, 10 do
+    print(index)
+end
+
+for key: string, value in pairs({}) do
+    print(key, (value :: any).name)
+end
+
+local x = y :: number + 1
+"#;
+
+    assert_eq!(
+        strip(code, &StripTypesOptions::new()),
+        r#"--!strict
+
+
+
+local function distance(a, b, ...)
+    local dx, dy = a.x - b.x, a.y - b.y
+    return math.sqrt(dx * dx + dy * dy)
+end
+
+for index = 1, 10 do
+    print(index)
+end
+
+for key, value in pairs({}) do
+    print(key, (value).name)
+end
+
+local x = y + 1
+"#
+    );
+}
+
+#[test]
+fn test_strip_types_comments() {
+    let code = r#"-- the first
+type A = number -- after a
+local a: --[[ inline ]] A = 1
+local b: {
+    -- the field
+    field: number, -- after the field
+} = {}
+local function f(): -- returns
+    number
+    return 1
+end
+do
+    -- alone
+    type B = string
+end
+local c = 1 -- after c
+type C = number -- at the end"#;
+
+    assert_eq!(
+        strip(code, &StripTypesOptions::new()),
+        r#"-- the first
+-- after a
+local a --[[ inline ]] = 1
+local b
+    -- the field
+    -- after the field
+= {}
+local function f() -- returns
+
+    return 1
+end
+do
+    -- alone
+
+end
+local c = 1 -- after c
+-- at the end
+"#
+    );
+}
+
+#[test]
+fn test_strip_types_attributes() {
+    let code = r#"@native function f() end
+local g = @checked function() end
+@native -- fast
+local function h()
+    return @checked --[[ checked ]] function() end
+end
+"#;
+
+    assert_eq!(
+        strip(code, &StripTypesOptions::new()),
+        r#"function f() end
+local g = function() end
+-- fast
+local function h()
+    return --[[ checked ]] function() end
+end
+"#
+    );
+}
+
+#[test]
+fn test_strip_types_keeps_luau_syntax() {
+    let code = "x += 1\nfor i = 1, 10 do\n    continue\nend\n";
+    let ast = strip_types(parse(code).unwrap());
+    assert_eq!(print(&ast), code);
+}
+
+#[test]
+fn test_desugar_compound_assignments() {
+    let code = r#"x += 1
+t.count *= a + b -- scale
+local s: string = "a"
+s ..= "b" .. "c"
+y ^= 2
+"#;
+
+    assert_eq!(
+        strip(code, &all_options()),
+        r#"x = x + 1
+t.count = t.count * (a + b) -- scale
+local s = "a"
+s = s .. "b" .. "c"
+y = y ^ 2
+"#
+    );
+}
+
+#[test]
+fn test_desugar_continue() {
+    let code = r#"for i = 1, 10 do
+    if i % 2 == 0 then
+        continue
+    end
+    print(i)
+end
+
+while true do -- forever
+    local line = read()
+    if line == "" then continue elseif line == "quit" then break end
+    print(line)
+end
+
+repeat
+    for _, v in ipairs(t) do
+        if v then continue end
+    end
+until done
+"#;
+
+    assert_eq!(
+        strip(code, &all_options()),
+        r#"for i = 1, 10 do repeat
+    if i % 2 == 0 then
+        break
+    end
+    print(i)
+until true end
+
+while true do local __break = false repeat -- forever
+    local line = read()
+    if line == "" then break elseif line == "quit" then __break = true break end
+    print(line)
+until true if __break then break end end
+
+repeat
+    for _, v in ipairs(t) do repeat
+        if v then break end
+    until true end
+until done
+"#
+    );
+}
+
+#[test]
+fn test_desugar_continue_repeat_locals() {
+    // The condition can't use `line` once the body is in another loop, so `continue` is kept
+    let code = r#"repeat
+    local line = read()
+    if line == "" then continue end
+    print(line)
+until line == "quit"
+"#;
+
+    let output = strip_types_with_options(parse(code).unwrap(), &all_options());
+    assert_eq!(print(&output), code);
+
+    assert_eq!(
+        strip(
+            r#"repeat
+    local function skip() end
+    if skip() then continue end
+until done
+"#,
+            &all_options()
+        ),
+        r#"repeat repeat
+    local function skip() end
+    if skip() then break end
+until true until done
+"#
+    );
+}
@@ -0,0 +1,174 @@
+use full_moon::{
+    ast::Stmt,
+    node::Node,
+    parse, print,
+    tokenizer::{Token, TokenKind, TokenReference, TokenType, TokenizerErrorType},
+};
+
+#[test]
+fn test_delete_statement_keeping_comments() {
+    let ast = parse("local a = 1\n-- explains b\nlocal b = 2 -- and more\nlocal c = 3\n").unwrap();
+    let mut stmts = ast
+        .nodes()
+        .stmts_with_semicolon()
+        .cloned()
+        .collect::<Vec<_>>();
+    let (deleted, _) = stmts.remove(1);
+
+    // Comments before the statement and after it on the same line move onto the next statement
+    let mut tokens = deleted.tokens();
+    let mut comments = tokens.next().unwrap().clone().take_leading_trivia();
+    let mut trailing = tokens.next_back().unwrap().clone().take_trailing_trivia();
+    trailing
+        .retain(|token| token.token_kind() != TokenKind::Whitespace || token.to_string() == "\n");
+    comments.extend(trailing);
+
+    stmts[1].0 = match stmts[1].0.clone() {
+        Stmt::LocalAssignment(local_assignment) => {
+            let mut local_token = local_assignment.local_token().clone();
+            for (index, comment) in comments.into_iter().enumerate() {
+                local_token.insert_leading_trivia(index, comment).unwrap();
+            }
+
+            Stmt::LocalAssignment(local_assignment.with_local_token(local_token))
+        }
+
+        other => panic!("expected local assignment, got {}", other),
+    };
+
+    let block = ast.nodes().clone().with_stmts(stmts);
+    let ast = ast.with_nodes(block);
+    assert_eq!(
+        print(&ast),
+        "local a = 1\n-- explains b\n-- and more\nlocal c = 3\n"
+    );
+}
+
+#[test]
+fn test_trivia_validation() {
+    let mut token = TokenReference::symbol("local").unwrap();
+    let identifier = Token::new(TokenType::Identifier {
+        identifier: "x".into(),
+    });
+
+    assert_eq!(
+        token.push_leading_trivia(identifier.clone()),
+        Err(TokenizerErrorType::InvalidTrivia(TokenKind::Identifier))
+    );
+    assert_eq!(
+        token.insert_trailing_trivia(0, identifier),
+        Err(TokenizerErrorType::InvalidTrivia(TokenKind::Identifier))
+    );
+    assert_eq!(token.to_string(), "local");
+
+    token
+        .push_trailing_trivia(Token::new(TokenType::spaces(1)))
+        .unwrap();
+    token
+        .push_trailing_trivia(Token::new(TokenType::SingleLineComment {
+            comment: " hi".into(),
+        }))
+        .unwrap();
+    assert_eq!(token.to_string(), "local -- hi");
+
+    token.retain_trailing_trivia(|token| token.token_kind() != TokenKind::Whitespace);
+    assert_eq!(token.to_string(), "local-- hi");
+    assert_eq!(token.take_trailing_trivia().len(), 1);
+    assert_eq!(token.to_string(), "local");
+
+    assert_eq!(
+        token.set_leading_whitespace(" x"),
+        Err(TokenizerErrorType::UnexpectedToken('x'))
+    );
+}
+
+#[test]
+fn test_prepend_comment() {
+    let ast = parse("do\n    local x = 1\nend").unwrap();
+    let stmt = ast.nodes().stmts().next().unwrap();
+    let mut token = match stmt {
+        Stmt::Do(do_block) => do_block
+            .block()
+            .stmts()
+            .next()
+            .unwrap()
+            .tokens()
+            .next()
+            .unwrap()
+            .clone(),
+        _ => unreachable!(),
+    };
+
+    token.prepend_comment(" one");
+    token.prepend_comment(" multiple\n lines ]]");
+    assert_eq!(
+        token.to_string(),
+        "    -- one\n    --[=[ multiple\n lines ]]]=]\n    local "
+    );
+
+    token.set_leading_whitespace("").unwrap();
+    assert_eq!(
+        token.to_string(),
+        "    -- one\n    --[=[ multiple\n lines ]]]=]local "
+    );
+}
+
+fn trailing_trivia(code: &str) -> Vec<String> {
+    let ast = parse(code).unwrap();
+    assert_eq!(print(&ast), code);
+    ast.trailing_trivia().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_trailing_trivia() {
+    // Without a new line at the end
+    assert_eq!(trailing_trivia("local x = 1"), Vec::<String>::new());
+    assert_eq!(trailing_trivia("return x;"), Vec::<String>::new());
+    assert_eq!(trailing_trivia("local x = 1 -- one"), vec![" ", "-- one"]);
+
+    // Three blank lines and a comment
+    assert_eq!(
+        trailing_trivia("local x = 1\n\n\n\n-- end"),
+        vec!["\n", "\n", "\n", "\n", "-- end"]
+    );
+
+    // Only trivia
+    assert_eq!(trailing_trivia(""), Vec::<String>::new());
+    assert_eq!(
+        trailing_trivia("  -- a\n--[[ b ]]\n"),
+        vec!["  ", "-- a", "\n", "--[[ b ]]", "\n"]
+    );
+}
+
+#[test]
+fn test_eof_positions() {
+    for (code, line, character) in [
+        ("", 1, 1),
+        ("local x = 1", 1, 12),
+        ("local x = 1\n\n\n\n-- end", 5, 7),
+        ("-- only a comment\n", 2, 1),
+    ] {
+        let ast = parse(code).unwrap();
+        let position = ast.eof().start_position().unwrap();
+        assert_eq!(position.bytes(), code.len(), "{:?}", code);
+        assert_eq!((position.line(), position.character()), (line, character));
+
+        // The same positions are given after updating them
+        let ast = ast.update_positions();
+        assert_eq!(ast.eof().start_position(), Some(position));
+    }
+}
+
+#[test]
+fn test_append_before_trailing_comments() {
+    let ast = parse("local x = 1\n\n-- end of file\n").unwrap();
+
+    let mut block = ast.nodes().clone();
+    block.splice(1, "print(x)").unwrap();
+    let ast = ast.with_nodes(block);
+    assert_eq!(print(&ast), "local x = 1\nprint(x)\n\n-- end of file\n");
+
+    // Removing the trivia at the end
+    let eof = TokenReference::new(Vec::new(), ast.eof().token().clone(), Vec::new());
+    assert_eq!(print(&ast.with_eof(eof)), "local x = 1\nprint(x)\n");
+}
@@ -0,0 +1,424 @@
+#![cfg(feature = "roblox")]
+use full_moon::{
+    ast::{
+        owned::Owned,
+        punctuated::{Pair, Punctuated},
+        span::ContainedSpan,
+        types::{GenericDeclaration, SimplifyOptions, TypeField, TypeFieldKey, TypeInfo},
+        Stmt,
+    },
+    node::Node,
+    parse, print, query, query_mut,
+    tokenizer::{Token, TokenReference, TokenType},
+    visitors::Visitor,
+};
+
+const TYPES: &[&str] = &[
+    "{\n\tnumber -- the values\n}",
+    "string",
+    "( string ,\n number )  ->  boolean",
+    "(...  number) -> ()",
+    "map< number,string >",
+    "Foo&  Bar",
+    "module . Foo",
+    "module.Foo< T >",
+    "string  ?",
+    "{\n\tfoo: number, -- the foo\n\t[ string ]:boolean,\n\tbar : { }\n}",
+    "{ }",
+    "typeof( foo . bar  +--[[ comment ]]1 )",
+    "typeof(x)",
+    "typeof( f ( a ,b ) [ 1 ] : m(not  c, { }) )",
+    "()->( string , number )",
+    "string|\n\tnil",
+    "A | (B & C)?",
+    "< T ,U >( T ) ->U",
+];
+
+#[test]
+fn test_canonical_types() {
+    let mut output = String::new();
+    for source in TYPES {
+        let code = format!("type T = {}", source);
+        let ast = parse(&code).unwrap();
+        let type_info = match ast.nodes().stmts().next() {
+            Some(Stmt::TypeDeclaration(declaration)) => declaration.type_definition(),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(type_info.to_string(), *source);
+        output.push_str(&format!(
+            "{:?}\n{}\n\n",
+            source,
+            type_info.to_canonical_string()
+        ));
+    }
+
+    insta::assert_snapshot!("canonical_types", output);
+}
+
+fn simplify(source: &str, options: &SimplifyOptions) -> String {
+    let code = format!("type T = {}", source);
+    let ast = parse(&code).unwrap();
+    let simplified = match ast.nodes().stmts().next() {
+        Some(Stmt::TypeDeclaration(declaration)) => declaration
+            .type_definition()
+            .simplify_with_options(options)
+            .to_canonical_string(),
+        _ => unreachable!(),
+    };
+
+    simplified
+}
+
+#[test]
+fn test_simplify() {
+    let options = SimplifyOptions::new();
+    let cases = [
+        ("number | number", "number"),
+        (
+            "string | number | string | boolean | number",
+            "string | number | boolean",
+        ),
+        ("B | A | B | A", "B | A"),
+        ("A & B & A", "A & B"),
+        ("{ [string]: number | number }", "{ [string]: number }"),
+        (
+            "(number | number, string) -> boolean & boolean",
+            "(number, string) -> boolean",
+        ),
+        ("map<string | string>", "map<string>"),
+        ("typeof(x) | typeof( x )", "typeof(x)"),
+        ("string | nil", "string | nil"),
+        ("A | (B & C) | (B & C)", "A | (B & C)"),
+        ("(A | A) & B", "A & B"),
+        ("A | (B | A)", "A | B"),
+        ("(string?)?", "string?"),
+        ("((A | (B)) | C) | A", "A | B | C"),
+        ("(A | B) & C", "(A | B) & C"),
+        ("(() -> ()) | A", "(() -> ()) | A"),
+        ("(() -> ())?", "(() -> ())?"),
+        ("(A | B)?", "(A | B)?"),
+    ];
+
+    for (source, simplified) in &cases {
+        assert_eq!(simplify(source, &options), *simplified, "{}", source);
+    }
+}
+
+#[test]
+fn test_simplify_nil_to_optional() {
+    let options = SimplifyOptions::new().with_nil_to_optional(true);
+    let cases = [
+        ("string | nil", "string?"),
+        ("nil | string", "string?"),
+        ("A | nil | B", "A | B?"),
+        ("A? | nil | B", "A? | B"),
+        ("nil | nil", "nil"),
+        ("(A & B) | C | nil", "(A & B) | C?"),
+        ("() -> () | nil", "() -> () | nil"),
+        ("(string | nil)", "(string?)"),
+        ("(nil | string)?", "string?"),
+    ];
+
+    for (source, simplified) in &cases {
+        assert_eq!(simplify(source, &options), *simplified, "{}", source);
+    }
+}
+
+// Tokens that are added or removed by simplifying keep the rest of the trivia where it was
+#[test]
+fn test_simplify_printed() {
+    let print_simplified = |source: &str, options: &SimplifyOptions| {
+        let code = format!("type T = {}", source);
+        let ast = parse(&code).unwrap();
+        let simplified = match ast.nodes().stmts().next() {
+            Some(Stmt::TypeDeclaration(declaration)) => declaration
+                .type_definition()
+                .simplify_with_options(options)
+                .to_string(),
+            _ => unreachable!(),
+        };
+
+        simplified
+    };
+
+    let options = SimplifyOptions::new();
+    let cases = [
+        ("A | (B | A)", "A | B"),
+        ("(string?)?", "string?"),
+        ("number | number --[[ end ]]", "number --[[ end ]]"),
+    ];
+
+    for (source, simplified) in &cases {
+        assert_eq!(
+            print_simplified(source, &options),
+            *simplified,
+            "{}",
+            source
+        );
+    }
+
+    let options = SimplifyOptions::new().with_nil_to_optional(true);
+    let cases = [
+        ("(string | nil)", "(string?)"),
+        ("string | nil", "string?"),
+        ("nil | string", "string?"),
+        ("A | nil | B", "A | B?"),
+        ("A | nil | () -> ()", "A? | () -> ()"),
+    ];
+
+    for (source, simplified) in &cases {
+        assert_eq!(
+            print_simplified(source, &options),
+            *simplified,
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_simplify_nested() {
+    // Unions built by hand can be nested on either side, and the first occurrence of each member is kept
+    let basic = |index: usize| {
+        TypeInfo::Basic(TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::Identifier {
+                identifier: format!("T{}", index % 7).into(),
+            }),
+            Vec::new(),
+        ))
+    };
+
+    let mut type_info = basic(0);
+    for index in 1..200 {
+        let member = basic(index);
+        let pipe = TokenReference::symbol("|").unwrap();
+        type_info = if index % 2 == 0 {
+            TypeInfo::Union {
+                left: Box::new(type_info),
+                pipe,
+                right: Box::new(member),
+            }
+        } else {
+            TypeInfo::Union {
+                left: Box::new(member),
+                pipe,
+                right: Box::new(type_info),
+            }
+        };
+    }
+
+    let type_info = TypeInfo::Optional {
+        base: Box::new(TypeInfo::Optional {
+            base: Box::new(TypeInfo::Array {
+                braces: ContainedSpan::new(
+                    TokenReference::symbol("{").unwrap(),
+                    TokenReference::symbol("}").unwrap(),
+                ),
+                type_info: Box::new(type_info),
+            }),
+            question_mark: TokenReference::symbol("?").unwrap(),
+        }),
+        question_mark: TokenReference::symbol("?").unwrap(),
+    };
+
+    assert_eq!(type_info.simplify().to_string(), "{T3|T1|T6|T4|T2|T0|T5}?");
+}
+
+// Writes the type with parentheses around every optional, union, and intersection
+fn shape(type_info: &TypeInfo) -> String {
+    match type_info {
+        TypeInfo::Optional { base, .. } => format!("({})?", shape(base)),
+        TypeInfo::Union { left, right, .. } => format!("({} | {})", shape(left), shape(right)),
+        TypeInfo::Intersection { left, right, .. } => {
+            format!("({} & {})", shape(left), shape(right))
+        }
+        other => other.to_canonical_string(),
+    }
+}
+
+#[test]
+fn test_optional_precedence() {
+    let cases = [
+        ("string?", "(string)?"),
+        ("string??", "((string)?)?"),
+        ("string ? ?", "((string)?)?"),
+        ("string | number?", "(string | (number)?)"),
+        ("string? | number", "((string)? | number)"),
+        ("A & B??", "(A & ((B)?)?)"),
+        ("{ number }?", "({ number })?"),
+        ("() -> string?", "() -> string?"),
+    ];
+
+    for (source, expected) in &cases {
+        let code = format!("type T = {}", source);
+        let ast = parse(&code).unwrap();
+        assert_eq!(full_moon::print(&ast), code);
+
+        let type_info = match ast.nodes().stmts().next() {
+            Some(Stmt::TypeDeclaration(declaration)) => declaration.type_definition(),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(shape(type_info), *expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_nested_optional_nodes() {
+    struct OptionalCounter(usize);
+
+    impl Visitor<'_> for OptionalCounter {
+        fn visit_type_info(&mut self, type_info: &TypeInfo) {
+            if let TypeInfo::Optional { .. } = type_info {
+                self.0 += 1;
+            }
+        }
+    }
+
+    let ast = parse("local x: string?? = nil").unwrap();
+    let mut counter = OptionalCounter(0);
+    counter.visit_ast(&ast);
+    assert_eq!(counter.0, 2);
+
+    let type_info = match ast.nodes().stmts().next() {
+        Some(Stmt::LocalAssignment(local_assignment)) => local_assignment
+            .type_specifiers()
+            .next()
+            .unwrap()
+            .unwrap()
+            .type_info(),
+        _ => unreachable!(),
+    };
+
+    let (start, end) = type_info.range().unwrap();
+    assert_eq!((start.bytes(), end.bytes()), (9, 17));
+    match type_info {
+        TypeInfo::Optional { base, .. } => {
+            let (start, end) = base.range().unwrap();
+            assert_eq!((start.bytes(), end.bytes()), (9, 16));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_type_field_keys() {
+    let basic = |name: &str| {
+        TypeInfo::Basic(TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::Identifier {
+                identifier: name.to_owned().into(),
+            }),
+            Vec::new(),
+        ))
+    };
+
+    let mut fields = Punctuated::new();
+    fields.push(Pair::new(
+        TypeField::indexer(basic("string"), basic("number")),
+        Some(TokenReference::symbol(", ").unwrap()),
+    ));
+    fields.push(Pair::new(
+        TypeField::new(TypeFieldKey::name("count"), basic("number")),
+        None,
+    ));
+
+    let table = TypeInfo::Table {
+        braces: ContainedSpan::new(
+            TokenReference::symbol("{ ").unwrap(),
+            TokenReference::symbol(" }").unwrap(),
+        ),
+        fields,
+    };
+
+    let source = "{ [string]: number, count: number }";
+    assert_eq!(table.to_string(), source);
+
+    // The table built from scratch has the same shape as the one parsed
+    let code = format!("type T = {}", source);
+    let ast = parse(&code).unwrap();
+    let parsed = match ast.nodes().stmts().next() {
+        Some(Stmt::TypeDeclaration(declaration)) => declaration.type_definition(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(parsed.to_string(), source);
+    assert_eq!(parsed.to_canonical_string(), table.to_canonical_string());
+
+    let keys = match parsed {
+        TypeInfo::Table { fields, .. } => fields.iter().map(TypeField::key).collect::<Vec<_>>(),
+        _ => unreachable!(),
+    };
+
+    let (brackets, inner) = keys[0].as_index_signature().unwrap();
+    assert_eq!(brackets.tokens().0.to_string(), "[");
+    assert_eq!(inner.to_string(), "string");
+    assert!(keys[0].as_name().is_none());
+
+    assert_eq!(keys[1].as_name().unwrap().to_string(), "count");
+    assert!(keys[1].as_index_signature().is_none());
+}
+
+#[test]
+#[should_panic(expected = "\"end\" is not a valid identifier")]
+fn test_type_field_key_keyword() {
+    TypeFieldKey::name("end");
+}
+
+#[test]
+fn test_generic_callbacks() {
+    let source = "type F = { map: <T, U>(T) -> U, [string]: <T>() -> T? } | <T>(T) -> ()";
+    let ast = parse(source).unwrap();
+
+    let generics = query::<TypeInfo>(&ast)
+        .filter_map(|type_info| match type_info {
+            TypeInfo::Callback {
+                generics: Some(generics),
+                ..
+            } => Some(generics.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(generics, vec!["<T, U>", "<T>", "<T>"]);
+    assert_eq!(query::<GenericDeclaration>(&ast).count(), 3);
+
+    assert_eq!(ast.nodes().to_string(), source);
+    assert_eq!(ast.owned().nodes().to_string(), source);
+}
+
+#[test]
+fn test_generic_pack_callbacks() {
+    // Type packs can't be held by a GenericDeclaration, so they're an error instead of being lost
+    let error = parse("type F = <T, U...>(T, U...) -> T").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "error occurred while creating ast: unexpected token `...`. (starting from line 1, character 15 and ending on line 1, character 18)\nadditional information: generic type packs are not supported in function types"
+    );
+}
+
+#[test]
+fn test_table_inner_padding() {
+    let ast = parse("type T = {foo: number}\ntype U = { bar: string }\ntype V = {}").unwrap();
+
+    let mut index = 0;
+    let ast = query_mut(ast, |type_info: &mut TypeInfo| {
+        if let TypeInfo::Table { braces, .. } = type_info {
+            match index {
+                0 => braces.set_inner_padding(" ", " ").unwrap(),
+                // The space before `}` is the trailing trivia of `string`, so it's kept
+                1 => braces.set_inner_padding("", "").unwrap(),
+                _ => braces.tokens_mut().1.set_leading_whitespace("\n").unwrap(),
+            }
+
+            index += 1;
+        }
+    });
+
+    assert_eq!(
+        print(&ast),
+        "type T = { foo: number }\ntype U = {bar: string }\ntype V = {\n}"
+    );
+    assert!(ContainedSpan::braces().set_inner_padding("--", "").is_err());
+}
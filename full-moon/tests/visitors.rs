@@ -112,6 +112,43 @@ fn test_visit_token() {
     );
 }
 
+#[test]
+fn test_visit_token_reference() {
+    #[derive(Default)]
+    struct TokenVisitor {
+        token_references: Vec<String>,
+        tokens: Vec<String>,
+    }
+
+    impl Visitor<'_> for TokenVisitor {
+        fn visit_token_reference(&mut self, token: &TokenReference<'_>) {
+            self.token_references.push(token.token().to_string());
+        }
+
+        fn visit_token(&mut self, token: &Token<'_>) {
+            self.tokens.push(token.to_string());
+        }
+    }
+
+    let code = parse("local x = 1 -- one").unwrap();
+    let mut visitor = TokenVisitor::default();
+    visitor.visit_ast(&code);
+
+    assert_eq!(visitor.token_references, vec!["local", "x", "=", "1", ""]);
+    assert_eq!(
+        full_moon::query::<TokenReference>(&code)
+            .map(|token| token.token().to_string())
+            .collect::<Vec<_>>(),
+        visitor.token_references
+    );
+
+    // Tokens are visited for their references as well as on their own
+    assert_eq!(
+        visitor.tokens,
+        vec!["local", "local", " ", "x", "x", " ", "=", "=", " ", "1", "1", " ", "-- one", "", ""]
+    );
+}
+
 #[test]
 fn test_end_visit() {
     #[derive(Default)]
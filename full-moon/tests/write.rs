@@ -0,0 +1,85 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+// Counts the allocations made on the current thread, so that tests running at the same time
+// don't affect each other
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn source() -> String {
+    // Long strings and comments have equals signs in their brackets
+    format!("do\n{}end\n", include_str!("fixtures/stats.lua")).repeat(50)
+        + "local s = [==[ long ]==] --[=[ comment ]=]\n"
+}
+
+#[test]
+fn test_write_does_not_allocate() {
+    let source = source();
+    let ast = full_moon::parse(&source).unwrap();
+
+    let mut written = Vec::with_capacity(source.len());
+    assert_eq!(
+        allocations(|| full_moon::write(&ast, &mut written).unwrap()),
+        0
+    );
+    assert_eq!(written, source.as_bytes());
+
+    let mut formatted = String::with_capacity(source.len());
+    assert_eq!(
+        allocations(|| full_moon::fmt_to(&ast, &mut formatted).unwrap()),
+        0
+    );
+    assert_eq!(formatted, source);
+}
+
+#[test]
+fn test_print_only_allocates_the_output() {
+    let source = source();
+    let ast = full_moon::parse(&source).unwrap();
+
+    let mut printed = String::new();
+    let count = allocations(|| printed = full_moon::print(&ast));
+    assert_eq!(printed, source);
+
+    // Growing the string, rather than an allocation for every token
+    assert!(
+        count < 64,
+        "printing {} tokens made {} allocations",
+        ast.all_tokens().count(),
+        count
+    );
+}